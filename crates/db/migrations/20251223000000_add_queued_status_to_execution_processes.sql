@@ -0,0 +1,30 @@
+-- Allow execution processes to wait in a FIFO queue until a concurrency slot frees up
+
+-- 1. Drop indexes that mention the old column
+DROP INDEX IF EXISTS idx_execution_processes_status;
+DROP INDEX IF EXISTS idx_execution_processes_session_status_run_reason;
+
+-- 2. Add the replacement column with the wider CHECK
+ALTER TABLE execution_processes
+  ADD COLUMN status_new TEXT NOT NULL DEFAULT 'running'
+    CHECK (status_new IN ('queued','running','completed','failed','killed'));
+
+-- 3. Copy existing values across
+UPDATE execution_processes
+  SET status_new = status;
+
+-- 4. Remove the old column and rename the new one back
+ALTER TABLE execution_processes DROP COLUMN status;
+ALTER TABLE execution_processes
+  RENAME COLUMN status_new TO status;
+
+-- 5. Re-create the indexes
+CREATE INDEX idx_execution_processes_status ON execution_processes(status);
+
+CREATE INDEX idx_execution_processes_session_status_run_reason
+ON execution_processes (session_id, status, run_reason);
+
+-- Queue is drained oldest first
+CREATE INDEX idx_execution_processes_queued_created_at
+ON execution_processes (created_at ASC)
+WHERE status = 'queued';
@@ -0,0 +1,20 @@
+-- Token usage and spend reported by the coding agent for each execution process
+CREATE TABLE execution_process_usage (
+    execution_process_id        BLOB PRIMARY KEY,
+    coding_agent_turn_id        BLOB,
+    input_tokens                INTEGER NOT NULL DEFAULT 0,
+    output_tokens               INTEGER NOT NULL DEFAULT 0,
+    cache_creation_input_tokens INTEGER NOT NULL DEFAULT 0,
+    cache_read_input_tokens     INTEGER NOT NULL DEFAULT 0,
+    cost_usd                    REAL,
+    created_at                  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at                  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE,
+    FOREIGN KEY (coding_agent_turn_id) REFERENCES coding_agent_turns(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_execution_process_usage_coding_agent_turn_id
+    ON execution_process_usage(coding_agent_turn_id);
+
+CREATE INDEX idx_execution_process_usage_created_at
+    ON execution_process_usage(created_at);
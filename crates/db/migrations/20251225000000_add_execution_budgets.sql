@@ -0,0 +1,47 @@
+-- Budget guardrails: executions that run past their token, runtime or tool call limits are
+-- interrupted and recorded as 'budget_exceeded'
+
+-- 1. Drop indexes that mention the old column
+DROP INDEX IF EXISTS idx_execution_processes_status;
+DROP INDEX IF EXISTS idx_execution_processes_session_status_run_reason;
+DROP INDEX IF EXISTS idx_execution_processes_queued_created_at;
+
+-- 2. Add the replacement column with the wider CHECK
+ALTER TABLE execution_processes
+  ADD COLUMN status_new TEXT NOT NULL DEFAULT 'running'
+    CHECK (status_new IN ('queued','running','completed','failed','killed','budget_exceeded'));
+
+-- 3. Copy existing values across
+UPDATE execution_processes
+  SET status_new = status;
+
+-- 4. Remove the old column and rename the new one back
+ALTER TABLE execution_processes DROP COLUMN status;
+ALTER TABLE execution_processes
+  RENAME COLUMN status_new TO status;
+
+-- 5. Re-create the indexes
+CREATE INDEX idx_execution_processes_status ON execution_processes(status);
+
+CREATE INDEX idx_execution_processes_session_status_run_reason
+ON execution_processes (session_id, status, run_reason);
+
+CREATE INDEX idx_execution_processes_queued_created_at
+ON execution_processes (created_at ASC)
+WHERE status = 'queued';
+
+-- Limits configured for a whole project or for a single task. Unset limits fall through
+-- from task to project to the global config.
+CREATE TABLE execution_budgets (
+    id                  BLOB PRIMARY KEY,
+    project_id          BLOB UNIQUE,
+    task_id             BLOB UNIQUE,
+    max_tokens          INTEGER,
+    max_runtime_seconds INTEGER,
+    max_tool_calls      INTEGER,
+    created_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    CHECK ((project_id IS NULL) != (task_id IS NULL))
+);
@@ -0,0 +1,3 @@
+-- Attempts that lost a best-of-N comparison are archived rather than deleted,
+-- so their branches and history stay available
+ALTER TABLE workspaces ADD COLUMN archived_at DATETIME;
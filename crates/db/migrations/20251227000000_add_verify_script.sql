@@ -0,0 +1,33 @@
+-- Verify scripts run after the coding agent (and any cleanup script) finish. A task only
+-- moves to In Review once every repo's verify script passes.
+ALTER TABLE project_repos ADD COLUMN verify_script TEXT;
+
+-- Widen the run_reason CHECK to allow 'verifyscript'
+
+-- 1. Drop indexes that mention the old column
+DROP INDEX IF EXISTS idx_execution_processes_run_reason;
+DROP INDEX IF EXISTS idx_execution_processes_session_status_run_reason;
+DROP INDEX IF EXISTS idx_execution_processes_session_run_reason_created;
+
+-- 2. Add the replacement column with the wider CHECK
+ALTER TABLE execution_processes
+  ADD COLUMN run_reason_new TEXT NOT NULL DEFAULT 'setupscript'
+    CHECK (run_reason_new IN ('setupscript','codingagent','devserver','cleanupscript','verifyscript'));
+
+-- 3. Copy existing values across
+UPDATE execution_processes
+  SET run_reason_new = run_reason;
+
+-- 4. Remove the old column and rename the new one back
+ALTER TABLE execution_processes DROP COLUMN run_reason;
+ALTER TABLE execution_processes
+  RENAME COLUMN run_reason_new TO run_reason;
+
+-- 5. Re-create the indexes
+CREATE INDEX idx_execution_processes_run_reason ON execution_processes(run_reason);
+
+CREATE INDEX idx_execution_processes_session_status_run_reason
+ON execution_processes (session_id, status, run_reason);
+
+CREATE INDEX idx_execution_processes_session_run_reason_created
+ON execution_processes (session_id, run_reason, created_at DESC);
@@ -0,0 +1,4 @@
+-- A turn that took over a session from another executor points at the turn it was
+-- handed off from. Follow-ups never resume agent sessions from before a handoff.
+ALTER TABLE coding_agent_turns ADD COLUMN handoff_from_turn_id BLOB
+    REFERENCES coding_agent_turns(id) ON DELETE SET NULL;
@@ -0,0 +1,7 @@
+-- How direct merges land on the target branch, and an optional template for the commit
+-- message of squash and merge commits
+ALTER TABLE projects
+  ADD COLUMN merge_strategy TEXT NOT NULL DEFAULT 'squash'
+    CHECK (merge_strategy IN ('squash','rebase','merge_commit'));
+
+ALTER TABLE projects ADD COLUMN merge_commit_template TEXT;
@@ -0,0 +1,29 @@
+-- Merge queue: attempts waiting to be rebased onto their target branch, verified and merged
+-- one at a time per repo and target branch
+CREATE TABLE merge_queue_entries (
+    id              BLOB PRIMARY KEY,
+    project_id      BLOB NOT NULL,
+    workspace_id    BLOB NOT NULL,
+    repo_id         BLOB NOT NULL,
+    target_branch   TEXT NOT NULL,
+    status          TEXT NOT NULL DEFAULT 'queued'
+                       CHECK (status IN ('queued','rebasing','verifying','merging','merged','failed','cancelled')),
+    failure_reason  TEXT,
+    merge_commit    TEXT,
+    created_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_merge_queue_entries_project_id
+    ON merge_queue_entries(project_id);
+
+CREATE INDEX idx_merge_queue_entries_repo_target_status
+    ON merge_queue_entries(repo_id, target_branch, status);
+
+-- A workspace repo can only be in the queue once at a time
+CREATE UNIQUE INDEX idx_merge_queue_entries_active_workspace_repo
+    ON merge_queue_entries(workspace_id, repo_id)
+    WHERE status IN ('queued','rebasing','verifying','merging');
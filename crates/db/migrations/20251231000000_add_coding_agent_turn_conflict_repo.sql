@@ -0,0 +1,4 @@
+-- A turn started to resolve the conflicts of a stopped rebase points at the repo being
+-- rebased, so the rebase can be continued once the agent is done
+ALTER TABLE coding_agent_turns ADD COLUMN conflict_repo_id BLOB
+    REFERENCES repos(id) ON DELETE SET NULL;
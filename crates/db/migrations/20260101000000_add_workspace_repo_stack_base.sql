@@ -0,0 +1,3 @@
+-- Repos of a child attempt stacked on its parent attempt's branch remember the parent
+-- commit they were last based on, so they can be restacked when the parent changes
+ALTER TABLE workspace_repos ADD COLUMN stack_base_commit TEXT;
@@ -0,0 +1,4 @@
+-- Newline-separated sparse-checkout patterns for the worktrees of a project repo;
+-- in cone mode each pattern is a directory to check out
+ALTER TABLE project_repos ADD COLUMN sparse_checkout TEXT;
+ALTER TABLE project_repos ADD COLUMN sparse_checkout_cone INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,29 @@
+-- Worktree pool: workspaces created and set up ahead of time so a new attempt can claim one
+-- instead of waiting for worktrees, file copies and setup scripts
+ALTER TABLE projects ADD COLUMN worktree_pool_size INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE pooled_workspaces (
+    id                  BLOB PRIMARY KEY,
+    project_id          BLOB NOT NULL,
+    container_ref       TEXT NOT NULL,
+    -- NULL while the worktrees are being created or refreshed; only ready entries are claimed
+    ready_at            TEXT,
+    created_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_pooled_workspaces_project_id
+    ON pooled_workspaces(project_id);
+
+-- The commit each repo's detached worktree was set up at
+CREATE TABLE pooled_workspace_repos (
+    id                   BLOB PRIMARY KEY,
+    pooled_workspace_id  BLOB NOT NULL,
+    repo_id              BLOB NOT NULL,
+    target_branch        TEXT NOT NULL,
+    base_commit          TEXT NOT NULL,
+    FOREIGN KEY (pooled_workspace_id) REFERENCES pooled_workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE,
+    UNIQUE (pooled_workspace_id, repo_id)
+);
@@ -0,0 +1,21 @@
+-- Rules that decide a project's tool call approvals before anyone is asked. They are
+-- checked in position order and the first one that matches decides; unset matchers match
+-- any executor, tool or input.
+CREATE TABLE approval_rules (
+    id                BLOB PRIMARY KEY,
+    project_id        BLOB NOT NULL,
+    name              TEXT NOT NULL,
+    position          INTEGER NOT NULL DEFAULT 0,
+    executor          TEXT,
+    variant           TEXT,
+    tool_pattern      TEXT,
+    input_pattern     TEXT,
+    outside_workspace BOOLEAN NOT NULL DEFAULT 0,
+    action            TEXT NOT NULL CHECK (action IN ('allow','deny','ask')),
+    reason            TEXT,
+    created_at        TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at        TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_approval_rules_project_id ON approval_rules(project_id, position);
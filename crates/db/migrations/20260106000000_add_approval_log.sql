@@ -0,0 +1,22 @@
+-- Every tool call approval and how it was decided, kept for auditing. Rows outlive the
+-- execution, task and project they belong to, so the ids are not foreign keys.
+CREATE TABLE approval_log (
+    id                   BLOB PRIMARY KEY,
+    project_id           BLOB NOT NULL,
+    task_id              BLOB NOT NULL,
+    execution_process_id BLOB NOT NULL,
+    tool_name            TEXT NOT NULL,
+    tool_call_id         TEXT NOT NULL,
+    tool_input           TEXT NOT NULL,
+    status               TEXT NOT NULL DEFAULT 'pending'
+                            CHECK (status IN ('pending','approved','denied','timed_out')),
+    deny_reason          TEXT,
+    -- Who decided: a user, an approval rule, or NULL while pending and on timeout
+    responder            TEXT,
+    requested_at         TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    responded_at         TEXT,
+    latency_ms           INTEGER
+);
+
+CREATE INDEX idx_approval_log_project_id ON approval_log(project_id, requested_at);
+CREATE INDEX idx_approval_log_task_id ON approval_log(task_id, requested_at);
@@ -0,0 +1,35 @@
+-- Requests whose execution ended, or whose server stopped, before anyone decided them are
+-- logged as cancelled instead of staying pending forever. SQLite can't change a CHECK
+-- constraint in place, so the table is rebuilt.
+CREATE TABLE approval_log_new (
+    id                   BLOB PRIMARY KEY,
+    project_id           BLOB NOT NULL,
+    task_id              BLOB NOT NULL,
+    execution_process_id BLOB NOT NULL,
+    tool_name            TEXT NOT NULL,
+    tool_call_id         TEXT NOT NULL,
+    tool_input           TEXT NOT NULL,
+    status               TEXT NOT NULL DEFAULT 'pending'
+                            CHECK (status IN ('pending','approved','denied','timed_out','cancelled')),
+    deny_reason          TEXT,
+    -- Who decided: a user, an approval rule, or NULL while pending, on timeout and when cancelled
+    responder            TEXT,
+    requested_at         TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    responded_at         TEXT,
+    latency_ms           INTEGER
+);
+
+INSERT INTO approval_log_new (id, project_id, task_id, execution_process_id, tool_name,
+                              tool_call_id, tool_input, status, deny_reason, responder,
+                              requested_at, responded_at, latency_ms)
+SELECT id, project_id, task_id, execution_process_id, tool_name, tool_call_id, tool_input,
+       status, deny_reason, responder, requested_at, responded_at, latency_ms
+FROM approval_log;
+
+DROP TABLE approval_log;
+
+ALTER TABLE approval_log_new RENAME TO approval_log;
+
+CREATE INDEX idx_approval_log_project_id ON approval_log(project_id, requested_at);
+CREATE INDEX idx_approval_log_task_id ON approval_log(task_id, requested_at);
+CREATE INDEX idx_approval_log_pending ON approval_log(execution_process_id) WHERE status = 'pending';
@@ -0,0 +1,13 @@
+-- Whether the dependent task is still waiting for its prerequisite to be done. Only finishing
+-- an awaited prerequisite starts the dependent task automatically, so adding a dependency on a
+-- task that is already done does not launch anything.
+ALTER TABLE task_dependencies ADD COLUMN awaited BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- Reopening a prerequisite makes its dependents wait for it again
+CREATE TRIGGER IF NOT EXISTS trg_task_dependencies_reawait
+AFTER UPDATE OF status ON tasks
+FOR EACH ROW
+WHEN OLD.status = 'done' AND NEW.status != 'done'
+BEGIN
+    UPDATE task_dependencies SET awaited = TRUE WHERE depends_on_task_id = NEW.id;
+END;
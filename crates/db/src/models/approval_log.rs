@@ -0,0 +1,202 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Most entries returned by one query
+const MAX_QUERY_LIMIT: i64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalLogStatus {
+    Pending,
+    Approved,
+    Denied,
+    TimedOut,
+    /// The execution ended, or the server stopped, before anyone decided
+    Cancelled,
+}
+
+/// A tool call approval and how it was decided
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ApprovalLogEntry {
+    /// Id of the approval request
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub task_id: Uuid,
+    pub execution_process_id: Uuid,
+    pub tool_name: String,
+    pub tool_call_id: String,
+    #[ts(type = "JsonValue")]
+    pub tool_input: sqlx::types::Json<Value>,
+    pub status: ApprovalLogStatus,
+    pub deny_reason: Option<String>,
+    /// Who decided: the responding user or the approval rule that fired. Unset while
+    /// pending and when the approval timed out or was cancelled.
+    pub responder: Option<String>,
+    pub requested_at: DateTime<Utc>,
+    pub responded_at: Option<DateTime<Utc>>,
+    /// Time from the request to its decision, timeout or cancellation
+    #[ts(type = "number | null")]
+    pub latency_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateApprovalLogEntry {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub task_id: Uuid,
+    pub execution_process_id: Uuid,
+    pub tool_name: String,
+    pub tool_call_id: String,
+    pub tool_input: Value,
+    pub requested_at: DateTime<Utc>,
+}
+
+/// Filters for [`ApprovalLogEntry::find`]; unset filters match every entry
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+pub struct ApprovalLogQuery {
+    pub project_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub tool_name: Option<String>,
+    /// Most recent entries to return, at most 500
+    #[ts(type = "number | null")]
+    pub limit: Option<i64>,
+}
+
+impl ApprovalLogEntry {
+    /// Log a request that is waiting for a decision
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateApprovalLogEntry,
+    ) -> Result<(), sqlx::Error> {
+        let tool_input = sqlx::types::Json(&data.tool_input);
+        sqlx::query!(
+            r#"INSERT INTO approval_log (id, project_id, task_id, execution_process_id, tool_name,
+                                         tool_call_id, tool_input, requested_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+            data.id,
+            data.project_id,
+            data.task_id,
+            data.execution_process_id,
+            data.tool_name,
+            data.tool_call_id,
+            tool_input,
+            data.requested_at
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record the decision of a pending request. Decided requests are left unchanged, so
+    /// a late timeout can't overwrite an answer.
+    pub async fn record_decision(
+        pool: &SqlitePool,
+        id: Uuid,
+        status: ApprovalLogStatus,
+        deny_reason: Option<&str>,
+        responder: Option<&str>,
+        responded_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE approval_log
+               SET status = $2,
+                   deny_reason = $3,
+                   responder = $4,
+                   responded_at = $5,
+                   latency_ms = CAST(ROUND((julianday($5) - julianday(requested_at)) * 86400000) AS INTEGER)
+               WHERE id = $1 AND status = 'pending'"#,
+            id,
+            status,
+            deny_reason,
+            responder,
+            responded_at
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Cancel the requests of an execution process that are still pending, once it has
+    /// exited and can no longer act on a decision
+    pub async fn cancel_pending_for_execution_process(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        cancelled_at: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE approval_log
+               SET status = 'cancelled',
+                   responded_at = $2,
+                   latency_ms = CAST(ROUND((julianday($2) - julianday(requested_at)) * 86400000) AS INTEGER)
+               WHERE execution_process_id = $1 AND status = 'pending'"#,
+            execution_process_id,
+            cancelled_at
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Cancel every pending request. Pending approvals only live in memory, so at startup
+    /// none of them can be answered anymore.
+    pub async fn cancel_all_pending(
+        pool: &SqlitePool,
+        cancelled_at: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE approval_log
+               SET status = 'cancelled',
+                   responded_at = $1,
+                   latency_ms = CAST(ROUND((julianday($1) - julianday(requested_at)) * 86400000) AS INTEGER)
+               WHERE status = 'pending'"#,
+            cancelled_at
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Most recent entries first
+    pub async fn find(
+        pool: &SqlitePool,
+        query: &ApprovalLogQuery,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let limit = query
+            .limit
+            .unwrap_or(MAX_QUERY_LIMIT)
+            .clamp(1, MAX_QUERY_LIMIT);
+        sqlx::query_as!(
+            ApprovalLogEntry,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      task_id as "task_id!: Uuid",
+                      execution_process_id as "execution_process_id!: Uuid",
+                      tool_name,
+                      tool_call_id,
+                      tool_input as "tool_input!: sqlx::types::Json<Value>",
+                      status as "status!: ApprovalLogStatus",
+                      deny_reason,
+                      responder,
+                      requested_at as "requested_at!: DateTime<Utc>",
+                      responded_at as "responded_at: DateTime<Utc>",
+                      latency_ms
+               FROM approval_log
+               WHERE ($1 IS NULL OR project_id = $1)
+                 AND ($2 IS NULL OR task_id = $2)
+                 AND ($3 IS NULL OR tool_name = $3)
+               ORDER BY requested_at DESC
+               LIMIT $4"#,
+            query.project_id,
+            query.task_id,
+            query.tool_name,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,201 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// What an approval rule does with the tool calls it matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalRuleAction {
+    /// Approve without asking
+    Allow,
+    /// Deny without asking, telling the agent the rule's reason
+    Deny,
+    /// Always ask, even when a later rule would decide
+    Ask,
+}
+
+/// A project's rule for deciding tool call approvals before anyone is asked
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ApprovalRule {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub name: String,
+    /// Rules are checked in ascending position and the first match decides
+    #[ts(type = "number")]
+    pub position: i64,
+    /// Executor the rule applies to, e.g. `CLAUDE_CODE`; every executor when unset
+    pub executor: Option<String>,
+    /// Executor profile variant the rule applies to; every variant when unset
+    pub variant: Option<String>,
+    /// Regex the whole tool name must match; every tool when unset
+    pub tool_pattern: Option<String>,
+    /// Regex searched for in each string of the tool input; every input when unset. Allow
+    /// rules must match the whole command, or path for file tools, so `cargo test.*` is needed
+    /// to allow arguments, and never match commands that chain or redirect others.
+    pub input_pattern: Option<String>,
+    /// Only match tool calls with a path argument outside the workspace
+    pub outside_workspace: bool,
+    pub action: ApprovalRuleAction,
+    /// Given to the agent when the rule denies a tool call
+    pub reason: Option<String>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Every field of a rule; updates replace the whole rule
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreateApprovalRule {
+    pub name: String,
+    /// Appended after the project's other rules when omitted
+    #[ts(type = "number | null")]
+    pub position: Option<i64>,
+    pub executor: Option<String>,
+    pub variant: Option<String>,
+    pub tool_pattern: Option<String>,
+    pub input_pattern: Option<String>,
+    #[serde(default)]
+    pub outside_workspace: bool,
+    pub action: ApprovalRuleAction,
+    pub reason: Option<String>,
+}
+
+impl ApprovalRule {
+    /// Rules of a project in the order they are checked
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ApprovalRule,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      name,
+                      position,
+                      executor,
+                      variant,
+                      tool_pattern,
+                      input_pattern,
+                      outside_workspace as "outside_workspace!: bool",
+                      action as "action!: ApprovalRuleAction",
+                      reason,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM approval_rules
+               WHERE project_id = $1
+               ORDER BY position ASC, created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &CreateApprovalRule,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            ApprovalRule,
+            r#"INSERT INTO approval_rules (id, project_id, name, position, executor, variant,
+                                           tool_pattern, input_pattern, outside_workspace,
+                                           action, reason)
+               VALUES ($1, $2, $3,
+                       COALESCE($4, (SELECT COALESCE(MAX(position) + 1, 0)
+                                     FROM approval_rules WHERE project_id = $2)),
+                       $5, $6, $7, $8, $9, $10, $11)
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         name,
+                         position,
+                         executor,
+                         variant,
+                         tool_pattern,
+                         input_pattern,
+                         outside_workspace as "outside_workspace!: bool",
+                         action as "action!: ApprovalRuleAction",
+                         reason,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            data.name,
+            data.position,
+            data.executor,
+            data.variant,
+            data.tool_pattern,
+            data.input_pattern,
+            data.outside_workspace,
+            data.action,
+            data.reason
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Replace a rule of the project; `None` when the project has no such rule
+    pub async fn update(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        id: Uuid,
+        data: &CreateApprovalRule,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ApprovalRule,
+            r#"UPDATE approval_rules
+               SET name = $3,
+                   position = COALESCE($4, position),
+                   executor = $5,
+                   variant = $6,
+                   tool_pattern = $7,
+                   input_pattern = $8,
+                   outside_workspace = $9,
+                   action = $10,
+                   reason = $11,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1 AND project_id = $2
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         name,
+                         position,
+                         executor,
+                         variant,
+                         tool_pattern,
+                         input_pattern,
+                         outside_workspace as "outside_workspace!: bool",
+                         action as "action!: ApprovalRuleAction",
+                         reason,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            data.name,
+            data.position,
+            data.executor,
+            data.variant,
+            data.tool_pattern,
+            data.input_pattern,
+            data.outside_workspace,
+            data.action,
+            data.reason
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, project_id: Uuid, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM approval_rules WHERE id = $1 AND project_id = $2",
+            id,
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
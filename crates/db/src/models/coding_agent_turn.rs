@@ -11,6 +11,8 @@ pub struct CodingAgentTurn {
     pub agent_session_id: Option<String>, // Session ID from Claude/Amp coding agent
     pub prompt: Option<String>,           // The prompt sent to the executor
     pub summary: Option<String>,          // Final assistant message/summary
+    pub handoff_from_turn_id: Option<Uuid>, // Turn of the executor this one took over from
+    pub conflict_repo_id: Option<Uuid>,   // Repo whose rebase conflicts this turn resolves
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -19,6 +21,8 @@ pub struct CodingAgentTurn {
 pub struct CreateCodingAgentTurn {
     pub execution_process_id: Uuid,
     pub prompt: Option<String>,
+    /// Turn of the executor this one takes the session over from
+    pub handoff_from_turn_id: Option<Uuid>,
 }
 
 impl CodingAgentTurn {
@@ -35,6 +39,8 @@ impl CodingAgentTurn {
                 agent_session_id,
                 prompt,
                 summary,
+                handoff_from_turn_id as "handoff_from_turn_id: Uuid",
+                conflict_repo_id as "conflict_repo_id: Uuid",
                 created_at as "created_at!: DateTime<Utc>",
                 updated_at as "updated_at!: DateTime<Utc>"
                FROM coding_agent_turns
@@ -57,6 +63,8 @@ impl CodingAgentTurn {
                 agent_session_id,
                 prompt,
                 summary,
+                handoff_from_turn_id as "handoff_from_turn_id: Uuid",
+                conflict_repo_id as "conflict_repo_id: Uuid",
                 created_at as "created_at!: DateTime<Utc>",
                 updated_at as "updated_at!: DateTime<Utc>"
                FROM coding_agent_turns
@@ -69,6 +77,35 @@ impl CodingAgentTurn {
         .await
     }
 
+    /// Find the turns of a session's coding agent runs, oldest first
+    pub async fn find_by_session_id(
+        pool: &SqlitePool,
+        session_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            CodingAgentTurn,
+            r#"SELECT
+                cat.id as "id!: Uuid",
+                cat.execution_process_id as "execution_process_id!: Uuid",
+                cat.agent_session_id,
+                cat.prompt,
+                cat.summary,
+                cat.handoff_from_turn_id as "handoff_from_turn_id: Uuid",
+                cat.conflict_repo_id as "conflict_repo_id: Uuid",
+                cat.created_at as "created_at!: DateTime<Utc>",
+                cat.updated_at as "updated_at!: DateTime<Utc>"
+               FROM coding_agent_turns cat
+               JOIN execution_processes ep ON ep.id = cat.execution_process_id
+               WHERE ep.session_id = $1
+                 AND ep.run_reason = 'codingagent'
+                 AND ep.dropped = FALSE
+               ORDER BY ep.created_at ASC"#,
+            session_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     /// Create a new coding agent turn
     pub async fn create(
         pool: &SqlitePool,
@@ -87,15 +124,17 @@ impl CodingAgentTurn {
             CodingAgentTurn,
             r#"INSERT INTO coding_agent_turns (
                 id, execution_process_id, agent_session_id, prompt, summary,
-                created_at, updated_at
+                handoff_from_turn_id, created_at, updated_at
                )
-               VALUES ($1, $2, $3, $4, $5, $6, $7)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
                RETURNING
                 id as "id!: Uuid",
                 execution_process_id as "execution_process_id!: Uuid",
                 agent_session_id,
                 prompt,
                 summary,
+                handoff_from_turn_id as "handoff_from_turn_id: Uuid",
+                conflict_repo_id as "conflict_repo_id: Uuid",
                 created_at as "created_at!: DateTime<Utc>",
                 updated_at as "updated_at!: DateTime<Utc>""#,
             id,
@@ -103,8 +142,9 @@ impl CodingAgentTurn {
             None::<String>, // agent_session_id initially None until parsed from output
             data.prompt,
             None::<String>, // summary initially None
-            now,            // created_at
-            now             // updated_at
+            data.handoff_from_turn_id,
+            now, // created_at
+            now  // updated_at
         )
         .fetch_one(pool)
         .await
@@ -151,4 +191,25 @@ impl CodingAgentTurn {
 
         Ok(())
     }
+
+    /// Mark a turn as resolving the rebase conflicts of a workspace repo
+    pub async fn update_conflict_repo_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        conflict_repo_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query!(
+            r#"UPDATE coding_agent_turns
+               SET conflict_repo_id = $1, updated_at = $2
+               WHERE execution_process_id = $3"#,
+            conflict_repo_id,
+            now,
+            execution_process_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,159 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Per-execution limits configured for a project or a single task
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ExecutionBudget {
+    pub id: Uuid,
+    pub project_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub max_tokens: Option<u32>,
+    pub max_runtime_seconds: Option<u32>,
+    pub max_tool_calls: Option<u32>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+pub struct UpdateExecutionBudget {
+    pub max_tokens: Option<u32>,
+    pub max_runtime_seconds: Option<u32>,
+    pub max_tool_calls: Option<u32>,
+}
+
+impl ExecutionBudget {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionBudget,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id: Uuid",
+                      task_id as "task_id: Uuid",
+                      max_tokens as "max_tokens: u32",
+                      max_runtime_seconds as "max_runtime_seconds: u32",
+                      max_tool_calls as "max_tool_calls: u32",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM execution_budgets
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionBudget,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id: Uuid",
+                      task_id as "task_id: Uuid",
+                      max_tokens as "max_tokens: u32",
+                      max_runtime_seconds as "max_runtime_seconds: u32",
+                      max_tool_calls as "max_tool_calls: u32",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM execution_budgets
+               WHERE task_id = $1"#,
+            task_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn upsert_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpdateExecutionBudget,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            ExecutionBudget,
+            r#"INSERT INTO execution_budgets (id, project_id, max_tokens, max_runtime_seconds, max_tool_calls)
+               VALUES ($1, $2, $3, $4, $5)
+               ON CONFLICT(project_id) DO UPDATE SET
+                   max_tokens = excluded.max_tokens,
+                   max_runtime_seconds = excluded.max_runtime_seconds,
+                   max_tool_calls = excluded.max_tool_calls,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id: Uuid",
+                         task_id as "task_id: Uuid",
+                         max_tokens as "max_tokens: u32",
+                         max_runtime_seconds as "max_runtime_seconds: u32",
+                         max_tool_calls as "max_tool_calls: u32",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            data.max_tokens,
+            data.max_runtime_seconds,
+            data.max_tool_calls
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn upsert_for_task(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        data: &UpdateExecutionBudget,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            ExecutionBudget,
+            r#"INSERT INTO execution_budgets (id, task_id, max_tokens, max_runtime_seconds, max_tool_calls)
+               VALUES ($1, $2, $3, $4, $5)
+               ON CONFLICT(task_id) DO UPDATE SET
+                   max_tokens = excluded.max_tokens,
+                   max_runtime_seconds = excluded.max_runtime_seconds,
+                   max_tool_calls = excluded.max_tool_calls,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id: Uuid",
+                         task_id as "task_id: Uuid",
+                         max_tokens as "max_tokens: u32",
+                         max_runtime_seconds as "max_runtime_seconds: u32",
+                         max_tool_calls as "max_tool_calls: u32",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            task_id,
+            data.max_tokens,
+            data.max_runtime_seconds,
+            data.max_tool_calls
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM execution_budgets WHERE project_id = $1",
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn delete_for_task(pool: &SqlitePool, task_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM execution_budgets WHERE task_id = $1", task_id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -41,10 +41,16 @@ pub enum ExecutionProcessError {
 #[serde(rename_all = "lowercase")]
 #[ts(use_ts_enum)]
 pub enum ExecutionProcessStatus {
+    /// Waiting for a concurrency slot before it is spawned
+    Queued,
     Running,
     Completed,
     Failed,
     Killed,
+    /// Interrupted after running past its token, runtime or tool call budget
+    #[serde(rename = "budget_exceeded")]
+    #[sqlx(rename = "budget_exceeded")]
+    BudgetExceeded,
 }
 
 #[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS)]
@@ -53,6 +59,8 @@ pub enum ExecutionProcessStatus {
 pub enum ExecutionProcessRunReason {
     SetupScript,
     CleanupScript,
+    /// Exit code decides whether the task can move to In Review
+    VerifyScript,
     CodingAgent,
     DevServer,
 }
@@ -344,7 +352,8 @@ impl ExecutionProcess {
         .await
     }
 
-    /// Find latest coding_agent_turn agent_session_id by session (simple scalar query)
+    /// Find latest coding_agent_turn agent_session_id by session (simple scalar query).
+    /// Agent sessions from before the latest executor handoff are never returned.
     pub async fn find_latest_coding_agent_turn_session_id(
         pool: &SqlitePool,
         session_id: Uuid,
@@ -361,6 +370,16 @@ impl ExecutionProcess {
                  AND ep.run_reason = 'codingagent'
                  AND ep.dropped = FALSE
                  AND cat.agent_session_id IS NOT NULL
+                 AND NOT EXISTS (
+                     SELECT 1
+                     FROM execution_processes later_ep
+                     JOIN coding_agent_turns later_cat
+                       ON later_ep.id = later_cat.execution_process_id
+                     WHERE later_ep.session_id = ep.session_id
+                       AND later_ep.dropped = FALSE
+                       AND later_cat.handoff_from_turn_id IS NOT NULL
+                       AND later_ep.created_at > ep.created_at
+                 )
                ORDER BY ep.created_at DESC
                LIMIT 1"#,
             session_id
@@ -434,6 +453,85 @@ impl ExecutionProcess {
         .await
     }
 
+    /// Find queued execution processes, oldest first
+    pub async fn find_queued(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcess,
+            r#"SELECT
+                    ep.id as "id!: Uuid",
+                    ep.session_id as "session_id!: Uuid",
+                    ep.run_reason as "run_reason!: ExecutionProcessRunReason",
+                    ep.executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
+                    ep.status as "status!: ExecutionProcessStatus",
+                    ep.exit_code,
+                    ep.dropped as "dropped!: bool",
+                    ep.started_at as "started_at!: DateTime<Utc>",
+                    ep.completed_at as "completed_at?: DateTime<Utc>",
+                    ep.created_at as "created_at!: DateTime<Utc>",
+                    ep.updated_at as "updated_at!: DateTime<Utc>"
+               FROM execution_processes ep WHERE ep.status = 'queued' ORDER BY ep.created_at ASC"#,
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Count workspaces that currently have a running non-dev-server process,
+    /// optionally restricted to a single project
+    pub async fn count_active_workspaces(
+        pool: &SqlitePool,
+        project_id: Option<Uuid>,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(DISTINCT s.workspace_id) as "count!: i64"
+               FROM execution_processes ep
+               JOIN sessions s ON ep.session_id = s.id
+               JOIN workspaces w ON s.workspace_id = w.id
+               JOIN tasks t ON w.task_id = t.id
+               WHERE ep.status = 'running'
+                 AND ep.run_reason != 'devserver'
+                 AND ($1 IS NULL OR t.project_id = $1)"#,
+            project_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Count failed processes of one run reason in a session, ignoring dropped ones
+    pub async fn count_failed_by_session_and_run_reason(
+        pool: &SqlitePool,
+        session_id: Uuid,
+        run_reason: &ExecutionProcessRunReason,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64"
+               FROM execution_processes
+               WHERE session_id = $1
+                 AND run_reason = $2
+                 AND status = 'failed'
+                 AND dropped = FALSE"#,
+            session_id,
+            run_reason
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Mark a queued process as running, resetting its start time
+    pub async fn mark_started(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET status = $1, started_at = $2
+               WHERE id = $3"#,
+            ExecutionProcessStatus::Running,
+            now,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     /// Create a new execution process
     ///
     /// Note: We intentionally avoid using a transaction here. SQLite update
@@ -446,6 +544,7 @@ impl ExecutionProcess {
         data: &CreateExecutionProcess,
         process_id: Uuid,
         repo_states: &[CreateExecutionProcessRepoState],
+        status: ExecutionProcessStatus,
     ) -> Result<Self, sqlx::Error> {
         let now = Utc::now();
         let executor_action_json = sqlx::types::Json(&data.executor_action);
@@ -459,7 +558,7 @@ impl ExecutionProcess {
             data.session_id,
             data.run_reason,
             executor_action_json,
-            ExecutionProcessStatus::Running,
+            status,
             None::<i64>,
             now,
             None::<DateTime<Utc>>,
@@ -480,6 +579,7 @@ impl ExecutionProcess {
         if let Ok(exp_process) = Self::find_by_id(pool, id).await
             && exp_process.is_some_and(|ep| {
                 ep.status == ExecutionProcessStatus::Killed
+                    || ep.status == ExecutionProcessStatus::BudgetExceeded
                     || ep.status == ExecutionProcessStatus::Completed
             })
         {
@@ -495,7 +595,10 @@ impl ExecutionProcess {
         status: ExecutionProcessStatus,
         exit_code: Option<i64>,
     ) -> Result<(), sqlx::Error> {
-        let completed_at = if matches!(status, ExecutionProcessStatus::Running) {
+        let completed_at = if matches!(
+            status,
+            ExecutionProcessStatus::Queued | ExecutionProcessStatus::Running
+        ) {
             None
         } else {
             Some(Utc::now())
@@ -0,0 +1,240 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use utils::usage::UsageReport;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ExecutionProcessUsage {
+    pub execution_process_id: Uuid,
+    pub coding_agent_turn_id: Option<Uuid>,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_creation_input_tokens: i64,
+    pub cache_read_input_tokens: i64,
+    pub cost_usd: Option<f64>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Usage summed over a set of execution processes
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct UsageSummary {
+    pub execution_count: i64,
+    /// Coding agent executions without a usage report, because their agent does not report
+    /// usage or never got far enough to. The token totals leave them out rather than count 0.
+    pub unreported_execution_count: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_creation_input_tokens: i64,
+    pub cache_read_input_tokens: i64,
+    pub cost_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct DailyUsage {
+    pub day: String, // YYYY-MM-DD in UTC
+    pub execution_count: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_creation_input_tokens: i64,
+    pub cache_read_input_tokens: i64,
+    pub cost_usd: f64,
+}
+
+impl ExecutionProcessUsage {
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcessUsage,
+            r#"SELECT execution_process_id as "execution_process_id!: Uuid",
+                      coding_agent_turn_id as "coding_agent_turn_id: Uuid",
+                      input_tokens,
+                      output_tokens,
+                      cache_creation_input_tokens,
+                      cache_read_input_tokens,
+                      cost_usd,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM execution_process_usage
+               WHERE execution_process_id = $1"#,
+            execution_process_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_coding_agent_turn_id(
+        pool: &SqlitePool,
+        coding_agent_turn_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcessUsage,
+            r#"SELECT execution_process_id as "execution_process_id!: Uuid",
+                      coding_agent_turn_id as "coding_agent_turn_id: Uuid",
+                      input_tokens,
+                      output_tokens,
+                      cache_creation_input_tokens,
+                      cache_read_input_tokens,
+                      cost_usd,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM execution_process_usage
+               WHERE coding_agent_turn_id = $1"#,
+            coding_agent_turn_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Store the latest usage report for an execution process. Reports are cumulative,
+    /// so this replaces any previous report rather than adding to it.
+    pub async fn upsert(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        usage: &UsageReport,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO execution_process_usage (
+                   execution_process_id, coding_agent_turn_id, input_tokens, output_tokens,
+                   cache_creation_input_tokens, cache_read_input_tokens, cost_usd
+               )
+               VALUES (
+                   $1,
+                   (SELECT id FROM coding_agent_turns WHERE execution_process_id = $1),
+                   $2, $3, $4, $5, $6
+               )
+               ON CONFLICT(execution_process_id) DO UPDATE SET
+                   coding_agent_turn_id = excluded.coding_agent_turn_id,
+                   input_tokens = excluded.input_tokens,
+                   output_tokens = excluded.output_tokens,
+                   cache_creation_input_tokens = excluded.cache_creation_input_tokens,
+                   cache_read_input_tokens = excluded.cache_read_input_tokens,
+                   cost_usd = excluded.cost_usd,
+                   updated_at = datetime('now', 'subsec')"#,
+            execution_process_id,
+            usage.input_tokens,
+            usage.output_tokens,
+            usage.cache_creation_input_tokens,
+            usage.cache_read_input_tokens,
+            usage.cost_usd
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Total usage across every execution of every attempt of a task
+    pub async fn summary_for_task(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<UsageSummary, sqlx::Error> {
+        sqlx::query_as!(
+            UsageSummary,
+            r#"SELECT COUNT(u.execution_process_id) as "execution_count!: i64",
+                      COUNT(ep.id) - COUNT(u.execution_process_id) as "unreported_execution_count!: i64",
+                      COALESCE(SUM(u.input_tokens), 0) as "input_tokens!: i64",
+                      COALESCE(SUM(u.output_tokens), 0) as "output_tokens!: i64",
+                      COALESCE(SUM(u.cache_creation_input_tokens), 0) as "cache_creation_input_tokens!: i64",
+                      COALESCE(SUM(u.cache_read_input_tokens), 0) as "cache_read_input_tokens!: i64",
+                      COALESCE(SUM(u.cost_usd), 0.0) as "cost_usd!: f64"
+               FROM execution_processes ep
+               LEFT JOIN execution_process_usage u ON u.execution_process_id = ep.id
+               JOIN sessions s ON ep.session_id = s.id
+               JOIN workspaces w ON s.workspace_id = w.id
+               WHERE w.task_id = $1
+                 AND ep.run_reason = 'codingagent'"#,
+            task_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Total usage across every execution of a single attempt
+    pub async fn summary_for_workspace(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<UsageSummary, sqlx::Error> {
+        sqlx::query_as!(
+            UsageSummary,
+            r#"SELECT COUNT(u.execution_process_id) as "execution_count!: i64",
+                      COUNT(ep.id) - COUNT(u.execution_process_id) as "unreported_execution_count!: i64",
+                      COALESCE(SUM(u.input_tokens), 0) as "input_tokens!: i64",
+                      COALESCE(SUM(u.output_tokens), 0) as "output_tokens!: i64",
+                      COALESCE(SUM(u.cache_creation_input_tokens), 0) as "cache_creation_input_tokens!: i64",
+                      COALESCE(SUM(u.cache_read_input_tokens), 0) as "cache_read_input_tokens!: i64",
+                      COALESCE(SUM(u.cost_usd), 0.0) as "cost_usd!: f64"
+               FROM execution_processes ep
+               LEFT JOIN execution_process_usage u ON u.execution_process_id = ep.id
+               JOIN sessions s ON ep.session_id = s.id
+               WHERE s.workspace_id = $1
+                 AND ep.run_reason = 'codingagent'"#,
+            workspace_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Total usage across every task of a project
+    pub async fn summary_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<UsageSummary, sqlx::Error> {
+        sqlx::query_as!(
+            UsageSummary,
+            r#"SELECT COUNT(u.execution_process_id) as "execution_count!: i64",
+                      COUNT(ep.id) - COUNT(u.execution_process_id) as "unreported_execution_count!: i64",
+                      COALESCE(SUM(u.input_tokens), 0) as "input_tokens!: i64",
+                      COALESCE(SUM(u.output_tokens), 0) as "output_tokens!: i64",
+                      COALESCE(SUM(u.cache_creation_input_tokens), 0) as "cache_creation_input_tokens!: i64",
+                      COALESCE(SUM(u.cache_read_input_tokens), 0) as "cache_read_input_tokens!: i64",
+                      COALESCE(SUM(u.cost_usd), 0.0) as "cost_usd!: f64"
+               FROM execution_processes ep
+               LEFT JOIN execution_process_usage u ON u.execution_process_id = ep.id
+               JOIN sessions s ON ep.session_id = s.id
+               JOIN workspaces w ON s.workspace_id = w.id
+               JOIN tasks t ON w.task_id = t.id
+               WHERE t.project_id = $1
+                 AND ep.run_reason = 'codingagent'"#,
+            project_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Usage per UTC day over the last `days` days, optionally limited to one project
+    pub async fn daily(
+        pool: &SqlitePool,
+        project_id: Option<Uuid>,
+        days: i64,
+    ) -> Result<Vec<DailyUsage>, sqlx::Error> {
+        sqlx::query_as!(
+            DailyUsage,
+            r#"SELECT date(u.created_at) as "day!: String",
+                      COUNT(u.execution_process_id) as "execution_count!: i64",
+                      COALESCE(SUM(u.input_tokens), 0) as "input_tokens!: i64",
+                      COALESCE(SUM(u.output_tokens), 0) as "output_tokens!: i64",
+                      COALESCE(SUM(u.cache_creation_input_tokens), 0) as "cache_creation_input_tokens!: i64",
+                      COALESCE(SUM(u.cache_read_input_tokens), 0) as "cache_read_input_tokens!: i64",
+                      COALESCE(SUM(u.cost_usd), 0.0) as "cost_usd!: f64"
+               FROM execution_process_usage u
+               JOIN execution_processes ep ON u.execution_process_id = ep.id
+               JOIN sessions s ON ep.session_id = s.id
+               JOIN workspaces w ON s.workspace_id = w.id
+               JOIN tasks t ON w.task_id = t.id
+               WHERE ($1 IS NULL OR t.project_id = $1)
+                 AND u.created_at >= datetime('now', '-' || $2 || ' days')
+               GROUP BY date(u.created_at)
+               ORDER BY date(u.created_at) ASC"#,
+            project_id,
+            days
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,309 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Type)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum MergeQueueStatus {
+    /// Waiting for the entries ahead of it on the same target branch or of the same workspace
+    Queued,
+    Rebasing,
+    /// Running the repo's setup and verify scripts on the rebased branch
+    Verifying,
+    Merging,
+    Merged,
+    Failed,
+    Cancelled,
+}
+
+/// A workspace repo waiting to be rebased, verified and merged into its target branch
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct MergeQueueEntry {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub workspace_id: Uuid,
+    pub repo_id: Uuid,
+    pub target_branch: String,
+    pub status: MergeQueueStatus,
+    /// Why the entry failed, shown on the task
+    pub failure_reason: Option<String>,
+    pub merge_commit: Option<String>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateMergeQueueEntry {
+    pub project_id: Uuid,
+    pub workspace_id: Uuid,
+    pub repo_id: Uuid,
+    pub target_branch: String,
+}
+
+impl MergeQueueEntry {
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateMergeQueueEntry,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            MergeQueueEntry,
+            r#"INSERT INTO merge_queue_entries (id, project_id, workspace_id, repo_id, target_branch)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         repo_id as "repo_id!: Uuid",
+                         target_branch,
+                         status as "status!: MergeQueueStatus",
+                         failure_reason,
+                         merge_commit,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            data.project_id,
+            data.workspace_id,
+            data.repo_id,
+            data.target_branch
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            MergeQueueEntry,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      target_branch,
+                      status as "status!: MergeQueueStatus",
+                      failure_reason,
+                      merge_commit,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM merge_queue_entries
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            MergeQueueEntry,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      target_branch,
+                      status as "status!: MergeQueueStatus",
+                      failure_reason,
+                      merge_commit,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM merge_queue_entries
+               WHERE rowid = $1"#,
+            rowid
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Active entries of a project plus those that finished in the last day, in queue order
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            MergeQueueEntry,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      target_branch,
+                      status as "status!: MergeQueueStatus",
+                      failure_reason,
+                      merge_commit,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM merge_queue_entries
+               WHERE project_id = $1
+                 AND (status IN ('queued', 'rebasing', 'verifying', 'merging')
+                      OR updated_at > datetime('now', '-1 day'))
+               ORDER BY rowid ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_active_for_workspace_repo(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        repo_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            MergeQueueEntry,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      target_branch,
+                      status as "status!: MergeQueueStatus",
+                      failure_reason,
+                      merge_commit,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM merge_queue_entries
+               WHERE workspace_id = $1
+                 AND repo_id = $2
+                 AND status IN ('queued', 'rebasing', 'verifying', 'merging')"#,
+            workspace_id,
+            repo_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// The oldest queued entry of every repo and target branch that has nothing in progress.
+    /// Entries of one workspace are landed one at a time, oldest first, since each moves the
+    /// workspace's task and refuses to start while another runs scripts in the workspace.
+    pub async fn find_ready(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            MergeQueueEntry,
+            r#"SELECT q.id as "id!: Uuid",
+                      q.project_id as "project_id!: Uuid",
+                      q.workspace_id as "workspace_id!: Uuid",
+                      q.repo_id as "repo_id!: Uuid",
+                      q.target_branch,
+                      q.status as "status!: MergeQueueStatus",
+                      q.failure_reason,
+                      q.merge_commit,
+                      q.created_at as "created_at!: DateTime<Utc>",
+                      q.updated_at as "updated_at!: DateTime<Utc>"
+               FROM merge_queue_entries q
+               WHERE q.status = 'queued'
+                 AND q.rowid = (
+                     SELECT MIN(h.rowid) FROM merge_queue_entries h
+                     WHERE h.repo_id = q.repo_id
+                       AND h.target_branch = q.target_branch
+                       AND h.status = 'queued'
+                 )
+                 AND NOT EXISTS (
+                     SELECT 1 FROM merge_queue_entries r
+                     WHERE r.repo_id = q.repo_id
+                       AND r.target_branch = q.target_branch
+                       AND r.status IN ('rebasing', 'verifying', 'merging')
+                 )
+                 AND NOT EXISTS (
+                     SELECT 1 FROM merge_queue_entries w
+                     WHERE w.workspace_id = q.workspace_id
+                       AND (w.status IN ('rebasing', 'verifying', 'merging')
+                            OR (w.status = 'queued' AND w.rowid < q.rowid))
+                 )
+               ORDER BY q.rowid ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Whether the workspace still has entries waiting or being processed
+    pub async fn has_active_for_workspace(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"SELECT EXISTS(
+                   SELECT 1 FROM merge_queue_entries
+                   WHERE workspace_id = $1
+                     AND status IN ('queued', 'rebasing', 'verifying', 'merging')
+               ) as "active!: bool""#,
+            workspace_id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(result.active)
+    }
+
+    pub async fn update_status(
+        pool: &SqlitePool,
+        id: Uuid,
+        status: MergeQueueStatus,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE merge_queue_entries
+               SET status = $2, updated_at = datetime('now', 'subsec')
+               WHERE id = $1"#,
+            id,
+            status
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn mark_merged(
+        pool: &SqlitePool,
+        id: Uuid,
+        merge_commit: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE merge_queue_entries
+               SET status = 'merged', merge_commit = $2, updated_at = datetime('now', 'subsec')
+               WHERE id = $1"#,
+            id,
+            merge_commit
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn mark_failed(pool: &SqlitePool, id: Uuid, reason: &str) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE merge_queue_entries
+               SET status = 'failed', failure_reason = $2, updated_at = datetime('now', 'subsec')
+               WHERE id = $1"#,
+            id,
+            reason
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Remove a queued entry from the queue. Entries already being processed are left alone;
+    /// returns whether the entry was cancelled.
+    pub async fn cancel(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE merge_queue_entries
+               SET status = 'cancelled', updated_at = datetime('now', 'subsec')
+               WHERE id = $1 AND status = 'queued'"#,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Fail entries that were being processed when the app stopped, call at startup
+    pub async fn fail_interrupted(pool: &SqlitePool, reason: &str) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE merge_queue_entries
+               SET status = 'failed', failure_reason = $1, updated_at = datetime('now', 'subsec')
+               WHERE status IN ('rebasing', 'verifying', 'merging')"#,
+            reason
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
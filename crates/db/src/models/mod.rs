@@ -1,9 +1,15 @@
+pub mod approval_log;
+pub mod approval_rule;
 pub mod coding_agent_turn;
+pub mod execution_budget;
 pub mod execution_process;
 pub mod execution_process_logs;
 pub mod execution_process_repo_state;
+pub mod execution_process_usage;
 pub mod image;
 pub mod merge;
+pub mod merge_queue;
+pub mod pooled_workspace;
 pub mod project;
 pub mod project_repo;
 pub mod repo;
@@ -11,5 +17,6 @@ pub mod scratch;
 pub mod session;
 pub mod tag;
 pub mod task;
+pub mod task_dependency;
 pub mod workspace;
 pub mod workspace_repo;
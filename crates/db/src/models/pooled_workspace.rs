@@ -0,0 +1,214 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+/// A workspace directory of detached worktrees, created and set up ahead of time so a new
+/// attempt of its project can claim it
+#[derive(Debug, Clone, FromRow)]
+pub struct PooledWorkspace {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub container_ref: String,
+    /// Set once the worktrees are checked out and set up; unset while they are refreshed
+    pub ready_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The commit of a target branch that one repo of a pooled workspace is checked out at
+#[derive(Debug, Clone, FromRow)]
+pub struct PooledWorkspaceRepo {
+    pub id: Uuid,
+    pub pooled_workspace_id: Uuid,
+    pub repo_id: Uuid,
+    pub target_branch: String,
+    pub base_commit: String,
+}
+
+impl PooledWorkspace {
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        container_ref: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            PooledWorkspace,
+            r#"INSERT INTO pooled_workspaces (id, project_id, container_ref)
+               VALUES ($1, $2, $3)
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         container_ref,
+                         ready_at as "ready_at: DateTime<Utc>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            container_ref
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Pooled workspaces of a project, oldest first
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PooledWorkspace,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      container_ref,
+                      ready_at as "ready_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM pooled_workspaces
+               WHERE project_id = $1
+               ORDER BY created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Pooled workspaces that were left half created or half refreshed
+    pub async fn find_not_ready(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PooledWorkspace,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      container_ref,
+                      ready_at as "ready_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM pooled_workspaces
+               WHERE ready_at IS NULL"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn mark_ready(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE pooled_workspaces
+               SET ready_at = datetime('now', 'subsec'), updated_at = datetime('now', 'subsec')
+               WHERE id = $1"#,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Take a ready pooled workspace out of circulation for a refresh. Returns false when it
+    /// was claimed or taken first.
+    pub async fn take_for_refresh(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE pooled_workspaces
+               SET ready_at = NULL, updated_at = datetime('now', 'subsec')
+               WHERE id = $1 AND ready_at IS NOT NULL"#,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Remove a ready pooled workspace from the pool for an attempt. Returns false when
+    /// another attempt claimed it first or it is being refreshed.
+    pub async fn claim(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM pooled_workspaces WHERE id = $1 AND ready_at IS NOT NULL",
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Returns false when the pooled workspace was already claimed or removed
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM pooled_workspaces WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn container_ref_exists(
+        pool: &SqlitePool,
+        container_ref: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"SELECT EXISTS(SELECT 1 FROM pooled_workspaces WHERE container_ref = ?) as "exists!: bool""#,
+            container_ref
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(result.exists)
+    }
+}
+
+impl PooledWorkspaceRepo {
+    pub async fn create(
+        pool: &SqlitePool,
+        pooled_workspace_id: Uuid,
+        repo_id: Uuid,
+        target_branch: &str,
+        base_commit: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            PooledWorkspaceRepo,
+            r#"INSERT INTO pooled_workspace_repos (id, pooled_workspace_id, repo_id, target_branch, base_commit)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id as "id!: Uuid",
+                         pooled_workspace_id as "pooled_workspace_id!: Uuid",
+                         repo_id as "repo_id!: Uuid",
+                         target_branch,
+                         base_commit"#,
+            id,
+            pooled_workspace_id,
+            repo_id,
+            target_branch,
+            base_commit
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_pooled_workspace_id(
+        pool: &SqlitePool,
+        pooled_workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PooledWorkspaceRepo,
+            r#"SELECT id as "id!: Uuid",
+                      pooled_workspace_id as "pooled_workspace_id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      target_branch,
+                      base_commit
+               FROM pooled_workspace_repos
+               WHERE pooled_workspace_id = $1"#,
+            pooled_workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn update_base_commit(
+        pool: &SqlitePool,
+        id: Uuid,
+        base_commit: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE pooled_workspace_repos SET base_commit = $1 WHERE id = $2",
+            base_commit,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{Executor, FromRow, Sqlite, SqlitePool};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool, Type};
 use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
@@ -17,6 +17,20 @@ pub enum ProjectError {
     CreateFailed(String),
 }
 
+/// How a direct merge lands the task branch on its target branch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS, Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// One commit containing all of the task's changes
+    #[default]
+    Squash,
+    /// Keep the agent's individual commits and fast-forward the target branch to them
+    Rebase,
+    /// Keep the agent's commits and join them with a merge commit
+    MergeCommit,
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct Project {
     pub id: Uuid,
@@ -25,6 +39,13 @@ pub struct Project {
     pub dev_script_working_dir: Option<String>,
     pub default_agent_working_dir: Option<String>,
     pub remote_project_id: Option<Uuid>,
+    pub merge_strategy: MergeStrategy,
+    /// Commit message template for direct merges. Supports `{task_title}`,
+    /// `{task_description}`, `{task_id}`, `{task_short_id}` and `{executor}`.
+    pub merge_commit_template: Option<String>,
+    /// Number of set-up worktrees kept ready for new attempts; 0 disables the pool
+    #[ts(type = "number")]
+    pub worktree_pool_size: i64,
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
     #[ts(type = "Date")]
@@ -43,6 +64,13 @@ pub struct UpdateProject {
     pub dev_script: Option<String>,
     pub dev_script_working_dir: Option<String>,
     pub default_agent_working_dir: Option<String>,
+    /// Left unchanged when omitted
+    pub merge_strategy: Option<MergeStrategy>,
+    /// Left unchanged when omitted; an empty template restores the default message
+    pub merge_commit_template: Option<String>,
+    /// Left unchanged when omitted
+    #[ts(type = "number | null")]
+    pub worktree_pool_size: Option<i64>,
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -75,6 +103,9 @@ impl Project {
                       dev_script_working_dir,
                       default_agent_working_dir,
                       remote_project_id as "remote_project_id: Uuid",
+                      merge_strategy as "merge_strategy!: MergeStrategy",
+                      merge_commit_template,
+                      worktree_pool_size,
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM projects
@@ -92,6 +123,9 @@ impl Project {
             SELECT p.id as "id!: Uuid", p.name, p.dev_script, p.dev_script_working_dir,
                    p.default_agent_working_dir,
                    p.remote_project_id as "remote_project_id: Uuid",
+                   p.merge_strategy as "merge_strategy!: MergeStrategy",
+                   p.merge_commit_template,
+                   p.worktree_pool_size,
                    p.created_at as "created_at!: DateTime<Utc>", p.updated_at as "updated_at!: DateTime<Utc>"
             FROM projects p
             WHERE p.id IN (
@@ -117,6 +151,9 @@ impl Project {
                       dev_script_working_dir,
                       default_agent_working_dir,
                       remote_project_id as "remote_project_id: Uuid",
+                      merge_strategy as "merge_strategy!: MergeStrategy",
+                      merge_commit_template,
+                      worktree_pool_size,
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM projects
@@ -136,6 +173,9 @@ impl Project {
                       dev_script_working_dir,
                       default_agent_working_dir,
                       remote_project_id as "remote_project_id: Uuid",
+                      merge_strategy as "merge_strategy!: MergeStrategy",
+                      merge_commit_template,
+                      worktree_pool_size,
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM projects
@@ -158,6 +198,9 @@ impl Project {
                       dev_script_working_dir,
                       default_agent_working_dir,
                       remote_project_id as "remote_project_id: Uuid",
+                      merge_strategy as "merge_strategy!: MergeStrategy",
+                      merge_commit_template,
+                      worktree_pool_size,
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM projects
@@ -188,6 +231,9 @@ impl Project {
                           dev_script_working_dir,
                           default_agent_working_dir,
                           remote_project_id as "remote_project_id: Uuid",
+                          merge_strategy as "merge_strategy!: MergeStrategy",
+                          merge_commit_template,
+                          worktree_pool_size,
                           created_at as "created_at!: DateTime<Utc>",
                           updated_at as "updated_at!: DateTime<Utc>""#,
             project_id,
@@ -210,11 +256,22 @@ impl Project {
         let dev_script = payload.dev_script.clone();
         let dev_script_working_dir = payload.dev_script_working_dir.clone();
         let default_agent_working_dir = payload.default_agent_working_dir.clone();
+        let merge_strategy = payload.merge_strategy.unwrap_or(existing.merge_strategy);
+        let merge_commit_template = match &payload.merge_commit_template {
+            Some(template) if template.trim().is_empty() => None,
+            Some(template) => Some(template.clone()),
+            None => existing.merge_commit_template,
+        };
+        let worktree_pool_size = payload
+            .worktree_pool_size
+            .map(|size| size.max(0))
+            .unwrap_or(existing.worktree_pool_size);
 
         sqlx::query_as!(
             Project,
             r#"UPDATE projects
-               SET name = $2, dev_script = $3, dev_script_working_dir = $4, default_agent_working_dir = $5
+               SET name = $2, dev_script = $3, dev_script_working_dir = $4, default_agent_working_dir = $5,
+                   merge_strategy = $6, merge_commit_template = $7, worktree_pool_size = $8
                WHERE id = $1
                RETURNING id as "id!: Uuid",
                          name,
@@ -222,6 +279,9 @@ impl Project {
                          dev_script_working_dir,
                          default_agent_working_dir,
                          remote_project_id as "remote_project_id: Uuid",
+                         merge_strategy as "merge_strategy!: MergeStrategy",
+                         merge_commit_template,
+                         worktree_pool_size,
                          created_at as "created_at!: DateTime<Utc>",
                          updated_at as "updated_at!: DateTime<Utc>""#,
             id,
@@ -229,6 +289,9 @@ impl Project {
             dev_script,
             dev_script_working_dir,
             default_agent_working_dir,
+            merge_strategy,
+            merge_commit_template,
+            worktree_pool_size,
         )
         .fetch_one(pool)
         .await
@@ -26,8 +26,14 @@ pub struct ProjectRepo {
     pub repo_id: Uuid,
     pub setup_script: Option<String>,
     pub cleanup_script: Option<String>,
+    /// Runs after the coding agent and cleanup script; the task only moves to In Review when it passes
+    pub verify_script: Option<String>,
     pub copy_files: Option<String>,
     pub parallel_setup_script: bool,
+    /// Newline-separated sparse-checkout patterns; worktrees check out the full tree when unset
+    pub sparse_checkout: Option<String>,
+    /// Treat the sparse-checkout patterns as directories (git's cone mode)
+    pub sparse_checkout_cone: bool,
 }
 
 /// ProjectRepo with the associated repo name (for script execution in worktrees)
@@ -39,8 +45,11 @@ pub struct ProjectRepoWithName {
     pub repo_name: String,
     pub setup_script: Option<String>,
     pub cleanup_script: Option<String>,
+    pub verify_script: Option<String>,
     pub copy_files: Option<String>,
     pub parallel_setup_script: bool,
+    pub sparse_checkout: Option<String>,
+    pub sparse_checkout_cone: bool,
 }
 
 #[derive(Debug, Clone, Deserialize, TS)]
@@ -54,8 +63,11 @@ pub struct CreateProjectRepo {
 pub struct UpdateProjectRepo {
     pub setup_script: Option<String>,
     pub cleanup_script: Option<String>,
+    pub verify_script: Option<String>,
     pub copy_files: Option<String>,
     pub parallel_setup_script: Option<bool>,
+    pub sparse_checkout: Option<String>,
+    pub sparse_checkout_cone: Option<bool>,
 }
 
 impl ProjectRepo {
@@ -70,8 +82,11 @@ impl ProjectRepo {
                       repo_id as "repo_id!: Uuid",
                       setup_script,
                       cleanup_script,
+                      verify_script,
                       copy_files,
-                      parallel_setup_script as "parallel_setup_script!: bool"
+                      parallel_setup_script as "parallel_setup_script!: bool",
+                      sparse_checkout,
+                      sparse_checkout_cone as "sparse_checkout_cone!: bool"
                FROM project_repos
                WHERE project_id = $1"#,
             project_id
@@ -91,8 +106,11 @@ impl ProjectRepo {
                       repo_id as "repo_id!: Uuid",
                       setup_script,
                       cleanup_script,
+                      verify_script,
                       copy_files,
-                      parallel_setup_script as "parallel_setup_script!: bool"
+                      parallel_setup_script as "parallel_setup_script!: bool",
+                      sparse_checkout,
+                      sparse_checkout_cone as "sparse_checkout_cone!: bool"
                FROM project_repos
                WHERE repo_id = $1"#,
             repo_id
@@ -113,8 +131,11 @@ impl ProjectRepo {
                       r.name as "repo_name!",
                       pr.setup_script,
                       pr.cleanup_script,
+                      pr.verify_script,
                       pr.copy_files,
-                      pr.parallel_setup_script as "parallel_setup_script!: bool"
+                      pr.parallel_setup_script as "parallel_setup_script!: bool",
+                      pr.sparse_checkout,
+                      pr.sparse_checkout_cone as "sparse_checkout_cone!: bool"
                FROM project_repos pr
                JOIN repos r ON r.id = pr.repo_id
                WHERE pr.project_id = $1
@@ -159,8 +180,11 @@ impl ProjectRepo {
                       repo_id as "repo_id!: Uuid",
                       setup_script,
                       cleanup_script,
+                      verify_script,
                       copy_files,
-                      parallel_setup_script as "parallel_setup_script!: bool"
+                      parallel_setup_script as "parallel_setup_script!: bool",
+                      sparse_checkout,
+                      sparse_checkout_cone as "sparse_checkout_cone!: bool"
                FROM project_repos
                WHERE project_id = $1 AND repo_id = $2"#,
             project_id,
@@ -234,8 +258,11 @@ impl ProjectRepo {
                          repo_id as "repo_id!: Uuid",
                          setup_script,
                          cleanup_script,
+                         verify_script,
                          copy_files,
-                         parallel_setup_script as "parallel_setup_script!: bool""#,
+                         parallel_setup_script as "parallel_setup_script!: bool",
+                         sparse_checkout,
+                         sparse_checkout_cone as "sparse_checkout_cone!: bool""#,
             id,
             project_id,
             repo_id
@@ -255,30 +282,44 @@ impl ProjectRepo {
 
         let setup_script = payload.setup_script.clone();
         let cleanup_script = payload.cleanup_script.clone();
+        let verify_script = payload.verify_script.clone();
         let copy_files = payload.copy_files.clone();
         let parallel_setup_script = payload
             .parallel_setup_script
             .unwrap_or(existing.parallel_setup_script);
+        let sparse_checkout = payload.sparse_checkout.clone();
+        let sparse_checkout_cone = payload
+            .sparse_checkout_cone
+            .unwrap_or(existing.sparse_checkout_cone);
 
         sqlx::query_as!(
             ProjectRepo,
             r#"UPDATE project_repos
                SET setup_script = $1,
                    cleanup_script = $2,
-                   copy_files = $3,
-                   parallel_setup_script = $4
-               WHERE project_id = $5 AND repo_id = $6
+                   verify_script = $3,
+                   copy_files = $4,
+                   parallel_setup_script = $5,
+                   sparse_checkout = $6,
+                   sparse_checkout_cone = $7
+               WHERE project_id = $8 AND repo_id = $9
                RETURNING id as "id!: Uuid",
                          project_id as "project_id!: Uuid",
                          repo_id as "repo_id!: Uuid",
                          setup_script,
                          cleanup_script,
+                         verify_script,
                          copy_files,
-                         parallel_setup_script as "parallel_setup_script!: bool""#,
+                         parallel_setup_script as "parallel_setup_script!: bool",
+                         sparse_checkout,
+                         sparse_checkout_cone as "sparse_checkout_cone!: bool""#,
             setup_script,
             cleanup_script,
+            verify_script,
             copy_files,
             parallel_setup_script,
+            sparse_checkout,
+            sparse_checkout_cone,
             project_id,
             repo_id
         )
@@ -110,4 +110,21 @@ impl Session {
         .fetch_one(pool)
         .await?)
     }
+
+    /// Record the executor that actually runs the session, e.g. after falling back
+    /// from one that was unavailable
+    pub async fn update_executor(
+        pool: &SqlitePool,
+        id: Uuid,
+        executor: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE sessions SET executor = $1, updated_at = CURRENT_TIMESTAMP WHERE id = $2",
+            executor,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
 }
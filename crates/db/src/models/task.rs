@@ -41,8 +41,14 @@ pub struct TaskWithAttemptStatus {
     #[ts(flatten)]
     pub task: Task,
     pub has_in_progress_attempt: bool,
+    /// True while an execution for this task is waiting for a concurrency slot
+    pub is_queued: bool,
     pub last_attempt_failed: bool,
+    /// True when the latest attempt went to review with its verify script still failing
+    pub verification_failed: bool,
     pub executor: String,
+    /// True while any task this one depends on is not yet done
+    pub is_blocked: bool,
 }
 
 impl std::ops::Deref for TaskWithAttemptStatus {
@@ -156,30 +162,66 @@ impl Task {
       JOIN sessions s ON s.workspace_id = w.id
       JOIN execution_processes ep ON ep.session_id = s.id
      WHERE w.task_id       = t.id
+       AND w.archived_at    IS NULL
        AND ep.status        = 'running'
-       AND ep.run_reason IN ('setupscript','cleanupscript','codingagent')
+       AND ep.run_reason IN ('setupscript','cleanupscript','verifyscript','codingagent')
      LIMIT 1
   ) THEN 1 ELSE 0 END            AS "has_in_progress_attempt!: i64",
 
+  CASE WHEN EXISTS (
+    SELECT 1
+      FROM workspaces w
+      JOIN sessions s ON s.workspace_id = w.id
+      JOIN execution_processes ep ON ep.session_id = s.id
+     WHERE w.task_id       = t.id
+       AND w.archived_at    IS NULL
+       AND ep.status        = 'queued'
+     LIMIT 1
+  ) THEN 1 ELSE 0 END            AS "is_queued!: i64",
+
   CASE WHEN (
     SELECT ep.status
       FROM workspaces w
       JOIN sessions s ON s.workspace_id = w.id
       JOIN execution_processes ep ON ep.session_id = s.id
      WHERE w.task_id       = t.id
-     AND ep.run_reason IN ('setupscript','cleanupscript','codingagent')
+       AND w.archived_at    IS NULL
+     AND ep.run_reason IN ('setupscript','cleanupscript','verifyscript','codingagent')
      ORDER BY ep.created_at DESC
      LIMIT 1
   ) IN ('failed','killed') THEN 1 ELSE 0 END
                                  AS "last_attempt_failed!: i64",
 
+  CASE WHEN (
+    SELECT ep.run_reason || ':' || ep.status
+      FROM workspaces w
+      JOIN sessions s ON s.workspace_id = w.id
+      JOIN execution_processes ep ON ep.session_id = s.id
+     WHERE w.task_id       = t.id
+       AND w.archived_at    IS NULL
+     AND ep.run_reason IN ('setupscript','cleanupscript','verifyscript','codingagent')
+     ORDER BY ep.created_at DESC
+     LIMIT 1
+  ) = 'verifyscript:failed' THEN 1 ELSE 0 END
+                                 AS "verification_failed!: i64",
+
   ( SELECT s.executor
       FROM workspaces w
       JOIN sessions s ON s.workspace_id = w.id
       WHERE w.task_id = t.id
+        AND w.archived_at IS NULL
      ORDER BY s.created_at DESC
       LIMIT 1
-    )                               AS "executor!: String"
+    )                               AS "executor!: String",
+
+  CASE WHEN EXISTS (
+    SELECT 1
+      FROM task_dependencies td
+      JOIN tasks dep ON dep.id = td.depends_on_task_id
+     WHERE td.task_id = t.id
+       AND dep.status != 'done'
+     LIMIT 1
+  ) THEN 1 ELSE 0 END            AS "is_blocked!: i64"
 
 FROM tasks t
 WHERE t.project_id = $1
@@ -204,8 +246,11 @@ ORDER BY t.created_at DESC"#,
                     updated_at: rec.updated_at,
                 },
                 has_in_progress_attempt: rec.has_in_progress_attempt != 0,
+                is_queued: rec.is_queued != 0,
                 last_attempt_failed: rec.last_attempt_failed != 0,
+                verification_failed: rec.verification_failed != 0,
                 executor: rec.executor,
+                is_blocked: rec.is_blocked != 0,
             })
             .collect();
 
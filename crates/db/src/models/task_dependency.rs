@@ -0,0 +1,300 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::{Task, TaskStatus};
+
+#[derive(Debug, Error)]
+pub enum TaskDependencyError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Task not found")]
+    TaskNotFound,
+    #[error("A task cannot depend on itself")]
+    SelfDependency,
+    #[error("Tasks must belong to the same project")]
+    ProjectMismatch,
+    #[error("Dependency already exists")]
+    AlreadyExists,
+    #[error("Adding this dependency would create a cycle")]
+    Cycle,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskDependency {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub depends_on_task_id: Uuid,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreateTaskDependency {
+    pub depends_on_task_id: Uuid,
+}
+
+/// Both directions of the dependency graph around a single task
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TaskDependencies {
+    pub depends_on: Vec<Task>, // Tasks that must be done before this one can start
+    pub dependents: Vec<Task>, // Tasks waiting on this one
+}
+
+/// Returns true if adding the edge `task_id -> depends_on_task_id` to `edges`
+/// would close a cycle, i.e. `task_id` is already reachable from `depends_on_task_id`.
+pub fn would_create_cycle(edges: &[(Uuid, Uuid)], task_id: Uuid, depends_on_task_id: Uuid) -> bool {
+    if task_id == depends_on_task_id {
+        return true;
+    }
+
+    let mut adjacency: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for (from, to) in edges {
+        adjacency.entry(*from).or_default().push(*to);
+    }
+
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::from([depends_on_task_id]);
+    while let Some(current) = queue.pop_front() {
+        if current == task_id {
+            return true;
+        }
+        if !visited.insert(current) {
+            continue;
+        }
+        if let Some(next) = adjacency.get(&current) {
+            queue.extend(next.iter().copied());
+        }
+    }
+
+    false
+}
+
+impl TaskDependency {
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskDependency,
+            r#"SELECT id as "id!: Uuid",
+                      task_id as "task_id!: Uuid",
+                      depends_on_task_id as "depends_on_task_id!: Uuid",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM task_dependencies
+               WHERE task_id = $1
+               ORDER BY created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// All dependency edges between tasks of a project, as `(task_id, depends_on_task_id)` pairs
+    pub async fn find_edges_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<(Uuid, Uuid)>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"SELECT td.task_id as "task_id!: Uuid",
+                      td.depends_on_task_id as "depends_on_task_id!: Uuid"
+               FROM task_dependencies td
+               JOIN tasks t ON t.id = td.task_id
+               WHERE t.project_id = $1"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.task_id, row.depends_on_task_id))
+            .collect())
+    }
+
+    /// Tasks that `task_id` depends on
+    pub async fn find_prerequisites(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Task>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_workspace_id as "parent_workspace_id: Uuid", t.shared_task_id as "shared_task_id: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks t
+               JOIN task_dependencies td ON td.depends_on_task_id = t.id
+               WHERE td.task_id = $1
+               ORDER BY t.created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Tasks that depend on `task_id`
+    pub async fn find_dependents(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Task>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_workspace_id as "parent_workspace_id: Uuid", t.shared_task_id as "shared_task_id: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks t
+               JOIN task_dependencies td ON td.task_id = t.id
+               WHERE td.depends_on_task_id = $1
+               ORDER BY t.created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Todo tasks whose dependencies are all done, at least one of them having been finished
+    /// after the dependency was added, and that have never been started. These are ready to
+    /// be scheduled.
+    pub async fn find_unblocked_tasks(pool: &SqlitePool) -> Result<Vec<Task>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_workspace_id as "parent_workspace_id: Uuid", t.shared_task_id as "shared_task_id: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks t
+               WHERE t.status = 'todo'
+                 AND EXISTS (
+                     SELECT 1 FROM task_dependencies td WHERE td.task_id = t.id AND td.awaited
+                 )
+                 AND NOT EXISTS (
+                     SELECT 1
+                       FROM task_dependencies td
+                       JOIN tasks dep ON dep.id = td.depends_on_task_id
+                      WHERE td.task_id = t.id
+                        AND dep.status != 'done'
+                 )
+                 AND NOT EXISTS (
+                     SELECT 1 FROM workspaces w WHERE w.task_id = t.id
+                 )
+               ORDER BY t.created_at ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Create a dependency after validating that both tasks exist in the same project
+    /// and that the new edge keeps the project's dependency graph acyclic.
+    pub async fn create(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        depends_on_task_id: Uuid,
+    ) -> Result<Self, TaskDependencyError> {
+        if task_id == depends_on_task_id {
+            return Err(TaskDependencyError::SelfDependency);
+        }
+
+        let task = Task::find_by_id(pool, task_id)
+            .await?
+            .ok_or(TaskDependencyError::TaskNotFound)?;
+        let depends_on = Task::find_by_id(pool, depends_on_task_id)
+            .await?
+            .ok_or(TaskDependencyError::TaskNotFound)?;
+        if task.project_id != depends_on.project_id {
+            return Err(TaskDependencyError::ProjectMismatch);
+        }
+
+        let edges = Self::find_edges_for_project(pool, task.project_id).await?;
+        if edges.contains(&(task_id, depends_on_task_id)) {
+            return Err(TaskDependencyError::AlreadyExists);
+        }
+        if would_create_cycle(&edges, task_id, depends_on_task_id) {
+            return Err(TaskDependencyError::Cycle);
+        }
+
+        // A prerequisite that is already done never transitions to done, so it does not
+        // start the dependent task by itself
+        let id = Uuid::new_v4();
+        let awaited = depends_on.status != TaskStatus::Done;
+        let dependency = sqlx::query_as!(
+            TaskDependency,
+            r#"INSERT INTO task_dependencies (id, task_id, depends_on_task_id, awaited)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id as "id!: Uuid",
+                         task_id as "task_id!: Uuid",
+                         depends_on_task_id as "depends_on_task_id!: Uuid",
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            task_id,
+            depends_on_task_id,
+            awaited
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Self::touch_task(pool, task_id).await?;
+        Ok(dependency)
+    }
+
+    pub async fn delete(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        depends_on_task_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM task_dependencies WHERE task_id = $1 AND depends_on_task_id = $2",
+            task_id,
+            depends_on_task_id
+        )
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            Self::touch_task(pool, task_id).await?;
+        }
+        Ok(result.rows_affected())
+    }
+
+    /// Bump the dependent task so listeners re-read its blocked state
+    async fn touch_task(pool: &SqlitePool, task_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE tasks SET updated_at = CURRENT_TIMESTAMP WHERE id = $1",
+            task_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_self_dependency() {
+        let a = Uuid::new_v4();
+        assert!(would_create_cycle(&[], a, a));
+    }
+
+    #[test]
+    fn detects_transitive_cycle() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        // b depends on a, c depends on b
+        let edges = vec![(b, a), (c, b)];
+        // a depending on c would close a -> c -> b -> a
+        assert!(would_create_cycle(&edges, a, c));
+        // c depending on a directly is redundant but acyclic
+        assert!(!would_create_cycle(&edges, c, a));
+    }
+
+    #[test]
+    fn allows_diamond() {
+        let (a, b, c, d) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let edges = vec![(b, a), (c, a), (d, b)];
+        assert!(!would_create_cycle(&edges, d, c));
+    }
+}
@@ -52,6 +52,8 @@ pub struct Workspace {
     pub branch: String,
     pub agent_working_dir: Option<String>,
     pub setup_completed_at: Option<DateTime<Utc>>,
+    /// Set when another attempt of the task was promoted over this one
+    pub archived_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -112,6 +114,7 @@ impl Workspace {
                               branch,
                               agent_working_dir,
                               setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                              archived_at AS "archived_at: DateTime<Utc>",
                               created_at AS "created_at!: DateTime<Utc>",
                               updated_at AS "updated_at!: DateTime<Utc>"
                        FROM workspaces
@@ -130,6 +133,7 @@ impl Workspace {
                               branch,
                               agent_working_dir,
                               setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                              archived_at AS "archived_at: DateTime<Utc>",
                               created_at AS "created_at!: DateTime<Utc>",
                               updated_at AS "updated_at!: DateTime<Utc>"
                        FROM workspaces
@@ -158,6 +162,7 @@ impl Workspace {
                        w.branch,
                        w.agent_working_dir,
                        w.setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       w.archived_at AS "archived_at: DateTime<Utc>",
                        w.created_at        AS "created_at!: DateTime<Utc>",
                        w.updated_at        AS "updated_at!: DateTime<Utc>"
                FROM    workspaces w
@@ -210,6 +215,20 @@ impl Workspace {
         Ok(())
     }
 
+    /// Record that the setup scripts already ran in the workspace's worktrees
+    pub async fn mark_setup_completed(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE workspaces SET setup_completed_at = datetime('now', 'subsec'), updated_at = datetime('now', 'subsec') WHERE id = ?",
+            workspace_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     pub async fn clear_container_ref(
         pool: &SqlitePool,
         workspace_id: Uuid,
@@ -232,6 +251,7 @@ impl Workspace {
                        branch,
                        agent_working_dir,
                        setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       archived_at AS "archived_at: DateTime<Utc>",
                        created_at        AS "created_at!: DateTime<Utc>",
                        updated_at        AS "updated_at!: DateTime<Utc>"
                FROM    workspaces
@@ -251,6 +271,7 @@ impl Workspace {
                        branch,
                        agent_working_dir,
                        setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       archived_at AS "archived_at: DateTime<Utc>",
                        created_at        AS "created_at!: DateTime<Utc>",
                        updated_at        AS "updated_at!: DateTime<Utc>"
                FROM    workspaces
@@ -289,6 +310,7 @@ impl Workspace {
                 w.branch as "branch!",
                 w.agent_working_dir,
                 w.setup_completed_at as "setup_completed_at: DateTime<Utc>",
+                w.archived_at as "archived_at: DateTime<Utc>",
                 w.created_at as "created_at!: DateTime<Utc>",
                 w.updated_at as "updated_at!: DateTime<Utc>"
             FROM workspaces w
@@ -332,7 +354,7 @@ impl Workspace {
             Workspace,
             r#"INSERT INTO workspaces (id, task_id, container_ref, branch, agent_working_dir, setup_completed_at)
                VALUES ($1, $2, $3, $4, $5, $6)
-               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", container_ref, branch, agent_working_dir, setup_completed_at as "setup_completed_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", container_ref, branch, agent_working_dir, setup_completed_at as "setup_completed_at: DateTime<Utc>", archived_at as "archived_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             task_id,
             Option::<String>::None,
@@ -360,6 +382,33 @@ impl Workspace {
         Ok(())
     }
 
+    /// Delete a workspace together with its repos, sessions and execution processes
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM workspaces WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Archive every other unarchived attempt of the task, returning the archived workspaces
+    pub async fn archive_siblings(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        keep_workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Workspace,
+            r#"UPDATE workspaces
+               SET archived_at = datetime('now', 'subsec'), updated_at = datetime('now', 'subsec')
+               WHERE task_id = $1 AND id != $2 AND archived_at IS NULL
+               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", container_ref, branch, agent_working_dir, setup_completed_at as "setup_completed_at: DateTime<Utc>", archived_at as "archived_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            task_id,
+            keep_workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     pub async fn resolve_container_ref(
         pool: &SqlitePool,
         container_ref: &str,
@@ -14,6 +14,8 @@ pub struct WorkspaceRepo {
     pub workspace_id: Uuid,
     pub repo_id: Uuid,
     pub target_branch: String,
+    /// Commit of the parent attempt's branch this repo is stacked on, if it is stacked
+    pub stack_base_commit: Option<String>,
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
     #[ts(type = "Date")]
@@ -34,13 +36,28 @@ pub struct RepoWithTargetBranch {
     pub target_branch: String,
 }
 
-/// Repo info with copy_files configuration from project_repos.
+/// A child workspace's repo stacked on the branch of its task's parent workspace
+#[derive(Debug, Clone, FromRow)]
+pub struct StackedWorkspaceRepo {
+    pub workspace_id: Uuid,
+    pub repo_id: Uuid,
+    /// Branch of the stacked workspace
+    pub branch: String,
+    /// Parent branch commit the workspace branch was last based on
+    pub stack_base_commit: String,
+    pub parent_workspace_id: Uuid,
+    pub parent_branch: String,
+}
+
+/// Repo info with copy_files and sparse-checkout configuration from project_repos.
 #[derive(Debug, Clone)]
 pub struct RepoWithCopyFiles {
     pub id: Uuid,
     pub path: PathBuf,
     pub name: String,
     pub copy_files: Option<String>,
+    pub sparse_checkout: Option<String>,
+    pub sparse_checkout_cone: bool,
 }
 
 impl WorkspaceRepo {
@@ -61,6 +78,7 @@ impl WorkspaceRepo {
                              workspace_id as "workspace_id!: Uuid",
                              repo_id as "repo_id!: Uuid",
                              target_branch,
+                             stack_base_commit,
                              created_at as "created_at!: DateTime<Utc>",
                              updated_at as "updated_at!: DateTime<Utc>""#,
                 id,
@@ -86,6 +104,7 @@ impl WorkspaceRepo {
                       workspace_id as "workspace_id!: Uuid",
                       repo_id as "repo_id!: Uuid",
                       target_branch,
+                      stack_base_commit,
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM workspace_repos
@@ -166,6 +185,7 @@ impl WorkspaceRepo {
                       workspace_id as "workspace_id!: Uuid",
                       repo_id as "repo_id!: Uuid",
                       target_branch,
+                      stack_base_commit,
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM workspace_repos
@@ -194,6 +214,48 @@ impl WorkspaceRepo {
         Ok(())
     }
 
+    pub async fn update_stack_base_commit(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        repo_id: Uuid,
+        stack_base_commit: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE workspace_repos SET stack_base_commit = $1, updated_at = datetime('now') WHERE workspace_id = $2 AND repo_id = $3",
+            stack_base_commit,
+            workspace_id,
+            repo_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Stacked repos of workspaces whose task is still open, with the branch of the parent
+    /// workspace they target
+    pub async fn find_stacked(pool: &SqlitePool) -> Result<Vec<StackedWorkspaceRepo>, sqlx::Error> {
+        sqlx::query_as!(
+            StackedWorkspaceRepo,
+            r#"SELECT wr.workspace_id as "workspace_id!: Uuid",
+                      wr.repo_id as "repo_id!: Uuid",
+                      w.branch,
+                      wr.stack_base_commit as "stack_base_commit!",
+                      p.id as "parent_workspace_id!: Uuid",
+                      p.branch as parent_branch
+               FROM workspace_repos wr
+               JOIN workspaces w ON w.id = wr.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               JOIN workspaces p ON p.id = t.parent_workspace_id
+               WHERE wr.stack_base_commit IS NOT NULL
+                 AND wr.target_branch = p.branch
+                 AND w.archived_at IS NULL
+                 AND t.status NOT IN ('done', 'cancelled')
+               ORDER BY wr.created_at ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     pub async fn update_target_branch_for_children_of_workspace(
         pool: &SqlitePool,
         parent_workspace_id: Uuid,
@@ -241,14 +303,20 @@ impl WorkspaceRepo {
         .await
     }
 
-    /// Find repos for a workspace with their copy_files configuration.
-    /// Uses LEFT JOIN so repos without project_repo entries still appear (with NULL copy_files).
+    /// Find repos for a workspace with their copy_files and sparse-checkout configuration.
+    /// Uses LEFT JOIN so repos without project_repo entries still appear (with NULL copy_files
+    /// and no sparse checkout).
     pub async fn find_repos_with_copy_files(
         pool: &SqlitePool,
         workspace_id: Uuid,
     ) -> Result<Vec<RepoWithCopyFiles>, sqlx::Error> {
         let rows = sqlx::query!(
-            r#"SELECT r.id as "id!: Uuid", r.path, r.name, pr.copy_files
+            r#"SELECT r.id as "id!: Uuid",
+                      r.path,
+                      r.name,
+                      pr.copy_files,
+                      pr.sparse_checkout,
+                      pr.sparse_checkout_cone as "sparse_checkout_cone?: bool"
                FROM repos r
                JOIN workspace_repos wr ON r.id = wr.repo_id
                JOIN workspaces w ON w.id = wr.workspace_id
@@ -267,6 +335,8 @@ impl WorkspaceRepo {
                 path: PathBuf::from(row.path),
                 name: row.name,
                 copy_files: row.copy_files,
+                sparse_checkout: row.sparse_checkout,
+                sparse_checkout_cone: row.sparse_checkout_cone.unwrap_or(true),
             })
             .collect())
     }
@@ -9,13 +9,16 @@ use agent_client_protocol as proto;
 use agent_client_protocol::Agent as _;
 use command_group::{AsyncCommandGroup, AsyncGroupChild};
 use futures::StreamExt;
+use serde_json::Value;
 use tokio::{io::AsyncWriteExt, process::Command, sync::mpsc};
 use tokio_util::{
     compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt},
     io::ReaderStream,
 };
 use tracing::error;
-use workspace_utils::{approvals::ApprovalStatus, stream_lines::LinesStreamExt};
+use workspace_utils::{
+    approvals::ApprovalStatus, stream_lines::LinesStreamExt, usage::UsageReport,
+};
 
 use super::{AcpClient, SessionManager};
 use crate::{
@@ -112,6 +115,7 @@ impl AcpAgentHarness {
             child,
             exit_signal: Some(exit_rx),
             interrupt_sender: None,
+            temp_file: None,
         })
     }
 
@@ -161,6 +165,7 @@ impl AcpAgentHarness {
             child,
             exit_signal: Some(exit_rx),
             interrupt_sender: None,
+            temp_file: None,
         })
     }
 
@@ -445,6 +450,9 @@ impl AcpAgentHarness {
                                     let stop_reason = serde_json::to_string(&resp.stop_reason)
                                         .unwrap_or_default();
                                     let _ = log_tx.send(AcpEvent::Done(stop_reason).to_string());
+                                    if let Some(usage) = prompt_usage(&resp) {
+                                        let _ = log_tx.send(AcpEvent::Usage(usage).to_string());
+                                    }
                                 }
                                 Err(e) => {
                                     tracing::debug!("error {} {e} {:?}", e.code, e.data);
@@ -507,3 +515,94 @@ impl AcpAgentHarness {
         Ok(())
     }
 }
+
+/// Tokens a prompt turn used, from the response's `usage` or, for agents predating it, the
+/// `usage` or `quota.token_count` of its `_meta`. Counts may be camelCase or snake_case.
+fn prompt_usage(response: &proto::PromptResponse) -> Option<UsageReport> {
+    let response = serde_json::to_value(response).ok()?;
+    let meta = response.get("_meta");
+    let usage = [
+        response.get("usage"),
+        meta.and_then(|meta| meta.get("usage")),
+        meta.and_then(|meta| meta.pointer("/quota/token_count")),
+    ]
+    .into_iter()
+    .flatten()
+    .find(|usage| usage.is_object())?;
+
+    let count = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| usage.get(*name).and_then(Value::as_i64))
+    };
+    let input_tokens = count(&["inputTokens", "input_tokens"]);
+    let output_tokens = count(&["outputTokens", "output_tokens"]);
+    if input_tokens.is_none() && output_tokens.is_none() {
+        return None;
+    }
+    let thought_tokens = count(&["thoughtTokens", "thought_tokens"]).unwrap_or(0);
+    let cache_read_input_tokens = count(&["cachedReadTokens", "cached_read_tokens"]).unwrap_or(0);
+    let cache_creation_input_tokens =
+        count(&["cachedWriteTokens", "cached_write_tokens"]).unwrap_or(0);
+    Some(UsageReport {
+        // Input counts include cached tokens, which are reported separately
+        input_tokens: (input_tokens.unwrap_or(0) - cache_read_input_tokens).max(0),
+        output_tokens: output_tokens.unwrap_or(0) + thought_tokens,
+        cache_creation_input_tokens,
+        cache_read_input_tokens,
+        cost_usd: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn response(value: Value) -> proto::PromptResponse {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn prompt_usage_is_read_from_the_response_or_its_meta() {
+        assert_eq!(
+            prompt_usage(&response(json!({
+                "stopReason": "end_turn",
+                "_meta": {
+                    "usage": {
+                        "totalTokens": 1600,
+                        "inputTokens": 1000,
+                        "outputTokens": 400,
+                        "thoughtTokens": 200,
+                        "cachedReadTokens": 300
+                    }
+                }
+            }))),
+            Some(UsageReport {
+                input_tokens: 700,
+                output_tokens: 600,
+                cache_creation_input_tokens: 0,
+                cache_read_input_tokens: 300,
+                cost_usd: None,
+            })
+        );
+        assert_eq!(
+            prompt_usage(&response(json!({
+                "stopReason": "end_turn",
+                "_meta": {
+                    "quota": { "token_count": { "input_tokens": 120, "output_tokens": 30 } }
+                }
+            }))),
+            Some(UsageReport {
+                input_tokens: 120,
+                output_tokens: 30,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            prompt_usage(&response(json!({ "stopReason": "end_turn" }))),
+            None
+        );
+    }
+}
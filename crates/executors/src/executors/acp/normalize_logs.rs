@@ -8,7 +8,7 @@ use agent_client_protocol::{self as acp, SessionNotification};
 use futures::StreamExt;
 use regex::Regex;
 use serde::Deserialize;
-use workspace_utils::{approvals::ApprovalStatus, msg_store::MsgStore};
+use workspace_utils::{approvals::ApprovalStatus, msg_store::MsgStore, usage::UsageReport};
 
 pub use super::AcpAgentHarness;
 use super::AcpEvent;
@@ -36,6 +36,8 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
         let mut stored_session_id = false;
         let mut streaming: StreamingState = StreamingState::default();
         let mut tool_states: ToolStates = HashMap::new();
+        // Usage is reported per prompt turn, while the store expects the total so far
+        let mut usage = UsageReport::default();
 
         let mut stdout_lines = msg_store.stdout_lines_stream();
         while let Some(Ok(line)) = stdout_lines.next().await {
@@ -64,6 +66,10 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                         streaming.assistant_text = None;
                         streaming.thinking_text = None;
                     }
+                    AcpEvent::Usage(turn) => {
+                        usage = usage + turn;
+                        msg_store.push_usage(usage.clone());
+                    }
                     AcpEvent::Message(content) => {
                         streaming.thinking_text = None;
                         if let agent_client_protocol::ContentBlock::Text(text) = content {
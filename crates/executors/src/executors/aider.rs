@@ -0,0 +1,201 @@
+use std::{path::Path, process::Stdio, sync::Arc};
+
+use async_trait::async_trait;
+use command_group::AsyncCommandGroup;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use ts_rs::TS;
+use uuid::Uuid;
+use workspace_utils::{msg_store::MsgStore, shell::resolve_executable_path_blocking};
+
+use crate::{
+    command::{CmdOverrides, CommandBuilder, apply_overrides},
+    env::ExecutionEnv,
+    executors::{
+        AppendPrompt, AvailabilityInfo, ExecutorError, SpawnedChild, StandardCodingAgentExecutor,
+    },
+    logs::utils::EntryIndexProvider,
+    stdout_dup,
+};
+
+pub mod normalize_logs;
+pub mod session;
+
+use normalize_logs::normalize_logs;
+
+/// Aider executor configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct Aider {
+    #[serde(default)]
+    pub append_prompt: AppendPrompt,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Model",
+        description = "Model to use (e.g., sonnet, o3-mini, deepseek/deepseek-chat)"
+    )]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Edit Format",
+        description = "Edit format to request from the model, e.g. diff, diff-fenced or architect"
+    )]
+    pub edit_format: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Auto Commits",
+        description = "Let Aider commit each change itself instead of leaving commits to Vibe Kanban"
+    )]
+    pub auto_commits: Option<bool>,
+    #[serde(flatten)]
+    pub cmd: CmdOverrides,
+}
+
+impl Aider {
+    const SESSION_PREFIX: &'static str = "[aider-session] ";
+
+    fn base_command() -> &'static str {
+        "aider"
+    }
+
+    fn build_command_builder(&self) -> CommandBuilder {
+        let mut builder = CommandBuilder::new(Self::base_command()).params([
+            "--yes-always",
+            "--no-pretty",
+            "--no-stream",
+            "--no-fancy-input",
+            "--no-check-update",
+            "--no-show-release-notes",
+            "--no-analytics",
+        ]);
+
+        if !self.auto_commits.unwrap_or(false) {
+            builder = builder.extend_params(["--no-auto-commits"]);
+        }
+        if let Some(model) = &self.model {
+            builder = builder.extend_params(["--model", model]);
+        }
+        if let Some(edit_format) = &self.edit_format {
+            builder = builder.extend_params(["--edit-format", edit_format]);
+        }
+
+        apply_overrides(builder, &self.cmd)
+    }
+
+    /// Session file arguments, appended after the command is resolved because the
+    /// asset directory may contain spaces
+    fn session_args(session_id: Uuid, restore_history: bool) -> Vec<String> {
+        let mut args = vec![
+            "--chat-history-file".to_string(),
+            session::chat_history_path(session_id)
+                .to_string_lossy()
+                .to_string(),
+            "--input-history-file".to_string(),
+            session::input_history_path(session_id)
+                .to_string_lossy()
+                .to_string(),
+            "--message-file".to_string(),
+            session::message_path(session_id)
+                .to_string_lossy()
+                .to_string(),
+        ];
+        if restore_history {
+            args.push("--restore-chat-history".to_string());
+        }
+        args
+    }
+
+    async fn spawn_aider(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: Uuid,
+        restore_history: bool,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        // Passed as a file so long prompts survive argument length limits and quoting
+        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        tokio::fs::write(session::message_path(session_id), combined_prompt)
+            .await
+            .map_err(ExecutorError::Io)?;
+
+        let command_parts = self.build_command_builder().build_initial()?;
+        let (program_path, mut args) = command_parts.into_resolved().await?;
+        args.extend(Self::session_args(session_id, restore_history));
+
+        let mut command = Command::new(program_path);
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .current_dir(current_dir)
+            .args(&args);
+
+        env.clone()
+            .with_profile(&self.cmd)
+            .apply_to_command(&mut command);
+
+        let mut child = command.group_spawn()?;
+
+        // Aider never prints a session id, so announce the one we picked
+        let (_, appender) = stdout_dup::tee_stdout_with_appender(&mut child)?;
+        appender.append_line(format!("{}{}\n", Self::SESSION_PREFIX, session_id));
+
+        Ok(child.into())
+    }
+}
+
+#[async_trait]
+impl StandardCodingAgentExecutor for Aider {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let session_id = session::new_session().map_err(ExecutorError::Io)?;
+        self.spawn_aider(current_dir, prompt, session_id, false, env)
+            .await
+    }
+
+    async fn spawn_follow_up(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let forked_session_id = session::fork_session(session_id).map_err(|e| {
+            ExecutorError::FollowUpNotSupported(format!(
+                "Failed to fork Aider session {session_id}: {e}"
+            ))
+        })?;
+        self.spawn_aider(current_dir, prompt, forked_session_id, true, env)
+            .await
+    }
+
+    fn normalize_logs(&self, msg_store: Arc<MsgStore>, worktree_path: &Path) {
+        normalize_logs(
+            msg_store.clone(),
+            worktree_path,
+            EntryIndexProvider::start_from(&msg_store),
+        );
+    }
+
+    // Aider does not support MCP servers
+    fn default_mcp_config_path(&self) -> Option<std::path::PathBuf> {
+        None
+    }
+
+    fn get_availability_info(&self) -> AvailabilityInfo {
+        // A leftover config file alone doesn't make aider runnable
+        let binary_found = resolve_executable_path_blocking(Self::base_command()).is_some();
+
+        if binary_found {
+            AvailabilityInfo::InstallationFound
+        } else {
+            AvailabilityInfo::NotFound
+        }
+    }
+}
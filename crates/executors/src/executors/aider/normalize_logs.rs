@@ -0,0 +1,513 @@
+use std::{
+    path::Path,
+    sync::{Arc, LazyLock},
+};
+
+use futures::StreamExt;
+use json_patch::Patch;
+use regex::Regex;
+use workspace_utils::{
+    diff::create_unified_diff, msg_store::MsgStore, path::make_path_relative, usage::UsageReport,
+};
+
+use super::Aider;
+use crate::logs::{
+    ActionType, FileChange, NormalizedEntry, NormalizedEntryType, ToolStatus,
+    stderr_processor::normalize_stderr_logs,
+    utils::{ConversationPatch, EntryIndexProvider},
+};
+
+static SEARCH_MARKER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^<{5,9} SEARCH\s*$").unwrap());
+static DIVIDER_MARKER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^={5,9}\s*$").unwrap());
+static REPLACE_MARKER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^>{5,9} REPLACE\s*$").unwrap());
+static TOKEN_COUNT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"([\d.]+)([kM]?) (sent|received|cache write|cache hit)").unwrap());
+static SESSION_COST: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\$([\d.]+) session").unwrap());
+static NO_EXACT_MATCH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"SearchReplaceNoExactMatch: This SEARCH block failed to exactly match lines in (.+)$",
+    )
+    .unwrap()
+});
+static SYSTEM_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(Added .+ to the chat\.?|Commit [0-9a-f]{7,} .+)$").unwrap());
+
+/// Startup and housekeeping lines Aider prints around the model's reply
+const SYSTEM_PREFIXES: &[&str] = &[
+    "Aider v",
+    "Main model:",
+    "Weak model:",
+    "Editor model:",
+    "Git repo:",
+    "Repo-map:",
+    "Restored previous conversation history",
+    "Use /help",
+    "Warning:",
+];
+
+pub fn normalize_logs(
+    msg_store: Arc<MsgStore>,
+    worktree_path: &Path,
+    entry_index_provider: EntryIndexProvider,
+) {
+    normalize_stderr_logs(msg_store.clone(), entry_index_provider.clone());
+
+    let worktree_path = worktree_path.to_string_lossy().to_string();
+    tokio::spawn(async move {
+        let mut stdout_lines = msg_store.stdout_lines_stream();
+        let mut processor = AiderLogProcessor::new(worktree_path, entry_index_provider);
+
+        while let Some(Ok(line)) = stdout_lines.next().await {
+            for output in processor.process_line(line.trim_end_matches(['\r', '\n'])) {
+                match output {
+                    AiderOutput::Patch(patch) => msg_store.push_patch(patch),
+                    AiderOutput::Usage(usage) => msg_store.push_usage(usage),
+                    AiderOutput::SessionId(session_id) => msg_store.push_session_id(session_id),
+                }
+            }
+        }
+
+        for output in processor.finish() {
+            if let AiderOutput::Patch(patch) = output {
+                msg_store.push_patch(patch);
+            }
+        }
+    });
+}
+
+#[derive(Debug)]
+pub enum AiderOutput {
+    Patch(Patch),
+    Usage(UsageReport),
+    SessionId(String),
+}
+
+#[derive(Debug, PartialEq)]
+enum EditSection {
+    Search,
+    Replace,
+}
+
+struct EditBlock {
+    path: String,
+    section: EditSection,
+    search: Vec<String>,
+    replace: Vec<String>,
+}
+
+/// An edit shown as created, waiting for Aider to report whether it applied
+struct PendingEdit {
+    path: String,
+    index: usize,
+    entry: NormalizedEntry,
+}
+
+/// Turns Aider's `--no-pretty` output into conversation entries. SEARCH/REPLACE
+/// blocks become file edits; everything else is assistant text.
+pub struct AiderLogProcessor {
+    worktree_path: String,
+    index_provider: EntryIndexProvider,
+    text_lines: Vec<String>,
+    text_entry: Option<(usize, String)>,
+    edit: Option<EditBlock>,
+    last_edit_path: Option<String>,
+    skip_closing_fence: bool,
+    pending_edits: Vec<PendingEdit>,
+    usage: UsageReport,
+}
+
+impl AiderLogProcessor {
+    pub fn new(worktree_path: String, index_provider: EntryIndexProvider) -> Self {
+        Self {
+            worktree_path,
+            index_provider,
+            text_lines: Vec::new(),
+            text_entry: None,
+            edit: None,
+            last_edit_path: None,
+            skip_closing_fence: false,
+            pending_edits: Vec::new(),
+            usage: UsageReport::default(),
+        }
+    }
+
+    pub fn process_line(&mut self, line: &str) -> Vec<AiderOutput> {
+        let trimmed = line.trim();
+
+        if let Some(edit) = self.edit.as_mut() {
+            match edit.section {
+                EditSection::Search if DIVIDER_MARKER.is_match(trimmed) => {
+                    edit.section = EditSection::Replace;
+                }
+                EditSection::Replace if REPLACE_MARKER.is_match(trimmed) => {
+                    return self.finish_edit().into_iter().collect();
+                }
+                EditSection::Search => edit.search.push(line.to_string()),
+                EditSection::Replace => edit.replace.push(line.to_string()),
+            }
+            return vec![];
+        }
+
+        if std::mem::take(&mut self.skip_closing_fence) && trimmed.starts_with("```") {
+            return vec![];
+        }
+
+        if let Some(session_id) = line.strip_prefix(Aider::SESSION_PREFIX) {
+            return vec![AiderOutput::SessionId(session_id.trim().to_string())];
+        }
+
+        if SEARCH_MARKER.is_match(trimmed) {
+            return self.start_edit();
+        }
+
+        if trimmed.starts_with("Tokens: ") {
+            let mut outputs = self.flush_text();
+            outputs.push(AiderOutput::Usage(self.record_usage(trimmed)));
+            return outputs;
+        }
+
+        if let Some(path) = trimmed.strip_prefix("Applied edit to ") {
+            return self.resolve_edits(path, ToolStatus::Success, false);
+        }
+
+        if let Some(caps) = NO_EXACT_MATCH.captures(trimmed) {
+            return self.resolve_edits(&caps[1], ToolStatus::Failed, true);
+        }
+
+        if SYSTEM_LINE.is_match(trimmed)
+            || SYSTEM_PREFIXES
+                .iter()
+                .any(|prefix| trimmed.starts_with(prefix))
+        {
+            let mut outputs = self.flush_text();
+            outputs.push(self.add_entry(NormalizedEntry {
+                timestamp: None,
+                entry_type: NormalizedEntryType::SystemMessage,
+                content: trimmed.to_string(),
+                metadata: None,
+            }));
+            return outputs;
+        }
+
+        self.text_lines.push(line.to_string());
+        // The last two lines may turn out to be the file name and opening fence of
+        // an edit block, so they are held back until more output arrives
+        let committed = self.text_lines.len().saturating_sub(2);
+        self.upsert_text(committed).into_iter().collect()
+    }
+
+    /// Flush anything still buffered once the process output ends
+    pub fn finish(&mut self) -> Vec<AiderOutput> {
+        let mut outputs = Vec::new();
+        if self.edit.is_some() {
+            outputs.extend(self.finish_edit());
+        }
+        outputs.extend(self.flush_text());
+        outputs
+    }
+
+    fn start_edit(&mut self) -> Vec<AiderOutput> {
+        while self
+            .text_lines
+            .last()
+            .is_some_and(|line| line.trim().is_empty())
+        {
+            self.text_lines.pop();
+        }
+        if self
+            .text_lines
+            .last()
+            .is_some_and(|line| line.trim().starts_with("```"))
+        {
+            self.text_lines.pop();
+            self.skip_closing_fence = true;
+        }
+
+        let path = self
+            .text_lines
+            .pop()
+            .map(|line| {
+                line.trim()
+                    .trim_matches(|c| c == '`' || c == '*' || c == ':')
+                    .to_string()
+            })
+            .filter(|path| !path.is_empty())
+            .or_else(|| self.last_edit_path.clone())
+            .unwrap_or_default();
+
+        let outputs = self.flush_text();
+        self.last_edit_path = Some(path.clone());
+        self.edit = Some(EditBlock {
+            path,
+            section: EditSection::Search,
+            search: Vec::new(),
+            replace: Vec::new(),
+        });
+        outputs
+    }
+
+    fn finish_edit(&mut self) -> Option<AiderOutput> {
+        let edit = self.edit.take()?;
+        let path = make_path_relative(&edit.path, &self.worktree_path);
+        let search = join_lines(&edit.search);
+        let replace = join_lines(&edit.replace);
+
+        // An empty SEARCH section is how Aider creates a new file
+        let change = if search.is_empty() {
+            FileChange::Write { content: replace }
+        } else {
+            FileChange::Edit {
+                unified_diff: create_unified_diff(&path, &search, &replace),
+                has_line_numbers: false,
+            }
+        };
+
+        let entry = NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: "edit".to_string(),
+                action_type: ActionType::FileEdit {
+                    path: path.clone(),
+                    changes: vec![change],
+                },
+                status: ToolStatus::Created,
+            },
+            content: path.clone(),
+            metadata: None,
+        };
+
+        let index = self.index_provider.next();
+        self.pending_edits.push(PendingEdit {
+            path,
+            index,
+            entry: entry.clone(),
+        });
+        Some(AiderOutput::Patch(ConversationPatch::add_normalized_entry(
+            index, entry,
+        )))
+    }
+
+    /// Mark pending edits of a file as applied or failed. Aider reports one
+    /// success line per file, but one failure line per block.
+    fn resolve_edits(
+        &mut self,
+        path: &str,
+        status: ToolStatus,
+        first_only: bool,
+    ) -> Vec<AiderOutput> {
+        let path = make_path_relative(path.trim(), &self.worktree_path);
+        let mut outputs = Vec::new();
+        let mut remaining = Vec::with_capacity(self.pending_edits.len());
+
+        for pending in std::mem::take(&mut self.pending_edits) {
+            let resolve = pending.path == path && !(first_only && !outputs.is_empty());
+            if !resolve {
+                remaining.push(pending);
+                continue;
+            }
+            if let Some(entry) = pending.entry.with_tool_status(status.clone()) {
+                outputs.push(AiderOutput::Patch(ConversationPatch::replace(
+                    pending.index,
+                    entry,
+                )));
+            }
+        }
+
+        self.pending_edits = remaining;
+        outputs
+    }
+
+    fn record_usage(&mut self, line: &str) -> UsageReport {
+        for caps in TOKEN_COUNT.captures_iter(line) {
+            let count = parse_token_count(&caps[1], &caps[2]);
+            match &caps[3] {
+                "sent" => self.usage.input_tokens += count,
+                "received" => self.usage.output_tokens += count,
+                "cache write" => self.usage.cache_creation_input_tokens += count,
+                "cache hit" => self.usage.cache_read_input_tokens += count,
+                _ => {}
+            }
+        }
+        // Aider's session cost is already cumulative for this process
+        if let Some(cost) = SESSION_COST
+            .captures(line)
+            .and_then(|caps| caps[1].parse::<f64>().ok())
+        {
+            self.usage.cost_usd = Some(cost);
+        }
+        self.usage.clone()
+    }
+
+    fn add_entry(&mut self, entry: NormalizedEntry) -> AiderOutput {
+        AiderOutput::Patch(ConversationPatch::add_normalized_entry(
+            self.index_provider.next(),
+            entry,
+        ))
+    }
+
+    fn upsert_text(&mut self, line_count: usize) -> Option<AiderOutput> {
+        let content = self.text_lines[..line_count].join("\n").trim().to_string();
+        if content.is_empty() {
+            return None;
+        }
+
+        let entry = NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::AssistantMessage,
+            content: content.clone(),
+            metadata: None,
+        };
+        match self.text_entry.as_mut() {
+            Some((_, previous)) if *previous == content => None,
+            Some((index, previous)) => {
+                *previous = content;
+                Some(AiderOutput::Patch(ConversationPatch::replace(
+                    *index, entry,
+                )))
+            }
+            None => {
+                let index = self.index_provider.next();
+                self.text_entry = Some((index, content));
+                Some(AiderOutput::Patch(ConversationPatch::add_normalized_entry(
+                    index, entry,
+                )))
+            }
+        }
+    }
+
+    fn flush_text(&mut self) -> Vec<AiderOutput> {
+        let output = self.upsert_text(self.text_lines.len());
+        self.text_lines.clear();
+        self.text_entry = None;
+        output.into_iter().collect()
+    }
+}
+
+fn join_lines(lines: &[String]) -> String {
+    if lines.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", lines.join("\n"))
+    }
+}
+
+/// Aider abbreviates counts, e.g. `950`, `1.2k`, `12k` or `1.5M`
+fn parse_token_count(number: &str, suffix: &str) -> i64 {
+    let value = number.parse::<f64>().unwrap_or(0.0);
+    let multiplier = match suffix {
+        "k" => 1_000.0,
+        "M" => 1_000_000.0,
+        _ => 1.0,
+    };
+    (value * multiplier).round() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::utils::patch::extract_normalized_entry_from_patch;
+
+    fn entries(outputs: Vec<AiderOutput>) -> Vec<(usize, NormalizedEntry)> {
+        outputs
+            .into_iter()
+            .filter_map(|output| match output {
+                AiderOutput::Patch(patch) => extract_normalized_entry_from_patch(&patch),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn run(processor: &mut AiderLogProcessor, output: &str) -> Vec<AiderOutput> {
+        let mut outputs: Vec<AiderOutput> = output
+            .lines()
+            .flat_map(|line| processor.process_line(line))
+            .collect();
+        outputs.extend(processor.finish());
+        outputs
+    }
+
+    #[test]
+    fn test_search_replace_block_becomes_file_edit() {
+        let mut processor =
+            AiderLogProcessor::new("/repo".to_string(), EntryIndexProvider::test_new());
+        let outputs = run(
+            &mut processor,
+            "I'll rename the function.\n\nsrc/lib.rs\n```rust\n<<<<<<< SEARCH\nfn old() {}\n=======\nfn new() {}\n>>>>>>> REPLACE\n```\n\nApplied edit to src/lib.rs\n",
+        );
+        let entries = entries(outputs);
+
+        let message = entries
+            .iter()
+            .find(|(_, e)| matches!(e.entry_type, NormalizedEntryType::AssistantMessage))
+            .unwrap();
+        assert_eq!(message.1.content, "I'll rename the function.");
+
+        let edits: Vec<_> = entries
+            .iter()
+            .filter_map(|(_, e)| match &e.entry_type {
+                NormalizedEntryType::ToolUse {
+                    action_type: ActionType::FileEdit { path, changes },
+                    status,
+                    ..
+                } => Some((path.clone(), changes.clone(), status.clone())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(edits.len(), 2, "edit is added, then marked applied");
+        assert_eq!(edits[0].0, "src/lib.rs");
+        assert!(matches!(edits[0].2, ToolStatus::Created));
+        assert!(matches!(edits[1].2, ToolStatus::Success));
+        match &edits[0].1[0] {
+            FileChange::Edit { unified_diff, .. } => {
+                assert!(unified_diff.contains("-fn old() {}"));
+                assert!(unified_diff.contains("+fn new() {}"));
+            }
+            other => panic!("expected an edit, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_empty_search_creates_file() {
+        let mut processor =
+            AiderLogProcessor::new("/repo".to_string(), EntryIndexProvider::test_new());
+        let outputs = run(
+            &mut processor,
+            "notes.txt\n<<<<<<< SEARCH\n=======\nhello\n>>>>>>> REPLACE\n",
+        );
+        let change = entries(outputs)
+            .into_iter()
+            .find_map(|(_, e)| match e.entry_type {
+                NormalizedEntryType::ToolUse {
+                    action_type: ActionType::FileEdit { mut changes, .. },
+                    ..
+                } => changes.pop(),
+                _ => None,
+            })
+            .unwrap();
+        assert!(matches!(change, FileChange::Write { ref content } if content == "hello\n"));
+    }
+
+    #[test]
+    fn test_token_line_is_accumulated_into_usage() {
+        let mut processor =
+            AiderLogProcessor::new("/repo".to_string(), EntryIndexProvider::test_new());
+        processor.process_line(
+            "Tokens: 2.1k sent, 1.5k cache hit, 150 received. Cost: $0.01 message, $0.01 session.",
+        );
+        let outputs = processor
+            .process_line("Tokens: 3k sent, 200 received. Cost: $0.02 message, $0.03 session.");
+        let usage = outputs
+            .into_iter()
+            .find_map(|output| match output {
+                AiderOutput::Usage(usage) => Some(usage),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(usage.input_tokens, 5_100);
+        assert_eq!(usage.cache_read_input_tokens, 1_500);
+        assert_eq!(usage.output_tokens, 350);
+        assert_eq!(usage.cost_usd, Some(0.03));
+    }
+}
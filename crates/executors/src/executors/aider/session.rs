@@ -0,0 +1,63 @@
+use std::{fs, io, path::PathBuf};
+
+use uuid::Uuid;
+use workspace_utils::assets::asset_dir;
+
+/// Aider has no session ids of its own. A session is a chat history file that
+/// Aider appends to and can restore with `--restore-chat-history`.
+pub fn new_session() -> io::Result<Uuid> {
+    fs::create_dir_all(sessions_root())?;
+    Ok(Uuid::new_v4())
+}
+
+/// Copy a session's chat history under a new id, so that a follow-up never
+/// rewrites the history an earlier attempt might be retried from. The id is
+/// stored session data, so it must be one of ours before it becomes a path.
+pub fn fork_session(session_id: &str) -> io::Result<Uuid> {
+    let session_id = Uuid::parse_str(session_id).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid session id {session_id:?}: {e}"),
+        )
+    })?;
+    let source = chat_history_path(session_id);
+    if !source.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("chat history {} not found", source.display()),
+        ));
+    }
+
+    let new_session_id = new_session()?;
+    fs::copy(&source, chat_history_path(new_session_id))?;
+    Ok(new_session_id)
+}
+
+pub fn chat_history_path(session_id: Uuid) -> PathBuf {
+    sessions_root().join(format!("{session_id}.chat.md"))
+}
+
+pub fn input_history_path(session_id: Uuid) -> PathBuf {
+    sessions_root().join(format!("{session_id}.input"))
+}
+
+pub fn message_path(session_id: Uuid) -> PathBuf {
+    sessions_root().join(format!("{session_id}.message.md"))
+}
+
+fn sessions_root() -> PathBuf {
+    asset_dir().join("aider_sessions")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fork_rejects_session_ids_that_are_not_uuids() {
+        for session_id in ["../../etc/passwd", "/tmp/history", ""] {
+            let err = fork_session(session_id).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+}
@@ -14,7 +14,7 @@ use tokio::process::Command;
 use ts_rs::TS;
 use workspace_utils::{
     approvals::ApprovalStatus, diff::create_unified_diff, log_msg::LogMsg, msg_store::MsgStore,
-    path::make_path_relative,
+    path::make_path_relative, usage::UsageReport,
 };
 
 use self::{
@@ -311,6 +311,7 @@ impl ClaudeCode {
             child,
             exit_signal: None,
             interrupt_sender: Some(interrupt_tx),
+            temp_file: None,
         })
     }
 }
@@ -332,6 +333,8 @@ pub struct ClaudeLogProcessor {
     strategy: HistoryStrategy,
     streaming_messages: HashMap<String, StreamingMessageState>,
     streaming_message_id: Option<String>,
+    // Map message id -> latest usage, for a running total before the final result arrives
+    message_usage: HashMap<String, ClaudeUsage>,
 }
 
 impl ClaudeLogProcessor {
@@ -347,6 +350,7 @@ impl ClaudeLogProcessor {
             strategy,
             streaming_messages: HashMap::new(),
             streaming_message_id: None,
+            message_usage: HashMap::new(),
         }
     }
 
@@ -368,7 +372,10 @@ impl ClaudeLogProcessor {
             while let Some(Ok(msg)) = stream.next().await {
                 let chunk = match msg {
                     LogMsg::Stdout(x) => x,
-                    LogMsg::JsonPatch(_) | LogMsg::SessionId(_) | LogMsg::Stderr(_) => continue,
+                    LogMsg::JsonPatch(_)
+                    | LogMsg::SessionId(_)
+                    | LogMsg::Usage(_)
+                    | LogMsg::Stderr(_) => continue,
                     LogMsg::Finished => break,
                 };
 
@@ -404,6 +411,10 @@ impl ClaudeLogProcessor {
                                 session_id_extracted = true;
                             }
 
+                            if let Some(usage) = processor.track_usage(&claude_json) {
+                                msg_store.push_usage(usage);
+                            }
+
                             let patches = processor.normalize_entries(
                                 &claude_json,
                                 &worktree_path,
@@ -467,6 +478,43 @@ impl ClaudeLogProcessor {
         }
     }
 
+    /// Extract the run's token usage from the final Claude result message
+    fn extract_usage(claude_json: &ClaudeJson) -> Option<UsageReport> {
+        let ClaudeJson::Result {
+            usage,
+            total_cost_usd,
+            ..
+        } = claude_json
+        else {
+            return None;
+        };
+        if usage.is_none() && total_cost_usd.is_none() {
+            return None;
+        }
+
+        let mut report = usage_report(&usage.clone().unwrap_or_default());
+        report.cost_usd = *total_cost_usd;
+        Some(report)
+    }
+
+    /// Running usage of this run. Assistant messages carry per-message usage, which is summed
+    /// until the final result message reports the authoritative total.
+    fn track_usage(&mut self, claude_json: &ClaudeJson) -> Option<UsageReport> {
+        if let ClaudeJson::Assistant { message, .. } = claude_json
+            && let (Some(id), Some(usage)) = (&message.id, &message.usage)
+        {
+            // Messages are repeated once per content block, so keep the latest usage per id
+            self.message_usage.insert(id.clone(), usage.clone());
+            return Some(
+                self.message_usage
+                    .values()
+                    .map(usage_report)
+                    .fold(UsageReport::default(), |total, report| total + report),
+            );
+        }
+        Self::extract_usage(claude_json)
+    }
+
     /// Generate warning entry if API key source is ANTHROPIC_API_KEY
     fn warn_if_unmanaged_key(src: &Option<String>) -> Option<NormalizedEntry> {
         match src.as_deref() {
@@ -1504,6 +1552,10 @@ pub enum ClaudeJson {
         num_turns: Option<u32>,
         #[serde(default, alias = "sessionId")]
         session_id: Option<String>,
+        #[serde(default)]
+        usage: Option<ClaudeUsage>,
+        #[serde(default, alias = "totalCostUsd")]
+        total_cost_usd: Option<f64>,
     },
     #[serde(rename = "approval_response")]
     ApprovalResponse {
@@ -1528,6 +1580,8 @@ pub struct ClaudeMessage {
     pub model: Option<String>,
     pub content: Vec<ClaudeContentItem>,
     pub stop_reason: Option<String>,
+    #[serde(default)]
+    pub usage: Option<ClaudeUsage>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
@@ -1600,6 +1654,17 @@ pub struct ClaudeMessageDelta {
     pub stop_sequence: Option<String>,
 }
 
+fn usage_report(usage: &ClaudeUsage) -> UsageReport {
+    let tokens = |count: Option<u64>| count.unwrap_or(0) as i64;
+    UsageReport {
+        input_tokens: tokens(usage.input_tokens),
+        output_tokens: tokens(usage.output_tokens),
+        cache_creation_input_tokens: tokens(usage.cache_creation_input_tokens),
+        cache_read_input_tokens: tokens(usage.cache_read_input_tokens),
+        cost_usd: None,
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
 pub struct ClaudeUsage {
     #[serde(default)]
@@ -1901,6 +1966,24 @@ mod tests {
 
         let entries = normalize(&parsed, "");
         assert_eq!(entries.len(), 0); // Should be ignored like in old implementation
+        assert_eq!(ClaudeLogProcessor::extract_usage(&parsed), None);
+    }
+
+    #[test]
+    fn test_result_usage_extraction() {
+        let result_json = r#"{"type":"result","subtype":"success","is_error":false,"result":"Done","total_cost_usd":0.0421,"usage":{"input_tokens":12,"output_tokens":345,"cache_creation_input_tokens":6789,"cache_read_input_tokens":10111}}"#;
+        let parsed: ClaudeJson = serde_json::from_str(result_json).unwrap();
+
+        assert_eq!(
+            ClaudeLogProcessor::extract_usage(&parsed),
+            Some(UsageReport {
+                input_tokens: 12,
+                output_tokens: 345,
+                cache_creation_input_tokens: 6789,
+                cache_read_input_tokens: 10111,
+                cost_usd: Some(0.0421),
+            })
+        );
     }
 
     #[test]
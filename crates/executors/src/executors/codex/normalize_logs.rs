@@ -17,8 +17,8 @@ use codex_protocol::{
         ErrorEvent, EventMsg, ExecApprovalRequestEvent, ExecCommandBeginEvent, ExecCommandEndEvent,
         ExecCommandOutputDeltaEvent, ExecOutputStream, FileChange as CodexProtoFileChange,
         McpInvocation, McpToolCallBeginEvent, McpToolCallEndEvent, PatchApplyBeginEvent,
-        PatchApplyEndEvent, StreamErrorEvent, TokenUsageInfo, ViewImageToolCallEvent, WarningEvent,
-        WebSearchBeginEvent, WebSearchEndEvent,
+        PatchApplyEndEvent, StreamErrorEvent, TokenUsage, TokenUsageInfo, ViewImageToolCallEvent,
+        WarningEvent, WebSearchBeginEvent, WebSearchEndEvent,
     },
 };
 use futures::StreamExt;
@@ -27,7 +27,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use workspace_utils::{
     approvals::ApprovalStatus, diff::normalize_unified_diff, msg_store::MsgStore,
-    path::make_path_relative,
+    path::make_path_relative, usage::UsageReport,
 };
 
 use crate::{
@@ -216,6 +216,7 @@ struct LogState {
     patches: HashMap<String, PatchState>,
     web_searches: HashMap<String, WebSearchState>,
     token_usage_info: Option<TokenUsageInfo>,
+    usage_baseline: Option<UsageReport>,
 }
 
 enum StreamingTextKind {
@@ -234,9 +235,20 @@ impl LogState {
             patches: HashMap::new(),
             web_searches: HashMap::new(),
             token_usage_info: None,
+            usage_baseline: None,
         }
     }
 
+    /// Usage attributable to this run. Codex reports conversation-wide totals, so whatever
+    /// had been used before the first report of this run is subtracted.
+    fn usage_report(&mut self, info: &TokenUsageInfo) -> UsageReport {
+        let total = usage_from_codex(&info.total_token_usage);
+        let baseline = self.usage_baseline.get_or_insert_with(|| {
+            subtract_usage(&total, &usage_from_codex(&info.last_token_usage))
+        });
+        subtract_usage(&total, baseline)
+    }
+
     fn streaming_text_update(
         &mut self,
         content: String,
@@ -357,6 +369,30 @@ fn format_todo_status(status: &StepStatus) -> String {
     .to_string()
 }
 
+/// Codex counts cached tokens as part of the input, split them out to match other executors
+fn usage_from_codex(usage: &TokenUsage) -> UsageReport {
+    UsageReport {
+        input_tokens: usage.input_tokens - usage.cached_input_tokens,
+        output_tokens: usage.output_tokens,
+        cache_creation_input_tokens: 0,
+        cache_read_input_tokens: usage.cached_input_tokens,
+        cost_usd: None,
+    }
+}
+
+fn subtract_usage(total: &UsageReport, earlier: &UsageReport) -> UsageReport {
+    UsageReport {
+        input_tokens: (total.input_tokens - earlier.input_tokens).max(0),
+        output_tokens: (total.output_tokens - earlier.output_tokens).max(0),
+        cache_creation_input_tokens: (total.cache_creation_input_tokens
+            - earlier.cache_creation_input_tokens)
+            .max(0),
+        cache_read_input_tokens: (total.cache_read_input_tokens - earlier.cache_read_input_tokens)
+            .max(0),
+        cost_usd: None,
+    }
+}
+
 pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
     let entry_index = EntryIndexProvider::start_from(&msg_store);
     normalize_stderr_logs(msg_store.clone(), entry_index.clone());
@@ -971,6 +1007,7 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                 }
                 EventMsg::TokenCount(payload) => {
                     if let Some(info) = payload.info {
+                        msg_store.push_usage(state.usage_report(&info));
                         state.token_usage_info = Some(info);
                     }
                 }
@@ -0,0 +1,608 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use command_group::AsyncCommandGroup;
+use derivative::Derivative;
+use futures::StreamExt;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tempfile::NamedTempFile;
+use tokio::{fs, io::AsyncWriteExt, process::Command};
+use ts_rs::TS;
+use workspace_utils::{
+    msg_store::MsgStore, path::get_vibe_kanban_temp_dir, shell::resolve_executable_path_blocking,
+};
+
+use crate::{
+    approvals::ExecutorApprovalService,
+    command::{CmdOverrides, CommandBuilder, apply_overrides},
+    env::ExecutionEnv,
+    executors::{
+        AppendPrompt, AvailabilityInfo, ExecutorError, SpawnedChild, StandardCodingAgentExecutor,
+        acp::AcpAgentHarness,
+    },
+    logs::{
+        ActionType, NormalizedEntry, NormalizedEntryError, NormalizedEntryType, ToolStatus,
+        plain_text_processor::PlainTextLogProcessor,
+        stderr_processor::normalize_stderr_logs,
+        utils::{ConversationPatch, EntryIndexProvider},
+    },
+};
+
+/// How the prompt is handed to the agent process: as the last argument, on stdin
+/// (which is then closed), or in a temporary file whose path is the last argument
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptInput {
+    Argument,
+    #[default]
+    Stdin,
+    File,
+}
+
+/// How the agent's stdout is turned into conversation entries: free-form text,
+/// Agent Client Protocol over stdio, or one JSON object per line read through
+/// the `jsonl` field mappings
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    #[default]
+    PlainText,
+    Acp,
+    Jsonl,
+}
+
+/// Maps the fields of a JSONL event stream onto conversation entries.
+/// Fields are addressed with JSON pointers, e.g. `/message/text`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct JsonlMapping {
+    #[serde(default = "default_type_field")]
+    #[schemars(
+        title = "Type Field",
+        description = "JSON pointer to the field holding the event type"
+    )]
+    pub type_field: String,
+    #[serde(default = "default_content_field")]
+    #[schemars(
+        title = "Content Field",
+        description = "JSON pointer to the field holding the event text"
+    )]
+    pub content_field: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Session ID Field",
+        description = "JSON pointer to the field holding the session ID used for follow-ups"
+    )]
+    pub session_id_field: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Tool Name Field",
+        description = "JSON pointer to the field holding the tool name of tool events"
+    )]
+    pub tool_name_field: Option<String>,
+    #[serde(default)]
+    #[schemars(
+        title = "Assistant Types",
+        description = "Event types shown as assistant messages. When empty, every other event with content is shown"
+    )]
+    pub assistant_types: Vec<String>,
+    #[serde(default)]
+    #[schemars(
+        title = "Thinking Types",
+        description = "Event types shown as thinking"
+    )]
+    pub thinking_types: Vec<String>,
+    #[serde(default)]
+    #[schemars(title = "Tool Types", description = "Event types shown as tool calls")]
+    pub tool_types: Vec<String>,
+    #[serde(default)]
+    #[schemars(title = "Error Types", description = "Event types shown as errors")]
+    pub error_types: Vec<String>,
+}
+
+fn default_type_field() -> String {
+    "/type".to_string()
+}
+
+fn default_content_field() -> String {
+    "/content".to_string()
+}
+
+impl Default for JsonlMapping {
+    fn default() -> Self {
+        Self {
+            type_field: default_type_field(),
+            content_field: default_content_field(),
+            session_id_field: None,
+            tool_name_field: None,
+            assistant_types: Vec::new(),
+            thinking_types: Vec::new(),
+            tool_types: Vec::new(),
+            error_types: Vec::new(),
+        }
+    }
+}
+
+impl JsonlMapping {
+    fn session_id(&self, event: &Value) -> Option<String> {
+        let field = self.session_id_field.as_ref()?;
+        event
+            .pointer(field)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    }
+
+    /// Convert one event into a conversation entry, or None if it should not be shown
+    fn to_entry(&self, event: &Value) -> Option<NormalizedEntry> {
+        let event_type = event
+            .pointer(&self.type_field)
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let content = match event.pointer(&self.content_field) {
+            Some(Value::String(text)) => text.clone(),
+            Some(Value::Null) | None => String::new(),
+            Some(other) => other.to_string(),
+        };
+        let matches = |types: &[String]| types.iter().any(|t| t == event_type);
+
+        let entry_type = if matches(&self.tool_types) {
+            let tool_name = self
+                .tool_name_field
+                .as_ref()
+                .and_then(|field| event.pointer(field))
+                .and_then(Value::as_str)
+                .unwrap_or(event_type)
+                .to_string();
+            NormalizedEntryType::ToolUse {
+                tool_name: tool_name.clone(),
+                action_type: ActionType::Tool {
+                    tool_name,
+                    arguments: None,
+                    result: None,
+                },
+                status: ToolStatus::Success,
+            }
+        } else if content.is_empty() {
+            return None;
+        } else if matches(&self.thinking_types) {
+            NormalizedEntryType::Thinking
+        } else if matches(&self.error_types) {
+            NormalizedEntryType::ErrorMessage {
+                error_type: NormalizedEntryError::Other,
+            }
+        } else if self.assistant_types.is_empty() || matches(&self.assistant_types) {
+            NormalizedEntryType::AssistantMessage
+        } else {
+            return None;
+        };
+
+        Some(NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content,
+            metadata: Some(event.clone()),
+        })
+    }
+}
+
+/// Executor for CLI agents without a built-in integration, described entirely by
+/// its profile configuration
+#[derive(Derivative, Clone, Serialize, Deserialize, TS, JsonSchema)]
+#[derivative(Debug, PartialEq)]
+pub struct CustomAgent {
+    #[serde(default)]
+    pub append_prompt: AppendPrompt,
+    #[schemars(
+        title = "Command",
+        description = "Command that starts the agent, including any fixed arguments"
+    )]
+    pub command: String,
+    #[serde(default)]
+    #[schemars(
+        title = "Prompt Input",
+        description = "Pass the prompt as the last argument, on stdin, or in a temporary file whose path is the last argument"
+    )]
+    pub prompt_input: PromptInput,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Prompt Flag",
+        description = "Flag placed before the prompt or prompt file argument, e.g. --prompt"
+    )]
+    pub prompt_flag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Resume Arguments",
+        description = "Arguments added for follow-ups, with {session_id} replaced by the session to resume. Not needed for ACP agents"
+    )]
+    pub resume_args: Option<Vec<String>>,
+    #[serde(default)]
+    #[schemars(
+        title = "Output Format",
+        description = "Plain text, an ACP stream, or JSON lines read through the JSONL mapping"
+    )]
+    pub output_format: OutputFormat,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Session ID Pattern",
+        description = "Regex matched against plain text output; its first capture group is the session ID"
+    )]
+    pub session_id_pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "JSONL Mapping",
+        description = "Field mappings used when the output format is JSONL"
+    )]
+    pub jsonl: Option<JsonlMapping>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "MCP Config Path",
+        description = "Absolute path of the agent's MCP server config file"
+    )]
+    pub mcp_config_path: Option<String>,
+    #[serde(flatten)]
+    pub cmd: CmdOverrides,
+    #[serde(skip)]
+    #[ts(skip)]
+    #[derivative(Debug = "ignore", PartialEq = "ignore")]
+    pub approvals: Option<Arc<dyn ExecutorApprovalService>>,
+}
+
+impl CustomAgent {
+    const ACP_SESSION_NAMESPACE: &'static str = "custom_agent_sessions";
+
+    fn build_command_builder(&self) -> CommandBuilder {
+        apply_overrides(CommandBuilder::new(self.command.clone()), &self.cmd)
+    }
+
+    fn resume_args(&self, session_id: &str) -> Result<Vec<String>, ExecutorError> {
+        let resume_args = self.resume_args.as_ref().ok_or_else(|| {
+            ExecutorError::FollowUpNotSupported(
+                "custom agent has no resume_args configured".to_string(),
+            )
+        })?;
+        Ok(resume_args
+            .iter()
+            .map(|arg| arg.replace("{session_id}", session_id))
+            .collect())
+    }
+
+    /// Write the prompt to a temporary file, which is removed when the returned handle drops
+    async fn write_prompt_file(
+        current_dir: &Path,
+        prompt: &str,
+    ) -> Result<NamedTempFile, ExecutorError> {
+        let prompt_dir = get_vibe_kanban_temp_dir()
+            .join("custom_agent_prompts")
+            .join(current_dir.file_name().unwrap_or_default());
+        fs::create_dir_all(&prompt_dir)
+            .await
+            .map_err(ExecutorError::Io)?;
+
+        let prompt_file = tempfile::Builder::new()
+            .suffix(".md")
+            .tempfile_in(&prompt_dir)
+            .map_err(ExecutorError::Io)?;
+        fs::write(prompt_file.path(), prompt)
+            .await
+            .map_err(ExecutorError::Io)?;
+        Ok(prompt_file)
+    }
+
+    async fn spawn_process(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: Option<&str>,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+
+        if self.output_format == OutputFormat::Acp {
+            let harness = AcpAgentHarness::with_session_namespace(Self::ACP_SESSION_NAMESPACE);
+            return match session_id {
+                Some(session_id) => {
+                    let command_parts = self.build_command_builder().build_follow_up(&[])?;
+                    harness
+                        .spawn_follow_up_with_command(
+                            current_dir,
+                            combined_prompt,
+                            session_id,
+                            command_parts,
+                            env,
+                            &self.cmd,
+                            self.approvals.clone(),
+                        )
+                        .await
+                }
+                None => {
+                    let command_parts = self.build_command_builder().build_initial()?;
+                    harness
+                        .spawn_with_command(
+                            current_dir,
+                            combined_prompt,
+                            command_parts,
+                            env,
+                            &self.cmd,
+                            self.approvals.clone(),
+                        )
+                        .await
+                }
+            };
+        }
+
+        let (program_path, mut args) = self
+            .build_command_builder()
+            .build_initial()?
+            .into_resolved()
+            .await?;
+
+        // Appended after resolving so the prompt is passed verbatim rather than re-split
+        if let Some(session_id) = session_id {
+            args.extend(self.resume_args(session_id)?);
+        }
+        if self.prompt_input != PromptInput::Stdin {
+            args.extend(self.prompt_flag.clone());
+        }
+        let mut prompt_file = None;
+        match self.prompt_input {
+            PromptInput::Argument => args.push(combined_prompt.clone()),
+            PromptInput::File => {
+                let file = Self::write_prompt_file(current_dir, &combined_prompt).await?;
+                args.push(file.path().to_string_lossy().to_string());
+                prompt_file = Some(file);
+            }
+            PromptInput::Stdin => {}
+        }
+
+        let stdin = if self.prompt_input == PromptInput::Stdin {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        };
+
+        let mut command = Command::new(program_path);
+        command
+            .kill_on_drop(true)
+            .stdin(stdin)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .current_dir(current_dir)
+            .args(&args);
+
+        env.clone()
+            .with_profile(&self.cmd)
+            .apply_to_command(&mut command);
+
+        let mut child = command.group_spawn()?;
+
+        if self.prompt_input == PromptInput::Stdin
+            && let Some(mut stdin) = child.inner().stdin.take()
+        {
+            stdin.write_all(combined_prompt.as_bytes()).await?;
+            stdin.shutdown().await?;
+        }
+
+        // The agent may read the prompt file at any point, so it lives as long as the process
+        let mut spawned = SpawnedChild::from(child);
+        spawned.temp_file = prompt_file;
+        Ok(spawned)
+    }
+
+    fn normalize_plain_text(&self, msg_store: Arc<MsgStore>, entry_index: EntryIndexProvider) {
+        let session_id_re =
+            self.session_id_pattern
+                .as_deref()
+                .and_then(|pattern| match Regex::new(pattern) {
+                    Ok(re) => Some(re),
+                    Err(e) => {
+                        tracing::warn!("Invalid session_id_pattern for custom agent: {}", e);
+                        None
+                    }
+                });
+
+        tokio::spawn(async move {
+            let mut stdout_lines = msg_store.stdout_lines_stream();
+            let mut processor = PlainTextLogProcessor::builder()
+                .normalized_entry_producer(Box::new(|content: String| NormalizedEntry {
+                    timestamp: None,
+                    entry_type: NormalizedEntryType::AssistantMessage,
+                    content,
+                    metadata: None,
+                }))
+                .transform_lines(Box::new(|lines| {
+                    lines.iter_mut().for_each(|line| {
+                        *line = strip_ansi_escapes::strip_str(&line);
+                    })
+                }))
+                .index_provider(entry_index)
+                .build();
+
+            let mut session_id_found = false;
+            while let Some(Ok(line)) = stdout_lines.next().await {
+                if !session_id_found
+                    && let Some(caps) = session_id_re.as_ref().and_then(|re| re.captures(&line))
+                    && let Some(session_id) = caps.get(1).or_else(|| caps.get(0))
+                {
+                    msg_store.push_session_id(session_id.as_str().to_string());
+                    session_id_found = true;
+                }
+
+                for patch in processor.process(line + "\n") {
+                    msg_store.push_patch(patch);
+                }
+            }
+        });
+    }
+
+    fn normalize_jsonl(&self, msg_store: Arc<MsgStore>, entry_index: EntryIndexProvider) {
+        let mapping = self.jsonl.clone().unwrap_or_default();
+
+        tokio::spawn(async move {
+            let mut stdout_lines = msg_store.stdout_lines_stream();
+            let mut session_id_found = false;
+
+            while let Some(Ok(line)) = stdout_lines.next().await {
+                let Ok(event) = serde_json::from_str::<Value>(line.trim()) else {
+                    continue;
+                };
+
+                if !session_id_found && let Some(session_id) = mapping.session_id(&event) {
+                    msg_store.push_session_id(session_id);
+                    session_id_found = true;
+                }
+
+                if let Some(entry) = mapping.to_entry(&event) {
+                    msg_store.push_patch(ConversationPatch::add_normalized_entry(
+                        entry_index.next(),
+                        entry,
+                    ));
+                }
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl StandardCodingAgentExecutor for CustomAgent {
+    fn use_approvals(&mut self, approvals: Arc<dyn ExecutorApprovalService>) {
+        self.approvals = Some(approvals);
+    }
+
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        self.spawn_process(current_dir, prompt, None, env).await
+    }
+
+    async fn spawn_follow_up(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        self.spawn_process(current_dir, prompt, Some(session_id), env)
+            .await
+    }
+
+    fn normalize_logs(&self, msg_store: Arc<MsgStore>, worktree_path: &Path) {
+        if self.output_format == OutputFormat::Acp {
+            crate::executors::acp::normalize_logs(msg_store, worktree_path);
+            return;
+        }
+
+        let entry_index = EntryIndexProvider::start_from(&msg_store);
+        normalize_stderr_logs(msg_store.clone(), entry_index.clone());
+
+        match self.output_format {
+            OutputFormat::Jsonl => self.normalize_jsonl(msg_store, entry_index),
+            _ => self.normalize_plain_text(msg_store, entry_index),
+        }
+    }
+
+    // MCP configuration methods
+    fn default_mcp_config_path(&self) -> Option<std::path::PathBuf> {
+        self.mcp_config_path.as_ref().map(PathBuf::from)
+    }
+
+    fn get_availability_info(&self) -> AvailabilityInfo {
+        let binary_found = self
+            .build_command_builder()
+            .build_initial()
+            .ok()
+            .and_then(|parts| resolve_executable_path_blocking(parts.program()))
+            .is_some();
+
+        if binary_found {
+            AvailabilityInfo::InstallationFound
+        } else {
+            AvailabilityInfo::NotFound
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn prompt_file_is_removed_when_dropped() {
+        let prompt_file = CustomAgent::write_prompt_file(Path::new("/tmp/worktree"), "do it")
+            .await
+            .unwrap();
+        let path = prompt_file.path().to_path_buf();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "do it");
+        assert_eq!(path.extension().unwrap(), "md");
+
+        drop(prompt_file);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_jsonl_mapping_to_entry() {
+        let mapping = JsonlMapping {
+            content_field: "/message/text".to_string(),
+            session_id_field: Some("/session".to_string()),
+            tool_name_field: Some("/tool".to_string()),
+            assistant_types: vec!["say".to_string()],
+            thinking_types: vec!["think".to_string()],
+            tool_types: vec!["tool_call".to_string()],
+            ..Default::default()
+        };
+
+        let event = json!({"type": "say", "session": "abc", "message": {"text": "hello"}});
+        assert_eq!(mapping.session_id(&event).as_deref(), Some("abc"));
+        let entry = mapping.to_entry(&event).unwrap();
+        assert!(matches!(
+            entry.entry_type,
+            NormalizedEntryType::AssistantMessage
+        ));
+        assert_eq!(entry.content, "hello");
+
+        let event = json!({"type": "think", "message": {"text": "hmm"}});
+        let entry = mapping.to_entry(&event).unwrap();
+        assert!(matches!(entry.entry_type, NormalizedEntryType::Thinking));
+
+        let event = json!({"type": "tool_call", "tool": "grep"});
+        let entry = mapping.to_entry(&event).unwrap();
+        assert!(matches!(
+            entry.entry_type,
+            NormalizedEntryType::ToolUse { ref tool_name, .. } if tool_name == "grep"
+        ));
+
+        // Unmapped types are dropped once assistant types are listed
+        let event = json!({"type": "progress", "message": {"text": "50%"}});
+        assert!(mapping.to_entry(&event).is_none());
+    }
+
+    #[test]
+    fn test_resume_args_substitute_session_id() {
+        let agent: CustomAgent = serde_json::from_value(json!({
+            "command": "my-agent run",
+            "resume_args": ["--resume", "{session_id}"]
+        }))
+        .unwrap();
+        assert_eq!(
+            agent.resume_args("s-1").unwrap(),
+            vec!["--resume".to_string(), "s-1".to_string()]
+        );
+
+        let agent: CustomAgent =
+            serde_json::from_value(json!({ "command": "my-agent run" })).unwrap();
+        assert!(matches!(
+            agent.resume_args("s-1"),
+            Err(ExecutorError::FollowUpNotSupported(_))
+        ));
+    }
+}
@@ -8,6 +8,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use sqlx::Type;
 use strum_macros::{Display, EnumDiscriminants, EnumString, VariantNames};
+use tempfile::NamedTempFile;
 use thiserror::Error;
 use ts_rs::TS;
 use workspace_utils::msg_store::MsgStore;
@@ -18,18 +19,29 @@ use crate::{
     command::CommandBuildError,
     env::ExecutionEnv,
     executors::{
-        amp::Amp, claude::ClaudeCode, codex::Codex, copilot::Copilot, cursor::CursorAgent,
-        droid::Droid, gemini::Gemini, opencode::Opencode, qwen::QwenCode,
+        aider::Aider,
+        amp::Amp,
+        claude::ClaudeCode,
+        codex::Codex,
+        copilot::Copilot,
+        cursor::CursorAgent,
+        custom::{CustomAgent, OutputFormat},
+        droid::Droid,
+        gemini::Gemini,
+        opencode::Opencode,
+        qwen::QwenCode,
     },
     mcp_config::McpConfig,
 };
 
 pub mod acp;
+pub mod aider;
 pub mod amp;
 pub mod claude;
 pub mod codex;
 pub mod copilot;
 pub mod cursor;
+pub mod custom;
 pub mod droid;
 pub mod gemini;
 pub mod opencode;
@@ -42,6 +54,8 @@ pub enum BaseAgentCapability {
     SessionFork,
     /// Agent requires a setup script before it can run (e.g., login, installation)
     SetupHelper,
+    /// Agent reports token usage. Executions of other agents have no usage, not zero usage.
+    UsageReporting,
 }
 
 #[derive(Debug, Error)]
@@ -100,6 +114,8 @@ pub enum CodingAgent {
     QwenCode,
     Copilot,
     Droid,
+    Aider,
+    CustomAgent,
 }
 
 impl CodingAgent {
@@ -155,18 +171,32 @@ impl CodingAgent {
 
     pub fn capabilities(&self) -> Vec<BaseAgentCapability> {
         match self {
-            Self::ClaudeCode(_)
-            | Self::Amp(_)
-            | Self::Gemini(_)
-            | Self::QwenCode(_)
-            | Self::Droid(_)
-            | Self::Opencode(_) => vec![BaseAgentCapability::SessionFork],
+            Self::ClaudeCode(_) => vec![
+                BaseAgentCapability::SessionFork,
+                BaseAgentCapability::UsageReporting,
+            ],
+            Self::Amp(_) | Self::Droid(_) | Self::Opencode(_) => {
+                vec![BaseAgentCapability::SessionFork]
+            }
+            Self::Gemini(_) | Self::QwenCode(_) => vec![
+                BaseAgentCapability::SessionFork,
+                BaseAgentCapability::UsageReporting,
+            ],
+            Self::Aider(_) => vec![
+                BaseAgentCapability::SessionFork,
+                BaseAgentCapability::UsageReporting,
+            ],
             Self::Codex(_) => vec![
                 BaseAgentCapability::SessionFork,
                 BaseAgentCapability::SetupHelper,
+                BaseAgentCapability::UsageReporting,
             ],
             Self::CursorAgent(_) => vec![BaseAgentCapability::SetupHelper],
-            Self::Copilot(_) => vec![],
+            Self::CustomAgent(custom) if custom.output_format == OutputFormat::Acp => vec![
+                BaseAgentCapability::SessionFork,
+                BaseAgentCapability::UsageReporting,
+            ],
+            Self::Copilot(_) | Self::CustomAgent(_) => vec![],
         }
     }
 }
@@ -255,6 +285,8 @@ pub struct SpawnedChild {
     pub exit_signal: Option<ExecutorExitSignal>,
     /// Container → Executor: signals when container wants to interrupt
     pub interrupt_sender: Option<InterruptSender>,
+    /// File the process reads while it runs, removed once it has exited
+    pub temp_file: Option<NamedTempFile>,
 }
 
 impl From<AsyncGroupChild> for SpawnedChild {
@@ -263,6 +295,7 @@ impl From<AsyncGroupChild> for SpawnedChild {
             child,
             exit_signal: None,
             interrupt_sender: None,
+            temp_file: None,
         }
     }
 }
@@ -1,9 +1,9 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     io,
     path::{Path, PathBuf},
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use anyhow::anyhow;
@@ -19,7 +19,7 @@ use db::{
         execution_process_repo_state::ExecutionProcessRepoState,
         project_repo::ProjectRepo,
         repo::Repo,
-        scratch::{DraftFollowUpData, Scratch, ScratchType},
+        scratch::{Scratch, ScratchType},
         task::{Task, TaskStatus},
         workspace::Workspace,
         workspace_repo::WorkspaceRepo,
@@ -35,24 +35,33 @@ use executors::{
     approvals::{ExecutorApprovalService, NoopExecutorApprovalService},
     env::ExecutionEnv,
     executors::{BaseCodingAgent, ExecutorExitResult, ExecutorExitSignal, InterruptSender},
-    logs::{NormalizedEntryType, utils::patch::extract_normalized_entry_from_patch},
-    profile::ExecutorProfileId,
+    logs::{
+        NormalizedEntryError, NormalizedEntryType,
+        utils::{
+            ConversationPatch, EntryIndexProvider, patch::extract_normalized_entry_from_patch,
+        },
+    },
+    profile::{ExecutorConfigs, ExecutorProfileId},
 };
-use futures::{FutureExt, TryStreamExt, stream::select};
+use futures::{FutureExt, StreamExt, TryStreamExt, stream::select};
 use serde_json::json;
 use services::services::{
     analytics::AnalyticsContext,
     approvals::{Approvals, executor_approvals::ExecutorApprovalBridge},
-    config::Config,
-    container::{ContainerError, ContainerRef, ContainerService},
+    budget::{BudgetLimits, token_limit_skipped_entry},
+    config::{ConcurrencyConfig, Config},
+    conflict_resolution,
+    container::{ContainerError, ContainerRef, ContainerService, executor_skipped_entry},
     diff_stream::{self, DiffStreamHandle},
-    git::{Commit, GitCli, GitService},
+    git::{Commit, GitCli, GitService, GitServiceError, SparseCheckout},
     image::ImageService,
     notification::NotificationService,
     queued_message::QueuedMessageService,
     share::SharePublisher,
     workspace_manager::{RepoWorkspaceInput, WorkspaceManager},
+    worktree_pool,
 };
+use tempfile::NamedTempFile;
 use tokio::{sync::RwLock, task::JoinHandle};
 use tokio_util::io::ReaderStream;
 use utils::{
@@ -311,7 +320,7 @@ impl LocalContainerService {
     }
 
     /// Commit changes to each repo. Logs failures but continues with other repos.
-    fn commit_repos(&self, repos_with_changes: Vec<(Repo, PathBuf)>, message: &str) -> bool {
+    async fn commit_repos(&self, repos_with_changes: Vec<(Repo, PathBuf)>, message: &str) -> bool {
         let mut any_committed = false;
 
         for (repo, worktree_path) in repos_with_changes {
@@ -329,6 +338,16 @@ impl LocalContainerService {
                 Ok(false) => {
                     tracing::warn!("No changes committed in repo '{}' (unexpected)", repo.name);
                 }
+                Err(GitServiceError::SigningFailed(msg)) => {
+                    tracing::error!("Failed to sign commit in repo '{}': {}", repo.name, msg);
+                    // Changes stay uncommitted in the worktree until signing works again
+                    self.notification_service()
+                        .notify(
+                            "Commit Signing Failed",
+                            &format!("❌ Changes in '{}' were not committed\n{msg}", repo.name),
+                        )
+                        .await;
+                }
                 Err(e) => {
                     tracing::warn!("Failed to commit in repo '{}': {}", repo.name, e);
                 }
@@ -344,6 +363,7 @@ impl LocalContainerService {
         &self,
         exec_id: &Uuid,
         exit_signal: Option<ExecutorExitSignal>,
+        temp_file: Option<NamedTempFile>,
     ) -> JoinHandle<()> {
         let exec_id = *exec_id;
         let child_store = self.child_store.clone();
@@ -390,6 +410,9 @@ impl LocalContainerService {
                 }
             }
 
+            // The process is gone, so the file it was reading can go too
+            drop(temp_file);
+
             let (exit_code, status) = match status_result {
                 Ok(exit_status) => {
                     let code = exit_status.code().unwrap_or(-1) as i64;
@@ -410,6 +433,11 @@ impl LocalContainerService {
                 tracing::error!("Failed to update execution process completion: {}", e);
             }
 
+            container
+                .approvals
+                .cancel_for_execution_process(exec_id)
+                .await;
+
             if let Ok(ctx) = ExecutionProcess::load_context(&db.pool, exec_id).await {
                 // Update executor session summary if available
                 if let Err(e) = container.update_executor_session_summary(&exec_id).await {
@@ -429,7 +457,19 @@ impl LocalContainerService {
                     ExecutionProcessStatus::Running
                 );
 
-                if success || cleanup_done {
+                // A turn resolving rebase conflicts leaves its changes to the rebase
+                let conflict_repo_id =
+                    if ctx.execution_process.run_reason == ExecutionProcessRunReason::CodingAgent {
+                        CodingAgentTurn::find_by_execution_process_id(&db.pool, exec_id)
+                            .await
+                            .ok()
+                            .flatten()
+                            .and_then(|turn| turn.conflict_repo_id)
+                    } else {
+                        None
+                    };
+
+                if (success || cleanup_done) && conflict_repo_id.is_none() {
                     // Commit changes (if any) and get feedback about whether changes were made
                     let changes_committed = match container.try_commit_changes(&ctx).await {
                         Ok(committed) => committed,
@@ -465,12 +505,49 @@ impl LocalContainerService {
                     }
                 }
 
-                if container.should_finalize(&ctx) {
+                // A failed verification goes back to the agent while retries remain,
+                // in which case the task isn't finalized yet
+                let verify_retry_started = ctx.execution_process.run_reason
+                    == ExecutionProcessRunReason::VerifyScript
+                    && ctx.execution_process.status == ExecutionProcessStatus::Failed
+                    && match container.try_start_verify_retry(&ctx).await {
+                        Ok(started) => started,
+                        Err(e) => {
+                            tracing::error!("Failed to start verify retry follow-up: {}", e);
+                            false
+                        }
+                    };
+
+                // An agent that exited because it isn't signed in hands the task to the
+                // next executor in the fallback chain
+                let fallback_started = ctx.execution_process.run_reason
+                    == ExecutionProcessRunReason::CodingAgent
+                    && ctx.execution_process.status == ExecutionProcessStatus::Failed
+                    && container.try_start_fallback_after_setup_error(&ctx).await;
+
+                // Once the agent has resolved the conflicts the rebase is continued, which
+                // may stop on the next conflicting commit and start another resolution turn
+                let conflict_resolution_started = match conflict_repo_id {
+                    Some(repo_id) if success => {
+                        container
+                            .try_continue_conflict_resolution(&ctx, repo_id)
+                            .await
+                    }
+                    _ => false,
+                };
+
+                if !verify_retry_started
+                    && !fallback_started
+                    && !conflict_resolution_started
+                    && container.should_finalize(&ctx)
+                {
                     // Only execute queued messages if the execution succeeded
-                    // If it failed or was killed, just clear the queue and finalize
+                    // If it failed, was killed or ran over budget, just clear the queue and finalize
                     let should_execute_queued = !matches!(
                         ctx.execution_process.status,
-                        ExecutionProcessStatus::Failed | ExecutionProcessStatus::Killed
+                        ExecutionProcessStatus::Failed
+                            | ExecutionProcessStatus::Killed
+                            | ExecutionProcessStatus::BudgetExceeded
                     );
 
                     if let Some(queued_msg) =
@@ -498,7 +575,11 @@ impl LocalContainerService {
 
                             // Execute the queued follow-up
                             if let Err(e) = container
-                                .start_queued_follow_up(&ctx, &queued_msg.data)
+                                .start_follow_up(
+                                    &ctx,
+                                    &queued_msg.data.message,
+                                    queued_msg.data.variant.clone(),
+                                )
                                 .await
                             {
                                 tracing::error!("Failed to start queued follow-up: {}", e);
@@ -558,6 +639,11 @@ impl LocalContainerService {
 
             // Cleanup child handle
             child_store.write().await.remove(&exec_id);
+
+            // A slot may have freed up for queued executions
+            if let Err(e) = container.start_queued_executions().await {
+                tracing::error!("Failed to start queued executions: {}", e);
+            }
         })
     }
 
@@ -598,6 +684,67 @@ impl LocalContainerService {
         rx
     }
 
+    /// Watch a coding agent's output and interrupt it once it runs past any of its limits
+    pub fn spawn_budget_monitor(&self, exec_id: Uuid, limits: BudgetLimits) -> JoinHandle<()> {
+        let container = self.clone();
+
+        tokio::spawn(async move {
+            let Some(store) = container.get_msg_store_by_id(&exec_id).await else {
+                return;
+            };
+            let started_at = Instant::now();
+            let mut stream = store.history_plus_stream();
+            let mut ticker = tokio::time::interval(Duration::from_secs(1));
+            let mut tokens = 0;
+            let mut tool_call_indices = HashSet::new();
+
+            let violation = loop {
+                tokio::select! {
+                    msg = stream.next() => match msg {
+                        Some(Ok(LogMsg::Usage(usage))) => tokens = usage.total_tokens(),
+                        Some(Ok(LogMsg::JsonPatch(patch))) => {
+                            // Tool status updates replace the same entry, so count distinct indices
+                            if let Some((index, entry)) = extract_normalized_entry_from_patch(&patch)
+                                && matches!(entry.entry_type, NormalizedEntryType::ToolUse { .. })
+                            {
+                                tool_call_indices.insert(index);
+                            }
+                        }
+                        Some(Ok(LogMsg::Finished)) | Some(Err(_)) | None => return,
+                        Some(Ok(_)) => continue,
+                    },
+                    _ = ticker.tick() => {}
+                }
+
+                if let Some(violation) =
+                    limits.exceeded(tokens, tool_call_indices.len(), started_at.elapsed())
+                {
+                    break violation;
+                }
+            };
+
+            let process = match ExecutionProcess::find_by_id(&container.db.pool, exec_id).await {
+                Ok(Some(process)) if process.status == ExecutionProcessStatus::Running => process,
+                _ => return,
+            };
+            tracing::warn!(
+                "Execution process {} exceeded its {}, stopping it",
+                exec_id,
+                violation
+            );
+            if let Err(e) = container
+                .stop_execution(&process, ExecutionProcessStatus::BudgetExceeded)
+                .await
+            {
+                tracing::error!(
+                    "Failed to stop execution process {} after it exceeded its budget: {}",
+                    exec_id,
+                    e
+                );
+            }
+        })
+    }
+
     pub fn dir_name_from_workspace(workspace_id: &Uuid, task_title: &str) -> String {
         let task_title_id = git_branch_id(task_title);
         format!("{}-{}", short_uuid(workspace_id), task_title_id)
@@ -678,6 +825,142 @@ impl LocalContainerService {
         None
     }
 
+    /// The setup error (e.g. missing authentication) reported in an execution's conversation
+    fn extract_setup_error(&self, exec_id: &Uuid) -> Option<String> {
+        let msg_stores = self.msg_stores.try_read().ok()?;
+        let msg_store = msg_stores.get(exec_id)?;
+
+        msg_store.get_history().iter().find_map(|msg| match msg {
+            LogMsg::JsonPatch(patch) => extract_normalized_entry_from_patch(patch)
+                .filter(|(_, entry)| {
+                    matches!(
+                        entry.entry_type,
+                        NormalizedEntryType::ErrorMessage {
+                            error_type: NormalizedEntryError::SetupRequired
+                        }
+                    )
+                })
+                .map(|(_, entry)| entry.content),
+            _ => None,
+        })
+    }
+
+    /// Start the next fallback executor for a coding agent run that failed on a setup error,
+    /// noting the skip in the failed run's conversation. Returns whether one was started.
+    async fn try_start_fallback_after_setup_error(&self, ctx: &ExecutionContext) -> bool {
+        let Some(reason) = self.extract_setup_error(&ctx.execution_process.id) else {
+            return false;
+        };
+        let Ok(ExecutorActionType::CodingAgentInitialRequest(request)) = ctx
+            .execution_process
+            .executor_action()
+            .map(|action| action.typ())
+        else {
+            return false;
+        };
+
+        match self
+            .try_start_executor_fallback(&ctx.workspace, &ctx.session, &ctx.execution_process)
+            .await
+        {
+            Ok(Some((fallback, _))) => {
+                if let Some(msg_store) = self.get_msg_store_by_id(&ctx.execution_process.id).await {
+                    let notice = executor_skipped_entry(
+                        &request.executor_profile_id,
+                        &fallback,
+                        reason.trim_end_matches('.'),
+                    );
+                    let index = EntryIndexProvider::start_from(&msg_store).next();
+                    msg_store.push_patch(ConversationPatch::add_normalized_entry(index, notice));
+                }
+                true
+            }
+            Ok(None) => false,
+            Err(e) => {
+                tracing::error!("Failed to start fallback executor: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Continue the rebase a conflict resolution turn was started for. Conflict markers left
+    /// in the worktree keep the rebase stopped for the user to look at. Returns whether the
+    /// rebase stopped on another conflict and a new resolution turn was started.
+    async fn try_continue_conflict_resolution(
+        &self,
+        ctx: &ExecutionContext,
+        repo_id: Uuid,
+    ) -> bool {
+        let Some(repo) = ctx.repos.iter().find(|repo| repo.id == repo_id) else {
+            return false;
+        };
+        let worktree_path = self
+            .workspace_to_current_dir(&ctx.workspace)
+            .join(&repo.name);
+
+        let changed = self
+            .git
+            .get_uncommitted_paths(&worktree_path)
+            .unwrap_or_default();
+        let unresolved = conflict_resolution::files_with_conflict_markers(&worktree_path, &changed);
+        if !unresolved.is_empty() {
+            tracing::warn!(
+                "Conflict markers remain in {} for workspace {}, leaving the rebase stopped",
+                unresolved.join(", "),
+                ctx.workspace.id
+            );
+            return false;
+        }
+
+        match self.git.continue_rebase(&worktree_path) {
+            Ok(()) => {
+                tracing::info!(
+                    "Rebase of workspace {} in {} continued after conflict resolution",
+                    ctx.workspace.id,
+                    repo.name
+                );
+                false
+            }
+            Err(GitServiceError::MergeConflicts(msg)) => {
+                tracing::info!("{} Starting another conflict resolution turn", msg);
+                let target_branch = match WorkspaceRepo::find_by_workspace_and_repo_id(
+                    &self.db.pool,
+                    ctx.workspace.id,
+                    repo.id,
+                )
+                .await
+                {
+                    Ok(Some(workspace_repo)) => workspace_repo.target_branch,
+                    _ => return false,
+                };
+                match conflict_resolution::start_resolution(
+                    self,
+                    &ctx.workspace,
+                    &ctx.session,
+                    repo,
+                    &target_branch,
+                )
+                .await
+                {
+                    Ok(_) => true,
+                    Err(e) => {
+                        tracing::error!("Failed to start conflict resolution follow-up: {}", e);
+                        false
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Failed to continue rebase of workspace {} in {}: {}",
+                    ctx.workspace.id,
+                    repo.name,
+                    e
+                );
+                false
+            }
+        }
+    }
+
     /// Update the coding agent turn summary with the final assistant message
     async fn update_executor_session_summary(&self, exec_id: &Uuid) -> Result<(), anyhow::Error> {
         // Check if there's a coding agent turn for this execution process
@@ -697,6 +980,24 @@ impl LocalContainerService {
         Ok(())
     }
 
+    /// Sparse checkouts configured for the repos of a workspace, by repo id
+    async fn sparse_checkouts(
+        &self,
+        workspace: &Workspace,
+    ) -> Result<HashMap<Uuid, SparseCheckout>, ContainerError> {
+        let repos = WorkspaceRepo::find_repos_with_copy_files(&self.db.pool, workspace.id).await?;
+        Ok(repos
+            .into_iter()
+            .filter_map(|repo| {
+                SparseCheckout::from_config(
+                    repo.sparse_checkout.as_deref(),
+                    repo.sparse_checkout_cone,
+                )
+                .map(|sparse| (repo.id, sparse))
+            })
+            .collect())
+    }
+
     /// Copy project files and images to the workspace.
     /// Skips files/images that already exist (fast no-op if all exist).
     async fn copy_files_and_images(
@@ -711,7 +1012,11 @@ impl LocalContainerService {
                 && !copy_files.trim().is_empty()
             {
                 let worktree_path = workspace_dir.join(&repo.name);
-                self.copy_project_files(&repo.path, &worktree_path, copy_files)
+                let sparse = SparseCheckout::from_config(
+                    repo.sparse_checkout.as_deref(),
+                    repo.sparse_checkout_cone,
+                );
+                self.copy_project_files(&repo.path, &worktree_path, copy_files, sparse.as_ref())
                     .await
                     .unwrap_or_else(|e| {
                         tracing::warn!(
@@ -790,11 +1095,102 @@ impl LocalContainerService {
         Ok(())
     }
 
-    /// Start a follow-up execution from a queued message
-    async fn start_queued_follow_up(
+    /// Combined stdout and stderr of a script execution, keeping only the tail when it is long
+    fn extract_script_output(&self, exec_id: &Uuid) -> Option<String> {
+        const MAX_OUTPUT_LENGTH: usize = 8192;
+
+        let msg_stores = self.msg_stores.try_read().ok()?;
+        let msg_store = msg_stores.get(exec_id)?;
+
+        let output: String = msg_store
+            .get_history()
+            .into_iter()
+            .filter_map(|msg| match msg {
+                LogMsg::Stdout(s) | LogMsg::Stderr(s) => Some(s),
+                _ => None,
+            })
+            .collect();
+
+        if output.len() <= MAX_OUTPUT_LENGTH {
+            return Some(output);
+        }
+        let mut start = output.len() - MAX_OUTPUT_LENGTH;
+        while !output.is_char_boundary(start) {
+            start += 1;
+        }
+        Some(format!("...{}", &output[start..]))
+    }
+
+    /// Send a failed verify script's output back to the agent as a follow-up, as long as
+    /// the session has retries left. Returns whether a follow-up was started.
+    async fn try_start_verify_retry(&self, ctx: &ExecutionContext) -> Result<bool, ContainerError> {
+        let max_retries = self.config.read().await.verify.max_auto_retries;
+        if max_retries == 0 {
+            return Ok(false);
+        }
+
+        let failures = ExecutionProcess::count_failed_by_session_and_run_reason(
+            &self.db.pool,
+            ctx.session.id,
+            &ExecutionProcessRunReason::VerifyScript,
+        )
+        .await?;
+        if failures > i64::from(max_retries) {
+            tracing::info!(
+                "Verify script for workspace {} failed {} times, not retrying",
+                ctx.workspace.id,
+                failures
+            );
+            return Ok(false);
+        }
+
+        let ExecutorActionType::ScriptRequest(script) =
+            ctx.execution_process.executor_action()?.typ()
+        else {
+            return Ok(false);
+        };
+        let output = self
+            .extract_script_output(&ctx.execution_process.id)
+            .unwrap_or_default();
+        let location = script
+            .working_dir
+            .as_ref()
+            .map(|dir| format!(" in `{dir}`"))
+            .unwrap_or_default();
+        let prompt = format!(
+            "The verify script{location} failed with exit code {}. Fix the problem so that it passes.\n\nScript:\n```\n{}\n```\n\nOutput:\n```\n{}\n```",
+            ctx.execution_process
+                .exit_code
+                .map_or_else(|| "unknown".to_string(), |code| code.to_string()),
+            script.script.trim(),
+            output.trim_end()
+        );
+
+        let executor_profile_id =
+            ExecutionProcess::latest_executor_profile_for_session(&self.db.pool, ctx.session.id)
+                .await
+                .map_err(|e| {
+                    ContainerError::Other(anyhow!("Failed to get executor profile: {e}"))
+                })?;
+
+        tracing::info!(
+            "Verify script failed for workspace {}, sending follow-up (retry {} of {})",
+            ctx.workspace.id,
+            failures,
+            max_retries
+        );
+        self.start_follow_up(ctx, &prompt, executor_profile_id.variant)
+            .await?;
+        Ok(true)
+    }
+
+    /// Start a follow-up execution in the context's session, using the session's executor
+    /// with the given variant
+    async fn start_follow_up(
         &self,
         ctx: &ExecutionContext,
-        queued_data: &DraftFollowUpData,
+        prompt: &str,
+        variant: Option<String>,
     ) -> Result<ExecutionProcess, ContainerError> {
         // Get executor profile from the latest CodingAgent process in this session
         let initial_executor_profile_id =
@@ -806,7 +1202,7 @@ impl LocalContainerService {
 
         let executor_profile_id = ExecutorProfileId {
             executor: initial_executor_profile_id.executor,
-            variant: queued_data.variant.clone(),
+            variant,
         };
 
         // Get latest agent session ID for session continuity (from coding agent turns)
@@ -818,7 +1214,7 @@ impl LocalContainerService {
 
         let project_repos =
             ProjectRepo::find_by_project_id_with_names(&self.db.pool, ctx.project.id).await?;
-        let cleanup_action = self.cleanup_actions_for_repos(&project_repos);
+        let post_agent_action = self.post_agent_actions_for_repos(&project_repos);
 
         let working_dir = ctx
             .workspace
@@ -829,20 +1225,20 @@ impl LocalContainerService {
 
         let action_type = if let Some(agent_session_id) = latest_agent_session_id {
             ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
-                prompt: queued_data.message.clone(),
+                prompt: prompt.to_string(),
                 session_id: agent_session_id,
                 executor_profile_id: executor_profile_id.clone(),
                 working_dir: working_dir.clone(),
             })
         } else {
             ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
-                prompt: queued_data.message.clone(),
+                prompt: prompt.to_string(),
                 executor_profile_id: executor_profile_id.clone(),
                 working_dir,
             })
         };
 
-        let action = ExecutorAction::new(action_type, cleanup_action.map(Box::new));
+        let action = ExecutorAction::new(action_type, post_agent_action.map(Box::new));
 
         self.start_execution(
             &ctx.workspace,
@@ -893,6 +1289,14 @@ impl ContainerService for LocalContainerService {
         self.config.read().await.git_branch_prefix.clone()
     }
 
+    async fn concurrency_config(&self) -> ConcurrencyConfig {
+        self.config.read().await.concurrency.clone()
+    }
+
+    async fn executor_fallbacks(&self) -> Vec<ExecutorProfileId> {
+        self.config.read().await.executor_fallbacks.clone()
+    }
+
     fn workspace_to_current_dir(&self, workspace: &Workspace) -> PathBuf {
         PathBuf::from(workspace.container_ref.clone().unwrap_or_default())
     }
@@ -923,20 +1327,53 @@ impl ContainerService for LocalContainerService {
             .map(|wr| (wr.repo_id, wr.target_branch.clone()))
             .collect();
 
+        let mut sparse_checkouts = self.sparse_checkouts(workspace).await?;
         let workspace_inputs: Vec<RepoWorkspaceInput> = repositories
             .iter()
             .map(|repo| {
                 let target_branch = target_branches.get(&repo.id).cloned().unwrap_or_default();
                 RepoWorkspaceInput::new(repo.clone(), target_branch)
+                    .with_sparse_checkout(sparse_checkouts.remove(&repo.id))
             })
             .collect();
 
-        let created_workspace = WorkspaceManager::create_workspace(
-            &workspace_dir,
+        let claimed = match worktree_pool::claim_pooled_workspace(
+            &self.db.pool,
+            &self.git,
+            task.project_id,
             &workspace_inputs,
+            &workspace_dir,
             &workspace.branch,
         )
-        .await?;
+        .await
+        {
+            Ok(claimed) => claimed,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to claim a pooled workspace, creating one instead: {}",
+                    e
+                );
+                None
+            }
+        };
+        let created_workspace = match claimed {
+            Some(claimed) => {
+                // Pooled workspaces ran their setup scripts ahead of time, unless a target
+                // branch moved since, in which case the attempt runs them as usual
+                if claimed.setup_current {
+                    Workspace::mark_setup_completed(&self.db.pool, workspace.id).await?;
+                }
+                claimed.container
+            }
+            None => {
+                WorkspaceManager::create_workspace(
+                    &workspace_dir,
+                    &workspace_inputs,
+                    &workspace.branch,
+                )
+                .await?
+            }
+        };
 
         // Copy project files and images to workspace
         self.copy_files_and_images(&created_workspace.workspace_dir, workspace)
@@ -989,8 +1426,14 @@ impl ContainerService for LocalContainerService {
             WorkspaceManager::get_workspace_base_dir().join(&workspace_dir_name)
         };
 
-        WorkspaceManager::ensure_workspace_exists(&workspace_dir, &repositories, &workspace.branch)
-            .await?;
+        let sparse_checkouts = self.sparse_checkouts(workspace).await?;
+        WorkspaceManager::ensure_workspace_exists(
+            &workspace_dir,
+            &repositories,
+            &workspace.branch,
+            &sparse_checkouts,
+        )
+        .await?;
 
         if workspace.container_ref.is_none() {
             Workspace::update_container_ref(
@@ -1055,7 +1498,8 @@ impl ContainerService for LocalContainerService {
                     | BaseCodingAgent::ClaudeCode
                     | BaseCodingAgent::Gemini
                     | BaseCodingAgent::QwenCode
-                    | BaseCodingAgent::Opencode,
+                    | BaseCodingAgent::Opencode
+                    | BaseCodingAgent::CustomAgent,
                 ) => ExecutorApprovalBridge::new(
                     self.approvals.clone(),
                     self.db.clone(),
@@ -1086,6 +1530,33 @@ impl ContainerService for LocalContainerService {
         env.insert("VK_WORKSPACE_ID", workspace.id.to_string());
         env.insert("VK_WORKSPACE_BRANCH", &workspace.branch);
 
+        // Resolve budgets before spawning. A token limit can only be enforced from usage
+        // reports, so agents that don't report usage run under the other limits alone.
+        let mut token_limit_skipped = None;
+        let budget_limits = if matches!(
+            execution_process.run_reason,
+            ExecutionProcessRunReason::CodingAgent
+        ) {
+            let defaults = self.config.read().await.budget.clone();
+            let mut limits = BudgetLimits::for_task(&self.db.pool, &task, &defaults).await?;
+            if let Some(profile_id) = executor_action.executor_profile_id()
+                && !limits.enforceable_for(
+                    &ExecutorConfigs::get_cached().get_coding_agent_or_default(profile_id),
+                )
+            {
+                tracing::warn!(
+                    "Not enforcing the token limit of execution process {}: {} does not report token usage",
+                    execution_process.id,
+                    profile_id.executor
+                );
+                limits.max_tokens = None;
+                token_limit_skipped = Some(profile_id.executor);
+            }
+            Some(limits)
+        } else {
+            None
+        };
+
         // Create the child and stream, add to execution tracker with timeout
         let mut spawned = tokio::time::timeout(
             Duration::from_secs(30),
@@ -1100,6 +1571,15 @@ impl ContainerService for LocalContainerService {
 
         self.track_child_msgs_in_store(execution_process.id, &mut spawned.child)
             .await;
+        if let Some(executor) = token_limit_skipped
+            && let Some(msg_store) = self.get_msg_store_by_id(&execution_process.id).await
+        {
+            let index = EntryIndexProvider::start_from(&msg_store).next();
+            msg_store.push_patch(ConversationPatch::add_normalized_entry(
+                index,
+                token_limit_skipped_entry(&executor),
+            ));
+        }
 
         self.add_child_to_store(execution_process.id, spawned.child)
             .await;
@@ -1111,7 +1591,17 @@ impl ContainerService for LocalContainerService {
         }
 
         // Spawn unified exit monitor: watches OS exit and optional executor signal
-        let _hn = self.spawn_exit_monitor(&execution_process.id, spawned.exit_signal);
+        let _hn = self.spawn_exit_monitor(
+            &execution_process.id,
+            spawned.exit_signal,
+            spawned.temp_file,
+        );
+
+        if let Some(limits) = budget_limits
+            && !limits.is_unlimited()
+        {
+            self.spawn_budget_monitor(execution_process.id, limits);
+        }
 
         Ok(())
     }
@@ -1121,6 +1611,18 @@ impl ContainerService for LocalContainerService {
         execution_process: &ExecutionProcess,
         status: ExecutionProcessStatus,
     ) -> Result<(), ContainerError> {
+        // Queued processes were never spawned, so there is nothing to kill
+        if execution_process.status == ExecutionProcessStatus::Queued {
+            ExecutionProcess::update_completion(&self.db.pool, execution_process.id, status, None)
+                .await?;
+            if let Ok(ctx) =
+                ExecutionProcess::load_context(&self.db.pool, execution_process.id).await
+            {
+                Task::update_status(&self.db.pool, ctx.task.id, TaskStatus::InReview).await?;
+            }
+            return Ok(());
+        }
+
         let child = self
             .get_child_from_store(&execution_process.id)
             .await
@@ -1312,7 +1814,7 @@ impl ContainerService for LocalContainerService {
             return Ok(false);
         }
 
-        Ok(self.commit_repos(repos_with_changes, &message))
+        Ok(self.commit_repos(repos_with_changes, &message).await)
     }
 
     /// Copy files from the original project directory to the worktree.
@@ -1322,15 +1824,22 @@ impl ContainerService for LocalContainerService {
         source_dir: &Path,
         target_dir: &Path,
         copy_files: &str,
+        sparse: Option<&SparseCheckout>,
     ) -> Result<(), ContainerError> {
         let source_dir = source_dir.to_path_buf();
         let target_dir = target_dir.to_path_buf();
         let copy_files = copy_files.to_string();
+        let sparse = sparse.cloned();
 
         tokio::time::timeout(
             std::time::Duration::from_secs(30),
             tokio::task::spawn_blocking(move || {
-                copy::copy_project_files_impl(&source_dir, &target_dir, &copy_files)
+                copy::copy_project_files_impl(
+                    &source_dir,
+                    &target_dir,
+                    &copy_files,
+                    sparse.as_ref(),
+                )
             }),
         )
         .await
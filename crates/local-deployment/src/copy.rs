@@ -6,7 +6,7 @@ use std::{
 
 use anyhow::anyhow;
 use globwalk::GlobWalkerBuilder;
-use services::services::container::ContainerError;
+use services::services::{container::ContainerError, git::SparseCheckout};
 
 /// Normalize pattern for cross-platform glob matching (convert backslashes to forward slashes)
 fn normalize_pattern(pattern: &str) -> String {
@@ -14,11 +14,13 @@ fn normalize_pattern(pattern: &str) -> String {
 }
 
 /// Copy project files from source to target directory based on glob patterns.
-/// Skips files that already exist at target with same size.
+/// Skips files that already exist at target with same size, and files outside the
+/// target's sparse checkout.
 pub(crate) fn copy_project_files_impl(
     source_dir: &Path,
     target_dir: &Path,
     copy_files: &str,
+    sparse: Option<&SparseCheckout>,
 ) -> Result<(), ContainerError> {
     let patterns: Vec<&str> = copy_files
         .split(',')
@@ -34,7 +36,9 @@ pub(crate) fn copy_project_files_impl(
         let pattern_path = source_dir.join(&pattern);
 
         if pattern_path.is_file() {
-            if let Err(e) = copy_single_file(&pattern_path, source_dir, target_dir, &mut seen) {
+            if let Err(e) =
+                copy_single_file(&pattern_path, source_dir, target_dir, sparse, &mut seen)
+            {
                 tracing::warn!(
                     "Failed to copy file {} (from {}): {}",
                     pattern,
@@ -64,7 +68,9 @@ pub(crate) fn copy_project_files_impl(
         };
 
         for entry in walker.flatten() {
-            if let Err(e) = copy_single_file(entry.path(), source_dir, target_dir, &mut seen) {
+            if let Err(e) =
+                copy_single_file(entry.path(), source_dir, target_dir, sparse, &mut seen)
+            {
                 tracing::warn!("Failed to copy file {:?}: {e}", entry.path());
             }
         }
@@ -77,6 +83,7 @@ fn copy_single_file(
     source_file: &Path,
     source_root: &Path,
     target_root: &Path,
+    sparse: Option<&SparseCheckout>,
     seen: &mut HashSet<PathBuf>,
 ) -> Result<bool, ContainerError> {
     let canonical_source = source_root.canonicalize()?;
@@ -98,6 +105,13 @@ fn copy_single_file(
         ))
     })?;
 
+    if let Some(sparse) = sparse
+        && !sparse.includes(&relative_path.to_string_lossy(), false)
+    {
+        tracing::debug!("Skipping {relative_path:?}, it is outside the sparse checkout");
+        return Ok(false);
+    }
+
     let target_file = target_root.join(relative_path);
 
     if target_file.exists() {
@@ -142,6 +156,7 @@ mod tests {
             source_dir.path(),
             target_dir.path(),
             ".env, *.json, src, config",
+            None,
         )
         .unwrap();
 
@@ -157,8 +172,12 @@ mod tests {
         let source_dir = TempDir::new().unwrap();
         let target_dir = TempDir::new().unwrap();
 
-        let result =
-            copy_project_files_impl(source_dir.path(), target_dir.path(), "nonexistent.txt");
+        let result = copy_project_files_impl(
+            source_dir.path(),
+            target_dir.path(),
+            "nonexistent.txt",
+            None,
+        );
 
         assert!(result.is_ok());
         assert!(!target_dir.path().join("nonexistent.txt").exists());
@@ -169,7 +188,7 @@ mod tests {
         let source_dir = TempDir::new().unwrap();
         let target_dir = TempDir::new().unwrap();
 
-        let result = copy_project_files_impl(source_dir.path(), target_dir.path(), "");
+        let result = copy_project_files_impl(source_dir.path(), target_dir.path(), "", None);
 
         assert!(result.is_ok());
         assert_eq!(fs::read_dir(target_dir.path()).unwrap().count(), 0);
@@ -182,7 +201,13 @@ mod tests {
 
         fs::write(source_dir.path().join("test.txt"), "content").unwrap();
 
-        copy_project_files_impl(source_dir.path(), target_dir.path(), "  test.txt  ,  ").unwrap();
+        copy_project_files_impl(
+            source_dir.path(),
+            target_dir.path(),
+            "  test.txt  ,  ",
+            None,
+        )
+        .unwrap();
 
         assert!(target_dir.path().join("test.txt").exists());
     }
@@ -200,7 +225,7 @@ mod tests {
         fs::create_dir(&nested_dir).unwrap();
         fs::write(nested_dir.join("deep.txt"), "deep").unwrap();
 
-        copy_project_files_impl(source_dir.path(), target_dir.path(), "config").unwrap();
+        copy_project_files_impl(source_dir.path(), target_dir.path(), "config", None).unwrap();
 
         assert!(target_dir.path().join("config/app.json").exists());
         assert!(target_dir.path().join("config/nested/deep.txt").exists());
@@ -217,7 +242,8 @@ mod tests {
         fs::write(&outside_file, "secret").unwrap();
 
         // Pattern referencing parent directory should resolve to outside_file and be rejected
-        let result = copy_project_files_impl(source_dir.path(), target_dir.path(), "../secret.txt");
+        let result =
+            copy_project_files_impl(source_dir.path(), target_dir.path(), "../secret.txt", None);
 
         assert!(result.is_ok());
         assert_eq!(fs::read_dir(target_dir.path()).unwrap().count(), 0);
@@ -244,7 +270,13 @@ mod tests {
         fs::write(deep_dir.join("deep.yml"), "deep: config").unwrap();
 
         // Copy all YAML files recursively
-        copy_project_files_impl(source_dir.path(), target_dir.path(), "config/**/*.yml").unwrap();
+        copy_project_files_impl(
+            source_dir.path(),
+            target_dir.path(),
+            "config/**/*.yml",
+            None,
+        )
+        .unwrap();
 
         // Verify only YAML files are copied
         assert!(target_dir.path().join("config/app.yml").exists());
@@ -278,8 +310,13 @@ mod tests {
         fs::write(src_dir.join("main.rs"), "main code").unwrap();
 
         // Copy with overlapping patterns: glob and specific file
-        copy_project_files_impl(source_dir.path(), target_dir.path(), "src/*.rs, src/lib.rs")
-            .unwrap();
+        copy_project_files_impl(
+            source_dir.path(),
+            target_dir.path(),
+            "src/*.rs, src/lib.rs",
+            None,
+        )
+        .unwrap();
 
         // Verify file exists once (deduplication works)
         let target_file = target_dir.path().join("src/lib.rs");
@@ -301,7 +338,7 @@ mod tests {
         fs::write(src_dir.join("lib.rs"), "library code").unwrap();
 
         // Copy single file by exact path (exercises fast path)
-        copy_project_files_impl(source_dir.path(), target_dir.path(), "src/lib.rs").unwrap();
+        copy_project_files_impl(source_dir.path(), target_dir.path(), "src/lib.rs", None).unwrap();
 
         // Verify file is copied
         let target_file = target_dir.path().join("src/lib.rs");
@@ -309,6 +346,31 @@ mod tests {
         assert_eq!(fs::read_to_string(target_file).unwrap(), "library code");
     }
 
+    #[test]
+    fn test_copy_project_files_skips_files_outside_sparse_checkout() {
+        let source_dir = TempDir::new().unwrap();
+        let target_dir = TempDir::new().unwrap();
+
+        fs::write(source_dir.path().join(".env"), "secret").unwrap();
+        for dir in ["services/api", "services/billing"] {
+            fs::create_dir_all(source_dir.path().join(dir)).unwrap();
+            fs::write(source_dir.path().join(dir).join(".env"), "secret").unwrap();
+        }
+
+        let sparse = SparseCheckout::from_patterns("services/api", true).unwrap();
+        copy_project_files_impl(
+            source_dir.path(),
+            target_dir.path(),
+            "**/.env",
+            Some(&sparse),
+        )
+        .unwrap();
+
+        assert!(target_dir.path().join(".env").exists());
+        assert!(target_dir.path().join("services/api/.env").exists());
+        assert!(!target_dir.path().join("services/billing").exists());
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_symlink_loop_is_skipped() {
@@ -320,7 +382,7 @@ mod tests {
         std::fs::create_dir(&loop_dir).unwrap();
         symlink(".", loop_dir.join("self")).unwrap(); // loop/self -> loop
 
-        copy_project_files_impl(src.path(), dst.path(), "loop").unwrap();
+        copy_project_files_impl(src.path(), dst.path(), "loop", None).unwrap();
 
         assert_eq!(std::fs::read_dir(dst.path()).unwrap().count(), 0);
     }
@@ -15,12 +15,16 @@ use services::services::{
     filesystem::FilesystemService,
     git::GitService,
     image::ImageService,
+    merge_queue::MergeQueueService,
     oauth_credentials::OAuthCredentials,
     project::ProjectService,
     queued_message::QueuedMessageService,
     remote_client::{RemoteClient, RemoteClientError},
     repo::RepoService,
     share::{ShareConfig, SharePublisher},
+    stack::StackService,
+    task_scheduler::TaskSchedulerService,
+    worktree_pool::WorktreePoolService,
 };
 use tokio::sync::RwLock;
 use utils::{
@@ -91,10 +95,11 @@ impl Deployment for LocalDeployment {
         // Always save config (may have been migrated or version updated)
         save_config_to_file(&raw_config, &config_path()).await?;
 
+        let git = GitService::new();
+        git.set_commit_signing(raw_config.commit_signing.clone());
         let config = Arc::new(RwLock::new(raw_config));
         let user_id = generate_user_id();
         let analytics = AnalyticsConfig::new().map(AnalyticsService::new);
-        let git = GitService::new();
         let project = ProjectService::new();
         let repo = RepoService::new();
         let msg_stores = Arc::new(RwLock::new(HashMap::new()));
@@ -125,7 +130,7 @@ impl Deployment for LocalDeployment {
             });
         }
 
-        let approvals = Approvals::new(msg_stores.clone());
+        let approvals = Approvals::new(db.pool.clone(), msg_stores.clone());
         let queued_message_service = QueuedMessageService::new();
 
         let share_config = ShareConfig::from_env();
@@ -185,6 +190,11 @@ impl Deployment for LocalDeployment {
         )
         .await;
 
+        TaskSchedulerService::spawn(db.clone(), container.clone(), config.clone()).await;
+        MergeQueueService::spawn(db.clone(), container.clone()).await;
+        StackService::spawn(db.clone(), container.clone()).await;
+        WorktreePoolService::spawn(db.clone(), container.clone()).await;
+
         let events = EventService::new(db.clone(), events_msg_store, events_entry_count);
 
         let file_search_cache = Arc::new(FileSearchCache::new());
@@ -7,16 +7,19 @@ use axum::{
 use db::models::{
     execution_process::ExecutionProcessError, project::ProjectError,
     project_repo::ProjectRepoError, repo::RepoError, scratch::ScratchError, session::SessionError,
-    workspace::WorkspaceError,
+    task_dependency::TaskDependencyError, workspace::WorkspaceError,
 };
 use deployment::{DeploymentError, RemoteClientNotConfigured};
 use executors::executors::ExecutorError;
 use git2::Error as Git2Error;
 use services::services::{
+    attempt_archive::AttemptArchiveError,
     config::{ConfigError, EditorOpenError},
     container::ContainerError,
+    forge::ForgeError,
     git::GitServiceError,
     github::GitHubServiceError,
+    gitlab::GitLabServiceError,
     image::ImageError,
     project::ProjectServiceError,
     remote_client::RemoteClientError,
@@ -47,6 +50,8 @@ pub enum ApiError {
     #[error(transparent)]
     GitHubService(#[from] GitHubServiceError),
     #[error(transparent)]
+    Forge(#[from] ForgeError),
+    #[error(transparent)]
     Deployment(#[from] DeploymentError),
     #[error(transparent)]
     Container(#[from] ContainerError),
@@ -76,6 +81,8 @@ pub enum ApiError {
     Conflict(String),
     #[error("Forbidden: {0}")]
     Forbidden(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
 }
 
 impl From<&'static str> for ApiError {
@@ -121,6 +128,16 @@ impl IntoResponse for ApiError {
                 _ => (StatusCode::INTERNAL_SERVER_ERROR, "GitServiceError"),
             },
             ApiError::GitHubService(_) => (StatusCode::INTERNAL_SERVER_ERROR, "GitHubServiceError"),
+            ApiError::Forge(err) => match err {
+                ForgeError::GitLab(GitLabServiceError::AuthFailed(_)) => {
+                    (StatusCode::UNAUTHORIZED, "ForgeError")
+                }
+                ForgeError::GitLab(GitLabServiceError::InsufficientPermissions(_)) => {
+                    (StatusCode::FORBIDDEN, "ForgeError")
+                }
+                ForgeError::UnsupportedRemote(_) => (StatusCode::BAD_REQUEST, "ForgeError"),
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "ForgeError"),
+            },
             ApiError::Deployment(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DeploymentError"),
             ApiError::Container(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ContainerError"),
             ApiError::Executor(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ExecutorError"),
@@ -177,6 +194,7 @@ impl IntoResponse for ApiError {
             ApiError::BadRequest(_) => (StatusCode::BAD_REQUEST, "BadRequest"),
             ApiError::Conflict(_) => (StatusCode::CONFLICT, "ConflictError"),
             ApiError::Forbidden(_) => (StatusCode::FORBIDDEN, "ForbiddenError"),
+            ApiError::NotFound(_) => (StatusCode::NOT_FOUND, "NotFound"),
         };
 
         let error_message = match &self {
@@ -197,6 +215,9 @@ impl IntoResponse for ApiError {
                 services::services::git::GitServiceError::RebaseInProgress => {
                     "A rebase is already in progress. Resolve conflicts or abort the rebase, then retry.".to_string()
                 }
+                services::services::git::GitServiceError::SigningFailed(msg) => format!(
+                    "Commit signing failed. Check your signing key and the commit signing settings. {msg}"
+                ),
                 _ => format!("{}: {}", error_type, self),
             },
             ApiError::Multipart(_) => "Failed to upload file. Please ensure the file is valid and try again.".to_string(),
@@ -249,10 +270,12 @@ impl IntoResponse for ApiError {
                 RemoteClientError::Serde(_) => "Unexpected response from remote service.".to_string(),
                 RemoteClientError::Url(_) => "Remote service URL is invalid.".to_string(),
             },
+            ApiError::Forge(err) => err.to_string(),
             ApiError::Unauthorized => "Unauthorized. Please sign in again.".to_string(),
             ApiError::BadRequest(msg) => msg.clone(),
             ApiError::Conflict(msg) => msg.clone(),
             ApiError::Forbidden(msg) => msg.clone(),
+            ApiError::NotFound(msg) => msg.clone(),
             _ => format!("{}: {}", error_type, self),
         };
         let response = ApiResponse::<()>::error(&error_message);
@@ -313,6 +336,18 @@ impl From<ShareError> for ApiError {
     }
 }
 
+impl From<AttemptArchiveError> for ApiError {
+    fn from(err: AttemptArchiveError) -> Self {
+        match err {
+            AttemptArchiveError::GitService(git_err) => ApiError::GitService(git_err),
+            AttemptArchiveError::Io(io_err) => ApiError::Io(io_err),
+            AttemptArchiveError::Json(_)
+            | AttemptArchiveError::Invalid(_)
+            | AttemptArchiveError::NoCommits => ApiError::BadRequest(err.to_string()),
+        }
+    }
+}
+
 impl From<ProjectServiceError> for ApiError {
     fn from(err: ProjectServiceError) -> Self {
         match err {
@@ -389,3 +424,20 @@ impl From<ProjectRepoError> for ApiError {
         }
     }
 }
+
+impl From<TaskDependencyError> for ApiError {
+    fn from(err: TaskDependencyError) -> Self {
+        match err {
+            TaskDependencyError::Database(db_err) => ApiError::Database(db_err),
+            TaskDependencyError::TaskNotFound => {
+                ApiError::BadRequest("Dependency task not found".to_string())
+            }
+            TaskDependencyError::SelfDependency | TaskDependencyError::ProjectMismatch => {
+                ApiError::BadRequest(err.to_string())
+            }
+            TaskDependencyError::AlreadyExists | TaskDependencyError::Cycle => {
+                ApiError::Conflict(err.to_string())
+            }
+        }
+    }
+}
@@ -62,6 +62,10 @@ async fn main() -> Result<(), VibeKanbanError> {
         .backfill_repo_names()
         .await
         .map_err(DeploymentError::from)?;
+    // Resume executions that were still queued when the server last stopped
+    if let Err(e) = deployment.container().start_queued_executions().await {
+        tracing::error!("Failed to start queued executions: {}", e);
+    }
     deployment.spawn_pr_monitor_service().await;
     deployment
         .track_if_analytics_allowed("session_start", serde_json::json!({}))
@@ -88,8 +92,6 @@ async fn main() -> Result<(), VibeKanbanError> {
         }
     });
 
-    let app_router = routes::router(deployment.clone());
-
     let port = std::env::var("BACKEND_PORT")
         .or_else(|_| std::env::var("PORT"))
         .ok()
@@ -114,6 +116,12 @@ async fn main() -> Result<(), VibeKanbanError> {
     }
 
     tracing::info!("Server running on http://{host}:{actual_port}");
+    let local_base_url = format!("http://127.0.0.1:{actual_port}");
+    deployment
+        .approvals()
+        .links()
+        .set_local_base_url(local_base_url.clone());
+    let app_router = routes::router(deployment.clone(), &local_base_url);
 
     if !cfg!(debug_assertions) {
         tracing::info!("Opening browser...");
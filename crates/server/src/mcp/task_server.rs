@@ -1,8 +1,10 @@
-use std::{future::Future, str::FromStr};
+use std::{future::Future, str::FromStr, time::Duration};
 
 use db::models::{
+    execution_process::ExecutionProcess,
     project::Project,
     repo::Repo,
+    session::Session,
     tag::Tag,
     task::{CreateTask, Task, TaskStatus, TaskWithAttemptStatus, UpdateTask},
     workspace::{Workspace, WorkspaceContext},
@@ -23,9 +25,16 @@ use uuid::Uuid;
 
 use crate::routes::{
     containers::ContainerQuery,
+    execution_processes::ExecutionConversation,
+    sessions::CreateFollowUpAttempt,
     task_attempts::{CreateTaskAttemptBody, WorkspaceRepoInput},
 };
 
+/// How often `wait_for_task_status` polls the task
+const TASK_STATUS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const DEFAULT_WAIT_TIMEOUT_SECS: u64 = 300;
+const MAX_WAIT_TIMEOUT_SECS: u64 = 1800;
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct CreateTaskRequest {
     #[schemars(description = "The ID of the project to create the task in. This is required!")]
@@ -227,6 +236,10 @@ pub struct StartWorkspaceSessionRequest {
     pub variant: Option<String>,
     #[schemars(description = "Base branch for each repository in the project")]
     pub repos: Vec<McpWorkspaceRepoInput>,
+    #[schemars(
+        description = "For subtasks: base the workspace on the parent workspace's branch and restack it when that branch changes"
+    )]
+    pub stack_on_parent: Option<bool>,
 }
 
 #[derive(Debug, Serialize, schemars::JsonSchema)]
@@ -251,6 +264,152 @@ pub struct GetTaskResponse {
     pub task: TaskDetails,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SendFollowUpRequest {
+    #[schemars(description = "The ID of the workspace whose latest session receives the message")]
+    pub workspace_id: Uuid,
+    #[schemars(description = "The follow-up message for the coding agent")]
+    pub prompt: String,
+    #[schemars(description = "Optional executor variant to run the follow-up with")]
+    pub variant: Option<String>,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct SendFollowUpResponse {
+    pub session_id: String,
+    pub execution_process_id: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ListExecutionsRequest {
+    #[schemars(description = "The ID of the workspace to list executions of")]
+    pub workspace_id: Uuid,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct ExecutionSummary {
+    #[schemars(description = "The unique identifier of the execution process")]
+    pub id: String,
+    #[schemars(description = "The session the execution belongs to")]
+    pub session_id: String,
+    #[schemars(
+        description = "Why it ran: 'codingagent', 'setupscript', 'cleanupscript', 'verifyscript' or 'devserver'"
+    )]
+    pub run_reason: String,
+    #[schemars(
+        description = "Current status: 'queued', 'running', 'completed', 'failed', 'killed' or 'budget_exceeded'"
+    )]
+    pub status: String,
+    #[schemars(description = "Exit code once the process has exited")]
+    pub exit_code: Option<i64>,
+    #[schemars(description = "When the execution started")]
+    pub started_at: String,
+    #[schemars(description = "When the execution finished")]
+    pub completed_at: Option<String>,
+}
+
+impl ExecutionSummary {
+    fn from_execution_process(process: ExecutionProcess) -> Self {
+        Self {
+            id: process.id.to_string(),
+            session_id: process.session_id.to_string(),
+            run_reason: serde_name(&process.run_reason),
+            status: serde_name(&process.status),
+            exit_code: process.exit_code,
+            started_at: process.started_at.to_rfc3339(),
+            completed_at: process.completed_at.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+/// The serialized name of a unit enum variant
+fn serde_name<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct ListExecutionsResponse {
+    pub executions: Vec<ExecutionSummary>,
+    pub count: usize,
+    pub workspace_id: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetExecutionConversationRequest {
+    #[schemars(description = "The ID of the execution process to read")]
+    pub execution_process_id: Uuid,
+    #[schemars(
+        description = "Only return the agent's final (or latest) message instead of the conversation"
+    )]
+    pub summary_only: Option<bool>,
+    #[schemars(description = "Only return the last N conversation entries")]
+    pub last_n: Option<usize>,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct GetExecutionConversationResponse {
+    pub execution_process_id: String,
+    #[schemars(description = "The agent's final message, or its latest one while it is running")]
+    pub summary: Option<String>,
+    #[schemars(description = "Normalized conversation entries, oldest first")]
+    pub entries: Option<Vec<serde_json::Value>>,
+    #[schemars(description = "Number of entries in the whole conversation")]
+    pub total_entries: usize,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetWorkspaceChangesRequest {
+    #[schemars(description = "The ID of the workspace to inspect")]
+    pub workspace_id: Uuid,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct GetWorkspaceChangesResponse {
+    pub workspace_id: String,
+    #[schemars(
+        description = "Per repository: commits ahead/behind the target branch, uncommitted changes and conflicts"
+    )]
+    pub branch_status: serde_json::Value,
+    #[schemars(
+        description = "Unified diff of the workspace against its base, uncommitted changes included"
+    )]
+    pub diff: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct StopExecutionRequest {
+    #[schemars(description = "The ID of the execution process to stop")]
+    pub execution_process_id: Uuid,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct StopExecutionResponse {
+    pub stopped_execution_process_id: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct WaitForTaskStatusRequest {
+    #[schemars(description = "The ID of the task to watch")]
+    pub task_id: Uuid,
+    #[schemars(
+        description = "Status to wait for: 'todo', 'inprogress', 'inreview', 'done', 'cancelled'"
+    )]
+    pub status: String,
+    #[schemars(description = "Seconds to wait before giving up (default: 300, max: 1800)")]
+    pub timeout_seconds: Option<u64>,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct WaitForTaskStatusResponse {
+    #[schemars(description = "Whether the task reached the status before the timeout")]
+    pub reached: bool,
+    pub waited_seconds: u64,
+    pub task: TaskDetails,
+}
+
 #[derive(Debug, Clone)]
 pub struct TaskServer {
     client: reqwest::Client,
@@ -292,6 +451,14 @@ impl TaskServer {
         }
     }
 
+    /// A server for clients outside any workspace, e.g. over HTTP, which have no context
+    /// to fetch
+    pub fn without_context(base_url: &str) -> Self {
+        let mut server = Self::new(base_url);
+        server.tool_router.map.remove("get_context");
+        server
+    }
+
     pub async fn init(mut self) -> Self {
         let context = self.fetch_context_at_startup().await;
 
@@ -392,6 +559,22 @@ impl TaskServer {
         &self,
         rb: reqwest::RequestBuilder,
     ) -> Result<T, CallToolResult> {
+        self.send_request::<T>(rb)
+            .await?
+            .data
+            .ok_or_else(|| Self::err("VK API response missing data field", None).unwrap())
+    }
+
+    /// Like [`Self::send_json`] for endpoints that answer without data
+    async fn send_unit(&self, rb: reqwest::RequestBuilder) -> Result<(), CallToolResult> {
+        self.send_request::<serde_json::Value>(rb).await?;
+        Ok(())
+    }
+
+    async fn send_request<T: DeserializeOwned>(
+        &self,
+        rb: reqwest::RequestBuilder,
+    ) -> Result<ApiResponseEnvelope<T>, CallToolResult> {
         let resp = rb
             .send()
             .await
@@ -413,9 +596,31 @@ impl TaskServer {
             return Err(Self::err("VK API returned error", Some(msg)).unwrap());
         }
 
-        api_response
-            .data
-            .ok_or_else(|| Self::err("VK API response missing data field", None).unwrap())
+        Ok(api_response)
+    }
+
+    /// Poll the task until it has `want` or `timeout` has passed
+    async fn poll_task_status(
+        &self,
+        task_id: Uuid,
+        want: TaskStatus,
+        timeout: Duration,
+    ) -> Result<WaitForTaskStatusResponse, CallToolResult> {
+        let url = self.url(&format!("/api/tasks/{}", task_id));
+        let started = tokio::time::Instant::now();
+        loop {
+            let task: Task = self.send_json(self.client.get(&url)).await?;
+            let reached = task.status == want;
+            let elapsed = started.elapsed();
+            if reached || elapsed >= timeout {
+                return Ok(WaitForTaskStatusResponse {
+                    reached,
+                    waited_seconds: elapsed.as_secs(),
+                    task: TaskDetails::from_task(task),
+                });
+            }
+            tokio::time::sleep(TASK_STATUS_POLL_INTERVAL.min(timeout - elapsed)).await;
+        }
     }
 
     fn url(&self, path: &str) -> String {
@@ -650,6 +855,7 @@ impl TaskServer {
             executor,
             variant,
             repos,
+            stack_on_parent,
         }): Parameters<StartWorkspaceSessionRequest>,
     ) -> Result<CallToolResult, ErrorData> {
         if repos.is_empty() {
@@ -701,6 +907,7 @@ impl TaskServer {
             task_id,
             executor_profile_id,
             repos: workspace_repos,
+            stack_on_parent: stack_on_parent.unwrap_or(false),
         };
 
         let url = self.url("/api/task-attempts");
@@ -808,12 +1015,215 @@ impl TaskServer {
 
         TaskServer::success(&response)
     }
+
+    #[tool(
+        description = "Send a follow-up message to the coding agent of a workspace, continuing its latest session. `workspace_id` and `prompt` are required!"
+    )]
+    async fn send_follow_up(
+        &self,
+        Parameters(SendFollowUpRequest {
+            workspace_id,
+            prompt,
+            variant,
+        }): Parameters<SendFollowUpRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if prompt.trim().is_empty() {
+            return Self::err("Prompt must not be empty.".to_string(), None::<String>);
+        }
+
+        let url = self.url(&format!("/api/sessions?workspace_id={}", workspace_id));
+        let sessions: Vec<Session> = match self.send_json(self.client.get(&url)).await {
+            Ok(s) => s,
+            Err(e) => return Ok(e),
+        };
+        // Sessions are listed newest first
+        let Some(session) = sessions.into_iter().next() else {
+            return Self::err(
+                "Workspace has no session yet. Use 'start_workspace_session' first.".to_string(),
+                Some(workspace_id.to_string()),
+            );
+        };
+
+        let payload = CreateFollowUpAttempt {
+            prompt: self.expand_tags(&prompt).await,
+            variant: variant.filter(|v| !v.trim().is_empty()),
+            retry_process_id: None,
+            force_when_dirty: None,
+            perform_git_reset: None,
+        };
+        let url = self.url(&format!("/api/sessions/{}/follow-up", session.id));
+        let process: ExecutionProcess =
+            match self.send_json(self.client.post(&url).json(&payload)).await {
+                Ok(p) => p,
+                Err(e) => return Ok(e),
+            };
+
+        TaskServer::success(&SendFollowUpResponse {
+            session_id: session.id.to_string(),
+            execution_process_id: process.id.to_string(),
+        })
+    }
+
+    #[tool(
+        description = "List the executions (coding agent turns and scripts) of a workspace, oldest first, with their status. `workspace_id` is required!"
+    )]
+    async fn list_executions(
+        &self,
+        Parameters(ListExecutionsRequest { workspace_id }): Parameters<ListExecutionsRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let url = self.url(&format!(
+            "/api/execution-processes?workspace_id={}",
+            workspace_id
+        ));
+        let processes: Vec<ExecutionProcess> = match self.send_json(self.client.get(&url)).await {
+            Ok(p) => p,
+            Err(e) => return Ok(e),
+        };
+
+        let executions: Vec<ExecutionSummary> = processes
+            .into_iter()
+            .map(ExecutionSummary::from_execution_process)
+            .collect();
+
+        TaskServer::success(&ListExecutionsResponse {
+            count: executions.len(),
+            executions,
+            workspace_id: workspace_id.to_string(),
+        })
+    }
+
+    #[tool(
+        description = "Read the normalized conversation of an execution (messages, tool calls and their results), or only the agent's last message with `summary_only`. Use `list_executions` to find `execution_process_id`s."
+    )]
+    async fn get_execution_conversation(
+        &self,
+        Parameters(GetExecutionConversationRequest {
+            execution_process_id,
+            summary_only,
+            last_n,
+        }): Parameters<GetExecutionConversationRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let url = self.url(&format!(
+            "/api/execution-processes/{}/conversation",
+            execution_process_id
+        ));
+        let conversation: ExecutionConversation = match self.send_json(self.client.get(&url)).await
+        {
+            Ok(c) => c,
+            Err(e) => return Ok(e),
+        };
+
+        let total_entries = conversation.entries.len();
+        let entries = if summary_only.unwrap_or(false) {
+            None
+        } else {
+            let skip = last_n.map_or(0, |n| total_entries.saturating_sub(n));
+            Some(
+                conversation
+                    .entries
+                    .iter()
+                    .skip(skip)
+                    .filter_map(|entry| serde_json::to_value(entry).ok())
+                    .collect(),
+            )
+        };
+
+        TaskServer::success(&GetExecutionConversationResponse {
+            execution_process_id: execution_process_id.to_string(),
+            summary: conversation.summary,
+            entries,
+            total_entries,
+        })
+    }
+
+    #[tool(
+        description = "Get the changes of a workspace: its diff against the base branch and each repository's branch status. `workspace_id` is required!"
+    )]
+    async fn get_workspace_changes(
+        &self,
+        Parameters(GetWorkspaceChangesRequest { workspace_id }): Parameters<
+            GetWorkspaceChangesRequest,
+        >,
+    ) -> Result<CallToolResult, ErrorData> {
+        let url = self.url(&format!(
+            "/api/task-attempts/{}/branch-status",
+            workspace_id
+        ));
+        let branch_status: serde_json::Value = match self.send_json(self.client.get(&url)).await {
+            Ok(s) => s,
+            Err(e) => return Ok(e),
+        };
+
+        let url = self.url(&format!("/api/task-attempts/{}/diff", workspace_id));
+        let diff: String = match self.send_json(self.client.get(&url)).await {
+            Ok(d) => d,
+            Err(e) => return Ok(e),
+        };
+
+        TaskServer::success(&GetWorkspaceChangesResponse {
+            workspace_id: workspace_id.to_string(),
+            branch_status,
+            diff,
+        })
+    }
+
+    #[tool(description = "Stop a running execution. `execution_process_id` is required!")]
+    async fn stop_execution(
+        &self,
+        Parameters(StopExecutionRequest {
+            execution_process_id,
+        }): Parameters<StopExecutionRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let url = self.url(&format!(
+            "/api/execution-processes/{}/stop",
+            execution_process_id
+        ));
+        if let Err(e) = self.send_unit(self.client.post(&url)).await {
+            return Ok(e);
+        }
+
+        TaskServer::success(&StopExecutionResponse {
+            stopped_execution_process_id: execution_process_id.to_string(),
+        })
+    }
+
+    #[tool(
+        description = "Wait until a task reaches a status, e.g. 'inreview' once its agent is done, or until the timeout passes. `task_id` and `status` are required!"
+    )]
+    async fn wait_for_task_status(
+        &self,
+        Parameters(WaitForTaskStatusRequest {
+            task_id,
+            status,
+            timeout_seconds,
+        }): Parameters<WaitForTaskStatusRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let want = match TaskStatus::from_str(&status) {
+            Ok(s) => s,
+            Err(_) => {
+                return Self::err(
+                    "Invalid status. Valid values: 'todo', 'inprogress', 'inreview', 'done', 'cancelled'".to_string(),
+                    Some(status),
+                );
+            }
+        };
+        let timeout = Duration::from_secs(
+            timeout_seconds
+                .unwrap_or(DEFAULT_WAIT_TIMEOUT_SECS)
+                .min(MAX_WAIT_TIMEOUT_SECS),
+        );
+
+        match self.poll_task_status(task_id, want, timeout).await {
+            Ok(response) => TaskServer::success(&response),
+            Err(e) => Ok(e),
+        }
+    }
 }
 
 #[tool_handler]
 impl ServerHandler for TaskServer {
     fn get_info(&self) -> ServerInfo {
-        let mut instruction = "A task and project management server. If you need to create or update tickets or tasks then use these tools. Most of them absolutely require that you pass the `project_id` of the project that you are currently working on. You can get project ids by using `list projects`. Call `list_tasks` to fetch the `task_ids` of all the tasks in a project`.. TOOLS: 'list_projects', 'list_tasks', 'create_task', 'start_workspace_session', 'get_task', 'update_task', 'delete_task', 'list_repos'. Make sure to pass `project_id` or `task_id` where required. You can use list tools to get the available ids.".to_string();
+        let mut instruction = "A task and project management server. If you need to create or update tickets or tasks then use these tools. Most of them absolutely require that you pass the `project_id` of the project that you are currently working on. You can get project ids by using `list projects`. Call `list_tasks` to fetch the `task_ids` of all the tasks in a project`.. TOOLS: 'list_projects', 'list_tasks', 'create_task', 'start_workspace_session', 'get_task', 'update_task', 'delete_task', 'list_repos', 'send_follow_up', 'list_executions', 'get_execution_conversation', 'get_workspace_changes', 'stop_execution', 'wait_for_task_status'. To supervise a workspace session, send it follow-ups, read its executions' conversations and its changes, and use 'wait_for_task_status' to wait for the task to reach 'inreview'. Make sure to pass `project_id` or `task_id` where required. You can use list tools to get the available ids.".to_string();
         if self.context.is_some() {
             let context_instruction = "Use 'get_context' to fetch project/task/workspace metadata for the active Vibe Kanban workspace session when available.";
             instruction = format!("{} {}", context_instruction, instruction);
@@ -830,3 +1240,95 @@ impl ServerHandler for TaskServer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Json, Router,
+        extract::Path,
+        routing::{get, post},
+    };
+    use chrono::Utc;
+
+    use super::*;
+
+    /// Serve tasks that stay `todo` and a stop endpoint answering without data, returning
+    /// the base URL
+    async fn serve_api() -> String {
+        let app = Router::new()
+            .route(
+                "/api/tasks/{id}",
+                get(|Path(id): Path<Uuid>| async move {
+                    let now = Utc::now();
+                    Json(serde_json::json!({
+                        "success": true,
+                        "data": Task {
+                            id,
+                            project_id: Uuid::new_v4(),
+                            title: "task".to_string(),
+                            description: None,
+                            status: TaskStatus::Todo,
+                            parent_workspace_id: None,
+                            shared_task_id: None,
+                            created_at: now,
+                            updated_at: now,
+                        },
+                        "message": null,
+                    }))
+                }),
+            )
+            .route(
+                "/api/stop",
+                post(|| async {
+                    Json(serde_json::json!({ "success": true, "data": null, "message": null }))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn waiting_for_a_status_gives_up_at_the_timeout() {
+        let server = TaskServer::new(&serve_api().await);
+        let task_id = Uuid::new_v4();
+
+        let response = server
+            .poll_task_status(task_id, TaskStatus::InReview, Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        assert!(!response.reached);
+        assert_eq!(response.waited_seconds, 1);
+        assert_eq!(response.task.id, task_id.to_string());
+        assert_eq!(response.task.status, "todo");
+    }
+
+    #[tokio::test]
+    async fn waiting_for_a_status_returns_once_it_is_reached() {
+        let server = TaskServer::new(&serve_api().await);
+
+        let response = server
+            .poll_task_status(Uuid::new_v4(), TaskStatus::Todo, Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        assert!(response.reached);
+        assert_eq!(response.waited_seconds, 0);
+    }
+
+    #[tokio::test]
+    async fn only_unit_requests_accept_responses_without_data() {
+        let server = TaskServer::new(&serve_api().await);
+        let url = server.url("/api/stop");
+
+        assert!(server.send_unit(server.client.post(&url)).await.is_ok());
+        assert!(
+            server
+                .send_json::<Task>(server.client.post(&url))
+                .await
+                .is_err()
+        );
+    }
+}
@@ -1,22 +1,88 @@
 use axum::{
     Json, Router,
-    extract::{Path, State},
+    body::Bytes,
+    extract::{FromRequest, Path, Query, Request, State},
     http::StatusCode,
-    routing::post,
+    response::{Html, Json as ResponseJson},
+    routing::get,
 };
+use db::models::approval_log::{ApprovalLogEntry, ApprovalLogQuery};
 use deployment::Deployment;
-use utils::approvals::{ApprovalResponse, ApprovalStatus};
+use serde::Deserialize;
+use services::services::approvals::{ApprovalError, links::ApprovalLinkDecision};
+use utils::{
+    approvals::{ApprovalResponse, ApprovalStatus},
+    response::ApiResponse,
+};
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// A signed approval link, as sent in approval notifications. The token carries the
+/// decision the link gives.
+#[derive(Debug, Deserialize)]
+pub struct ApprovalLinkQuery {
+    pub token: Option<String>,
+}
 
-use crate::DeploymentImpl;
+fn approval_error_status(e: &ApprovalError) -> StatusCode {
+    match e {
+        ApprovalError::NotFound => StatusCode::NOT_FOUND,
+        ApprovalError::AlreadyCompleted => StatusCode::CONFLICT,
+        ApprovalError::InvalidLink => StatusCode::FORBIDDEN,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Who is answering, as recorded in the approval log: the logged in account when there is
+/// one, otherwise the local user
+async fn responder_identity(deployment: &DeploymentImpl) -> String {
+    match deployment.auth_context().cached_profile().await {
+        Some(profile) => match profile.username {
+            Some(username) => format!("{username} <{}>", profile.email),
+            None => profile.email,
+        },
+        None => "local user".to_string(),
+    }
+}
 
+/// Answer an approval with a JSON [`ApprovalResponse`] from the board, or with the `token`
+/// of a signed link and an empty body. Only the board path reads a body, and only as JSON,
+/// so a cross-site form can't answer an approval.
 pub async fn respond_to_approval(
     State(deployment): State<DeploymentImpl>,
     Path(id): Path<String>,
-    Json(request): Json<ApprovalResponse>,
+    Query(link): Query<ApprovalLinkQuery>,
+    request: Request,
 ) -> Result<Json<ApprovalStatus>, StatusCode> {
     let service = deployment.approvals();
 
-    match service.respond(&deployment.db().pool, &id, request).await {
+    let result = match link.token {
+        Some(token) => {
+            let body = Bytes::from_request(request, &deployment)
+                .await
+                .map_err(|_| StatusCode::BAD_REQUEST)?;
+            if !body.is_empty() {
+                return Err(StatusCode::BAD_REQUEST);
+            }
+            service.respond_with_link(&id, &token).await
+        }
+        None => {
+            let Json(request) = Json::<ApprovalResponse>::from_request(request, &deployment)
+                .await
+                .map_err(|rejection| rejection.status())?;
+            let responder = responder_identity(&deployment).await;
+            service.respond(&id, request, &responder).await
+        }
+    };
+
+    match result {
         Ok((status, context)) => {
             deployment
                 .track_if_analytics_allowed(
@@ -34,11 +100,62 @@ pub async fn respond_to_approval(
         }
         Err(e) => {
             tracing::error!("Failed to respond to approval: {:?}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(approval_error_status(&e))
         }
     }
 }
 
+/// Page a signed approval link opens, asking to confirm the link's decision. Opening the
+/// link does not answer the approval, so link previews can't.
+pub async fn approval_link_page(
+    State(deployment): State<DeploymentImpl>,
+    Path(id): Path<String>,
+    Query(link): Query<ApprovalLinkQuery>,
+) -> Result<Html<String>, StatusCode> {
+    let token = link.token.ok_or(StatusCode::FORBIDDEN)?;
+    let (tool_name, content, decision) = deployment
+        .approvals()
+        .pending_for_link(&id, &token)
+        .map_err(|e| approval_error_status(&e))?;
+
+    let action = format!(
+        "/api/approvals/{}/respond?token={}",
+        escape_html(&id),
+        escape_html(&token)
+    );
+    let button = match decision {
+        ApprovalLinkDecision::Approve => "Approve",
+        ApprovalLinkDecision::Deny => "Deny",
+    };
+    Ok(Html(format!(
+        r#"<!doctype html>
+<html>
+<head><meta charset="utf-8"><title>Approval needed</title></head>
+<body>
+<h1>Tool '{tool_name}' requires approval</h1>
+<pre>{content}</pre>
+<form method="post" action="{action}"><button>{button}</button></form>
+</body>
+</html>"#,
+        tool_name = escape_html(&tool_name),
+        content = escape_html(&content),
+    )))
+}
+
+/// Approval history, most recent first, optionally filtered by project, task and tool
+pub async fn get_approval_log(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ApprovalLogQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<ApprovalLogEntry>>>, ApiError> {
+    let entries = ApprovalLogEntry::find(&deployment.db().pool, &query).await?;
+    Ok(ResponseJson(ApiResponse::success(entries)))
+}
+
 pub fn router() -> Router<DeploymentImpl> {
-    Router::new().route("/approvals/{id}/respond", post(respond_to_approval))
+    Router::new()
+        .route("/approvals", get(get_approval_log))
+        .route(
+            "/approvals/{id}/respond",
+            get(approval_link_page).post(respond_to_approval),
+        )
 }
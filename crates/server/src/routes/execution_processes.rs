@@ -10,13 +10,18 @@ use axum::{
     routing::{get, post},
 };
 use db::models::{
+    coding_agent_turn::CodingAgentTurn,
     execution_process::{ExecutionProcess, ExecutionProcessError, ExecutionProcessStatus},
     execution_process_repo_state::ExecutionProcessRepoState,
+    execution_process_usage::ExecutionProcessUsage,
+    session::Session,
 };
 use deployment::Deployment;
+use executors::logs::{NormalizedEntry, NormalizedEntryType};
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
-use serde::Deserialize;
-use services::services::container::ContainerService;
+use serde::{Deserialize, Serialize};
+use services::services::{container::ContainerService, handoff};
+use ts_rs::TS;
 use utils::{log_msg::LogMsg, response::ApiResponse};
 use uuid::Uuid;
 
@@ -30,6 +35,31 @@ pub struct ExecutionProcessQuery {
     pub show_soft_deleted: Option<bool>,
 }
 
+/// The normalized conversation of an execution so far
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct ExecutionConversation {
+    pub entries: Vec<NormalizedEntry>,
+    /// The agent's final message once the turn is over, otherwise its latest message
+    pub summary: Option<String>,
+}
+
+/// Execution processes of a workspace across its sessions, oldest first
+pub async fn get_execution_processes(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ExecutionProcessQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<ExecutionProcess>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let show_soft_deleted = query.show_soft_deleted.unwrap_or(false);
+    let mut processes = Vec::new();
+    for session in Session::find_by_workspace_id(pool, query.workspace_id).await? {
+        processes.extend(
+            ExecutionProcess::find_by_session_id(pool, session.id, show_soft_deleted).await?,
+        );
+    }
+    processes.sort_by_key(|process| process.created_at);
+    Ok(ResponseJson(ApiResponse::success(processes)))
+}
+
 pub async fn get_execution_process_by_id(
     Extension(execution_process): Extension<ExecutionProcess>,
     State(_deployment): State<DeploymentImpl>,
@@ -166,6 +196,39 @@ async fn handle_normalized_logs_ws(
     Ok(())
 }
 
+pub async fn get_execution_process_conversation(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ExecutionConversation>>, ApiError> {
+    let entries = handoff::load_conversation(deployment.container(), execution_process.id).await;
+    let turn_summary =
+        CodingAgentTurn::find_by_execution_process_id(&deployment.db().pool, execution_process.id)
+            .await?
+            .and_then(|turn| turn.summary);
+    let summary = conversation_summary(turn_summary, &entries);
+    Ok(ResponseJson(ApiResponse::success(ExecutionConversation {
+        entries,
+        summary,
+    })))
+}
+
+/// The summary recorded for the turn, or else the agent's latest non-empty message
+fn conversation_summary(
+    turn_summary: Option<String>,
+    entries: &[NormalizedEntry],
+) -> Option<String> {
+    turn_summary.or_else(|| {
+        entries
+            .iter()
+            .rev()
+            .find(|entry| {
+                matches!(entry.entry_type, NormalizedEntryType::AssistantMessage)
+                    && !entry.content.trim().is_empty()
+            })
+            .map(|entry| entry.content.trim().to_string())
+    })
+}
+
 pub async fn stop_execution_process(
     Extension(execution_process): Extension<ExecutionProcess>,
     State(deployment): State<DeploymentImpl>,
@@ -243,11 +306,25 @@ pub async fn get_execution_process_repo_states(
     Ok(ResponseJson(ApiResponse::success(repo_states)))
 }
 
+pub async fn get_execution_process_usage(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ExecutionProcessUsage>>>, ApiError> {
+    let usage = ExecutionProcessUsage::find_by_execution_process_id(
+        &deployment.db().pool,
+        execution_process.id,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(usage)))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let workspace_id_router = Router::new()
         .route("/", get(get_execution_process_by_id))
         .route("/stop", post(stop_execution_process))
+        .route("/conversation", get(get_execution_process_conversation))
         .route("/repo-states", get(get_execution_process_repo_states))
+        .route("/usage", get(get_execution_process_usage))
         .route("/raw-logs/ws", get(stream_raw_logs_ws))
         .route("/normalized-logs/ws", get(stream_normalized_logs_ws))
         .layer(from_fn_with_state(
@@ -256,8 +333,49 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         ));
 
     let workspaces_router = Router::new()
+        .route("/", get(get_execution_processes))
         .route("/stream/ws", get(stream_execution_processes_ws))
         .nest("/{id}", workspace_id_router);
 
     Router::new().nest("/execution-processes", workspaces_router)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn summary_falls_back_to_the_latest_agent_message() {
+        let entries = vec![
+            entry(NormalizedEntryType::AssistantMessage, "Looking at the code"),
+            entry(NormalizedEntryType::AssistantMessage, "  Fixed the bug\n"),
+            entry(NormalizedEntryType::AssistantMessage, "   "),
+            entry(NormalizedEntryType::UserMessage, "Thanks"),
+        ];
+
+        assert_eq!(
+            conversation_summary(None, &entries).as_deref(),
+            Some("Fixed the bug")
+        );
+        assert_eq!(
+            conversation_summary(Some("Final summary".to_string()), &entries).as_deref(),
+            Some("Final summary")
+        );
+        assert_eq!(
+            conversation_summary(
+                None,
+                &[entry(NormalizedEntryType::UserMessage, "Fix the bug")]
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! The MCP task server over streamable HTTP, for agents and IDEs that connect to a running
+//! instance instead of spawning the stdio `mcp_task_server`.
+
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    extract::{Request, State},
+    http::{StatusCode, header},
+    middleware::{Next, from_fn_with_state},
+    response::Response,
+};
+use rmcp::transport::streamable_http_server::{
+    StreamableHttpService, session::local::LocalSessionManager,
+};
+use subtle::ConstantTimeEq;
+
+use crate::{DeploymentImpl, mcp::task_server::TaskServer};
+
+/// Environment variable holding the token clients must send. It is kept out of the config,
+/// which the unauthenticated settings API reads and writes.
+const MCP_TOKEN_ENV: &str = "VK_MCP_TOKEN";
+
+/// The token from `VK_MCP_TOKEN`, unless it is unset or blank
+fn configured_token() -> Option<Arc<str>> {
+    std::env::var(MCP_TOKEN_ENV)
+        .ok()
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+        .map(Arc::from)
+}
+
+/// Let through requests bearing the configured token. Without a token the endpoint stays
+/// closed, since the tools can start agents and delete tasks.
+async fn require_mcp_token(
+    State(token): State<Option<Arc<str>>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let Some(token) = token else {
+        tracing::warn!("Refusing MCP client: {MCP_TOKEN_ENV} is not set");
+        return Err(StatusCode::FORBIDDEN);
+    };
+
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim_start().split_once(' '))
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("bearer"))
+        .map(|(_, provided)| provided.trim());
+    match provided {
+        Some(provided) if bool::from(provided.as_bytes().ct_eq(token.as_bytes())) => {
+            Ok(next.run(request).await)
+        }
+        _ => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// Serves the task server's tools at `/mcp`. They call the API at `local_base_url` like
+/// the stdio server does, so both behave the same.
+pub fn router(local_base_url: &str) -> Router<DeploymentImpl> {
+    let base_url = local_base_url.to_string();
+    let service = StreamableHttpService::new(
+        move || Ok(TaskServer::without_context(&base_url)),
+        LocalSessionManager::default().into(),
+        Default::default(),
+    );
+
+    Router::new()
+        .nest_service("/mcp", service)
+        .layer(from_fn_with_state(configured_token(), require_mcp_token))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::routing::post;
+
+    use super::*;
+
+    /// Serve a stand-in for the MCP service behind the token check, returning its URL
+    async fn serve(token: Option<&str>) -> String {
+        let app = Router::new()
+            .route("/mcp", post(|| async { "ok" }))
+            .layer(from_fn_with_state(token.map(Arc::from), require_mcp_token));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}/mcp")
+    }
+
+    async fn status(url: &str, authorization: Option<&str>) -> StatusCode {
+        let mut request = reqwest::Client::new().post(url);
+        if let Some(authorization) = authorization {
+            request = request.header(header::AUTHORIZATION, authorization);
+        }
+        request.send().await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn endpoint_is_closed_without_a_configured_token() {
+        let url = serve(None).await;
+
+        assert_eq!(status(&url, None).await, StatusCode::FORBIDDEN);
+        assert_eq!(
+            status(&url, Some("Bearer anything")).await,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn only_the_configured_bearer_token_is_let_through() {
+        let url = serve(Some("secret")).await;
+
+        assert_eq!(status(&url, None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            status(&url, Some("Bearer wrong")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(&url, Some("Basic secret")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(status(&url, Some("Bearer secret")).await, StatusCode::OK);
+        assert_eq!(status(&url, Some("bearer  secret ")).await, StatusCode::OK);
+    }
+}
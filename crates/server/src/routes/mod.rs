@@ -15,6 +15,7 @@ pub mod execution_processes;
 pub mod frontend;
 pub mod health;
 pub mod images;
+pub mod mcp;
 pub mod oauth;
 pub mod organizations;
 pub mod projects;
@@ -25,8 +26,10 @@ pub mod shared_tasks;
 pub mod tags;
 pub mod task_attempts;
 pub mod tasks;
+pub mod usage;
 
-pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
+/// `local_base_url` is where the server can reach its own API, for the MCP task server
+pub fn router(deployment: DeploymentImpl, local_base_url: &str) -> IntoMakeService<Router> {
     // Create routers with different middleware layers
     let base_routes = Router::new()
         .route("/health", get(health::health_check))
@@ -46,6 +49,8 @@ pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
         .merge(approvals::router())
         .merge(scratch::router(&deployment))
         .merge(sessions::router(&deployment))
+        .merge(usage::router())
+        .merge(mcp::router(local_base_url))
         .nest("/images", images::routes())
         .with_state(deployment);
 
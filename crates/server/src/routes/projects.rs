@@ -10,9 +10,13 @@ use axum::{
     http::StatusCode,
     middleware::from_fn_with_state,
     response::{IntoResponse, Json as ResponseJson},
-    routing::{get, post},
+    routing::{get, post, put},
 };
 use db::models::{
+    approval_rule::{ApprovalRule, CreateApprovalRule},
+    execution_budget::{ExecutionBudget, UpdateExecutionBudget},
+    execution_process_usage::{ExecutionProcessUsage, UsageSummary},
+    merge_queue::MergeQueueEntry,
     project::{CreateProject, Project, ProjectError, SearchResult, UpdateProject},
     project_repo::{CreateProjectRepo, ProjectRepo, UpdateProjectRepo},
     repo::Repo,
@@ -21,7 +25,7 @@ use deployment::Deployment;
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
 use serde::Deserialize;
 use services::services::{
-    file_search_cache::SearchQuery, project::ProjectServiceError,
+    approvals::policy, file_search_cache::SearchQuery, project::ProjectServiceError,
     remote_client::CreateRemoteProjectPayload,
 };
 use ts_rs::TS;
@@ -93,6 +97,59 @@ async fn handle_projects_ws(socket: WebSocket, deployment: DeploymentImpl) -> an
     Ok(())
 }
 
+pub async fn get_project_merge_queue(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<MergeQueueEntry>>>, ApiError> {
+    let entries = MergeQueueEntry::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(entries)))
+}
+
+pub async fn stream_project_merge_queue_ws(
+    ws: WebSocketUpgrade,
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| async move {
+        if let Err(e) = handle_merge_queue_ws(socket, deployment, project.id).await {
+            tracing::warn!("merge queue WS closed: {}", e);
+        }
+    })
+}
+
+async fn handle_merge_queue_ws(
+    socket: WebSocket,
+    deployment: DeploymentImpl,
+    project_id: Uuid,
+) -> anyhow::Result<()> {
+    let mut stream = deployment
+        .events()
+        .stream_merge_queue_raw(project_id)
+        .await?
+        .map_ok(|msg| msg.to_ws_message_unchecked());
+
+    let (mut sender, mut receiver) = socket.split();
+
+    // Drain (and ignore) any client->server messages so pings/pongs work
+    tokio::spawn(async move { while let Some(Ok(_)) = receiver.next().await {} });
+
+    while let Some(item) = stream.next().await {
+        match item {
+            Ok(msg) => {
+                if sender.send(msg).await.is_err() {
+                    break; // client disconnected
+                }
+            }
+            Err(e) => {
+                tracing::error!("stream error: {}", e);
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 pub async fn get_project(
     Extension(project): Extension<Project>,
 ) -> Result<ResponseJson<ApiResponse<Project>>, ApiError> {
@@ -391,23 +448,12 @@ pub async fn search_project_files(
         )));
     }
 
-    let repositories = match deployment
-        .project()
-        .get_repositories(&deployment.db().pool, project.id)
-        .await
-    {
-        Ok(repos) => repos,
-        Err(e) => {
-            tracing::error!("Failed to get repositories: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        }
-    };
-
     match deployment
         .project()
         .search_files(
+            &deployment.db().pool,
             deployment.file_search_cache().as_ref(),
-            &repositories,
+            project.id,
             &search_query,
         )
         .await
@@ -420,6 +466,86 @@ pub async fn search_project_files(
     }
 }
 
+pub async fn get_project_usage(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<UsageSummary>>, ApiError> {
+    let usage =
+        ExecutionProcessUsage::summary_for_project(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(usage)))
+}
+
+pub async fn get_project_budget(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ExecutionBudget>>>, ApiError> {
+    let budget = ExecutionBudget::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(budget)))
+}
+
+pub async fn update_project_budget(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateExecutionBudget>,
+) -> Result<ResponseJson<ApiResponse<ExecutionBudget>>, ApiError> {
+    let budget =
+        ExecutionBudget::upsert_for_project(&deployment.db().pool, project.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(budget)))
+}
+
+pub async fn delete_project_budget(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    ExecutionBudget::delete_for_project(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn get_project_approval_rules(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ApprovalRule>>>, ApiError> {
+    let rules = ApprovalRule::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(rules)))
+}
+
+pub async fn create_project_approval_rule(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateApprovalRule>,
+) -> Result<ResponseJson<ApiResponse<ApprovalRule>>, ApiError> {
+    policy::validate_rule(&payload).map_err(ApiError::BadRequest)?;
+    let rule = ApprovalRule::create(&deployment.db().pool, project.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(rule)))
+}
+
+pub async fn update_project_approval_rule(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, rule_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<CreateApprovalRule>,
+) -> Result<ResponseJson<ApiResponse<ApprovalRule>>, ApiError> {
+    policy::validate_rule(&payload).map_err(ApiError::BadRequest)?;
+    match ApprovalRule::update(&deployment.db().pool, project_id, rule_id, &payload).await? {
+        Some(rule) => Ok(ResponseJson(ApiResponse::success(rule))),
+        None => Err(ApiError::BadRequest(
+            "Approval rule not found in project".to_string(),
+        )),
+    }
+}
+
+pub async fn delete_project_approval_rule(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, rule_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = ApprovalRule::delete(&deployment.db().pool, project_id, rule_id).await?;
+    if rows_affected == 0 {
+        return Err(ApiError::BadRequest(
+            "Approval rule not found in project".to_string(),
+        ));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
 pub async fn get_project_repositories(
     Extension(project): Extension<Project>,
     State(deployment): State<DeploymentImpl>,
@@ -591,6 +717,19 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/remote/members", get(get_project_remote_members))
         .route("/search", get(search_project_files))
         .route("/open-editor", post(open_project_in_editor))
+        .route("/usage", get(get_project_usage))
+        .route("/merge-queue", get(get_project_merge_queue))
+        .route("/merge-queue/stream/ws", get(stream_project_merge_queue_ws))
+        .route(
+            "/budget",
+            get(get_project_budget)
+                .put(update_project_budget)
+                .delete(delete_project_budget),
+        )
+        .route(
+            "/approval-rules",
+            get(get_project_approval_rules).post(create_project_approval_rule),
+        )
         .route(
             "/link",
             post(link_project_to_existing_remote).delete(unlink_project),
@@ -613,6 +752,10 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
                 .put(update_project_repository)
                 .delete(delete_project_repository),
         )
+        .route(
+            "/{project_id}/approval-rules/{rule_id}",
+            put(update_project_approval_rule).delete(delete_project_approval_rule),
+        )
         .route("/stream/ws", get(stream_projects_ws))
         .nest("/{id}", project_id_router);
 
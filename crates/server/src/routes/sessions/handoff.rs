@@ -0,0 +1,154 @@
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use db::models::{
+    coding_agent_turn::CodingAgentTurn,
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+    project_repo::ProjectRepo,
+    session::Session,
+    workspace::{Workspace, WorkspaceError},
+};
+use deployment::Deployment;
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType, coding_agent_initial::CodingAgentInitialRequest,
+    },
+    profile::{ExecutorConfigs, ExecutorProfileId},
+};
+use serde::Deserialize;
+use services::services::{
+    container::ContainerService,
+    handoff::{self, HandoffContext},
+};
+use sqlx::Error as SqlxError;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateHandoff {
+    /// Executor that continues the session
+    pub executor_profile_id: ExecutorProfileId,
+    /// What the new executor should do next. Defaults to carrying on with the task.
+    pub prompt: Option<String>,
+}
+
+/// Continue a session on a different executor. Any running agent is stopped, and the new
+/// one starts a fresh conversation briefed with the previous turns, the latest conversation
+/// and the workspace diff. Later follow-ups resume the new executor's session.
+pub async fn handoff(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateHandoff>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace = Workspace::find_by_id(pool, session.workspace_id)
+        .await?
+        .ok_or(ApiError::Workspace(WorkspaceError::ValidationError(
+            "Workspace not found".to_string(),
+        )))?;
+
+    if ExecutorConfigs::get_cached()
+        .get_coding_agent(&payload.executor_profile_id)
+        .is_none()
+    {
+        return Err(ApiError::Workspace(WorkspaceError::ValidationError(
+            format!("Executor profile {} not found", payload.executor_profile_id),
+        )));
+    }
+
+    let turns = CodingAgentTurn::find_by_session_id(pool, session.id).await?;
+    let Some(previous_turn) = turns.last() else {
+        return Err(ApiError::Workspace(WorkspaceError::ValidationError(
+            "The session has no coding agent turns to hand off".to_string(),
+        )));
+    };
+    let from = ExecutionProcess::latest_executor_profile_for_session(pool, session.id).await?;
+
+    deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+
+    // Stop the current agent so it doesn't keep editing while the new one takes over
+    deployment.container().try_stop(&workspace, false).await;
+
+    // Earlier handoffs are already summarised in the prompt of the turn that received them
+    let turns_since_handoff = turns
+        .iter()
+        .rposition(|turn| turn.handoff_from_turn_id.is_some())
+        .map_or(&turns[..], |index| &turns[index..]);
+    let mut conversation = Vec::new();
+    for turn in turns_since_handoff {
+        conversation.extend(
+            handoff::load_conversation(deployment.container(), turn.execution_process_id).await,
+        );
+    }
+    let diff = handoff::load_workspace_diff(deployment.container(), &workspace).await?;
+
+    let prompt = handoff::build_transfer_prompt(
+        &HandoffContext {
+            from: &from,
+            turns: &turns,
+            conversation: &conversation,
+            diff: &diff,
+        },
+        payload.prompt.as_deref(),
+    );
+
+    let task = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let project_repos = ProjectRepo::find_by_project_id_with_names(pool, task.project_id).await?;
+    let post_agent_action = deployment
+        .container()
+        .post_agent_actions_for_repos(&project_repos);
+
+    let working_dir = workspace
+        .agent_working_dir
+        .as_ref()
+        .filter(|dir| !dir.is_empty())
+        .cloned();
+
+    let action = ExecutorAction::new(
+        ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+            prompt,
+            executor_profile_id: payload.executor_profile_id.clone(),
+            working_dir,
+        }),
+        post_agent_action.map(Box::new),
+    );
+
+    Session::update_executor(
+        pool,
+        session.id,
+        &payload.executor_profile_id.executor.to_string(),
+    )
+    .await?;
+
+    let execution_process = deployment
+        .container()
+        .start_execution_from_turn(
+            &workspace,
+            &session,
+            &action,
+            &ExecutionProcessRunReason::CodingAgent,
+            Some(previous_turn.id),
+        )
+        .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "session_handed_off",
+            serde_json::json!({
+                "session_id": session.id.to_string(),
+                "workspace_id": workspace.id.to_string(),
+                "from_executor": from.executor.to_string(),
+                "to_executor": payload.executor_profile_id.executor.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(execution_process)))
+}
@@ -1,3 +1,4 @@
+pub mod handoff;
 pub mod queue;
 
 use axum::{
@@ -21,7 +22,7 @@ use executors::{
     },
     profile::ExecutorProfileId,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use services::services::container::ContainerService;
 use sqlx::Error as SqlxError;
 use ts_rs::TS;
@@ -85,7 +86,7 @@ pub async fn create_session(
     Ok(ResponseJson(ApiResponse::success(session)))
 }
 
-#[derive(Debug, Deserialize, TS)]
+#[derive(Debug, Serialize, Deserialize, TS)]
 pub struct CreateFollowUpAttempt {
     pub prompt: String,
     pub variant: Option<String>,
@@ -177,9 +178,9 @@ pub async fn follow_up(
     let prompt = payload.prompt;
 
     let project_repos = ProjectRepo::find_by_project_id_with_names(pool, project.id).await?;
-    let cleanup_action = deployment
+    let post_agent_action = deployment
         .container()
-        .cleanup_actions_for_repos(&project_repos);
+        .post_agent_actions_for_repos(&project_repos);
 
     let working_dir = workspace
         .agent_working_dir
@@ -204,7 +205,7 @@ pub async fn follow_up(
         )
     };
 
-    let action = ExecutorAction::new(action_type, cleanup_action.map(Box::new));
+    let action = ExecutorAction::new(action_type, post_agent_action.map(Box::new));
 
     let execution_process = deployment
         .container()
@@ -234,6 +235,7 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let session_id_router = Router::new()
         .route("/", get(get_session))
         .route("/follow-up", post(follow_up))
+        .route("/handoff", post(handoff::handoff))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_session_middleware,
@@ -1,7 +1,10 @@
+pub mod archive;
+pub mod best_of_n;
 pub mod codex_setup;
 pub mod cursor_setup;
 pub mod gh_cli_setup;
 pub mod images;
+pub mod merge_queue;
 pub mod pr;
 pub mod util;
 
@@ -13,7 +16,7 @@ use std::{
 use axum::{
     Extension, Json, Router,
     extract::{
-        Query, State,
+        DefaultBodyLimit, Query, State,
         ws::{WebSocket, WebSocketUpgrade},
     },
     http::StatusCode,
@@ -24,10 +27,12 @@ use axum::{
 use db::models::{
     execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
     merge::{Merge, MergeStatus, PrMerge, PullRequestInfo},
+    project::MergeStrategy,
     project_repo::ProjectRepo,
     repo::{Repo, RepoError},
     session::{CreateSession, Session},
     task::{Task, TaskRelationships, TaskStatus},
+    task_dependency::TaskDependency,
     workspace::{CreateWorkspace, Workspace, WorkspaceError},
     workspace_repo::{CreateWorkspaceRepo, RepoWithTargetBranch, WorkspaceRepo},
 };
@@ -43,9 +48,13 @@ use executors::{
 use git2::BranchType;
 use serde::{Deserialize, Serialize};
 use services::services::{
+    commit_message::{self, CommitMessageContext},
+    conflict_resolution,
     container::ContainerService,
+    forge::ForgeKind,
     git::{ConflictOp, GitCliError, GitServiceError},
     github::GitHubService,
+    handoff, stack,
 };
 use sqlx::Error as SqlxError;
 use ts_rs::TS;
@@ -69,6 +78,11 @@ pub struct AbortConflictsRequest {
     pub repo_id: Uuid,
 }
 
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct ResolveConflictsRequest {
+    pub repo_id: Uuid,
+}
+
 #[derive(Debug, Serialize, Deserialize, TS)]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[ts(tag = "type", rename_all = "snake_case")]
@@ -80,6 +94,9 @@ pub enum GitOperationError {
 #[derive(Debug, Deserialize)]
 pub struct TaskAttemptQuery {
     pub task_id: Option<Uuid>,
+    /// Also list attempts archived when another attempt was promoted
+    #[serde(default)]
+    pub include_archived: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -93,7 +110,10 @@ pub async fn get_task_attempts(
     Query(query): Query<TaskAttemptQuery>,
 ) -> Result<ResponseJson<ApiResponse<Vec<Workspace>>>, ApiError> {
     let pool = &deployment.db().pool;
-    let workspaces = Workspace::fetch_all(pool, query.task_id).await?;
+    let mut workspaces = Workspace::fetch_all(pool, query.task_id).await?;
+    if !query.include_archived {
+        workspaces.retain(|workspace| workspace.archived_at.is_none());
+    }
     Ok(ResponseJson(ApiResponse::success(workspaces)))
 }
 
@@ -108,6 +128,10 @@ pub struct CreateTaskAttemptBody {
     pub task_id: Uuid,
     pub executor_profile_id: ExecutorProfileId,
     pub repos: Vec<WorkspaceRepoInput>,
+    /// Base the attempt on the parent attempt's branch for the repos both share, and
+    /// restack it whenever that branch changes
+    #[serde(default)]
+    pub stack_on_parent: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, ts_rs::TS)]
@@ -137,11 +161,82 @@ pub async fn create_task_attempt(
         ));
     }
 
-    let pool = &deployment.db().pool;
     let task = Task::find_by_id(&deployment.db().pool, payload.task_id)
         .await?
         .ok_or(SqlxError::RowNotFound)?;
+    ensure_not_blocked(&deployment, &task).await?;
+
+    let workspace = create_and_start_attempt(
+        &deployment,
+        &task,
+        &executor_profile_id,
+        &payload.repos,
+        payload.stack_on_parent,
+    )
+    .await?;
 
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_started",
+            serde_json::json!({
+                "task_id": workspace.task_id.to_string(),
+                "variant": &executor_profile_id.variant,
+                "executor": &executor_profile_id.executor,
+                "workspace_id": workspace.id.to_string(),
+                "repository_count": payload.repos.len(),
+                "stack_on_parent": payload.stack_on_parent,
+            }),
+        )
+        .await;
+
+    tracing::info!("Created attempt for task {}", task.id);
+
+    Ok(ResponseJson(ApiResponse::success(workspace)))
+}
+
+/// Refuse to start a task while any task it depends on is not done yet
+async fn ensure_not_blocked(deployment: &DeploymentImpl, task: &Task) -> Result<(), ApiError> {
+    let prerequisites = TaskDependency::find_prerequisites(&deployment.db().pool, task.id).await?;
+    if prerequisites
+        .iter()
+        .any(|prerequisite| prerequisite.status != TaskStatus::Done)
+    {
+        return Err(ApiError::Conflict(
+            "Task is blocked until the tasks it depends on are done".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Create a workspace for the task on the given repos and start the executor in it.
+/// Failing to start is logged rather than returned, so the attempt still exists and can be retried.
+async fn create_and_start_attempt(
+    deployment: &DeploymentImpl,
+    task: &Task,
+    executor_profile_id: &ExecutorProfileId,
+    repos: &[WorkspaceRepoInput],
+    stack_on_parent: bool,
+) -> Result<Workspace, ApiError> {
+    let workspace = create_attempt(deployment, task, repos, stack_on_parent).await?;
+    if let Err(err) = deployment
+        .container()
+        .start_workspace(&workspace, executor_profile_id.clone())
+        .await
+    {
+        tracing::error!("Failed to start task attempt: {}", err);
+    }
+
+    Ok(workspace)
+}
+
+/// Create a workspace for the task on the given repos, without creating its worktrees
+async fn create_attempt(
+    deployment: &DeploymentImpl,
+    task: &Task,
+    repos: &[WorkspaceRepoInput],
+    stack_on_parent: bool,
+) -> Result<Workspace, ApiError> {
+    let pool = &deployment.db().pool;
     let project = task
         .parent_project(pool)
         .await?
@@ -166,44 +261,29 @@ pub async fn create_task_attempt(
             agent_working_dir,
         },
         attempt_id,
-        payload.task_id,
+        task.id,
     )
     .await?;
 
-    let workspace_repos: Vec<CreateWorkspaceRepo> = payload
-        .repos
+    let mut workspace_repos: Vec<CreateWorkspaceRepo> = repos
         .iter()
         .map(|r| CreateWorkspaceRepo {
             repo_id: r.repo_id,
             target_branch: r.target_branch.clone(),
         })
         .collect();
+    if stack_on_parent {
+        stack::stack_on_parent(pool, task, &mut workspace_repos).await?;
+    }
 
     WorkspaceRepo::create_many(pool, workspace.id, &workspace_repos).await?;
-    if let Err(err) = deployment
-        .container()
-        .start_workspace(&workspace, executor_profile_id.clone())
-        .await
+    if stack_on_parent
+        && let Err(err) = stack::record_stack_bases(pool, deployment.git(), &workspace, task).await
     {
-        tracing::error!("Failed to start task attempt: {}", err);
+        tracing::warn!("Failed to record stack bases for {}: {}", workspace.id, err);
     }
 
-    deployment
-        .track_if_analytics_allowed(
-            "task_attempt_started",
-            serde_json::json!({
-                "task_id": workspace.task_id.to_string(),
-                "variant": &executor_profile_id.variant,
-                "executor": &executor_profile_id.executor,
-                "workspace_id": workspace.id.to_string(),
-                "repository_count": payload.repos.len(),
-            }),
-        )
-        .await;
-
-    tracing::info!("Created attempt for task {}", task.id);
-
-    Ok(ResponseJson(ApiResponse::success(workspace)))
+    Ok(workspace)
 }
 
 #[axum::debug_handler]
@@ -303,6 +383,9 @@ async fn handle_task_attempt_diff_ws(
 #[derive(Debug, Deserialize, Serialize, TS)]
 pub struct MergeTaskAttemptRequest {
     pub repo_id: Uuid,
+    /// Overrides the project's merge strategy for this merge
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<MergeStrategy>,
 }
 
 #[derive(Debug, Deserialize, Serialize, TS)]
@@ -338,25 +421,30 @@ pub async fn merge_task_attempt(
         .parent_task(pool)
         .await?
         .ok_or(ApiError::Workspace(WorkspaceError::TaskNotFound))?;
-    let task_uuid_str = task.id.to_string();
-    let first_uuid_section = task_uuid_str.split('-').next().unwrap_or(&task_uuid_str);
-
-    let mut commit_message = format!("{} (vibe-kanban {})", task.title, first_uuid_section);
+    let project = task
+        .parent_project(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let strategy = request.strategy.unwrap_or(project.merge_strategy);
+    let executor = Session::find_latest_by_workspace_id(pool, workspace.id)
+        .await?
+        .and_then(|session| session.executor);
 
-    // Add description on next line if it exists
-    if let Some(description) = &task.description
-        && !description.trim().is_empty()
-    {
-        commit_message.push_str("\n\n");
-        commit_message.push_str(description);
-    }
+    let commit_message = commit_message::render_commit_message(
+        project.merge_commit_template.as_deref(),
+        &CommitMessageContext {
+            task: &task,
+            executor: executor.as_deref(),
+        },
+    );
 
-    let merge_commit_id = deployment.git().merge_changes(
+    let merge_commit_id = deployment.git().merge_changes_with_strategy(
         &repo.path,
         &worktree_path,
         &workspace.branch,
         &workspace_repo.target_branch,
         &commit_message,
+        strategy,
     )?;
 
     Merge::create_direct(
@@ -416,6 +504,7 @@ pub async fn merge_task_attempt(
             serde_json::json!({
                 "task_id": task.id.to_string(),
                 "workspace_id": workspace.id.to_string(),
+                "strategy": strategy,
             }),
         )
         .await;
@@ -423,6 +512,17 @@ pub async fn merge_task_attempt(
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
+/// Pushes to GitHub authenticate through the gh credential helper, so check it is signed in.
+/// GitLab remotes use the user's own git credentials.
+async fn ensure_push_auth(deployment: &DeploymentImpl, repo_path: &Path) -> Result<(), ApiError> {
+    let remote_url = deployment.git().get_remote_url(repo_path)?;
+    let gitlab_config = deployment.config().read().await.gitlab.clone();
+    if ForgeKind::from_remote_url(&remote_url, &gitlab_config) != Some(ForgeKind::GitLab) {
+        GitHubService::new()?.check_token().await?;
+    }
+    Ok(())
+}
+
 pub async fn push_task_attempt_branch(
     Extension(workspace): Extension<Workspace>,
     State(deployment): State<DeploymentImpl>,
@@ -430,9 +530,6 @@ pub async fn push_task_attempt_branch(
 ) -> Result<ResponseJson<ApiResponse<(), PushError>>, ApiError> {
     let pool = &deployment.db().pool;
 
-    let github_service = GitHubService::new()?;
-    github_service.check_token().await?;
-
     let workspace_repo =
         WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, request.repo_id)
             .await?
@@ -442,6 +539,8 @@ pub async fn push_task_attempt_branch(
         .await?
         .ok_or(RepoError::NotFound)?;
 
+    ensure_push_auth(&deployment, &repo.path).await?;
+
     let container_ref = deployment
         .container()
         .ensure_container_exists(&workspace)
@@ -468,9 +567,6 @@ pub async fn force_push_task_attempt_branch(
 ) -> Result<ResponseJson<ApiResponse<(), PushError>>, ApiError> {
     let pool = &deployment.db().pool;
 
-    let github_service = GitHubService::new()?;
-    github_service.check_token().await?;
-
     let workspace_repo =
         WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, request.repo_id)
             .await?
@@ -480,6 +576,8 @@ pub async fn force_push_task_attempt_branch(
         .await?
         .ok_or(RepoError::NotFound)?;
 
+    ensure_push_auth(&deployment, &repo.path).await?;
+
     let container_ref = deployment
         .container()
         .ensure_container_exists(&workspace)
@@ -738,6 +836,20 @@ pub async fn get_task_attempt_branch_status(
     Ok(ResponseJson(ApiResponse::success(results)))
 }
 
+/// Unified diff of the workspace against each repo's base commit, including uncommitted
+/// changes. The streamed diff is better suited to the board; this is for API clients.
+pub async fn get_task_attempt_diff(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<String>>, ApiError> {
+    deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let diff = handoff::load_workspace_diff(deployment.container(), &workspace).await?;
+    Ok(ResponseJson(ApiResponse::success(diff)))
+}
+
 #[derive(serde::Deserialize, Debug, TS)]
 pub struct ChangeTargetBranchRequest {
     pub repo_id: Uuid,
@@ -1087,6 +1199,81 @@ pub async fn abort_conflicts_task_attempt(
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
+/// Ask the attempt's coding agent to resolve the conflicts of a stopped rebase. The agent
+/// gets a follow-up listing the conflicted files, their conflict markers and the commits on
+/// both sides; once it finishes without leaving markers behind the rebase is continued.
+#[axum::debug_handler]
+pub async fn resolve_conflicts_task_attempt(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ResolveConflictsRequest>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace_repo =
+        WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, payload.repo_id)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+    let repo = Repo::find_by_id(pool, payload.repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let worktree_path = Path::new(&container_ref).join(&repo.name);
+
+    if deployment.git().detect_conflict_op(&worktree_path)? != Some(ConflictOp::Rebase) {
+        return Err(ApiError::BadRequest(
+            "Only conflicts of a stopped rebase can be resolved by the agent".to_string(),
+        ));
+    }
+    if deployment
+        .git()
+        .get_conflicted_files(&worktree_path)?
+        .is_empty()
+    {
+        return Err(ApiError::BadRequest(
+            "There are no conflicted files to resolve".to_string(),
+        ));
+    }
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace.id)
+        .await?
+    {
+        return Err(ApiError::Conflict(
+            "Wait for the running agent or script to finish first".to_string(),
+        ));
+    }
+
+    let session = Session::find_latest_by_workspace_id(pool, workspace.id)
+        .await?
+        .ok_or_else(|| {
+            ApiError::BadRequest("This attempt has no session to continue".to_string())
+        })?;
+
+    let execution_process = conflict_resolution::start_resolution(
+        deployment.container(),
+        &workspace,
+        &session,
+        &repo,
+        &workspace_repo.target_branch,
+    )
+    .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_conflicts_resolve_started",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "repo_id": payload.repo_id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(execution_process)))
+}
+
 #[axum::debug_handler]
 pub async fn start_dev_server(
     Extension(workspace): Extension<Workspace>,
@@ -1486,21 +1673,30 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/run-setup-script", post(run_setup_script))
         .route("/run-cleanup-script", post(run_cleanup_script))
         .route("/branch-status", get(get_task_attempt_branch_status))
+        .route("/diff", get(get_task_attempt_diff))
         .route("/diff/ws", get(stream_task_attempt_diff_ws))
         .route("/merge", post(merge_task_attempt))
+        .route("/merge-queue", post(merge_queue::enqueue_merge))
+        .route(
+            "/merge-queue/cancel",
+            post(merge_queue::cancel_queued_merge),
+        )
         .route("/push", post(push_task_attempt_branch))
         .route("/push/force", post(force_push_task_attempt_branch))
         .route("/rebase", post(rebase_task_attempt))
         .route("/conflicts/abort", post(abort_conflicts_task_attempt))
+        .route("/conflicts/resolve", post(resolve_conflicts_task_attempt))
         .route("/pr", post(pr::create_github_pr))
         .route("/pr/attach", post(pr::attach_existing_pr))
         .route("/pr/comments", get(pr::get_pr_comments))
         .route("/open-editor", post(open_task_attempt_in_editor))
         .route("/children", get(get_task_attempt_children))
         .route("/stop", post(stop_task_attempt_execution))
+        .route("/promote", post(best_of_n::promote_task_attempt))
         .route("/change-target-branch", post(change_target_branch))
         .route("/rename-branch", post(rename_branch))
         .route("/repos", get(get_task_attempt_repos))
+        .route("/export", get(archive::export_task_attempt))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_workspace_middleware,
@@ -1508,6 +1704,12 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
 
     let task_attempts_router = Router::new()
         .route("/", get(get_task_attempts).post(create_task_attempt))
+        .route("/best-of-n", post(best_of_n::create_best_of_n_attempts))
+        .route(
+            "/import",
+            post(archive::import_task_attempt).layer(DefaultBodyLimit::max(200 * 1024 * 1024)), // 200MB limit
+        )
+        .route("/compare", get(best_of_n::compare_task_attempts))
         .nest("/{id}", task_attempt_id_router)
         .nest("/{id}/images", images::router(deployment));
 
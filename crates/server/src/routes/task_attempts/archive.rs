@@ -0,0 +1,172 @@
+use std::path::PathBuf;
+
+use axum::{
+    Extension,
+    extract::{Multipart, Query, State},
+    http::header,
+    response::{IntoResponse, Json as ResponseJson, Response},
+};
+use db::models::{
+    project_repo::ProjectRepo, repo::Repo, task::Task, workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::{
+    attempt_archive::{self, ArchivedRepo, AttemptArchive, AttemptArchiveFormat},
+    container::ContainerService,
+};
+use sqlx::Error as SqlxError;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use super::{WorkspaceRepoInput, create_attempt};
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize)]
+pub struct ExportTaskAttemptQuery {
+    pub format: AttemptArchiveFormat,
+}
+
+/// Download the attempt's commits since each repo's base commit as a tar archive of
+/// patch series or bundles, one per repo with commits
+pub async fn export_task_attempt(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ExportTaskAttemptQuery>,
+) -> Result<Response, ApiError> {
+    let repos = WorkspaceRepo::find_repos_with_target_branch_for_workspace(
+        &deployment.db().pool,
+        workspace.id,
+    )
+    .await?;
+
+    let archive = attempt_archive::export_workspace(
+        deployment.git(),
+        &workspace.branch,
+        &repos,
+        query.format,
+    )?;
+
+    let file_name: String = workspace
+        .branch
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/x-tar".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}.tar\""),
+            ),
+        ],
+        archive,
+    )
+        .into_response())
+}
+
+/// Create an attempt for a task from an exported archive, given as the multipart fields
+/// `task_id` and `archive`. The worktrees are created with the archive's commits on the
+/// new branch; no executor is started.
+pub async fn import_task_attempt(
+    State(deployment): State<DeploymentImpl>,
+    mut multipart: Multipart,
+) -> Result<ResponseJson<ApiResponse<Workspace>>, ApiError> {
+    let mut task_id = None;
+    let mut archive_bytes = None;
+    while let Some(field) = multipart.next_field().await? {
+        match field.name() {
+            Some("task_id") => task_id = Some(field.text().await?),
+            Some("archive") => archive_bytes = Some(field.bytes().await?),
+            _ => {}
+        }
+    }
+    let task_id = task_id
+        .and_then(|id| Uuid::parse_str(id.trim()).ok())
+        .ok_or_else(|| ApiError::BadRequest("A valid task_id is required".to_string()))?;
+    let archive_bytes = archive_bytes
+        .ok_or_else(|| ApiError::BadRequest("An archive file is required".to_string()))?;
+    let archive =
+        AttemptArchive::read(&archive_bytes).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    let pool = &deployment.db().pool;
+    let task = Task::find_by_id(pool, task_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let project_repos = ProjectRepo::find_repos_for_project(pool, task.project_id).await?;
+
+    // Check every repo before creating anything, so a bad archive leaves no attempt behind
+    let mut imports = Vec::with_capacity(archive.manifest.repos.len());
+    for archived in &archive.manifest.repos {
+        let repo = project_repos
+            .iter()
+            .find(|repo| repo.name == archived.name)
+            .ok_or_else(|| {
+                ApiError::BadRequest(format!(
+                    "The project has no repository named {}",
+                    archived.name
+                ))
+            })?;
+        archive.check_importable(deployment.git(), archived, repo)?;
+        imports.push((archived, repo));
+    }
+
+    let repos: Vec<WorkspaceRepoInput> = imports
+        .iter()
+        .map(|(archived, repo)| WorkspaceRepoInput {
+            repo_id: repo.id,
+            target_branch: archived.target_branch.clone(),
+        })
+        .collect();
+    let workspace = create_attempt(&deployment, &task, &repos, false).await?;
+    if let Err(e) = import_commits(&deployment, &workspace, &archive, &imports).await {
+        // Leave no half-imported attempt behind
+        deployment.container().discard_workspace(workspace.id).await;
+        return Err(e);
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_imported",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "workspace_id": workspace.id.to_string(),
+                "format": archive.manifest.format,
+                "repository_count": imports.len(),
+            }),
+        )
+        .await;
+
+    tracing::info!("Imported attempt {} for task {}", workspace.id, task.id);
+
+    let workspace = Workspace::find_by_id(pool, workspace.id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    Ok(ResponseJson(ApiResponse::success(workspace)))
+}
+
+/// Create the worktrees of a new attempt and recreate the archive's commits in them
+async fn import_commits(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    archive: &AttemptArchive,
+    imports: &[(&ArchivedRepo, &Repo)],
+) -> Result<(), ApiError> {
+    let workspace_dir = PathBuf::from(deployment.container().create(workspace).await?);
+    for (archived, repo) in imports {
+        archive.import_into_worktree(
+            deployment.git(),
+            archived,
+            &repo.path,
+            &workspace_dir.join(&repo.name),
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,304 @@
+use std::path::PathBuf;
+
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    execution_process_usage::{ExecutionProcessUsage, UsageSummary},
+    task::Task,
+    workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
+};
+use deployment::Deployment;
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use services::services::{
+    container::ContainerService,
+    git::{DiffTarget, GitServiceError},
+};
+use sqlx::Error as SqlxError;
+use ts_rs::TS;
+use utils::{diff::compute_line_change_counts, response::ApiResponse};
+use uuid::Uuid;
+
+use super::{WorkspaceRepoInput, create_and_start_attempt, ensure_not_blocked};
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct CreateBestOfNAttemptsBody {
+    pub task_id: Uuid,
+    pub executor_profile_ids: Vec<ExecutorProfileId>,
+    pub repos: Vec<WorkspaceRepoInput>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompareAttemptsQuery {
+    pub task_id: Uuid,
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct AttemptDiffStats {
+    pub files_changed: usize,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct AttemptComparison {
+    pub workspace: Workspace,
+    pub executor_profile_id: Option<ExecutorProfileId>,
+    /// Status of the latest coding agent run
+    pub status: Option<ExecutionProcessStatus>,
+    /// Changes against the target branch, summed over all repos
+    pub diff_stats: AttemptDiffStats,
+    /// Exit code of the latest cleanup script run
+    pub cleanup_script_exit_code: Option<i64>,
+    /// Exit code of the latest verify script run
+    pub verify_script_exit_code: Option<i64>,
+    pub usage: UsageSummary,
+    /// Wall-clock time of the latest coding agent run, up to now if it is still running
+    pub duration_seconds: Option<u32>,
+}
+
+/// Start the same task on several executor profiles at once, one workspace and branch each
+/// If one of them cannot be created, the ones already started are stopped and discarded.
+#[axum::debug_handler]
+pub async fn create_best_of_n_attempts(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateBestOfNAttemptsBody>,
+) -> Result<ResponseJson<ApiResponse<Vec<Workspace>>>, ApiError> {
+    if payload.repos.is_empty() {
+        return Err(ApiError::BadRequest(
+            "At least one repository is required".to_string(),
+        ));
+    }
+    if payload.executor_profile_ids.is_empty() {
+        return Err(ApiError::BadRequest(
+            "At least one executor profile is required".to_string(),
+        ));
+    }
+
+    let task = Task::find_by_id(&deployment.db().pool, payload.task_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    ensure_not_blocked(&deployment, &task).await?;
+
+    let mut workspaces = Vec::with_capacity(payload.executor_profile_ids.len());
+    for executor_profile_id in &payload.executor_profile_ids {
+        let workspace = match create_and_start_attempt(
+            &deployment,
+            &task,
+            executor_profile_id,
+            &payload.repos,
+            false,
+        )
+        .await
+        {
+            Ok(workspace) => workspace,
+            Err(err) => {
+                // Don't leave the attempts started so far running behind a failed request
+                for started in &workspaces {
+                    deployment.container().discard_workspace(started.id).await;
+                }
+                return Err(err);
+            }
+        };
+        workspaces.push(workspace);
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempts_best_of_n_started",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "executors": payload
+                    .executor_profile_ids
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<_>>(),
+                "attempt_count": workspaces.len(),
+                "repository_count": payload.repos.len(),
+            }),
+        )
+        .await;
+
+    tracing::info!(
+        "Created {} parallel attempts for task {}",
+        workspaces.len(),
+        task.id
+    );
+
+    Ok(ResponseJson(ApiResponse::success(workspaces)))
+}
+
+/// Side-by-side summary of the attempts of a task, newest first
+pub async fn compare_task_attempts(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<CompareAttemptsQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<AttemptComparison>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspaces = Workspace::fetch_all(pool, Some(query.task_id)).await?;
+
+    let mut comparisons = Vec::with_capacity(workspaces.len());
+    for workspace in workspaces {
+        if workspace.archived_at.is_some() && !query.include_archived {
+            continue;
+        }
+
+        let coding_agent = ExecutionProcess::find_latest_by_workspace_and_run_reason(
+            pool,
+            workspace.id,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?;
+        let cleanup_script = ExecutionProcess::find_latest_by_workspace_and_run_reason(
+            pool,
+            workspace.id,
+            &ExecutionProcessRunReason::CleanupScript,
+        )
+        .await?;
+        let verify_script = ExecutionProcess::find_latest_by_workspace_and_run_reason(
+            pool,
+            workspace.id,
+            &ExecutionProcessRunReason::VerifyScript,
+        )
+        .await?;
+
+        let executor_profile_id = match coding_agent {
+            Some(ref process) => {
+                ExecutionProcess::latest_executor_profile_for_session(pool, process.session_id)
+                    .await
+                    .ok()
+            }
+            None => None,
+        };
+
+        let duration_seconds = coding_agent.as_ref().map(|process| {
+            let end = process.completed_at.unwrap_or_else(chrono::Utc::now);
+            (end - process.started_at).num_seconds().max(0) as u32
+        });
+
+        let diff_stats = attempt_diff_stats(&deployment, &workspace).await?;
+        let usage = ExecutionProcessUsage::summary_for_workspace(pool, workspace.id).await?;
+
+        comparisons.push(AttemptComparison {
+            executor_profile_id,
+            status: coding_agent.map(|process| process.status),
+            diff_stats,
+            cleanup_script_exit_code: cleanup_script.and_then(|process| process.exit_code),
+            verify_script_exit_code: verify_script.and_then(|process| process.exit_code),
+            usage,
+            duration_seconds,
+            workspace,
+        });
+    }
+
+    Ok(ResponseJson(ApiResponse::success(comparisons)))
+}
+
+/// Keep this attempt and archive every other attempt of the task
+#[axum::debug_handler]
+pub async fn promote_task_attempt(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Workspace>>, ApiError> {
+    if workspace.archived_at.is_some() {
+        return Err(ApiError::BadRequest(
+            "Cannot promote an archived attempt".to_string(),
+        ));
+    }
+
+    let archived = deployment.container().promote_attempt(&workspace).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_promoted",
+            serde_json::json!({
+                "task_id": workspace.task_id.to_string(),
+                "workspace_id": workspace.id.to_string(),
+                "archived_count": archived.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(workspace)))
+}
+
+async fn attempt_diff_stats(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+) -> Result<AttemptDiffStats, ApiError> {
+    let repos = WorkspaceRepo::find_repos_with_target_branch_for_workspace(
+        &deployment.db().pool,
+        workspace.id,
+    )
+    .await?;
+
+    let mut stats = AttemptDiffStats::default();
+    for repo in repos {
+        // Diff the live worktree when it is still around so uncommitted work counts,
+        // otherwise fall back to the committed branch
+        let worktree_path = workspace
+            .container_ref
+            .as_ref()
+            .map(|container_ref| PathBuf::from(container_ref).join(&repo.repo.name))
+            .filter(|path| path.exists());
+
+        let diffs = match worktree_path {
+            Some(worktree_path) => deployment
+                .git()
+                .get_base_commit(&repo.repo.path, &workspace.branch, &repo.target_branch)
+                .and_then(|base_commit| {
+                    deployment.git().get_diffs(
+                        DiffTarget::Worktree {
+                            worktree_path: &worktree_path,
+                            base_commit: &base_commit,
+                        },
+                        None,
+                    )
+                }),
+            None => deployment.git().get_diffs(
+                DiffTarget::Branch {
+                    repo_path: &repo.repo.path,
+                    branch_name: &workspace.branch,
+                    base_branch: &repo.target_branch,
+                },
+                None,
+            ),
+        };
+
+        let diffs = match diffs {
+            Ok(diffs) => diffs,
+            Err(GitServiceError::BranchNotFound(_)) => continue,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to diff repo {} for attempt {}: {}",
+                    repo.repo.name,
+                    workspace.id,
+                    e
+                );
+                continue;
+            }
+        };
+
+        for diff in diffs {
+            let (additions, deletions) = match (diff.additions, diff.deletions) {
+                (Some(additions), Some(deletions)) => (additions, deletions),
+                _ => compute_line_change_counts(
+                    diff.old_content.as_deref().unwrap_or(""),
+                    diff.new_content.as_deref().unwrap_or(""),
+                ),
+            };
+            stats.files_changed += 1;
+            stats.additions += additions;
+            stats.deletions += deletions;
+        }
+    }
+
+    Ok(stats)
+}
@@ -0,0 +1,98 @@
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use db::models::{
+    merge_queue::{CreateMergeQueueEntry, MergeQueueEntry},
+    repo::RepoError,
+    workspace::{Workspace, WorkspaceError},
+    workspace_repo::WorkspaceRepo,
+};
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct MergeQueueRequest {
+    pub repo_id: Uuid,
+}
+
+/// Queue a workspace repo to be rebased onto its target branch, verified and merged once
+/// the attempts queued before it on the same branch have landed
+pub async fn enqueue_merge(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<MergeQueueRequest>,
+) -> Result<ResponseJson<ApiResponse<MergeQueueEntry>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace_repo =
+        WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, request.repo_id)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+    let task = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::Workspace(WorkspaceError::TaskNotFound))?;
+
+    if MergeQueueEntry::find_active_for_workspace_repo(pool, workspace.id, request.repo_id)
+        .await?
+        .is_some()
+    {
+        return Err(ApiError::Conflict(
+            "This attempt is already in the merge queue".to_string(),
+        ));
+    }
+
+    let entry = MergeQueueEntry::create(
+        pool,
+        &CreateMergeQueueEntry {
+            project_id: task.project_id,
+            workspace_id: workspace.id,
+            repo_id: request.repo_id,
+            target_branch: workspace_repo.target_branch.clone(),
+        },
+    )
+    .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_merge_queued",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "workspace_id": workspace.id.to_string(),
+                "repo_id": request.repo_id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(entry)))
+}
+
+/// Take a workspace repo out of the merge queue. Entries that are already being rebased,
+/// verified or merged can't be cancelled.
+pub async fn cancel_queued_merge(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<MergeQueueRequest>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let Some(entry) =
+        MergeQueueEntry::find_active_for_workspace_repo(pool, workspace.id, request.repo_id)
+            .await?
+    else {
+        return Err(ApiError::BadRequest(
+            "This attempt is not in the merge queue".to_string(),
+        ));
+    };
+
+    if !MergeQueueEntry::cancel(pool, entry.id).await? {
+        return Err(ApiError::Conflict(
+            "The merge queue is already processing this attempt".to_string(),
+        ));
+    }
+
+    Ok(ResponseJson(ApiResponse::success(())))
+}
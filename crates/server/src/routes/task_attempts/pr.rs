@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use axum::{
     Extension, Json,
@@ -23,8 +23,9 @@ use git2::BranchType;
 use serde::{Deserialize, Serialize};
 use services::services::{
     container::ContainerService,
+    forge::{self, Forge, ForgeError},
     git::{GitCliError, GitServiceError},
-    github::{CreatePrRequest, GitHubService, GitHubServiceError, UnifiedPrComment},
+    github::{CreatePrRequest, GitHubServiceError, UnifiedPrComment},
 };
 use ts_rs::TS;
 use utils::response::ApiResponse;
@@ -99,6 +100,19 @@ Analyze the changes in this branch and write:
 
 Use `gh pr edit` to update the PR."#;
 
+/// The GitHub or GitLab forge hosting the repo's default remote
+async fn repo_forge(
+    deployment: &DeploymentImpl,
+    repo_path: &Path,
+) -> Result<Box<dyn Forge>, ApiError> {
+    let gitlab_config = deployment.config().read().await.gitlab.clone();
+    Ok(forge::forge_for_repo(
+        deployment.git(),
+        repo_path,
+        &gitlab_config,
+    )?)
+}
+
 async fn trigger_pr_description_follow_up(
     deployment: &DeploymentImpl,
     workspace: &Workspace,
@@ -238,12 +252,15 @@ pub async fn create_github_pr(
         Ok(true) => {}
     }
 
-    // Push the branch to GitHub first
+    // Resolve the forge before pushing so unsupported remotes fail early
+    let forge = repo_forge(&deployment, &repo_path).await?;
+
+    // Push the branch to the remote first
     if let Err(e) = deployment
         .git()
         .push_to_github(&worktree_path, &workspace.branch, false)
     {
-        tracing::error!("Failed to push branch to GitHub: {}", e);
+        tracing::error!("Failed to push branch to remote: {}", e);
         match e {
             GitServiceError::GitCLI(GitCliError::AuthFailed(_)) => {
                 return Ok(ResponseJson(ApiResponse::error_with_data(
@@ -278,7 +295,7 @@ pub async fn create_github_pr(
     } else {
         target_branch
     };
-    // Create the PR using GitHub service
+    // Create the PR (a merge request on GitLab)
     let pr_request = CreatePrRequest {
         title: request.title.clone(),
         body: request.body.clone(),
@@ -286,12 +303,7 @@ pub async fn create_github_pr(
         base_branch: norm_target_branch_name.clone(),
         draft: request.draft,
     };
-    // Use GitService to get the remote URL, then create GitHubRepoInfo
-    let repo_info = deployment.git().get_github_repo_info(&repo_path)?;
-
-    // Use GitHubService to create the PR
-    let github_service = GitHubService::new()?;
-    match github_service.create_pr(&repo_info, &pr_request).await {
+    match forge.create_pr(&pr_request).await {
         Ok(pr_info) => {
             // Update the workspace with PR information
             if let Err(e) = Merge::create_pr(
@@ -316,6 +328,7 @@ pub async fn create_github_pr(
                     "github_pr_created",
                     serde_json::json!({
                         "workspace_id": workspace.id.to_string(),
+                        "forge": forge.kind().as_str(),
                     }),
                 )
                 .await;
@@ -340,19 +353,15 @@ pub async fn create_github_pr(
             Ok(ResponseJson(ApiResponse::success(pr_info.url)))
         }
         Err(e) => {
-            tracing::error!(
-                "Failed to create GitHub PR for attempt {}: {}",
-                workspace.id,
-                e
-            );
+            tracing::error!("Failed to create PR for attempt {}: {}", workspace.id, e);
             match &e {
-                GitHubServiceError::GhCliNotInstalled(_) => Ok(ResponseJson(
+                ForgeError::GitHub(GitHubServiceError::GhCliNotInstalled(_)) => Ok(ResponseJson(
                     ApiResponse::error_with_data(CreatePrError::GithubCliNotInstalled),
                 )),
-                GitHubServiceError::AuthFailed(_) => Ok(ResponseJson(
+                ForgeError::GitHub(GitHubServiceError::AuthFailed(_)) => Ok(ResponseJson(
                     ApiResponse::error_with_data(CreatePrError::GithubCliNotLoggedIn),
                 )),
-                _ => Err(ApiError::GitHubService(e)),
+                _ => Err(ApiError::Forge(e)),
             }
         }
     }
@@ -390,13 +399,10 @@ pub async fn attach_existing_pr(
         })));
     }
 
-    let github_service = GitHubService::new()?;
-    let repo_info = deployment.git().get_github_repo_info(&repo.path)?;
+    let forge = repo_forge(&deployment, &repo.path).await?;
 
     // List all PRs for branch (open, closed, and merged)
-    let prs = github_service
-        .list_all_prs_for_branch(&repo_info, &workspace.branch)
-        .await?;
+    let prs = forge.list_prs_for_branch(&workspace.branch).await?;
 
     // Take the first PR (prefer open, but also accept merged/closed)
     if let Some(pr_info) = prs.into_iter().next() {
@@ -489,14 +495,10 @@ pub async fn get_pr_comments(
         }
     };
 
-    let github_service = GitHubService::new()?;
-    let repo_info = deployment.git().get_github_repo_info(&repo.path)?;
+    let forge = repo_forge(&deployment, &repo.path).await?;
 
-    // Fetch comments from GitHub
-    match github_service
-        .get_pr_comments(&repo_info, pr_info.number)
-        .await
-    {
+    // Fetch comments from the forge
+    match forge.pr_comments(pr_info.number).await {
         Ok(comments) => Ok(ResponseJson(ApiResponse::success(PrCommentsResponse {
             comments,
         }))),
@@ -508,13 +510,13 @@ pub async fn get_pr_comments(
                 e
             );
             match &e {
-                GitHubServiceError::GhCliNotInstalled(_) => Ok(ResponseJson(
+                ForgeError::GitHub(GitHubServiceError::GhCliNotInstalled(_)) => Ok(ResponseJson(
                     ApiResponse::error_with_data(GetPrCommentsError::GithubCliNotInstalled),
                 )),
-                GitHubServiceError::AuthFailed(_) => Ok(ResponseJson(
+                ForgeError::GitHub(GitHubServiceError::AuthFailed(_)) => Ok(ResponseJson(
                     ApiResponse::error_with_data(GetPrCommentsError::GithubCliNotLoggedIn),
                 )),
-                _ => Err(ApiError::GitHubService(e)),
+                _ => Err(ApiError::Forge(e)),
             }
         }
     }
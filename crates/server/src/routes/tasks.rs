@@ -4,7 +4,7 @@ use anyhow;
 use axum::{
     Extension, Json, Router,
     extract::{
-        Query, State,
+        Path, Query, State,
         ws::{WebSocket, WebSocketUpgrade},
     },
     http::StatusCode,
@@ -13,10 +13,14 @@ use axum::{
     routing::{delete, get, post, put},
 };
 use db::models::{
+    execution_budget::{ExecutionBudget, UpdateExecutionBudget},
+    execution_process::ExecutionProcessStatus,
+    execution_process_usage::{ExecutionProcessUsage, UsageSummary},
     image::TaskImage,
     project::{Project, ProjectError},
     repo::Repo,
     task::{CreateTask, Task, TaskWithAttemptStatus, UpdateTask},
+    task_dependency::{CreateTaskDependency, TaskDependencies, TaskDependency},
     workspace::{CreateWorkspace, Workspace},
     workspace_repo::{CreateWorkspaceRepo, WorkspaceRepo},
 };
@@ -25,7 +29,7 @@ use executors::profile::ExecutorProfileId;
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use services::services::{
-    container::ContainerService, share::ShareError, workspace_manager::WorkspaceManager,
+    container::ContainerService, share::ShareError, stack, workspace_manager::WorkspaceManager,
 };
 use sqlx::Error as SqlxError;
 use ts_rs::TS;
@@ -145,6 +149,10 @@ pub struct CreateAndStartTaskRequest {
     pub task: CreateTask,
     pub executor_profile_id: ExecutorProfileId,
     pub repos: Vec<WorkspaceRepoInput>,
+    /// Base the attempt on the parent attempt's branch for the repos both share, and
+    /// restack it whenever that branch changes
+    #[serde(default)]
+    pub stack_on_parent: bool,
 }
 
 pub async fn create_task_and_start(
@@ -205,7 +213,7 @@ pub async fn create_task_and_start(
     )
     .await?;
 
-    let workspace_repos: Vec<CreateWorkspaceRepo> = payload
+    let mut workspace_repos: Vec<CreateWorkspaceRepo> = payload
         .repos
         .iter()
         .map(|r| CreateWorkspaceRepo {
@@ -213,14 +221,26 @@ pub async fn create_task_and_start(
             target_branch: r.target_branch.clone(),
         })
         .collect();
+    if payload.stack_on_parent {
+        stack::stack_on_parent(pool, &task, &mut workspace_repos).await?;
+    }
     WorkspaceRepo::create_many(&deployment.db().pool, workspace.id, &workspace_repos).await?;
+    if payload.stack_on_parent
+        && let Err(err) = stack::record_stack_bases(pool, deployment.git(), &workspace, &task).await
+    {
+        tracing::warn!("Failed to record stack bases for {}: {}", workspace.id, err);
+    }
 
-    let is_attempt_running = deployment
+    let execution_process = deployment
         .container()
         .start_workspace(&workspace, payload.executor_profile_id.clone())
         .await
         .inspect_err(|err| tracing::error!("Failed to start task attempt: {}", err))
-        .is_ok();
+        .ok();
+    let is_queued = execution_process
+        .as_ref()
+        .is_some_and(|process| process.status == ExecutionProcessStatus::Queued);
+    let is_attempt_running = execution_process.is_some() && !is_queued;
     deployment
         .track_if_analytics_allowed(
             "task_attempt_started",
@@ -241,8 +261,11 @@ pub async fn create_task_and_start(
     Ok(ResponseJson(ApiResponse::success(TaskWithAttemptStatus {
         task,
         has_in_progress_attempt: is_attempt_running,
+        is_queued,
         last_attempt_failed: false,
+        verification_failed: false,
         executor: payload.executor_profile_id.executor.to_string(),
+        is_blocked: false,
     })))
 }
 
@@ -460,14 +483,109 @@ pub async fn share_task(
     })))
 }
 
+pub async fn get_task_usage(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<UsageSummary>>, ApiError> {
+    let usage = ExecutionProcessUsage::summary_for_task(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(usage)))
+}
+
+pub async fn get_task_dependencies(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TaskDependencies>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let depends_on = TaskDependency::find_prerequisites(pool, task.id).await?;
+    let dependents = TaskDependency::find_dependents(pool, task.id).await?;
+
+    Ok(ResponseJson(ApiResponse::success(TaskDependencies {
+        depends_on,
+        dependents,
+    })))
+}
+
+pub async fn add_task_dependency(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateTaskDependency>,
+) -> Result<ResponseJson<ApiResponse<TaskDependency>>, ApiError> {
+    let dependency =
+        TaskDependency::create(&deployment.db().pool, task.id, payload.depends_on_task_id).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_dependency_added",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "depends_on_task_id": payload.depends_on_task_id.to_string(),
+                "project_id": task.project_id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(dependency)))
+}
+
+pub async fn remove_task_dependency(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_task_id, depends_on_task_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected =
+        TaskDependency::delete(&deployment.db().pool, task.id, depends_on_task_id).await?;
+    if rows_affected == 0 {
+        return Err(ApiError::NotFound("Dependency not found".to_string()));
+    }
+
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn get_task_budget(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ExecutionBudget>>>, ApiError> {
+    let budget = ExecutionBudget::find_by_task_id(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(budget)))
+}
+
+pub async fn update_task_budget(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateExecutionBudget>,
+) -> Result<ResponseJson<ApiResponse<ExecutionBudget>>, ApiError> {
+    let budget = ExecutionBudget::upsert_for_task(&deployment.db().pool, task.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(budget)))
+}
+
+pub async fn delete_task_budget(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    ExecutionBudget::delete_for_task(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_actions_router = Router::new()
         .route("/", put(update_task))
         .route("/", delete(delete_task))
-        .route("/share", post(share_task));
+        .route("/share", post(share_task))
+        .route("/dependencies", post(add_task_dependency))
+        .route(
+            "/dependencies/{depends_on_task_id}",
+            delete(remove_task_dependency),
+        )
+        .route(
+            "/budget",
+            put(update_task_budget).delete(delete_task_budget),
+        );
 
     let task_id_router = Router::new()
         .route("/", get(get_task))
+        .route("/dependencies", get(get_task_dependencies))
+        .route("/usage", get(get_task_usage))
+        .route("/budget", get(get_task_budget))
         .merge(task_actions_router)
         .layer(from_fn_with_state(deployment.clone(), load_task_middleware));
 
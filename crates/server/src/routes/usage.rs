@@ -0,0 +1,44 @@
+use axum::{
+    Router,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+    routing::get,
+};
+use db::models::execution_process_usage::{DailyUsage, ExecutionProcessUsage};
+use deployment::Deployment;
+use serde::Deserialize;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+const DEFAULT_USAGE_DAYS: i64 = 30;
+
+#[derive(Debug, Deserialize, TS)]
+pub struct DailyUsageQuery {
+    #[serde(default)]
+    pub project_id: Option<Uuid>,
+    /// How many days back to report, defaults to 30
+    #[serde(default)]
+    pub days: Option<i64>,
+}
+
+pub async fn get_daily_usage(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<DailyUsageQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<DailyUsage>>>, ApiError> {
+    let days = query.days.unwrap_or(DEFAULT_USAGE_DAYS);
+    if days <= 0 {
+        return Err(ApiError::BadRequest(
+            "days must be a positive number".to_string(),
+        ));
+    }
+
+    let usage = ExecutionProcessUsage::daily(&deployment.db().pool, query.project_id, days).await?;
+    Ok(ResponseJson(ApiResponse::success(usage)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/usage/daily", get(get_daily_usage))
+}
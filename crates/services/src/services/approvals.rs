@@ -1,9 +1,13 @@
 pub mod executor_approvals;
+pub mod links;
+pub mod policy;
 
 use std::{collections::HashMap, sync::Arc, time::Duration as StdDuration};
 
 use dashmap::DashMap;
 use db::models::{
+    approval_log::{ApprovalLogEntry, ApprovalLogStatus, CreateApprovalLogEntry},
+    approval_rule::{ApprovalRule, ApprovalRuleAction},
     execution_process::ExecutionProcess,
     task::{Task, TaskStatus},
 };
@@ -11,7 +15,10 @@ use executors::{
     approvals::ToolCallMetadata,
     logs::{
         NormalizedEntry, NormalizedEntryType, ToolStatus,
-        utils::patch::{ConversationPatch, extract_normalized_entry_from_patch},
+        utils::{
+            EntryIndexProvider,
+            patch::{ConversationPatch, extract_normalized_entry_from_patch},
+        },
     },
 };
 use futures::future::{BoxFuture, FutureExt, Shared};
@@ -25,6 +32,8 @@ use utils::{
 };
 use uuid::Uuid;
 
+use self::links::{ApprovalLinkDecision, ApprovalLinks};
+
 #[derive(Debug)]
 struct PendingApproval {
     entry_index: usize,
@@ -44,9 +53,11 @@ pub struct ToolContext {
 
 #[derive(Clone)]
 pub struct Approvals {
+    pool: SqlitePool,
     pending: Arc<DashMap<String, PendingApproval>>,
     completed: Arc<DashMap<String, ApprovalStatus>>,
     msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>,
+    links: ApprovalLinks,
 }
 
 #[derive(Debug, Error)]
@@ -59,6 +70,8 @@ pub enum ApprovalError {
     NoExecutorSession(String),
     #[error("corresponding tool use entry not found for approval request")]
     NoToolUseEntry,
+    #[error("invalid or expired approval link")]
+    InvalidLink,
     #[error(transparent)]
     Custom(#[from] anyhow::Error),
     #[error(transparent)]
@@ -66,14 +79,72 @@ pub enum ApprovalError {
 }
 
 impl Approvals {
-    pub fn new(msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>) -> Self {
+    pub fn new(pool: SqlitePool, msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>) -> Self {
         Self {
+            pool,
             pending: Arc::new(DashMap::new()),
             completed: Arc::new(DashMap::new()),
             msg_stores,
+            links: ApprovalLinks::new(),
+        }
+    }
+
+    pub fn links(&self) -> &ApprovalLinks {
+        &self.links
+    }
+
+    /// Tool name and conversation text of a pending approval, with the decision a link
+    /// `token` gives it. The token is checked first, so a link shows nothing about other
+    /// approvals.
+    pub fn pending_for_link(
+        &self,
+        id: &str,
+        token: &str,
+    ) -> Result<(String, String, ApprovalLinkDecision), ApprovalError> {
+        let decision = self
+            .links
+            .verify(id, token)
+            .map_err(|_| ApprovalError::InvalidLink)?;
+        match self.pending.get(id) {
+            Some(p) => Ok((p.tool_name.clone(), p.entry.content.clone(), decision)),
+            None if self.completed.contains_key(id) => Err(ApprovalError::AlreadyCompleted),
+            None => Err(ApprovalError::NotFound),
         }
     }
 
+    /// Answer a pending approval through a signed link instead of the board, with the
+    /// decision the link was signed for
+    pub async fn respond_with_link(
+        &self,
+        id: &str,
+        token: &str,
+    ) -> Result<(ApprovalStatus, ToolContext), ApprovalError> {
+        let decision = self
+            .links
+            .verify(id, token)
+            .map_err(|_| ApprovalError::InvalidLink)?;
+        let execution_process_id = match self.pending.get(id) {
+            Some(p) => p.execution_process_id,
+            None if self.completed.contains_key(id) => return Err(ApprovalError::AlreadyCompleted),
+            None => return Err(ApprovalError::NotFound),
+        };
+        let status = match decision {
+            ApprovalLinkDecision::Approve => ApprovalStatus::Approved,
+            ApprovalLinkDecision::Deny => ApprovalStatus::Denied {
+                reason: Some("Denied from an approval link".to_string()),
+            },
+        };
+        self.respond(
+            id,
+            ApprovalResponse {
+                execution_process_id,
+                status,
+            },
+            "approval link",
+        )
+        .await
+    }
+
     pub async fn create_with_waiter(
         &self,
         request: ApprovalRequest,
@@ -84,6 +155,7 @@ impl Approvals {
             .boxed()
             .shared();
         let req_id = request.id.clone();
+        log_request(&self.pool, &request).await;
 
         if let Some(store) = self.msg_store_by_id(&request.execution_process_id).await {
             // Find the matching tool use entry by name and input
@@ -133,16 +205,18 @@ impl Approvals {
         Ok((request, waiter))
     }
 
-    #[tracing::instrument(skip(self, id, req))]
+    #[tracing::instrument(skip(self, id, req, responder))]
     pub async fn respond(
         &self,
-        pool: &SqlitePool,
         id: &str,
         req: ApprovalResponse,
+        responder: &str,
     ) -> Result<(ApprovalStatus, ToolContext), ApprovalError> {
+        let pool = &self.pool;
         if let Some((_, p)) = self.pending.remove(id) {
             self.completed.insert(id.to_string(), req.status.clone());
             let _ = p.response_tx.send(req.status.clone());
+            log_decision(pool, id, &req.status, Some(responder)).await;
 
             if let Some(store) = self.msg_store_by_id(&p.execution_process_id).await {
                 let status = ToolStatus::from_approval_status(&req.status).ok_or(
@@ -196,6 +270,7 @@ impl Approvals {
         timeout_at: chrono::DateTime<chrono::Utc>,
         waiter: ApprovalWaiter,
     ) {
+        let pool = self.pool.clone();
         let pending = self.pending.clone();
         let completed = self.completed.clone();
         let msg_stores = self.msg_stores.clone();
@@ -221,6 +296,7 @@ impl Approvals {
                 if pending_approval.response_tx.send(status.clone()).is_err() {
                     tracing::debug!("approval '{}' timeout notification receiver dropped", id);
                 }
+                log_decision(&pool, &id, &status, None).await;
 
                 let store = {
                     let map = msg_stores.read().await;
@@ -252,6 +328,70 @@ impl Approvals {
         });
     }
 
+    /// Apply the decision `rule` made for `request` without asking anyone, logging it in
+    /// the conversation and the approval log. `None` when the rule asks for an approval.
+    pub async fn record_rule_decision(
+        &self,
+        request: &ApprovalRequest,
+        rule: &ApprovalRule,
+    ) -> Option<ApprovalStatus> {
+        let status = match rule.action {
+            ApprovalRuleAction::Allow => Some(ApprovalStatus::Approved),
+            ApprovalRuleAction::Deny => Some(ApprovalStatus::Denied {
+                reason: Some(policy::deny_reason(rule)),
+            }),
+            ApprovalRuleAction::Ask => None,
+        };
+
+        if let Some(status) = &status {
+            log_request(&self.pool, request).await;
+            let responder = format!("approval rule \"{}\"", rule.name);
+            log_decision(&self.pool, &request.id, status, Some(&responder)).await;
+        }
+
+        let Some(store) = self.msg_store_by_id(&request.execution_process_id).await else {
+            tracing::warn!(
+                "No msg_store found for execution_process_id: {}",
+                request.execution_process_id
+            );
+            return status;
+        };
+
+        // No approval request will update a denied tool's entry, so mark it here
+        if let Some(status @ ApprovalStatus::Denied { .. }) = &status
+            && let Some((idx, entry)) = find_matching_tool_use(store.clone(), &request.tool_call_id)
+            && let Some(denied_entry) = ToolStatus::from_approval_status(status)
+                .and_then(|tool_status| entry.with_tool_status(tool_status))
+        {
+            store.push_patch(ConversationPatch::replace(idx, denied_entry));
+        }
+
+        let notice = policy::rule_decision_entry(rule, &request.tool_name);
+        let index = EntryIndexProvider::start_from(&store).next();
+        store.push_patch(ConversationPatch::add_normalized_entry(index, notice));
+        status
+    }
+
+    /// Drop the approvals an exited execution process was still waiting on and log them as
+    /// cancelled, since nothing is left to act on a decision
+    pub async fn cancel_for_execution_process(&self, execution_process_id: Uuid) {
+        self.pending
+            .retain(|_, p| p.execution_process_id != execution_process_id);
+        if let Err(e) = ApprovalLogEntry::cancel_pending_for_execution_process(
+            &self.pool,
+            execution_process_id,
+            chrono::Utc::now(),
+        )
+        .await
+        {
+            tracing::warn!(
+                "Failed to cancel pending approvals of execution process {}: {}",
+                execution_process_id,
+                e
+            );
+        }
+    }
+
     async fn msg_store_by_id(&self, execution_process_id: &Uuid) -> Option<Arc<MsgStore>> {
         let map = self.msg_stores.read().await;
         map.get(execution_process_id).cloned()
@@ -270,6 +410,64 @@ pub(crate) async fn ensure_task_in_review(pool: &SqlitePool, execution_process_i
     }
 }
 
+/// Add a pending request to the approval log. Failures are only logged, so the audit log
+/// never blocks an approval.
+async fn log_request(pool: &SqlitePool, request: &ApprovalRequest) {
+    let Ok(id) = Uuid::parse_str(&request.id) else {
+        tracing::warn!("Not logging approval with non-uuid id '{}'", request.id);
+        return;
+    };
+    let ctx = match ExecutionProcess::load_context(pool, request.execution_process_id).await {
+        Ok(ctx) => ctx,
+        Err(e) => {
+            tracing::warn!("Failed to log approval request {}: {}", request.id, e);
+            return;
+        }
+    };
+    let entry = CreateApprovalLogEntry {
+        id,
+        project_id: ctx.project.id,
+        task_id: ctx.task.id,
+        execution_process_id: request.execution_process_id,
+        tool_name: request.tool_name.clone(),
+        tool_call_id: request.tool_call_id.clone(),
+        tool_input: request.tool_input.clone(),
+        requested_at: request.created_at,
+    };
+    if let Err(e) = ApprovalLogEntry::create(pool, &entry).await {
+        tracing::warn!("Failed to log approval request {}: {}", request.id, e);
+    }
+}
+
+async fn log_decision(
+    pool: &SqlitePool,
+    id: &str,
+    status: &ApprovalStatus,
+    responder: Option<&str>,
+) {
+    let Ok(uuid) = Uuid::parse_str(id) else {
+        return;
+    };
+    let (log_status, deny_reason) = match status {
+        ApprovalStatus::Pending => return,
+        ApprovalStatus::Approved => (ApprovalLogStatus::Approved, None),
+        ApprovalStatus::Denied { reason } => (ApprovalLogStatus::Denied, reason.as_deref()),
+        ApprovalStatus::TimedOut => (ApprovalLogStatus::TimedOut, None),
+    };
+    if let Err(e) = ApprovalLogEntry::record_decision(
+        pool,
+        uuid,
+        log_status,
+        deny_reason,
+        responder,
+        chrono::Utc::now(),
+    )
+    .await
+    {
+        tracing::warn!("Failed to log approval decision {}: {}", id, e);
+    }
+}
+
 /// Find a matching tool use entry that hasn't been assigned to an approval yet
 /// Matches by tool call id from tool metadata
 fn find_matching_tool_use(
@@ -312,10 +510,28 @@ fn find_matching_tool_use(
 mod tests {
     use std::sync::Arc;
 
-    use executors::logs::{ActionType, NormalizedEntry, NormalizedEntryType, ToolStatus};
+    use axum::{extract::Query, http::Uri};
+    use db::{
+        DBService,
+        models::{
+            approval_log::ApprovalLogQuery,
+            execution_process::{ExecutionProcessRunReason, ExecutionProcessStatus},
+        },
+    };
+    use executors::{
+        actions::script::ScriptContext,
+        logs::{ActionType, NormalizedEntry, NormalizedEntryType, ToolStatus},
+    };
     use utils::msg_store::MsgStore;
 
     use super::*;
+    use crate::services::{
+        container::ContainerService,
+        test_support::{
+            TestContainer, TestProject, create_process, create_project, create_session,
+            create_workspace, script_action, test_db,
+        },
+    };
 
     fn create_tool_use_entry(
         tool_name: &str,
@@ -398,4 +614,230 @@ mod tests {
             "Should not match different tool ids"
         );
     }
+
+    /// Approvals whose running execution process shows a `Read` tool call for each of
+    /// `tool_call_ids`
+    async fn approvals_with_tool_calls(
+        db: &DBService,
+        dir: &std::path::Path,
+        tool_call_ids: &[&str],
+    ) -> (Approvals, TestProject, Uuid) {
+        let fixture = create_project(db, dir).await;
+        let workspace = create_workspace(db, &fixture.task, &fixture.repo, dir).await;
+        let session = create_session(db, &workspace).await;
+        let process = create_process(
+            db,
+            &session,
+            script_action(ScriptContext::SetupScript),
+            ExecutionProcessRunReason::CodingAgent,
+            ExecutionProcessStatus::Running,
+        )
+        .await;
+
+        let store = Arc::new(MsgStore::new());
+        for (index, id) in tool_call_ids.iter().enumerate() {
+            let entry = create_tool_use_entry("Read", "foo.rs", id, ToolStatus::Created);
+            store.push_patch(ConversationPatch::add_normalized_entry(index, entry));
+        }
+        let msg_stores = Arc::new(RwLock::new(HashMap::from([(process.id, store)])));
+        (
+            Approvals::new(db.pool.clone(), msg_stores),
+            fixture,
+            process.id,
+        )
+    }
+
+    fn approval_request(
+        execution_process_id: Uuid,
+        tool_name: &str,
+        tool_call_id: &str,
+    ) -> ApprovalRequest {
+        let now = chrono::Utc::now();
+        ApprovalRequest {
+            id: Uuid::new_v4().to_string(),
+            tool_name: tool_name.to_string(),
+            tool_input: serde_json::json!({ "path": "foo.rs" }),
+            tool_call_id: tool_call_id.to_string(),
+            execution_process_id,
+            created_at: now,
+            timeout_at: now + chrono::Duration::hours(1),
+        }
+    }
+
+    async fn log_entries(pool: &SqlitePool, uri: &str) -> Vec<ApprovalLogEntry> {
+        let Query(query) = Query::<ApprovalLogQuery>::try_from_uri(&uri.parse::<Uri>().unwrap())
+            .expect("query string should parse");
+        ApprovalLogEntry::find(pool, &query).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn decisions_are_logged_and_can_be_queried() {
+        let test_db = test_db().await;
+        let db = &test_db.db;
+        let (approvals, fixture, process_id) =
+            approvals_with_tool_calls(db, test_db.dir.path(), &["read-id", "edit-id"]).await;
+
+        let read = approval_request(process_id, "Read", "read-id");
+        approvals.create_with_waiter(read.clone()).await.unwrap();
+        let edit = approval_request(process_id, "Edit", "edit-id");
+        approvals.create_with_waiter(edit.clone()).await.unwrap();
+
+        let entries = log_entries(
+            &db.pool,
+            &format!("/api/approvals?task_id={}", fixture.task.id),
+        )
+        .await;
+        assert_eq!(entries.len(), 2);
+        assert!(
+            entries
+                .iter()
+                .all(|entry| entry.status == ApprovalLogStatus::Pending
+                    && entry.project_id == fixture.project.id
+                    && entry.responded_at.is_none())
+        );
+
+        approvals
+            .respond(
+                &read.id,
+                ApprovalResponse {
+                    execution_process_id: process_id,
+                    status: ApprovalStatus::Denied {
+                        reason: Some("not that file".to_string()),
+                    },
+                },
+                "alice",
+            )
+            .await
+            .unwrap();
+
+        let entries = log_entries(
+            &db.pool,
+            &format!(
+                "/api/approvals?project_id={}&tool_name=Read",
+                fixture.project.id
+            ),
+        )
+        .await;
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.id.to_string(), read.id);
+        assert_eq!(entry.status, ApprovalLogStatus::Denied);
+        assert_eq!(entry.deny_reason.as_deref(), Some("not that file"));
+        assert_eq!(entry.responder.as_deref(), Some("alice"));
+        assert!(entry.responded_at.is_some());
+        assert!(entry.latency_ms.is_some_and(|latency| latency >= 0));
+
+        // Most recent first, and no more than the limit
+        let entries = log_entries(&db.pool, "/api/approvals?limit=1").await;
+        assert_eq!(
+            entries.iter().map(|e| e.id.to_string()).collect::<Vec<_>>(),
+            vec![edit.id]
+        );
+        let entries = log_entries(
+            &db.pool,
+            &format!("/api/approvals?project_id={}", Uuid::new_v4()),
+        )
+        .await;
+        assert!(entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn pending_approvals_are_cancelled_when_their_process_exits() {
+        let test_db = test_db().await;
+        let db = &test_db.db;
+        let (approvals, _, process_id) =
+            approvals_with_tool_calls(db, test_db.dir.path(), &["read-id"]).await;
+        let request = approval_request(process_id, "Read", "read-id");
+        let (_, waiter) = approvals.create_with_waiter(request.clone()).await.unwrap();
+
+        approvals.cancel_for_execution_process(process_id).await;
+
+        let status = tokio::time::timeout(StdDuration::from_secs(5), waiter)
+            .await
+            .expect("waiter should resolve once its approval is dropped");
+        assert!(matches!(status, ApprovalStatus::TimedOut));
+        let entries = log_entries(&db.pool, "/api/approvals").await;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].status, ApprovalLogStatus::Cancelled);
+        assert!(entries[0].responder.is_none());
+        assert!(entries[0].responded_at.is_some());
+        assert!(
+            approvals
+                .respond(
+                    &request.id,
+                    ApprovalResponse {
+                        execution_process_id: process_id,
+                        status: ApprovalStatus::Approved,
+                    },
+                    "alice",
+                )
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn approvals_left_pending_are_cancelled_at_startup() {
+        let test_db = test_db().await;
+        let db = &test_db.db;
+        let dir = test_db.dir.path();
+        let (approvals, _, process_id) =
+            approvals_with_tool_calls(db, dir, &["read-id", "edit-id"]).await;
+        let decided = approval_request(process_id, "Read", "read-id");
+        approvals.create_with_waiter(decided.clone()).await.unwrap();
+        approvals
+            .respond(
+                &decided.id,
+                ApprovalResponse {
+                    execution_process_id: process_id,
+                    status: ApprovalStatus::Approved,
+                },
+                "alice",
+            )
+            .await
+            .unwrap();
+        let pending = approval_request(process_id, "Edit", "edit-id");
+        approvals.create_with_waiter(pending.clone()).await.unwrap();
+
+        TestContainer::new(db, dir)
+            .cleanup_orphan_executions()
+            .await
+            .unwrap();
+
+        let entries = log_entries(&db.pool, "/api/approvals").await;
+        let status_of = |id: &str| {
+            entries
+                .iter()
+                .find(|entry| entry.id.to_string() == id)
+                .map(|entry| entry.status)
+        };
+        assert_eq!(status_of(&decided.id), Some(ApprovalLogStatus::Approved));
+        assert_eq!(status_of(&pending.id), Some(ApprovalLogStatus::Cancelled));
+    }
+
+    #[tokio::test]
+    async fn links_answer_with_the_decision_they_were_signed_for() {
+        let test_db = test_db().await;
+        let db = &test_db.db;
+        let (approvals, _, process_id) =
+            approvals_with_tool_calls(db, test_db.dir.path(), &["read-id"]).await;
+        let request = approval_request(process_id, "Read", "read-id");
+        approvals.create_with_waiter(request.clone()).await.unwrap();
+        let deny = approvals
+            .links()
+            .token(&request.id, ApprovalLinkDecision::Deny, request.timeout_at)
+            .unwrap();
+
+        let (_, _, decision) = approvals.pending_for_link(&request.id, &deny).unwrap();
+        assert_eq!(decision, ApprovalLinkDecision::Deny);
+        let (status, _) = approvals
+            .respond_with_link(&request.id, &deny)
+            .await
+            .unwrap();
+        assert!(matches!(status, ApprovalStatus::Denied { .. }));
+        assert!(matches!(
+            approvals.respond_with_link(&request.id, &deny).await,
+            Err(ApprovalError::AlreadyCompleted)
+        ));
+    }
 }
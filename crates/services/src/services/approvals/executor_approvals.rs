@@ -1,13 +1,19 @@
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
 use async_trait::async_trait;
-use db::{self, DBService};
+use db::{
+    self, DBService,
+    models::{approval_rule::ApprovalRule, execution_process::ExecutionProcess},
+};
 use executors::approvals::{ExecutorApprovalError, ExecutorApprovalService};
 use serde_json::Value;
 use utils::approvals::{ApprovalRequest, ApprovalStatus, CreateApprovalRequest};
 use uuid::Uuid;
 
-use crate::services::{approvals::Approvals, notification::NotificationService};
+use crate::services::{
+    approvals::{Approvals, policy},
+    notification::NotificationService,
+};
 
 pub struct ExecutorApprovalBridge {
     approvals: Approvals,
@@ -30,6 +36,46 @@ impl ExecutorApprovalBridge {
             execution_process_id,
         })
     }
+
+    /// Decide a request by the project's approval rules, logging the decision. `None` when
+    /// no rule decides and someone has to be asked.
+    async fn decide_by_rule(&self, request: &ApprovalRequest) -> Option<ApprovalStatus> {
+        let pool = &self.db.pool;
+        let ctx = match ExecutionProcess::load_context(pool, self.execution_process_id).await {
+            Ok(ctx) => ctx,
+            Err(e) => {
+                tracing::warn!("Failed to load context for approval rules: {}", e);
+                return None;
+            }
+        };
+        let rules = match ApprovalRule::find_by_project_id(pool, ctx.project.id).await {
+            Ok(rules) => rules,
+            Err(e) => {
+                tracing::warn!("Failed to load approval rules: {}", e);
+                return None;
+            }
+        };
+        if rules.is_empty() {
+            return None;
+        }
+
+        let action = ctx.execution_process.executor_action().ok();
+        let rule = policy::matching_rule(
+            &rules,
+            action.and_then(|action| action.executor_profile_id()),
+            &request.tool_name,
+            &request.tool_input,
+            ctx.workspace.container_ref.as_deref().map(Path::new),
+        )?;
+
+        tracing::info!(
+            "Approval rule '{}' decided {:?} for tool '{}'",
+            rule.name,
+            rule.action,
+            request.tool_name
+        );
+        self.approvals.record_rule_decision(request, rule).await
+    }
 }
 
 #[async_trait]
@@ -40,8 +86,6 @@ impl ExecutorApprovalService for ExecutorApprovalBridge {
         tool_input: Value,
         tool_call_id: &str,
     ) -> Result<ApprovalStatus, ExecutorApprovalError> {
-        super::ensure_task_in_review(&self.db.pool, self.execution_process_id).await;
-
         let request = ApprovalRequest::from_create(
             CreateApprovalRequest {
                 tool_name: tool_name.to_string(),
@@ -51,18 +95,20 @@ impl ExecutorApprovalService for ExecutorApprovalBridge {
             self.execution_process_id,
         );
 
-        let (_, waiter) = self
+        if let Some(status) = self.decide_by_rule(&request).await {
+            return Ok(status);
+        }
+
+        super::ensure_task_in_review(&self.db.pool, self.execution_process_id).await;
+
+        let (request, waiter) = self
             .approvals
             .create_with_waiter(request)
             .await
             .map_err(ExecutorApprovalError::request_failed)?;
 
-        // Play notification sound when approval is needed
         self.notification_service
-            .notify(
-                "Approval Needed",
-                &format!("Tool '{}' requires approval", tool_name),
-            )
+            .notify_approval(&request, self.approvals.links())
             .await;
 
         let status = waiter.clone().await;
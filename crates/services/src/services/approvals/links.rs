@@ -0,0 +1,161 @@
+//! Signed links that answer an approval request without opening the board, e.g. from a
+//! notification or a webhook.
+
+use std::sync::{Arc, OnceLock};
+
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+/// The answer a link gives to an approval request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalLinkDecision {
+    Approve,
+    Deny,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ApprovalLinkClaims {
+    /// Approval id
+    sub: String,
+    /// Signed along with the approval, so a link can only give the answer it was made for
+    decision: ApprovalLinkDecision,
+    exp: i64,
+}
+
+/// Signs and checks approval link tokens. Each approval gets one token per decision, and a
+/// token answers its approval until it expires with it; once the approval is answered it
+/// can't be answered again, which makes every link single-use.
+///
+/// The key is generated at startup rather than persisted. Pending approvals only live in
+/// memory and are cancelled when the server starts again, so a link from before a restart
+/// has nothing left to answer; a fresh key makes it fail as an invalid link.
+#[derive(Clone)]
+pub struct ApprovalLinks {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    local_base_url: Arc<OnceLock<String>>,
+}
+
+impl Default for ApprovalLinks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApprovalLinks {
+    pub fn new() -> Self {
+        let secret: [u8; 32] = rand::random();
+        Self {
+            encoding_key: EncodingKey::from_secret(&secret),
+            decoding_key: DecodingKey::from_secret(&secret),
+            local_base_url: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// Record the address the server listens on, used when no public base URL is configured
+    pub fn set_local_base_url(&self, url: String) {
+        let _ = self.local_base_url.set(url);
+    }
+
+    pub fn local_base_url(&self) -> Option<&str> {
+        self.local_base_url.get().map(String::as_str)
+    }
+
+    /// Token that gives `decision` to the approval `approval_id` until `expires_at`
+    pub fn token(
+        &self,
+        approval_id: &str,
+        decision: ApprovalLinkDecision,
+        expires_at: DateTime<Utc>,
+    ) -> Result<String, jsonwebtoken::errors::Error> {
+        let claims = ApprovalLinkClaims {
+            sub: approval_id.to_string(),
+            decision,
+            exp: expires_at.timestamp(),
+        };
+        jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key)
+    }
+
+    /// Check that `token` was signed here for the approval `approval_id` and hasn't
+    /// expired, returning the decision it gives
+    pub fn verify(
+        &self,
+        approval_id: &str,
+        token: &str,
+    ) -> Result<ApprovalLinkDecision, jsonwebtoken::errors::Error> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.leeway = 0;
+        validation.sub = Some(approval_id.to_string());
+        let data =
+            jsonwebtoken::decode::<ApprovalLinkClaims>(token, &self.decoding_key, &validation)?;
+        Ok(data.claims.decision)
+    }
+}
+
+/// Address of the respond endpoint of `approval_id` under `base_url`, carrying `token`
+pub fn respond_url(base_url: &str, approval_id: &str, token: &str) -> String {
+    format!(
+        "{}/api/approvals/{approval_id}/respond?token={token}",
+        base_url.trim_end_matches('/')
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    #[test]
+    fn tokens_only_answer_their_own_approval() {
+        let links = ApprovalLinks::new();
+        let token = links
+            .token(
+                "approval-1",
+                ApprovalLinkDecision::Approve,
+                Utc::now() + Duration::minutes(5),
+            )
+            .unwrap();
+
+        assert!(links.verify("approval-1", &token).is_ok());
+        assert!(links.verify("approval-2", &token).is_err());
+        assert!(ApprovalLinks::new().verify("approval-1", &token).is_err());
+    }
+
+    #[test]
+    fn tokens_give_the_decision_they_were_signed_with() {
+        let links = ApprovalLinks::new();
+        let expires_at = Utc::now() + Duration::minutes(5);
+        let approve = links
+            .token("approval-1", ApprovalLinkDecision::Approve, expires_at)
+            .unwrap();
+        let deny = links
+            .token("approval-1", ApprovalLinkDecision::Deny, expires_at)
+            .unwrap();
+
+        assert_eq!(
+            links.verify("approval-1", &approve).unwrap(),
+            ApprovalLinkDecision::Approve
+        );
+        assert_eq!(
+            links.verify("approval-1", &deny).unwrap(),
+            ApprovalLinkDecision::Deny
+        );
+    }
+
+    #[test]
+    fn expired_tokens_are_rejected() {
+        let links = ApprovalLinks::new();
+        let token = links
+            .token(
+                "approval-1",
+                ApprovalLinkDecision::Deny,
+                Utc::now() - Duration::seconds(1),
+            )
+            .unwrap();
+
+        assert!(links.verify("approval-1", &token).is_err());
+    }
+}
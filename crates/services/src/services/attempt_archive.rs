@@ -0,0 +1,304 @@
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+use db::models::{repo::Repo, workspace_repo::RepoWithTargetBranch};
+use serde::{Deserialize, Serialize};
+use tempfile::TempDir;
+use thiserror::Error;
+use ts_rs::TS;
+
+use crate::services::git::{GitService, GitServiceError};
+
+/// Name of the archive entry describing the exported repos
+const MANIFEST_NAME: &str = "manifest.json";
+/// Bumped whenever the manifest changes in a way older versions can't import
+const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum AttemptArchiveError {
+    #[error(transparent)]
+    GitService(#[from] GitServiceError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid attempt archive: {0}")]
+    Invalid(String),
+    #[error("The attempt has no commits to export")]
+    NoCommits,
+}
+
+/// How the commits of each repo are stored in an exported attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum AttemptArchiveFormat {
+    /// A `git format-patch` series, applied with `git am`
+    Patch,
+    /// A git bundle, fetched as is so commit ids are kept
+    Bundle,
+}
+
+impl AttemptArchiveFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Patch => "patch",
+            Self::Bundle => "bundle",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttemptArchiveManifest {
+    pub version: u32,
+    pub format: AttemptArchiveFormat,
+    /// Branch of the exported workspace
+    pub branch: String,
+    pub repos: Vec<ArchivedRepo>,
+}
+
+/// The commits of one repo of an exported workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedRepo {
+    /// Repo name, which is how the importing project's repo is found
+    pub name: String,
+    pub target_branch: String,
+    /// Merge base of the workspace branch and the target branch when exported
+    pub base_commit: String,
+    pub head_commit: String,
+    pub commit_count: usize,
+    /// Archive entry holding the patch series or bundle
+    pub file: String,
+}
+
+/// Package the commits of `branch` since its base commit in each of `repos` as a tar
+/// archive. Repos without commits are left out; uncommitted changes are not exported.
+pub fn export_workspace(
+    git: &GitService,
+    branch: &str,
+    repos: &[RepoWithTargetBranch],
+    format: AttemptArchiveFormat,
+) -> Result<Vec<u8>, AttemptArchiveError> {
+    let staging = TempDir::new()?;
+    let mut archived = Vec::new();
+
+    for repo in repos {
+        let base_commit = git.get_base_commit(&repo.repo.path, branch, &repo.target_branch)?;
+        let head_commit = git.get_branch_oid(&repo.repo.path, branch)?;
+        let (commit_count, _) = git.ahead_behind_commits_by_oid(
+            &repo.repo.path,
+            &head_commit,
+            &base_commit.to_string(),
+        )?;
+        if commit_count == 0 {
+            continue;
+        }
+
+        let file = format!("{}.{}", repo.repo.name, format.extension());
+        let output = staging.path().join(&file);
+        match format {
+            AttemptArchiveFormat::Patch => {
+                git.export_patch_series(&repo.repo.path, &base_commit, branch, &output)?
+            }
+            AttemptArchiveFormat::Bundle => {
+                git.export_bundle(&repo.repo.path, &base_commit, branch, &output)?
+            }
+        }
+
+        archived.push(ArchivedRepo {
+            name: repo.repo.name.clone(),
+            target_branch: repo.target_branch.clone(),
+            base_commit: base_commit.to_string(),
+            head_commit,
+            commit_count,
+            file,
+        });
+    }
+
+    if archived.is_empty() {
+        return Err(AttemptArchiveError::NoCommits);
+    }
+
+    let manifest = AttemptArchiveManifest {
+        version: ARCHIVE_VERSION,
+        format,
+        branch: branch.to_string(),
+        repos: archived,
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest_json.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, MANIFEST_NAME, manifest_json.as_slice())?;
+    for repo in &manifest.repos {
+        builder.append_path_with_name(staging.path().join(&repo.file), &repo.file)?;
+    }
+    Ok(builder.into_inner()?)
+}
+
+/// An exported attempt unpacked to a temporary directory, removed when dropped
+pub struct AttemptArchive {
+    pub manifest: AttemptArchiveManifest,
+    dir: TempDir,
+}
+
+impl AttemptArchive {
+    /// Unpack and validate an archive made by [`export_workspace`]
+    pub fn read(bytes: &[u8]) -> Result<Self, AttemptArchiveError> {
+        let dir = TempDir::new()?;
+        // Entries that would land outside `dir` are skipped when unpacking
+        tar::Archive::new(bytes).unpack(dir.path())?;
+
+        let manifest_path = dir.path().join(MANIFEST_NAME);
+        if !manifest_path.is_file() {
+            return Err(AttemptArchiveError::Invalid(format!(
+                "{MANIFEST_NAME} is missing"
+            )));
+        }
+        let manifest: AttemptArchiveManifest =
+            serde_json::from_slice(&std::fs::read(manifest_path)?)?;
+        if manifest.version > ARCHIVE_VERSION {
+            return Err(AttemptArchiveError::Invalid(format!(
+                "archive version {} is newer than this version supports",
+                manifest.version
+            )));
+        }
+        if manifest.repos.is_empty() {
+            return Err(AttemptArchiveError::Invalid(
+                "it holds no repos".to_string(),
+            ));
+        }
+        for repo in &manifest.repos {
+            let is_plain_name = Path::new(&repo.file).file_name() == Some(OsStr::new(&repo.file));
+            let is_file = std::fs::symlink_metadata(dir.path().join(&repo.file))
+                .is_ok_and(|metadata| metadata.is_file());
+            if !is_plain_name || !is_file {
+                return Err(AttemptArchiveError::Invalid(format!(
+                    "the commits of {} are missing",
+                    repo.name
+                )));
+            }
+        }
+
+        Ok(Self { manifest, dir })
+    }
+
+    pub fn repo_file(&self, repo: &ArchivedRepo) -> PathBuf {
+        self.dir.path().join(&repo.file)
+    }
+
+    /// Check that `repo`'s commits can be imported into the local `target`, before any
+    /// workspace is created for them
+    pub fn check_importable(
+        &self,
+        git: &GitService,
+        repo: &ArchivedRepo,
+        target: &Repo,
+    ) -> Result<(), AttemptArchiveError> {
+        if !git.check_branch_exists(&target.path, &repo.target_branch)? {
+            return Err(AttemptArchiveError::Invalid(format!(
+                "target branch {} does not exist in {}",
+                repo.target_branch, target.name
+            )));
+        }
+        // A bundle only holds the commits after its base, so the base must be present
+        if self.manifest.format == AttemptArchiveFormat::Bundle
+            && !git.commit_exists(&target.path, &repo.base_commit)
+        {
+            return Err(AttemptArchiveError::Invalid(format!(
+                "base commit {} is not in {}; fetch {} first",
+                repo.base_commit, target.name, repo.target_branch
+            )));
+        }
+        Ok(())
+    }
+
+    /// Recreate `repo`'s exported commits in a new worktree checked out on its target
+    /// branch. Patches are applied on the base commit when it is present locally and on the
+    /// target branch otherwise.
+    pub fn import_into_worktree(
+        &self,
+        git: &GitService,
+        repo: &ArchivedRepo,
+        repo_path: &Path,
+        worktree_path: &Path,
+    ) -> Result<(), AttemptArchiveError> {
+        let file = self.repo_file(repo);
+        match self.manifest.format {
+            AttemptArchiveFormat::Patch => {
+                if git.commit_exists(repo_path, &repo.base_commit) {
+                    git.reset_worktree_to_commit(worktree_path, &repo.base_commit, false)?;
+                }
+                git.apply_patch_series(worktree_path, &file)?;
+            }
+            AttemptArchiveFormat::Bundle => {
+                let refname = format!("refs/heads/{}", self.manifest.branch);
+                git.reset_worktree_to_bundle(worktree_path, &file, &refname, &repo.head_commit)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive_with(manifest: &[u8], files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, data) in [(MANIFEST_NAME, manifest)].iter().chain(files) {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn manifest_json(file: &str) -> Vec<u8> {
+        serde_json::to_vec(&AttemptArchiveManifest {
+            version: ARCHIVE_VERSION,
+            format: AttemptArchiveFormat::Patch,
+            branch: "vk/1234-feature".to_string(),
+            repos: vec![ArchivedRepo {
+                name: "app".to_string(),
+                target_branch: "main".to_string(),
+                base_commit: "a".repeat(40),
+                head_commit: "b".repeat(40),
+                commit_count: 1,
+                file: file.to_string(),
+            }],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn reads_an_archive_with_its_repo_files() {
+        let bytes = archive_with(&manifest_json("app.patch"), &[("app.patch", b"From x")]);
+
+        let archive = AttemptArchive::read(&bytes).unwrap();
+
+        let repo = &archive.manifest.repos[0];
+        assert_eq!(std::fs::read(archive.repo_file(repo)).unwrap(), b"From x");
+    }
+
+    #[test]
+    fn rejects_repo_files_outside_the_archive() {
+        let bytes = archive_with(&manifest_json("../app.patch"), &[("app.patch", b"From x")]);
+        assert!(matches!(
+            AttemptArchive::read(&bytes),
+            Err(AttemptArchiveError::Invalid(_))
+        ));
+
+        let bytes = archive_with(&manifest_json("app.patch"), &[]);
+        assert!(matches!(
+            AttemptArchive::read(&bytes),
+            Err(AttemptArchiveError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,187 @@
+use std::{fmt, time::Duration};
+
+use db::models::{execution_budget::ExecutionBudget, task::Task};
+use executors::{
+    executors::{BaseAgentCapability, BaseCodingAgent, CodingAgent},
+    logs::{NormalizedEntry, NormalizedEntryType},
+};
+use sqlx::SqlitePool;
+
+use crate::services::config::BudgetConfig;
+
+/// Which limit an execution ran past
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetViolation {
+    Tokens,
+    Runtime,
+    ToolCalls,
+}
+
+impl fmt::Display for BudgetViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetViolation::Tokens => write!(f, "token limit"),
+            BudgetViolation::Runtime => write!(f, "runtime limit"),
+            BudgetViolation::ToolCalls => write!(f, "tool call limit"),
+        }
+    }
+}
+
+/// Effective limits for a single execution. Each limit falls back from the task's budget
+/// to the project's budget to the global config independently.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BudgetLimits {
+    pub max_tokens: Option<i64>,
+    pub max_runtime: Option<Duration>,
+    pub max_tool_calls: Option<usize>,
+}
+
+impl BudgetLimits {
+    pub fn resolve(
+        task_budget: Option<&ExecutionBudget>,
+        project_budget: Option<&ExecutionBudget>,
+        defaults: &BudgetConfig,
+    ) -> Self {
+        let pick = |field: fn(&ExecutionBudget) -> Option<u32>, default: Option<u32>| {
+            task_budget
+                .and_then(field)
+                .or_else(|| project_budget.and_then(field))
+                .or(default)
+        };
+
+        Self {
+            max_tokens: pick(|b| b.max_tokens, defaults.max_tokens).map(i64::from),
+            max_runtime: pick(|b| b.max_runtime_seconds, defaults.max_runtime_seconds)
+                .map(|secs| Duration::from_secs(secs.into())),
+            max_tool_calls: pick(|b| b.max_tool_calls, defaults.max_tool_calls)
+                .map(|calls| calls as usize),
+        }
+    }
+
+    pub async fn for_task(
+        pool: &SqlitePool,
+        task: &Task,
+        defaults: &BudgetConfig,
+    ) -> Result<Self, sqlx::Error> {
+        let task_budget = ExecutionBudget::find_by_task_id(pool, task.id).await?;
+        let project_budget = ExecutionBudget::find_by_project_id(pool, task.project_id).await?;
+        Ok(Self::resolve(
+            task_budget.as_ref(),
+            project_budget.as_ref(),
+            defaults,
+        ))
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.max_tokens.is_none() && self.max_runtime.is_none() && self.max_tool_calls.is_none()
+    }
+
+    /// Token limits are enforced from the agent's usage reports, so they can't be applied to
+    /// an agent that doesn't report usage
+    pub fn enforceable_for(&self, agent: &CodingAgent) -> bool {
+        self.max_tokens.is_none()
+            || agent
+                .capabilities()
+                .contains(&BaseAgentCapability::UsageReporting)
+    }
+
+    pub fn exceeded(
+        &self,
+        tokens: i64,
+        tool_calls: usize,
+        elapsed: Duration,
+    ) -> Option<BudgetViolation> {
+        if self.max_tokens.is_some_and(|max| tokens > max) {
+            Some(BudgetViolation::Tokens)
+        } else if self.max_tool_calls.is_some_and(|max| tool_calls > max) {
+            Some(BudgetViolation::ToolCalls)
+        } else if self.max_runtime.is_some_and(|max| elapsed > max) {
+            Some(BudgetViolation::Runtime)
+        } else {
+            None
+        }
+    }
+}
+
+/// System message shown in the conversation of a run whose token limit isn't enforced,
+/// because `executor` doesn't report usage
+pub fn token_limit_skipped_entry(executor: &BaseCodingAgent) -> NormalizedEntry {
+    NormalizedEntry {
+        timestamp: None,
+        entry_type: NormalizedEntryType::SystemMessage,
+        content: format!(
+            "{executor} does not report token usage, so the token limit is not enforced for this run. Runtime and tool call limits still apply."
+        ),
+        metadata: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn budget(
+        max_tokens: Option<u32>,
+        max_runtime_seconds: Option<u32>,
+        max_tool_calls: Option<u32>,
+    ) -> ExecutionBudget {
+        ExecutionBudget {
+            id: Uuid::new_v4(),
+            project_id: None,
+            task_id: None,
+            max_tokens,
+            max_runtime_seconds,
+            max_tool_calls,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn each_limit_falls_back_independently() {
+        let task = budget(Some(1_000), None, None);
+        let project = budget(Some(5_000), Some(600), None);
+        let defaults = BudgetConfig {
+            max_tokens: Some(10_000),
+            max_runtime_seconds: Some(3_600),
+            max_tool_calls: Some(200),
+        };
+
+        let limits = BudgetLimits::resolve(Some(&task), Some(&project), &defaults);
+        assert_eq!(limits.max_tokens, Some(1_000));
+        assert_eq!(limits.max_runtime, Some(Duration::from_secs(600)));
+        assert_eq!(limits.max_tool_calls, Some(200));
+    }
+
+    #[test]
+    fn no_budgets_is_unlimited() {
+        let limits = BudgetLimits::resolve(None, None, &BudgetConfig::default());
+        assert!(limits.is_unlimited());
+        assert_eq!(limits.exceeded(i64::MAX, usize::MAX, Duration::MAX), None);
+    }
+
+    #[test]
+    fn reports_first_exceeded_limit() {
+        let limits = BudgetLimits {
+            max_tokens: Some(100),
+            max_runtime: Some(Duration::from_secs(60)),
+            max_tool_calls: Some(3),
+        };
+        assert_eq!(limits.exceeded(100, 3, Duration::from_secs(60)), None);
+        assert_eq!(
+            limits.exceeded(101, 0, Duration::ZERO),
+            Some(BudgetViolation::Tokens)
+        );
+        assert_eq!(
+            limits.exceeded(0, 4, Duration::ZERO),
+            Some(BudgetViolation::ToolCalls)
+        );
+        assert_eq!(
+            limits.exceeded(0, 0, Duration::from_secs(61)),
+            Some(BudgetViolation::Runtime)
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use db::models::task::Task;
+
+/// Values substituted into a project's merge commit template
+pub struct CommitMessageContext<'a> {
+    pub task: &'a Task,
+    /// Executor of the workspace's latest session, e.g. `CLAUDE_CODE`
+    pub executor: Option<&'a str>,
+}
+
+impl CommitMessageContext<'_> {
+    fn short_task_id(&self) -> String {
+        let task_id = self.task.id.to_string();
+        task_id.split('-').next().unwrap_or(&task_id).to_string()
+    }
+}
+
+/// Commit message for a direct merge. Without a template this is
+/// `<task title> (vibe-kanban <short id>)` followed by the task description.
+pub fn render_commit_message(template: Option<&str>, context: &CommitMessageContext) -> String {
+    let Some(template) = template.filter(|template| !template.trim().is_empty()) else {
+        return default_commit_message(context);
+    };
+
+    let message = template
+        .replace("{task_title}", &context.task.title)
+        .replace(
+            "{task_description}",
+            context.task.description.as_deref().unwrap_or("").trim(),
+        )
+        .replace("{task_id}", &context.task.id.to_string())
+        .replace("{task_short_id}", &context.short_task_id())
+        .replace("{executor}", context.executor.unwrap_or("unknown"));
+
+    tidy_blank_lines(&message)
+}
+
+fn default_commit_message(context: &CommitMessageContext) -> String {
+    let mut message = format!(
+        "{} (vibe-kanban {})",
+        context.task.title,
+        context.short_task_id()
+    );
+
+    // Add description on next line if it exists
+    if let Some(description) = &context.task.description
+        && !description.trim().is_empty()
+    {
+        message.push_str("\n\n");
+        message.push_str(description);
+    }
+    message
+}
+
+/// Empty placeholders shouldn't leave runs of blank lines or a trailing paragraph break
+fn tidy_blank_lines(message: &str) -> String {
+    let mut output = String::new();
+    let mut blank_run = 0;
+    for line in message.trim().lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank_run += 1;
+            if blank_run > 1 {
+                continue;
+            }
+        } else {
+            blank_run = 0;
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    output.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use db::models::task::TaskStatus;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn task(description: Option<&str>) -> Task {
+        Task {
+            id: Uuid::parse_str("3f2a9c1e-0000-4000-8000-000000000000").unwrap(),
+            project_id: Uuid::new_v4(),
+            title: "Add login page".to_string(),
+            description: description.map(str::to_string),
+            status: TaskStatus::InProgress,
+            parent_workspace_id: None,
+            shared_task_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn default_message_matches_previous_format() {
+        let task = task(Some("With OAuth"));
+        let context = CommitMessageContext {
+            task: &task,
+            executor: None,
+        };
+
+        assert_eq!(
+            render_commit_message(None, &context),
+            "Add login page (vibe-kanban 3f2a9c1e)\n\nWith OAuth"
+        );
+        assert_eq!(
+            render_commit_message(Some("  "), &context),
+            render_commit_message(None, &context)
+        );
+    }
+
+    #[test]
+    fn template_substitutes_placeholders() {
+        let template =
+            "feat: {task_title}\n\n{task_description}\n\nTask: {task_short_id}\nAgent: {executor}";
+        let task = task(None);
+        let context = CommitMessageContext {
+            task: &task,
+            executor: Some("CLAUDE_CODE"),
+        };
+
+        assert_eq!(
+            render_commit_message(Some(template), &context),
+            "feat: Add login page\n\nTask: 3f2a9c1e\nAgent: CLAUDE_CODE"
+        );
+    }
+}
@@ -26,6 +26,11 @@ pub type EditorType = versions::v8::EditorType;
 pub type GitHubConfig = versions::v8::GitHubConfig;
 pub type UiLanguage = versions::v8::UiLanguage;
 pub type ShowcaseState = versions::v8::ShowcaseState;
+pub type ConcurrencyConfig = versions::v8::ConcurrencyConfig;
+pub type BudgetConfig = versions::v8::BudgetConfig;
+pub type VerifyConfig = versions::v8::VerifyConfig;
+pub type GitLabConfig = versions::v8::GitLabConfig;
+pub type ApprovalNotificationConfig = versions::v8::ApprovalNotificationConfig;
 
 /// Will always return config, trying old schemas or eventually returning default
 pub async fn load_config_from_file(config_path: &PathBuf) -> Config {
@@ -7,7 +7,7 @@ pub use v7::{
     ThemeMode, UiLanguage,
 };
 
-use crate::services::config::versions::v7;
+use crate::services::{config::versions::v7, git::CommitSigningConfig};
 
 fn default_git_branch_prefix() -> String {
     "vk".to_string()
@@ -17,6 +17,53 @@ fn default_pr_auto_description_enabled() -> bool {
     true
 }
 
+/// Limits on how many workspaces may run agents at the same time.
+/// Executions beyond the limit are queued and started in FIFO order.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, TS)]
+pub struct ConcurrencyConfig {
+    pub max_running_agents: Option<u32>,
+    pub max_running_agents_per_project: Option<u32>,
+}
+
+/// Default per-execution limits for coding agents. Projects and tasks can override each one.
+/// An execution that exceeds any limit is interrupted.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, TS)]
+pub struct BudgetConfig {
+    pub max_tokens: Option<u32>,
+    pub max_runtime_seconds: Option<u32>,
+    pub max_tool_calls: Option<u32>,
+}
+
+/// What happens when a repo's verify script fails after a coding agent run
+#[derive(Clone, Debug, Default, Serialize, Deserialize, TS)]
+pub struct VerifyConfig {
+    /// Follow-ups sent to the agent with the failing output before giving up. 0 disables retries.
+    pub max_auto_retries: u32,
+}
+
+/// Access to GitLab for merge requests. The token falls back to the `GITLAB_TOKEN`
+/// environment variable.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, TS)]
+pub struct GitLabConfig {
+    pub token: Option<String>,
+    /// Hosts of self-hosted GitLab instances, e.g. `git.example.com`. gitlab.com and hosts
+    /// starting with `gitlab.` are recognised without being listed.
+    #[serde(default)]
+    pub hosts: Vec<String>,
+}
+
+/// Where approval requests are announced besides the sound and push notifications
+#[derive(Clone, Debug, Default, Serialize, Deserialize, TS)]
+pub struct ApprovalNotificationConfig {
+    /// Receives a JSON POST for every approval request, with links to approve or deny it.
+    /// The body is signed in the `X-Vibe-Kanban-Signature-256` header when the
+    /// `VK_APPROVAL_WEBHOOK_SECRET` environment variable is set.
+    pub webhook_url: Option<String>,
+    /// Base URL of this server used in approval links, e.g. when it is reached through a
+    /// tunnel. Defaults to the local address the server listens on.
+    pub public_base_url: Option<String>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, TS)]
 pub struct Config {
     pub config_version: String,
@@ -41,6 +88,22 @@ pub struct Config {
     pub pr_auto_description_enabled: bool,
     #[serde(default)]
     pub pr_auto_description_prompt: Option<String>,
+    #[serde(default)]
+    pub concurrency: ConcurrencyConfig,
+    #[serde(default)]
+    pub budget: BudgetConfig,
+    #[serde(default)]
+    pub verify: VerifyConfig,
+    /// Executors tried in order when the selected one is not installed or not signed in
+    #[serde(default)]
+    pub executor_fallbacks: Vec<ExecutorProfileId>,
+    #[serde(default)]
+    pub gitlab: GitLabConfig,
+    /// Signing of the commits vibe-kanban creates for agent turns and merges
+    #[serde(default)]
+    pub commit_signing: CommitSigningConfig,
+    #[serde(default)]
+    pub approval_notifications: ApprovalNotificationConfig,
 }
 
 impl Config {
@@ -66,6 +129,13 @@ impl Config {
             showcases: old_config.showcases,
             pr_auto_description_enabled: true,
             pr_auto_description_prompt: None,
+            concurrency: ConcurrencyConfig::default(),
+            budget: BudgetConfig::default(),
+            verify: VerifyConfig::default(),
+            executor_fallbacks: Vec::new(),
+            gitlab: GitLabConfig::default(),
+            commit_signing: CommitSigningConfig::default(),
+            approval_notifications: ApprovalNotificationConfig::default(),
         }
     }
 
@@ -116,6 +186,13 @@ impl Default for Config {
             showcases: ShowcaseState::default(),
             pr_auto_description_enabled: true,
             pr_auto_description_prompt: None,
+            concurrency: ConcurrencyConfig::default(),
+            budget: BudgetConfig::default(),
+            verify: VerifyConfig::default(),
+            executor_fallbacks: Vec::new(),
+            gitlab: GitLabConfig::default(),
+            commit_signing: CommitSigningConfig::default(),
+            approval_notifications: ApprovalNotificationConfig::default(),
         }
     }
 }
@@ -0,0 +1,354 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use db::models::{
+    coding_agent_turn::CodingAgentTurn,
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+    repo::Repo,
+    session::Session,
+    workspace::Workspace,
+};
+use executors::actions::{
+    ExecutorAction, ExecutorActionType, coding_agent_follow_up::CodingAgentFollowUpRequest,
+    coding_agent_initial::CodingAgentInitialRequest,
+};
+use utils::text::truncate_to_char_boundary;
+
+use crate::services::{
+    container::{ContainerError, ContainerService},
+    git::{GitService, GitServiceError, RebaseCommitSubjects},
+};
+
+/// Conflict hunks kept per file in a resolution prompt
+const MAX_FILE_HUNK_CHARS: usize = 6_000;
+/// Conflict hunks kept across all files in a resolution prompt
+const MAX_TOTAL_HUNK_CHARS: usize = 32_000;
+/// Commit subjects listed per side of the rebase
+const MAX_SUBJECTS: usize = 20;
+
+/// A file the rebase couldn't merge, with its conflict marker blocks
+pub struct ConflictedFile {
+    pub path: String,
+    /// Each block from `<<<<<<<` to `>>>>>>>`, with the line it starts on
+    pub hunks: Vec<(usize, String)>,
+}
+
+/// Everything an agent is told about a stopped rebase
+pub struct ConflictContext {
+    pub repo_name: String,
+    pub target_branch: String,
+    pub task_branch: String,
+    pub files: Vec<ConflictedFile>,
+    pub subjects: RebaseCommitSubjects,
+}
+
+/// Read the conflicted files, their marker blocks and the commits on both sides of the
+/// rebase stopped in `worktree_path`
+pub fn load_conflict_context(
+    git: &GitService,
+    worktree_path: &Path,
+    repo_name: &str,
+    target_branch: &str,
+    task_branch: &str,
+) -> Result<ConflictContext, GitServiceError> {
+    let paths = git.get_conflicted_files(worktree_path)?;
+    let subjects = git.rebase_commit_subjects(worktree_path, &paths)?;
+    let files = paths
+        .into_iter()
+        .map(|path| {
+            // Deleted or binary files have no markers to show
+            let hunks = std::fs::read(worktree_path.join(&path))
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .map(|content| conflict_hunks(&content))
+                .unwrap_or_default();
+            ConflictedFile { path, hunks }
+        })
+        .collect();
+
+    Ok(ConflictContext {
+        repo_name: repo_name.to_string(),
+        target_branch: target_branch.to_string(),
+        task_branch: task_branch.to_string(),
+        files,
+        subjects,
+    })
+}
+
+/// Build the follow-up prompt asking the agent to resolve a stopped rebase
+pub fn build_resolution_prompt(context: &ConflictContext) -> String {
+    let mut prompt = format!(
+        "Rebasing branch '{}' onto '{}' in the '{}' repository stopped on merge conflicts. \
+         Resolve every conflict below by editing the files so that both sides' intent is \
+         kept, and remove all conflict markers. Do not stage, commit, or run `git rebase` \
+         yourself; the rebase is continued for you once you are done.\n",
+        context.task_branch, context.target_branch, context.repo_name
+    );
+
+    let subjects = &context.subjects;
+    if let Some(replaying) = subjects.replaying.as_deref() {
+        prompt.push_str(&format!(
+            "\nThe rebase stopped on the commit \"{replaying}\".\n"
+        ));
+    }
+    push_subjects(
+        &mut prompt,
+        &format!(
+            "Commits on '{}' touching these files",
+            context.target_branch
+        ),
+        &subjects.base,
+    );
+    push_subjects(
+        &mut prompt,
+        &format!("Commits on '{}' touching these files", context.task_branch),
+        &subjects.task,
+    );
+
+    prompt.push_str("\n## Conflicted files\n");
+    let mut remaining = MAX_TOTAL_HUNK_CHARS;
+    for file in &context.files {
+        prompt.push_str(&format!("\n### {}\n", file.path));
+        if file.hunks.is_empty() {
+            prompt.push_str(
+                "No conflict markers in the file; it was deleted on one side or is binary. \
+                 Keep or remove it as appropriate.\n",
+            );
+            continue;
+        }
+
+        let mut file_remaining = MAX_FILE_HUNK_CHARS.min(remaining);
+        let mut omitted = 0;
+        for (line, hunk) in &file.hunks {
+            if hunk.len() > file_remaining {
+                omitted += 1;
+                continue;
+            }
+            file_remaining -= hunk.len();
+            remaining -= hunk.len();
+            prompt.push_str(&format!("Line {line}:\n```\n{hunk}\n```\n"));
+        }
+        if omitted > 0 {
+            prompt.push_str(&format!(
+                "{omitted} more conflict(s) in this file are not shown; open the file to see them.\n"
+            ));
+        }
+    }
+    prompt
+}
+
+fn push_subjects(prompt: &mut String, heading: &str, subjects: &[String]) {
+    if subjects.is_empty() {
+        return;
+    }
+    prompt.push_str(&format!("\n## {heading}\n"));
+    for subject in subjects.iter().take(MAX_SUBJECTS) {
+        prompt.push_str(&format!("- {}\n", truncate_to_char_boundary(subject, 200)));
+    }
+    if subjects.len() > MAX_SUBJECTS {
+        prompt.push_str(&format!(
+            "- ... and {} more\n",
+            subjects.len() - MAX_SUBJECTS
+        ));
+    }
+}
+
+/// Conflict marker blocks of a file, each with the 1-based line it starts on
+fn conflict_hunks(content: &str) -> Vec<(usize, String)> {
+    let mut hunks = Vec::new();
+    let mut current: Option<(usize, Vec<&str>)> = None;
+    for (index, line) in content.lines().enumerate() {
+        if is_marker(line, '<') {
+            current = Some((index + 1, vec![line]));
+        } else if let Some((start, mut lines)) = current.take() {
+            lines.push(line);
+            if is_marker(line, '>') {
+                hunks.push((start, lines.join("\n")));
+            } else {
+                current = Some((start, lines));
+            }
+        }
+    }
+    hunks
+}
+
+/// Whether a line opens or closes a conflict: seven marker characters followed by a
+/// space or the end of the line
+fn is_marker(line: &str, marker: char) -> bool {
+    line.len() >= 7
+        && line.chars().take(7).all(|c| c == marker)
+        && line[7..].chars().next().is_none_or(|c| c == ' ')
+}
+
+/// Files among `paths` that still contain conflict markers
+pub fn files_with_conflict_markers(worktree_path: &Path, paths: &[String]) -> Vec<String> {
+    paths
+        .iter()
+        .filter(|path| {
+            std::fs::read(worktree_path.join(path))
+                .map(|bytes| {
+                    String::from_utf8_lossy(&bytes)
+                        .lines()
+                        .any(|line| is_marker(line, '<') || is_marker(line, '>'))
+                })
+                .unwrap_or(false)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Start a follow-up in the session asking its agent to resolve the conflicts of the
+/// rebase stopped in `repo`. The turn is marked so the rebase is continued when the
+/// agent finishes.
+pub async fn start_resolution<C>(
+    container: &C,
+    workspace: &Workspace,
+    session: &Session,
+    repo: &Repo,
+    target_branch: &str,
+) -> Result<ExecutionProcess, ContainerError>
+where
+    C: ContainerService + Sync + ?Sized,
+{
+    let pool = &container.db().pool;
+    let worktree_path = container
+        .workspace_to_current_dir(workspace)
+        .join(&repo.name);
+    let context = load_conflict_context(
+        container.git(),
+        &worktree_path,
+        &repo.name,
+        target_branch,
+        &workspace.branch,
+    )?;
+    let prompt = build_resolution_prompt(&context);
+
+    let executor_profile_id =
+        ExecutionProcess::latest_executor_profile_for_session(pool, session.id)
+            .await
+            .map_err(|e| ContainerError::Other(anyhow!("Failed to get executor profile: {e}")))?;
+    let working_dir = workspace
+        .agent_working_dir
+        .as_ref()
+        .filter(|dir| !dir.is_empty())
+        .cloned();
+
+    let action_type =
+        match ExecutionProcess::find_latest_coding_agent_turn_session_id(pool, session.id).await? {
+            Some(agent_session_id) => {
+                ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
+                    prompt,
+                    session_id: agent_session_id,
+                    executor_profile_id,
+                    working_dir,
+                })
+            }
+            None => ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                prompt,
+                executor_profile_id,
+                working_dir,
+            }),
+        };
+    // No cleanup or verify scripts: the worktree is mid-rebase until the turn finishes
+    let action = ExecutorAction::new(action_type, None);
+
+    let execution_process = container
+        .start_execution(
+            workspace,
+            session,
+            &action,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?;
+    CodingAgentTurn::update_conflict_repo_id(pool, execution_process.id, repo.id).await?;
+
+    Ok(execution_process)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conflict_hunks_finds_marker_blocks_with_line_numbers() {
+        let content = "fn a() {}\n<<<<<<< HEAD\nlet x = 1;\n=======\nlet x = 2;\n>>>>>>> abc123 (Change x)\nfn b() {}\n<<<<<<< HEAD\n=======\nremoved\n>>>>>>> def456\n";
+
+        let hunks = conflict_hunks(content);
+
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].0, 2);
+        assert_eq!(
+            hunks[0].1,
+            "<<<<<<< HEAD\nlet x = 1;\n=======\nlet x = 2;\n>>>>>>> abc123 (Change x)"
+        );
+        assert_eq!(hunks[1].0, 8);
+    }
+
+    #[test]
+    fn markers_need_exactly_seven_characters() {
+        assert!(is_marker("<<<<<<< HEAD", '<'));
+        assert!(is_marker(">>>>>>>", '>'));
+        assert!(!is_marker("<<<<<<<< not a marker", '<'));
+        assert!(!is_marker("<<<<<<", '<'));
+        assert!(!is_marker("a <<<<<<< b", '<'));
+    }
+
+    #[test]
+    fn resolution_prompt_lists_files_hunks_and_both_sides() {
+        let context = ConflictContext {
+            repo_name: "app".to_string(),
+            target_branch: "main".to_string(),
+            task_branch: "vk/1234-feature".to_string(),
+            files: vec![
+                ConflictedFile {
+                    path: "src/lib.rs".to_string(),
+                    hunks: vec![(3, "<<<<<<< HEAD\na\n=======\nb\n>>>>>>> 1234".to_string())],
+                },
+                ConflictedFile {
+                    path: "logo.png".to_string(),
+                    hunks: vec![],
+                },
+            ],
+            subjects: RebaseCommitSubjects {
+                replaying: Some("Add feature".to_string()),
+                base: vec!["Refactor lib".to_string()],
+                task: vec!["Add feature".to_string()],
+            },
+        };
+
+        let prompt = build_resolution_prompt(&context);
+
+        assert!(prompt.contains("'vk/1234-feature' onto 'main'"));
+        assert!(prompt.contains("stopped on the commit \"Add feature\""));
+        assert!(prompt.contains("Commits on 'main' touching these files\n- Refactor lib"));
+        assert!(prompt.contains("### src/lib.rs\nLine 3:\n```\n<<<<<<< HEAD"));
+        assert!(prompt.contains("### logo.png\nNo conflict markers"));
+    }
+
+    #[test]
+    fn resolution_prompt_omits_hunks_over_the_file_budget() {
+        let big = format!(
+            "<<<<<<< HEAD\n{}\n>>>>>>> 1",
+            "x".repeat(MAX_FILE_HUNK_CHARS)
+        );
+        let context = ConflictContext {
+            repo_name: "app".to_string(),
+            target_branch: "main".to_string(),
+            task_branch: "feature".to_string(),
+            files: vec![ConflictedFile {
+                path: "big.txt".to_string(),
+                hunks: vec![
+                    (1, big),
+                    (10, "<<<<<<< HEAD\n=======\n>>>>>>> 1".to_string()),
+                ],
+            }],
+            subjects: RebaseCommitSubjects::default(),
+        };
+
+        let prompt = build_resolution_prompt(&context);
+
+        assert!(prompt.contains("Line 10:"));
+        assert!(!prompt.contains("Line 1:"));
+        assert!(prompt.contains("1 more conflict(s) in this file are not shown"));
+    }
+}
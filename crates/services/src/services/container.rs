@@ -9,6 +9,7 @@ use async_trait::async_trait;
 use db::{
     DBService,
     models::{
+        approval_log::ApprovalLogEntry,
         coding_agent_turn::{CodingAgentTurn, CreateCodingAgentTurn},
         execution_process::{
             CreateExecutionProcess, ExecutionContext, ExecutionProcess, ExecutionProcessRunReason,
@@ -18,6 +19,7 @@ use db::{
         execution_process_repo_state::{
             CreateExecutionProcessRepoState, ExecutionProcessRepoState,
         },
+        execution_process_usage::ExecutionProcessUsage,
         project::{Project, UpdateProject},
         project_repo::{ProjectRepo, ProjectRepoWithName},
         repo::Repo,
@@ -49,7 +51,8 @@ use utils::{
 use uuid::Uuid;
 
 use crate::services::{
-    git::{GitService, GitServiceError},
+    config::ConcurrencyConfig,
+    git::{GitService, GitServiceError, SparseCheckout},
     notification::NotificationService,
     share::SharePublisher,
     workspace_manager::WorkspaceError as WorkspaceManagerError,
@@ -57,6 +60,14 @@ use crate::services::{
 };
 pub type ContainerRef = String;
 
+/// Serializes concurrency checks with the status change that claims a slot,
+/// so simultaneous starts cannot overshoot the configured limits.
+///
+/// Hold it only while admitting: the lock is not reentrant, and launching a process can
+/// start further executions through [`ContainerService::start_execution`], which takes it
+/// again. Callers must release it before spawning anything.
+static EXECUTION_ADMISSION_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
 #[derive(Debug, Error)]
 pub enum ContainerError {
     #[error(transparent)]
@@ -81,6 +92,21 @@ pub enum ContainerError {
     Other(#[from] AnyhowError), // Catches any unclassified errors
 }
 
+/// System message shown in the conversation of a coding agent run that was skipped
+/// in favour of the next executor in the fallback chain
+pub fn executor_skipped_entry(
+    skipped: &ExecutorProfileId,
+    fallback: &ExecutorProfileId,
+    reason: &str,
+) -> NormalizedEntry {
+    NormalizedEntry {
+        timestamp: None,
+        entry_type: NormalizedEntryType::SystemMessage,
+        content: format!("Skipped {skipped}: {reason}. Continuing with {fallback}."),
+        metadata: None,
+    }
+}
+
 #[async_trait]
 pub trait ContainerService {
     fn msg_stores(&self) -> &Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>;
@@ -123,6 +149,46 @@ pub trait ContainerService {
         Ok(false)
     }
 
+    /// Keep this attempt and archive every other attempt of its task, stopping their
+    /// executions and removing their worktrees. Their branches are left in place.
+    async fn promote_attempt(
+        &self,
+        workspace: &Workspace,
+    ) -> Result<Vec<Workspace>, ContainerError> {
+        let archived =
+            Workspace::archive_siblings(&self.db().pool, workspace.task_id, workspace.id).await?;
+
+        for sibling in &archived {
+            if let Err(e) = self.delete(sibling).await {
+                tracing::warn!("Failed to clean up archived attempt {}: {}", sibling.id, e);
+            }
+        }
+        Ok(archived)
+    }
+
+    /// Remove a workspace along with its worktrees, stopping its executions. Used to undo a
+    /// creation or start that failed partway, so failures are only logged: the caller is
+    /// already reporting the original error.
+    async fn discard_workspace(&self, workspace_id: Uuid) {
+        let pool = &self.db().pool;
+        match Workspace::find_by_id(pool, workspace_id).await {
+            Ok(Some(workspace)) => {
+                if let Err(e) = self.delete(&workspace).await {
+                    tracing::warn!(
+                        "Failed to clean up worktrees of workspace {}: {}",
+                        workspace_id,
+                        e
+                    );
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to load workspace {}: {}", workspace_id, e),
+        }
+        if let Err(e) = Workspace::delete(pool, workspace_id).await {
+            tracing::error!("Failed to delete workspace {}: {}", workspace_id, e);
+        }
+    }
+
     /// A context is finalized when
     /// - Always when the execution process has failed or been killed
     /// - Never when the run reason is DevServer
@@ -149,10 +215,12 @@ pub trait ContainerService {
             return false;
         }
 
-        // Always finalize failed or killed executions, regardless of next action
+        // Always finalize failed, killed or over-budget executions, regardless of next action
         if matches!(
             ctx.execution_process.status,
-            ExecutionProcessStatus::Failed | ExecutionProcessStatus::Killed
+            ExecutionProcessStatus::Failed
+                | ExecutionProcessStatus::Killed
+                | ExecutionProcessStatus::BudgetExceeded
         ) {
             return true;
         }
@@ -161,12 +229,18 @@ pub trait ContainerService {
         action.next_action.is_none()
     }
 
-    /// Finalize task execution by updating status to InReview and sending notifications
+    /// Finalize task execution by updating status to InReview and sending notifications.
+    /// A task whose verify script still fails once its retries are used up goes to review
+    /// too, flagged on the board as failing verification.
     async fn finalize_task(
         &self,
         share_publisher: Option<&SharePublisher>,
         ctx: &ExecutionContext,
     ) {
+        let verification_failed = ctx.execution_process.run_reason
+            == ExecutionProcessRunReason::VerifyScript
+            && ctx.execution_process.status == ExecutionProcessStatus::Failed;
+
         match Task::update_status(&self.db().pool, ctx.task.id, TaskStatus::InReview).await {
             Ok(_) => {
                 if let Some(publisher) = share_publisher
@@ -191,6 +265,13 @@ pub trait ContainerService {
 
         let title = format!("Task Complete: {}", ctx.task.title);
         let message = match ctx.execution_process.status {
+            _ if verification_failed => format!(
+                "❌ '{}' failed verification (exit code {:?})\nBranch: {:?}\nExecutor: {:?}",
+                ctx.task.title,
+                ctx.execution_process.exit_code,
+                ctx.workspace.branch,
+                ctx.session.executor
+            ),
             ExecutionProcessStatus::Completed => format!(
                 "✅ '{}' completed successfully\nBranch: {:?}\nExecutor: {:?}",
                 ctx.task.title, ctx.workspace.branch, ctx.session.executor
@@ -199,6 +280,10 @@ pub trait ContainerService {
                 "❌ '{}' execution failed\nBranch: {:?}\nExecutor: {:?}",
                 ctx.task.title, ctx.workspace.branch, ctx.session.executor
             ),
+            ExecutionProcessStatus::BudgetExceeded => format!(
+                "⛔ '{}' was stopped after exceeding its budget\nBranch: {:?}\nExecutor: {:?}",
+                ctx.task.title, ctx.workspace.branch, ctx.session.executor
+            ),
             _ => {
                 tracing::warn!(
                     "Tried to notify workspace completion for {} but process is still running!",
@@ -262,12 +347,13 @@ pub trait ContainerService {
             }
             // Process marked as failed
             tracing::info!("Marked orphaned execution process {} as failed", process.id);
-            // Update task status to InReview for coding agent and setup script failures
+            // Update task status to InReview for coding agent and script failures
             if matches!(
                 process.run_reason,
                 ExecutionProcessRunReason::CodingAgent
                     | ExecutionProcessRunReason::SetupScript
                     | ExecutionProcessRunReason::CleanupScript
+                    | ExecutionProcessRunReason::VerifyScript
             ) && let Ok(Some(session)) =
                 Session::find_by_id(&self.db().pool, process.session_id).await
                 && let Ok(Some(workspace)) =
@@ -295,6 +381,13 @@ pub trait ContainerService {
                 }
             }
         }
+
+        // Approvals died with the previous server, so any still pending can't be answered
+        let cancelled =
+            ApprovalLogEntry::cancel_all_pending(&self.db().pool, chrono::Utc::now()).await?;
+        if cancelled > 0 {
+            tracing::info!("Cancelled {} approvals left pending at shutdown", cancelled);
+        }
         Ok(())
     }
 
@@ -413,6 +506,9 @@ pub trait ContainerService {
                                 } else {
                                     project.default_agent_working_dir.clone()
                                 },
+                                merge_strategy: None,
+                                merge_commit_template: None,
+                                worktree_pool_size: None,
                             },
                         )
                         .await?;
@@ -461,6 +557,55 @@ pub trait ContainerService {
         Some(root_action)
     }
 
+    fn verify_actions_for_repos(&self, repos: &[ProjectRepoWithName]) -> Option<ExecutorAction> {
+        let repos_with_verify: Vec<_> =
+            repos.iter().filter(|r| r.verify_script.is_some()).collect();
+
+        if repos_with_verify.is_empty() {
+            return None;
+        }
+
+        let mut iter = repos_with_verify.iter();
+        let first = iter.next()?;
+        let mut root_action = ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: first.verify_script.clone().unwrap(),
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::VerifyScript,
+                working_dir: Some(first.repo_name.clone()),
+            }),
+            None,
+        );
+
+        for repo in iter {
+            root_action = root_action.append_action(ExecutorAction::new(
+                ExecutorActionType::ScriptRequest(ScriptRequest {
+                    script: repo.verify_script.clone().unwrap(),
+                    language: ScriptRequestLanguage::Bash,
+                    context: ScriptContext::VerifyScript,
+                    working_dir: Some(repo.repo_name.clone()),
+                }),
+                None,
+            ));
+        }
+
+        Some(root_action)
+    }
+
+    /// Actions chained after a coding agent run: cleanup scripts, then verify scripts
+    fn post_agent_actions_for_repos(
+        &self,
+        repos: &[ProjectRepoWithName],
+    ) -> Option<ExecutorAction> {
+        match (
+            self.cleanup_actions_for_repos(repos),
+            self.verify_actions_for_repos(repos),
+        ) {
+            (Some(cleanup), Some(verify)) => Some(cleanup.append_action(verify)),
+            (cleanup, verify) => cleanup.or(verify),
+        }
+    }
+
     fn setup_actions_for_repos(&self, repos: &[ProjectRepoWithName]) -> Option<ExecutorAction> {
         let repos_with_setup: Vec<_> = repos.iter().filter(|r| r.setup_script.is_some()).collect();
 
@@ -548,7 +693,10 @@ pub trait ContainerService {
                     {
                         continue;
                     }
-                    if process.status == ExecutionProcessStatus::Running {
+                    if matches!(
+                        process.status,
+                        ExecutionProcessStatus::Running | ExecutionProcessStatus::Queued
+                    ) {
                         self.stop_execution(&process, ExecutionProcessStatus::Killed)
                             .await
                             .unwrap_or_else(|e| {
@@ -592,6 +740,7 @@ pub trait ContainerService {
         source_dir: &Path,
         target_dir: &Path,
         copy_files: &str,
+        sparse: Option<&SparseCheckout>,
     ) -> Result<(), ContainerError>;
 
     /// Stream diff updates as LogMsg for WebSocket endpoints.
@@ -609,6 +758,201 @@ pub trait ContainerService {
 
     async fn git_branch_prefix(&self) -> String;
 
+    async fn concurrency_config(&self) -> ConcurrencyConfig;
+
+    /// Executors to try, in order, when a coding agent can't run because it isn't
+    /// installed or signed in
+    async fn executor_fallbacks(&self) -> Vec<ExecutorProfileId>;
+
+    /// The first executor in the fallback chain that hasn't been tried in this session yet
+    async fn next_fallback_executor(
+        &self,
+        session: &Session,
+    ) -> Result<Option<ExecutorProfileId>, ContainerError> {
+        let fallbacks = self.executor_fallbacks().await;
+        if fallbacks.is_empty() {
+            return Ok(None);
+        }
+
+        let tried: Vec<_> = ExecutionProcess::find_by_session_id(&self.db().pool, session.id, true)
+            .await?
+            .iter()
+            .filter_map(|process| match process.executor_action().ok()?.typ() {
+                ExecutorActionType::CodingAgentInitialRequest(request) => {
+                    Some(request.executor_profile_id.executor)
+                }
+                _ => None,
+            })
+            .collect();
+
+        let configs = ExecutorConfigs::get_cached();
+        Ok(fallbacks.into_iter().find(|profile_id| {
+            !tried.contains(&profile_id.executor) && configs.get_coding_agent(profile_id).is_some()
+        }))
+    }
+
+    /// Retry a coding agent request that could not run with the next executor from the
+    /// fallback chain, recording the executor on the session. Returns `None` once the chain
+    /// is exhausted.
+    async fn try_start_executor_fallback(
+        &self,
+        workspace: &Workspace,
+        session: &Session,
+        failed_process: &ExecutionProcess,
+    ) -> Result<Option<(ExecutorProfileId, ExecutionProcess)>, ContainerError> {
+        let action = failed_process.executor_action()?;
+        let ExecutorActionType::CodingAgentInitialRequest(request) = action.typ() else {
+            return Ok(None);
+        };
+        let Some(fallback) = self.next_fallback_executor(session).await? else {
+            return Ok(None);
+        };
+
+        tracing::info!(
+            "Executor {} unavailable for session {}, falling back to {}",
+            request.executor_profile_id,
+            session.id,
+            fallback
+        );
+        Session::update_executor(&self.db().pool, session.id, &fallback.executor.to_string())
+            .await?;
+
+        let fallback_action = ExecutorAction::new(
+            ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                prompt: request.prompt.clone(),
+                executor_profile_id: fallback.clone(),
+                working_dir: request.working_dir.clone(),
+            }),
+            action.next_action().cloned().map(Box::new),
+        );
+        let execution_process = self
+            .start_execution(
+                workspace,
+                session,
+                &fallback_action,
+                &ExecutionProcessRunReason::CodingAgent,
+            )
+            .await?;
+        Ok(Some((fallback, execution_process)))
+    }
+
+    /// Whether an execution in `workspace` may start now without exceeding the global or
+    /// per-project concurrency limits. Dev servers and workspaces that already have a
+    /// running process (e.g. the next step of a setup -> agent -> cleanup chain) are never held back.
+    async fn has_execution_capacity(
+        &self,
+        workspace: &Workspace,
+        project_id: Uuid,
+        run_reason: &ExecutionProcessRunReason,
+    ) -> Result<bool, ContainerError> {
+        if run_reason == &ExecutionProcessRunReason::DevServer {
+            return Ok(true);
+        }
+        let pool = &self.db().pool;
+        if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace.id)
+            .await?
+        {
+            return Ok(true);
+        }
+
+        let limits = self.concurrency_config().await;
+        if let Some(max) = limits.max_running_agents
+            && ExecutionProcess::count_active_workspaces(pool, None).await? >= i64::from(max)
+        {
+            return Ok(false);
+        }
+        if let Some(max) = limits.max_running_agents_per_project
+            && ExecutionProcess::count_active_workspaces(pool, Some(project_id)).await?
+                >= i64::from(max)
+        {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Start queued executions, oldest first, for as long as there is capacity.
+    /// Called whenever a process finishes and frees up a slot.
+    async fn start_queued_executions(&self) -> Result<(), ContainerError> {
+        let pool = &self.db().pool;
+
+        // Claim slots under the admission lock, then launch once it is released: launching
+        // can start further executions, which take the lock again
+        let mut admitted = Vec::new();
+        {
+            let _guard = EXECUTION_ADMISSION_LOCK.lock().await;
+            for queued in ExecutionProcess::find_queued(pool).await? {
+                let ctx = ExecutionProcess::load_context(pool, queued.id).await?;
+                if !self
+                    .has_execution_capacity(
+                        &ctx.workspace,
+                        ctx.project.id,
+                        &ctx.execution_process.run_reason,
+                    )
+                    .await?
+                {
+                    continue;
+                }
+
+                let executor_action = match ctx.execution_process.executor_action() {
+                    Ok(action) => action.clone(),
+                    Err(e) => {
+                        tracing::error!(
+                            "Dropping queued execution process {} with invalid action: {}",
+                            queued.id,
+                            e
+                        );
+                        ExecutionProcess::update_completion(
+                            pool,
+                            queued.id,
+                            ExecutionProcessStatus::Failed,
+                            None,
+                        )
+                        .await?;
+                        continue;
+                    }
+                };
+
+                // The worktree may have been cleaned up while the process was waiting
+                if let Err(e) = self.ensure_container_exists(&ctx.workspace).await {
+                    tracing::error!(
+                        "Failed to prepare workspace for queued execution process {}: {}",
+                        queued.id,
+                        e
+                    );
+                    ExecutionProcess::update_completion(
+                        pool,
+                        queued.id,
+                        ExecutionProcessStatus::Failed,
+                        None,
+                    )
+                    .await?;
+                    continue;
+                }
+
+                ExecutionProcess::mark_started(pool, queued.id).await?;
+                let execution_process = ExecutionProcess::find_by_id(pool, queued.id)
+                    .await?
+                    .ok_or(SqlxError::RowNotFound)?;
+                admitted.push((ctx.workspace, execution_process, executor_action));
+            }
+        }
+
+        for (workspace, execution_process, executor_action) in admitted {
+            tracing::info!("Starting queued execution process {}", execution_process.id);
+            if let Err(e) = self
+                .launch_execution(&workspace, &execution_process, &executor_action)
+                .await
+            {
+                tracing::error!(
+                    "Failed to start queued execution process {}: {}",
+                    execution_process.id,
+                    e
+                );
+            }
+        }
+        Ok(())
+    }
+
     async fn git_branch_from_workspace(&self, workspace_id: &Uuid, task_title: &str) -> String {
         let task_title_id = git_branch_id(task_title);
         let prefix = self.git_branch_prefix().await;
@@ -868,6 +1212,17 @@ pub trait ContainerService {
                                 );
                             }
                         }
+                        LogMsg::Usage(usage) => {
+                            if let Err(e) =
+                                ExecutionProcessUsage::upsert(&db.pool, execution_id, usage).await
+                            {
+                                tracing::error!(
+                                    "Failed to record usage for execution process {}: {}",
+                                    execution_id,
+                                    e
+                                );
+                            }
+                        }
                         LogMsg::Finished => {
                             break;
                         }
@@ -918,14 +1273,19 @@ pub trait ContainerService {
 
         let prompt = task.to_prompt();
 
-        let repos_with_setup: Vec<_> = project_repos
-            .iter()
-            .filter(|pr| pr.setup_script.is_some())
-            .collect();
+        // Workspaces claimed from the worktree pool were set up ahead of time
+        let repos_with_setup: Vec<_> = if workspace.setup_completed_at.is_some() {
+            Vec::new()
+        } else {
+            project_repos
+                .iter()
+                .filter(|pr| pr.setup_script.is_some())
+                .collect()
+        };
 
         let all_parallel = repos_with_setup.iter().all(|pr| pr.parallel_setup_script);
 
-        let cleanup_action = self.cleanup_actions_for_repos(&project_repos);
+        let post_agent_action = self.post_agent_actions_for_repos(&project_repos);
 
         let working_dir = workspace
             .agent_working_dir
@@ -939,7 +1299,7 @@ pub trait ContainerService {
                 executor_profile_id: executor_profile_id.clone(),
                 working_dir,
             }),
-            cleanup_action.map(Box::new),
+            post_agent_action.map(Box::new),
         );
 
         let execution_process = if all_parallel {
@@ -986,6 +1346,21 @@ pub trait ContainerService {
         session: &Session,
         executor_action: &ExecutorAction,
         run_reason: &ExecutionProcessRunReason,
+    ) -> Result<ExecutionProcess, ContainerError> {
+        self.start_execution_from_turn(workspace, session, executor_action, run_reason, None)
+            .await
+    }
+
+    /// Like [`Self::start_execution`], for a coding agent taking the session over from
+    /// another executor. The coding agent turn is linked to `handoff_from_turn_id` when it is
+    /// created, so the link is in place before the agent starts (or fails to).
+    async fn start_execution_from_turn(
+        &self,
+        workspace: &Workspace,
+        session: &Session,
+        executor_action: &ExecutorAction,
+        run_reason: &ExecutionProcessRunReason,
+        handoff_from_turn_id: Option<Uuid>,
     ) -> Result<ExecutionProcess, ContainerError> {
         // Update task status to InProgress when starting an execution
         let task = workspace
@@ -1040,13 +1415,24 @@ pub trait ContainerService {
             run_reason: run_reason.clone(),
         };
 
+        let admission_guard = EXECUTION_ADMISSION_LOCK.lock().await;
+        let status = if self
+            .has_execution_capacity(workspace, task.project_id, run_reason)
+            .await?
+        {
+            ExecutionProcessStatus::Running
+        } else {
+            ExecutionProcessStatus::Queued
+        };
         let execution_process = ExecutionProcess::create(
             &self.db().pool,
             &create_execution_process,
             Uuid::new_v4(),
             &repo_states,
+            status,
         )
         .await?;
+        drop(admission_guard);
 
         if let Some(prompt) = match executor_action.typ() {
             ExecutorActionType::CodingAgentInitialRequest(coding_agent_request) => {
@@ -1060,6 +1446,7 @@ pub trait ContainerService {
             let create_coding_agent_turn = CreateCodingAgentTurn {
                 execution_process_id: execution_process.id,
                 prompt: Some(prompt),
+                handoff_from_turn_id,
             };
 
             let coding_agent_turn_id = Uuid::new_v4();
@@ -1072,8 +1459,48 @@ pub trait ContainerService {
             .await?;
         }
 
+        if execution_process.status == ExecutionProcessStatus::Queued {
+            tracing::info!(
+                "Concurrency limit reached, queued execution process {}",
+                execution_process.id
+            );
+            return Ok(execution_process);
+        }
+
+        self.launch_execution(workspace, &execution_process, executor_action)
+            .await
+    }
+
+    /// Persist a conversation entry for a process that has no live log stream
+    async fn append_normalized_entry_to_db(
+        &self,
+        execution_process_id: Uuid,
+        index: usize,
+        entry: NormalizedEntry,
+    ) {
+        let patch = ConversationPatch::add_normalized_entry(index, entry);
+        if let Ok(json_line) = serde_json::to_string::<LogMsg>(&LogMsg::JsonPatch(patch)) {
+            let _ = ExecutionProcessLogs::append_log_line(
+                &self.db().pool,
+                execution_process_id,
+                &format!("{json_line}\n"),
+            )
+            .await;
+        }
+    }
+
+    /// Spawn a created execution process and start streaming its logs.
+    /// On failure the process is marked failed and the task moves to review, unless an
+    /// unavailable coding agent can be replaced by the next executor in the fallback chain,
+    /// in which case the fallback's process is returned.
+    async fn launch_execution(
+        &self,
+        workspace: &Workspace,
+        execution_process: &ExecutionProcess,
+        executor_action: &ExecutorAction,
+    ) -> Result<ExecutionProcess, ContainerError> {
         if let Err(start_error) = self
-            .start_execution_inner(workspace, &execution_process, executor_action)
+            .start_execution_inner(workspace, execution_process, executor_action)
             .await
         {
             // Mark process as failed
@@ -1091,7 +1518,6 @@ pub trait ContainerService {
                     update_error
                 );
             }
-            Task::update_status(&self.db().pool, task.id, TaskStatus::InReview).await?;
 
             // Emit stderr error message
             let log_message = LogMsg::Stderr(format!("Failed to start execution: {start_error}"));
@@ -1105,28 +1531,59 @@ pub trait ContainerService {
             }
 
             // Emit NextAction with failure context for coding agent requests
-            if let ContainerError::ExecutorError(ExecutorError::ExecutableNotFound { program }) =
-                &start_error
-            {
-                let help_text = format!("The required executable `{program}` is not installed.");
+            let setup_error = match &start_error {
+                ContainerError::ExecutorError(ExecutorError::ExecutableNotFound { program }) => {
+                    Some(format!(
+                        "The required executable `{program}` is not installed."
+                    ))
+                }
+                ContainerError::ExecutorError(ExecutorError::AuthRequired(message)) => {
+                    Some(message.clone())
+                }
+                _ => None,
+            };
+            if let Some(help_text) = &setup_error {
                 let error_message = NormalizedEntry {
                     timestamp: None,
                     entry_type: NormalizedEntryType::ErrorMessage {
                         error_type: NormalizedEntryError::SetupRequired,
                     },
-                    content: help_text,
+                    content: help_text.clone(),
                     metadata: None,
                 };
-                let patch = ConversationPatch::add_normalized_entry(2, error_message);
-                if let Ok(json_line) = serde_json::to_string::<LogMsg>(&LogMsg::JsonPatch(patch)) {
-                    let _ = ExecutionProcessLogs::append_log_line(
-                        &self.db().pool,
-                        execution_process.id,
-                        &format!("{json_line}\n"),
-                    )
+                self.append_normalized_entry_to_db(execution_process.id, 2, error_message)
                     .await;
-                }
             };
+
+            // An executor that isn't installed or signed in is skipped for the next fallback
+            if let Some(reason) = &setup_error
+                && let ExecutorActionType::CodingAgentInitialRequest(request) =
+                    executor_action.typ()
+            {
+                let fallback = match execution_process.parent_session(&self.db().pool).await? {
+                    Some(session) => {
+                        self.try_start_executor_fallback(workspace, &session, execution_process)
+                            .await
+                    }
+                    None => Ok(None),
+                };
+                match fallback {
+                    Ok(Some((fallback_profile_id, fallback_process))) => {
+                        let notice = executor_skipped_entry(
+                            &request.executor_profile_id,
+                            &fallback_profile_id,
+                            reason.trim_end_matches('.'),
+                        );
+                        self.append_normalized_entry_to_db(execution_process.id, 3, notice)
+                            .await;
+                        return Ok(fallback_process);
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::error!("Failed to start fallback executor: {}", e),
+                }
+            }
+
+            Task::update_status(&self.db().pool, workspace.task_id, TaskStatus::InReview).await?;
             return Err(start_error);
         }
 
@@ -1155,7 +1612,7 @@ pub trait ContainerService {
         }
 
         self.spawn_stream_raw_logs_to_db(&execution_process.id);
-        Ok(execution_process)
+        Ok(execution_process.clone())
     }
 
     async fn try_start_next_action(&self, ctx: &ExecutionContext) -> Result<(), ContainerError> {
@@ -1169,6 +1626,11 @@ pub trait ContainerService {
 
         // Determine the run reason of the next action
         let next_run_reason = match (action.typ(), next_action.typ()) {
+            (_, ExecutorActionType::ScriptRequest(script))
+                if script.context == ScriptContext::VerifyScript =>
+            {
+                ExecutionProcessRunReason::VerifyScript
+            }
             (ExecutorActionType::ScriptRequest(_), ExecutorActionType::ScriptRequest(_)) => {
                 ExecutionProcessRunReason::SetupScript
             }
@@ -1191,3 +1653,257 @@ pub trait ContainerService {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use db::models::task::TaskWithAttemptStatus;
+    use executors::executors::BaseCodingAgent;
+
+    use super::*;
+    use crate::services::test_support::{
+        TestContainer, create_process, create_project, create_session, create_workspace,
+        script_action, test_db,
+    };
+
+    async fn board_card(db: &DBService, project_id: Uuid) -> TaskWithAttemptStatus {
+        Task::find_by_project_id_with_attempt_status(&db.pool, project_id)
+            .await
+            .unwrap()
+            .remove(0)
+    }
+
+    #[tokio::test]
+    async fn promoting_an_attempt_archives_the_others() {
+        let test_db = test_db().await;
+        let db = &test_db.db;
+        let dir = test_db.dir.path();
+        let fixture = create_project(db, dir).await;
+        let winner = create_workspace(db, &fixture.task, &fixture.repo, dir).await;
+        create_session(db, &winner).await;
+        let loser = create_workspace(db, &fixture.task, &fixture.repo, dir).await;
+        let loser_session = create_session(db, &loser).await;
+        create_process(
+            db,
+            &loser_session,
+            script_action(ScriptContext::SetupScript),
+            ExecutionProcessRunReason::SetupScript,
+            ExecutionProcessStatus::Failed,
+        )
+        .await;
+
+        assert!(board_card(db, fixture.project.id).await.last_attempt_failed);
+
+        let container = TestContainer::new(db, dir);
+        let archived = container.promote_attempt(&winner).await.unwrap();
+        assert_eq!(
+            archived.iter().map(|w| w.id).collect::<Vec<_>>(),
+            vec![loser.id]
+        );
+        assert_eq!(*container.deleted.lock().unwrap(), vec![loser.id]);
+
+        let pool = &db.pool;
+        let loser = Workspace::find_by_id(pool, loser.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(loser.archived_at.is_some());
+        let winner = Workspace::find_by_id(pool, winner.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(winner.archived_at.is_none());
+
+        // The board only reflects the attempt that was kept
+        assert!(!board_card(db, fixture.project.id).await.last_attempt_failed);
+
+        // Promoting again has nothing left to archive
+        assert!(container.promote_attempt(&winner).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn verify_failures_out_of_retries_go_to_review() {
+        let test_db = test_db().await;
+        let db = &test_db.db;
+        let dir = test_db.dir.path();
+        let fixture = create_project(db, dir).await;
+        let workspace = create_workspace(db, &fixture.task, &fixture.repo, dir).await;
+        let session = create_session(db, &workspace).await;
+        Task::update_status(&db.pool, fixture.task.id, TaskStatus::InProgress)
+            .await
+            .unwrap();
+        let process = create_process(
+            db,
+            &session,
+            script_action(ScriptContext::VerifyScript),
+            ExecutionProcessRunReason::VerifyScript,
+            ExecutionProcessStatus::Failed,
+        )
+        .await;
+
+        let container = TestContainer::new(db, dir);
+        let ctx = ExecutionProcess::load_context(&db.pool, process.id)
+            .await
+            .unwrap();
+        container.finalize_task(None, &ctx).await;
+
+        let card = board_card(db, fixture.project.id).await;
+        assert_eq!(card.status, TaskStatus::InReview);
+        assert!(card.verification_failed);
+    }
+
+    #[tokio::test]
+    async fn orphaned_verify_scripts_go_to_review() {
+        let test_db = test_db().await;
+        let db = &test_db.db;
+        let dir = test_db.dir.path();
+        let fixture = create_project(db, dir).await;
+        let workspace = create_workspace(db, &fixture.task, &fixture.repo, dir).await;
+        let session = create_session(db, &workspace).await;
+        Task::update_status(&db.pool, fixture.task.id, TaskStatus::InProgress)
+            .await
+            .unwrap();
+        let process = create_process(
+            db,
+            &session,
+            script_action(ScriptContext::VerifyScript),
+            ExecutionProcessRunReason::VerifyScript,
+            ExecutionProcessStatus::Running,
+        )
+        .await;
+
+        let container = TestContainer::new(db, dir);
+        container.cleanup_orphan_executions().await.unwrap();
+
+        let process = ExecutionProcess::find_by_id(&db.pool, process.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(process.status, ExecutionProcessStatus::Failed);
+        let card = board_card(db, fixture.project.id).await;
+        assert_eq!(card.status, TaskStatus::InReview);
+        assert!(card.verification_failed);
+    }
+
+    #[tokio::test]
+    async fn queued_launch_failure_falls_back_without_deadlocking() {
+        let test_db = test_db().await;
+        let db = &test_db.db;
+        let dir = test_db.dir.path();
+        let fixture = create_project(db, dir).await;
+        let workspace = create_workspace(db, &fixture.task, &fixture.repo, dir).await;
+        let session = create_session(db, &workspace).await;
+        let queued = create_process(
+            db,
+            &session,
+            ExecutorAction::new(
+                ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                    prompt: "do the task".to_string(),
+                    executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+                    working_dir: None,
+                }),
+                None,
+            ),
+            ExecutionProcessRunReason::CodingAgent,
+            ExecutionProcessStatus::Queued,
+        )
+        .await;
+
+        let mut container = TestContainer::new(db, dir);
+        container.unavailable = vec![BaseCodingAgent::ClaudeCode];
+        container.fallbacks = vec![ExecutorProfileId::new(BaseCodingAgent::Codex)];
+        container.concurrency.max_running_agents = Some(1);
+
+        tokio::time::timeout(
+            std::time::Duration::from_secs(10),
+            container.start_queued_executions(),
+        )
+        .await
+        .expect("starting queued executions deadlocked")
+        .unwrap();
+
+        let pool = &db.pool;
+        let queued = ExecutionProcess::find_by_id(pool, queued.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(queued.status, ExecutionProcessStatus::Failed);
+
+        let launched = container.launched();
+        assert_eq!(launched.len(), 1);
+        let fallback = ExecutionProcess::find_by_id(pool, launched[0])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fallback.status, ExecutionProcessStatus::Running);
+        assert_eq!(
+            fallback.executor_action().unwrap().executor_profile_id(),
+            Some(&ExecutorProfileId::new(BaseCodingAgent::Codex))
+        );
+        let session = Session::find_by_id(pool, session.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(session.executor.as_deref(), Some("CODEX"));
+    }
+
+    #[tokio::test]
+    async fn handoff_is_recorded_before_the_agent_starts() {
+        let test_db = test_db().await;
+        let db = &test_db.db;
+        let dir = test_db.dir.path();
+        let fixture = create_project(db, dir).await;
+        let workspace = create_workspace(db, &fixture.task, &fixture.repo, dir).await;
+        let session = create_session(db, &workspace).await;
+        let coding_action = |executor| {
+            ExecutorAction::new(
+                ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                    prompt: "do the task".to_string(),
+                    executor_profile_id: ExecutorProfileId::new(executor),
+                    working_dir: None,
+                }),
+                None,
+            )
+        };
+        let previous = create_process(
+            db,
+            &session,
+            coding_action(BaseCodingAgent::Codex),
+            ExecutionProcessRunReason::CodingAgent,
+            ExecutionProcessStatus::Completed,
+        )
+        .await;
+        let previous_turn = CodingAgentTurn::create(
+            &db.pool,
+            &CreateCodingAgentTurn {
+                execution_process_id: previous.id,
+                prompt: Some("do the task".to_string()),
+                handoff_from_turn_id: None,
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+
+        // The new executor failing to start must not lose the handoff
+        let mut container = TestContainer::new(db, dir);
+        container.unavailable = vec![BaseCodingAgent::ClaudeCode];
+        assert!(
+            container
+                .start_execution_from_turn(
+                    &workspace,
+                    &session,
+                    &coding_action(BaseCodingAgent::ClaudeCode),
+                    &ExecutionProcessRunReason::CodingAgent,
+                    Some(previous_turn.id),
+                )
+                .await
+                .is_err()
+        );
+
+        let turns = CodingAgentTurn::find_by_session_id(&db.pool, session.id)
+            .await
+            .unwrap();
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[1].handoff_from_turn_id, Some(previous_turn.id));
+    }
+}
@@ -21,7 +21,7 @@ use utils::{
 
 use crate::services::{
     filesystem_watcher::{self, FilesystemWatcherError},
-    git::{Commit, DiffTarget, GitService, GitServiceError},
+    git::{Commit, DiffTarget, GitService, GitServiceError, SparseCheckout},
 };
 
 /// Maximum cumulative diff bytes to stream before omitting content (200MB)
@@ -88,6 +88,7 @@ struct DiffWatcherContext {
     full_sent: Arc<std::sync::RwLock<HashSet<String>>>,
     stats_only: bool,
     path_prefix: Option<String>,
+    sparse: Option<SparseCheckout>,
     tx: mpsc::Sender<Result<LogMsg, io::Error>>,
 }
 
@@ -111,6 +112,7 @@ impl DiffWatcherContext {
         let full_sent = self.full_sent.clone();
         let stats_only = self.stats_only;
         let path_prefix = self.path_prefix.clone();
+        let sparse = self.sparse.clone();
 
         match tokio::task::spawn_blocking(move || {
             process_file_changes(
@@ -122,6 +124,7 @@ impl DiffWatcherContext {
                 &full_sent,
                 stats_only,
                 path_prefix.as_deref(),
+                sparse.as_ref(),
             )
         })
         .await
@@ -168,19 +171,22 @@ pub async fn create(
         let base_for_diff = base_commit.clone();
         let path_prefix_clone = path_prefix.clone();
 
+        // Files outside a sparse checkout aren't in the worktree and aren't shown
         let initial_diffs_result = tokio::task::spawn_blocking(move || {
-            git_for_diff.get_diffs(
+            let sparse = git_for_diff.sparse_checkout(&worktree_for_diff)?;
+            let diffs = git_for_diff.get_diffs(
                 DiffTarget::Worktree {
                     worktree_path: &worktree_for_diff,
                     base_commit: &base_for_diff,
                 },
                 None,
-            )
+            )?;
+            Ok::<_, GitServiceError>((sparse, diffs))
         })
         .await;
 
-        let initial_diffs_raw = match initial_diffs_result {
-            Ok(Ok(diffs)) => diffs,
+        let (sparse, initial_diffs_raw) = match initial_diffs_result {
+            Ok(Ok((sparse, diffs))) => (sparse, diffs),
             Ok(Err(e)) => {
                 tracing::error!("Failed to get initial diffs: {e}");
                 send_error(&tx_clone, e.to_string()).await;
@@ -195,6 +201,9 @@ pub async fn create(
 
         let mut initial_diffs = Vec::with_capacity(initial_diffs_raw.len());
         for mut diff in initial_diffs_raw {
+            if !in_sparse_checkout(&diff, sparse.as_ref()) {
+                continue;
+            }
             apply_stream_omit_policy(&mut diff, &cumulative, stats_only);
             initial_diffs.push(diff);
         }
@@ -245,6 +254,7 @@ pub async fn create(
             full_sent,
             stats_only,
             path_prefix,
+            sparse,
             tx: tx_clone,
         };
 
@@ -279,6 +289,10 @@ pub async fn create(
     ))
 }
 
+fn in_sparse_checkout(diff: &Diff, sparse: Option<&SparseCheckout>) -> bool {
+    sparse.is_none_or(|sparse| sparse.includes(&GitService::diff_path(diff), false))
+}
+
 fn prefix_path(path: String, prefix: Option<&str>) -> String {
     match prefix {
         Some(p) => format!("{p}/{path}"),
@@ -397,6 +411,7 @@ fn process_file_changes(
     full_sent_paths: &Arc<std::sync::RwLock<HashSet<String>>>,
     stats_only: bool,
     path_prefix: Option<&str>,
+    sparse: Option<&SparseCheckout>,
 ) -> Result<Vec<LogMsg>, DiffStreamError> {
     let path_filter: Vec<&str> = changed_paths.iter().map(|s| s.as_str()).collect();
 
@@ -412,6 +427,9 @@ fn process_file_changes(
     let mut files_with_diffs = HashSet::new();
 
     for mut diff in current_diffs {
+        if !in_sparse_checkout(&diff, sparse) {
+            continue;
+        }
         let raw_file_path = GitService::diff_path(&diff);
         files_with_diffs.insert(raw_file_path.clone());
 
@@ -3,8 +3,8 @@ use std::{str::FromStr, sync::Arc};
 use db::{
     DBService,
     models::{
-        execution_process::ExecutionProcess, project::Project, scratch::Scratch, task::Task,
-        workspace::Workspace,
+        execution_process::ExecutionProcess, merge_queue::MergeQueueEntry, project::Project,
+        scratch::Scratch, task::Task, task_dependency::TaskDependency, workspace::Workspace,
     },
 };
 use serde_json::json;
@@ -21,7 +21,8 @@ mod streams;
 pub mod types;
 
 pub use patches::{
-    execution_process_patch, project_patch, scratch_patch, task_patch, workspace_patch,
+    execution_process_patch, merge_queue_patch, project_patch, scratch_patch, task_patch,
+    workspace_patch,
 };
 pub use types::{EventError, EventPatch, EventPatchInner, HookTables, RecordTypes};
 
@@ -149,6 +150,14 @@ impl EventService {
                                     msg_store_for_preupdate.push_patch(patch);
                                 }
                             }
+                            "merge_queue_entries" => {
+                                if let Ok(value) = preupdate.get_old_column_value(0)
+                                    && let Ok(entry_id) = <Uuid as Decode<Sqlite>>::decode(value)
+                                {
+                                    let patch = merge_queue_patch::remove(entry_id);
+                                    msg_store_for_preupdate.push_patch(patch);
+                                }
+                            }
                             _ => {}
                         }
                     }
@@ -168,7 +177,8 @@ impl EventService {
                                 | (HookTables::Projects, SqliteOperation::Delete)
                                 | (HookTables::Workspaces, SqliteOperation::Delete)
                                 | (HookTables::ExecutionProcesses, SqliteOperation::Delete)
-                                | (HookTables::Scratch, SqliteOperation::Delete) => {
+                                | (HookTables::Scratch, SqliteOperation::Delete)
+                                | (HookTables::MergeQueueEntries, SqliteOperation::Delete) => {
                                     // Deletions handled in preupdate hook for reliable data capture
                                     return;
                                 }
@@ -246,6 +256,22 @@ impl EventService {
                                         }
                                     }
                                 }
+                                (HookTables::MergeQueueEntries, _) => {
+                                    match MergeQueueEntry::find_by_rowid(&db.pool, rowid).await {
+                                        Ok(Some(entry)) => RecordTypes::MergeQueueEntry(entry),
+                                        Ok(None) => RecordTypes::DeletedMergeQueueEntry {
+                                            rowid,
+                                            entry_id: None,
+                                        },
+                                        Err(e) => {
+                                            tracing::error!(
+                                                "Failed to fetch merge queue entry: {:?}",
+                                                e
+                                            );
+                                            return;
+                                        }
+                                    }
+                                }
                             };
 
                             let db_op: &str = match hook.operation {
@@ -265,20 +291,35 @@ impl EventService {
                                             task.project_id,
                                         )
                                         .await
-                                        && let Some(task_with_status) =
-                                            task_list.into_iter().find(|t| t.id == task.id)
                                     {
-                                        let patch = match hook.operation {
-                                            SqliteOperation::Insert => {
-                                                task_patch::add(&task_with_status)
-                                            }
-                                            SqliteOperation::Update => {
-                                                task_patch::replace(&task_with_status)
+                                        // Dependents' blocked state follows this task's status
+                                        if let Ok(dependents) =
+                                            TaskDependency::find_dependents(&db.pool, task.id)
+                                                .await
+                                        {
+                                            for dependent in task_list.iter().filter(|t| {
+                                                dependents.iter().any(|d| d.id == t.id)
+                                            }) {
+                                                msg_store_for_hook
+                                                    .push_patch(task_patch::replace(dependent));
                                             }
-                                            _ => task_patch::replace(&task_with_status), // fallback
-                                        };
-                                        msg_store_for_hook.push_patch(patch);
-                                        return;
+                                        }
+
+                                        if let Some(task_with_status) =
+                                            task_list.iter().find(|t| t.id == task.id)
+                                        {
+                                            let patch = match hook.operation {
+                                                SqliteOperation::Insert => {
+                                                    task_patch::add(task_with_status)
+                                                }
+                                                SqliteOperation::Update => {
+                                                    task_patch::replace(task_with_status)
+                                                }
+                                                _ => task_patch::replace(task_with_status), // fallback
+                                            };
+                                            msg_store_for_hook.push_patch(patch);
+                                            return;
+                                        }
                                     }
                                 }
                                 RecordTypes::DeletedTask {
@@ -316,6 +357,14 @@ impl EventService {
                                     msg_store_for_hook.push_patch(patch);
                                     return;
                                 }
+                                RecordTypes::MergeQueueEntry(entry) => {
+                                    let patch = match hook.operation {
+                                        SqliteOperation::Insert => merge_queue_patch::add(entry),
+                                        _ => merge_queue_patch::replace(entry),
+                                    };
+                                    msg_store_for_hook.push_patch(patch);
+                                    return;
+                                }
                                 RecordTypes::Workspace(workspace) => {
                                     // Workspaces should update the parent task with fresh data
                                     if let Ok(Some(task)) =
@@ -1,6 +1,6 @@
 use db::models::{
-    execution_process::ExecutionProcess, project::Project, scratch::Scratch,
-    task::TaskWithAttemptStatus, workspace::Workspace,
+    execution_process::ExecutionProcess, merge_queue::MergeQueueEntry, project::Project,
+    scratch::Scratch, task::TaskWithAttemptStatus, workspace::Workspace,
 };
 use json_patch::{AddOperation, Patch, PatchOperation, RemoveOperation, ReplaceOperation};
 use uuid::Uuid;
@@ -217,3 +217,46 @@ pub mod scratch_patch {
         })])
     }
 }
+
+/// Helper functions for creating merge queue entry-specific patches
+pub mod merge_queue_patch {
+    use super::*;
+
+    fn entry_path(entry_id: Uuid) -> String {
+        format!(
+            "/merge_queue/{}",
+            escape_pointer_segment(&entry_id.to_string())
+        )
+    }
+
+    /// Create patch for adding a new merge queue entry
+    pub fn add(entry: &MergeQueueEntry) -> Patch {
+        Patch(vec![PatchOperation::Add(AddOperation {
+            path: entry_path(entry.id)
+                .try_into()
+                .expect("Merge queue entry path should be valid"),
+            value: serde_json::to_value(entry)
+                .expect("Merge queue entry serialization should not fail"),
+        })])
+    }
+
+    /// Create patch for updating an existing merge queue entry
+    pub fn replace(entry: &MergeQueueEntry) -> Patch {
+        Patch(vec![PatchOperation::Replace(ReplaceOperation {
+            path: entry_path(entry.id)
+                .try_into()
+                .expect("Merge queue entry path should be valid"),
+            value: serde_json::to_value(entry)
+                .expect("Merge queue entry serialization should not fail"),
+        })])
+    }
+
+    /// Create patch for removing a merge queue entry
+    pub fn remove(entry_id: Uuid) -> Patch {
+        Patch(vec![PatchOperation::Remove(RemoveOperation {
+            path: entry_path(entry_id)
+                .try_into()
+                .expect("Merge queue entry path should be valid"),
+        })])
+    }
+}
@@ -1,5 +1,6 @@
 use db::models::{
     execution_process::ExecutionProcess,
+    merge_queue::MergeQueueEntry,
     project::Project,
     scratch::Scratch,
     session::Session,
@@ -226,6 +227,83 @@ impl EventService {
         Ok(combined_stream)
     }
 
+    /// Stream a project's merge queue entries with initial snapshot
+    pub async fn stream_merge_queue_raw(
+        &self,
+        project_id: Uuid,
+    ) -> Result<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>, EventError>
+    {
+        fn build_merge_queue_snapshot(entries: Vec<MergeQueueEntry>) -> LogMsg {
+            let entries_map: serde_json::Map<String, serde_json::Value> = entries
+                .into_iter()
+                .map(|entry| (entry.id.to_string(), serde_json::to_value(entry).unwrap()))
+                .collect();
+
+            let patch = json!([
+                {
+                    "op": "replace",
+                    "path": "/merge_queue",
+                    "value": entries_map
+                }
+            ]);
+
+            LogMsg::JsonPatch(serde_json::from_value(patch).unwrap())
+        }
+
+        let entries = MergeQueueEntry::find_by_project_id(&self.db.pool, project_id).await?;
+        let initial_msg = build_merge_queue_snapshot(entries);
+
+        let db_pool = self.db.pool.clone();
+
+        let filtered_stream =
+            BroadcastStream::new(self.msg_store.get_receiver()).filter_map(move |msg_result| {
+                let db_pool = db_pool.clone();
+                async move {
+                    match msg_result {
+                        Ok(LogMsg::JsonPatch(patch)) => {
+                            if let Some(patch_op) = patch.0.first()
+                                && patch_op.path().starts_with("/merge_queue/")
+                            {
+                                let value = match patch_op {
+                                    json_patch::PatchOperation::Add(op) => Some(&op.value),
+                                    json_patch::PatchOperation::Replace(op) => Some(&op.value),
+                                    // Removals carry no project, let the client ignore unknown ids
+                                    _ => None,
+                                };
+                                let in_project = value.is_none_or(|value| {
+                                    serde_json::from_value::<MergeQueueEntry>(value.clone())
+                                        .is_ok_and(|entry| entry.project_id == project_id)
+                                });
+                                if in_project {
+                                    return Some(Ok(LogMsg::JsonPatch(patch)));
+                                }
+                            }
+                            None
+                        }
+                        Ok(other) => Some(Ok(other)),
+                        Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                            tracing::warn!(
+                                skipped = skipped,
+                                "merge queue stream lagged; resyncing snapshot"
+                            );
+
+                            match MergeQueueEntry::find_by_project_id(&db_pool, project_id).await {
+                                Ok(entries) => Some(Ok(build_merge_queue_snapshot(entries))),
+                                Err(err) => Some(Err(std::io::Error::other(format!(
+                                    "failed to resync merge queue after lag: {err}"
+                                )))),
+                            }
+                        }
+                    }
+                }
+            });
+
+        let initial_stream = futures::stream::once(async move { Ok(initial_msg) });
+        let combined_stream = initial_stream.chain(filtered_stream).boxed();
+
+        Ok(combined_stream)
+    }
+
     /// Stream execution processes for a specific workspace with initial snapshot (raw LogMsg format for WebSocket)
     pub async fn stream_execution_processes_for_workspace_raw(
         &self,
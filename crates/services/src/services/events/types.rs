@@ -1,7 +1,7 @@
 use anyhow::Error as AnyhowError;
 use db::models::{
-    execution_process::ExecutionProcess, project::Project, scratch::Scratch, task::Task,
-    workspace::Workspace,
+    execution_process::ExecutionProcess, merge_queue::MergeQueueEntry, project::Project,
+    scratch::Scratch, task::Task, workspace::Workspace,
 };
 use serde::{Deserialize, Serialize};
 use sqlx::Error as SqlxError;
@@ -32,6 +32,8 @@ pub enum HookTables {
     Scratch,
     #[strum(to_string = "projects")]
     Projects,
+    #[strum(to_string = "merge_queue_entries")]
+    MergeQueueEntries,
 }
 
 #[derive(Serialize, Deserialize, TS)]
@@ -42,6 +44,7 @@ pub enum RecordTypes {
     ExecutionProcess(ExecutionProcess),
     Scratch(Scratch),
     Project(Project),
+    MergeQueueEntry(MergeQueueEntry),
     DeletedTask {
         rowid: i64,
         project_id: Option<Uuid>,
@@ -65,6 +68,10 @@ pub enum RecordTypes {
         rowid: i64,
         project_id: Option<Uuid>,
     },
+    DeletedMergeQueueEntry {
+        rowid: i64,
+        entry_id: Option<Uuid>,
+    },
 }
 
 #[derive(Serialize, Deserialize, TS)]
@@ -23,7 +23,7 @@ use ts_rs::TS;
 
 use super::{
     file_ranker::{FileRanker, FileStats},
-    git::GitService,
+    git::{GitService, SparseCheckout},
 };
 
 /// Search mode for different use cases
@@ -136,12 +136,13 @@ impl FileSearchCache {
         }
     }
 
-    /// Search files in repository using cache
+    /// Search files in repository using cache, limited to `sparse` when given
     pub async fn search(
         &self,
         repo_path: &Path,
         query: &str,
         mode: SearchMode,
+        sparse: Option<&SparseCheckout>,
     ) -> Result<Vec<SearchResult>, CacheError> {
         let repo_path_buf = repo_path.to_path_buf();
 
@@ -151,7 +152,7 @@ impl FileSearchCache {
             && head_info.oid == cached.head_sha
         {
             // Cache hit - perform fast search with mode-based filtering
-            return Ok(self.search_in_cache(&cached, query, mode).await);
+            return Ok(self.search_in_cache(&cached, query, mode, sparse).await);
         }
 
         // Cache miss - trigger background refresh and return error
@@ -233,6 +234,7 @@ impl FileSearchCache {
         cached: &CachedRepo,
         query: &str,
         mode: SearchMode,
+        sparse: Option<&SparseCheckout>,
     ) -> Vec<SearchResult> {
         let query_lower = query.to_lowercase();
         let mut results = Vec::new();
@@ -254,6 +256,13 @@ impl FileSearchCache {
                     }
                 }
 
+                // The index covers the whole repo; worktrees only have the sparse set
+                if let Some(sparse) = sparse
+                    && !sparse.includes(&indexed_file.path, !indexed_file.is_file)
+                {
+                    continue;
+                }
+
                 results.push(SearchResult {
                     path: indexed_file.path.clone(),
                     is_file: indexed_file.is_file,
@@ -0,0 +1,290 @@
+//! Hosting services that pull requests are opened on, chosen from a repo's remote URL.
+
+use std::path::Path;
+
+use async_trait::async_trait;
+use db::models::merge::PullRequestInfo;
+use thiserror::Error;
+use url::Url;
+
+use crate::services::{
+    config::GitLabConfig,
+    git::{GitService, GitServiceError},
+    github::{
+        CreatePrRequest, GitHubRepoInfo, GitHubService, GitHubServiceError, UnifiedPrComment,
+    },
+    gitlab::{GitLabRepoInfo, GitLabService, GitLabServiceError},
+};
+
+#[derive(Debug, Error)]
+pub enum ForgeError {
+    #[error(transparent)]
+    GitHub(#[from] GitHubServiceError),
+    #[error(transparent)]
+    GitLab(#[from] GitLabServiceError),
+    #[error(transparent)]
+    Git(#[from] GitServiceError),
+    #[error(
+        "Pull requests are not supported for remote {0}. Add self-hosted GitLab instances under Settings → Pull Requests."
+    )]
+    UnsupportedRemote(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForgeKind {
+    GitHub,
+    GitLab,
+}
+
+impl ForgeKind {
+    /// github.com is GitHub. gitlab.com, hosts starting with `gitlab.` and the configured
+    /// self-hosted instances are GitLab.
+    pub fn from_remote_url(remote_url: &str, gitlab: &GitLabConfig) -> Option<Self> {
+        let host = RemoteUrl::parse(remote_url)?.host;
+        if host == "github.com" || host.ends_with(".github.com") {
+            Some(Self::GitHub)
+        } else if host == "gitlab.com"
+            || host.starts_with("gitlab.")
+            || gitlab
+                .hosts
+                .iter()
+                .any(|configured| configured_host(configured) == host)
+        {
+            Some(Self::GitLab)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::GitHub => "github",
+            Self::GitLab => "gitlab",
+        }
+    }
+}
+
+/// Pull requests (merge requests on GitLab) of one repository
+#[async_trait]
+pub trait Forge: Send + Sync {
+    fn kind(&self) -> ForgeKind;
+
+    async fn check_auth(&self) -> Result<(), ForgeError>;
+
+    async fn create_pr(&self, request: &CreatePrRequest) -> Result<PullRequestInfo, ForgeError>;
+
+    async fn pr_status(&self, number: i64) -> Result<PullRequestInfo, ForgeError>;
+
+    /// All pull requests from `branch`, open ones first
+    async fn list_prs_for_branch(&self, branch: &str) -> Result<Vec<PullRequestInfo>, ForgeError>;
+
+    async fn pr_comments(&self, number: i64) -> Result<Vec<UnifiedPrComment>, ForgeError>;
+}
+
+/// The forge hosting `remote_url`, which may be a clone URL or a pull request URL
+pub fn forge_for_remote_url(
+    remote_url: &str,
+    gitlab: &GitLabConfig,
+) -> Result<Box<dyn Forge>, ForgeError> {
+    match ForgeKind::from_remote_url(remote_url, gitlab) {
+        Some(ForgeKind::GitHub) => Ok(Box::new(GitHubForge {
+            service: GitHubService::new()?,
+            repo_info: GitHubRepoInfo::from_remote_url(remote_url)?,
+        })),
+        Some(ForgeKind::GitLab) => Ok(Box::new(GitLabForge {
+            service: GitLabService::new(gitlab)?,
+            repo_info: GitLabRepoInfo::from_remote_url(remote_url)?,
+        })),
+        None => Err(ForgeError::UnsupportedRemote(remote_url.to_string())),
+    }
+}
+
+/// The forge hosting the default remote of the repo at `repo_path`
+pub fn forge_for_repo(
+    git: &GitService,
+    repo_path: &Path,
+    gitlab: &GitLabConfig,
+) -> Result<Box<dyn Forge>, ForgeError> {
+    let remote_url = git.get_remote_url(repo_path)?;
+    forge_for_remote_url(&remote_url, gitlab)
+}
+
+struct GitHubForge {
+    service: GitHubService,
+    repo_info: GitHubRepoInfo,
+}
+
+#[async_trait]
+impl Forge for GitHubForge {
+    fn kind(&self) -> ForgeKind {
+        ForgeKind::GitHub
+    }
+
+    async fn check_auth(&self) -> Result<(), ForgeError> {
+        Ok(self.service.check_token().await?)
+    }
+
+    async fn create_pr(&self, request: &CreatePrRequest) -> Result<PullRequestInfo, ForgeError> {
+        Ok(self.service.create_pr(&self.repo_info, request).await?)
+    }
+
+    async fn pr_status(&self, number: i64) -> Result<PullRequestInfo, ForgeError> {
+        Ok(self
+            .service
+            .update_pr_status(&self.repo_info, number)
+            .await?)
+    }
+
+    async fn list_prs_for_branch(&self, branch: &str) -> Result<Vec<PullRequestInfo>, ForgeError> {
+        Ok(self
+            .service
+            .list_all_prs_for_branch(&self.repo_info, branch)
+            .await?)
+    }
+
+    async fn pr_comments(&self, number: i64) -> Result<Vec<UnifiedPrComment>, ForgeError> {
+        Ok(self
+            .service
+            .get_pr_comments(&self.repo_info, number)
+            .await?)
+    }
+}
+
+struct GitLabForge {
+    service: GitLabService,
+    repo_info: GitLabRepoInfo,
+}
+
+#[async_trait]
+impl Forge for GitLabForge {
+    fn kind(&self) -> ForgeKind {
+        ForgeKind::GitLab
+    }
+
+    async fn check_auth(&self) -> Result<(), ForgeError> {
+        Ok(self.service.check_token(&self.repo_info).await?)
+    }
+
+    async fn create_pr(&self, request: &CreatePrRequest) -> Result<PullRequestInfo, ForgeError> {
+        Ok(self.service.create_mr(&self.repo_info, request).await?)
+    }
+
+    async fn pr_status(&self, number: i64) -> Result<PullRequestInfo, ForgeError> {
+        Ok(self
+            .service
+            .update_mr_status(&self.repo_info, number)
+            .await?)
+    }
+
+    async fn list_prs_for_branch(&self, branch: &str) -> Result<Vec<PullRequestInfo>, ForgeError> {
+        Ok(self
+            .service
+            .list_all_mrs_for_branch(&self.repo_info, branch)
+            .await?)
+    }
+
+    async fn pr_comments(&self, number: i64) -> Result<Vec<UnifiedPrComment>, ForgeError> {
+        Ok(self
+            .service
+            .get_mr_comments(&self.repo_info, number)
+            .await?)
+    }
+}
+
+/// Host and repository path of a git remote or web URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RemoteUrl {
+    pub host: String,
+    /// Where the web UI and API are served. SSH remotes are assumed to use HTTPS.
+    pub web_base_url: String,
+    /// Repository path without `.git` and without pull/merge request suffixes
+    pub path: String,
+}
+
+impl RemoteUrl {
+    pub(crate) fn parse(remote_url: &str) -> Option<Self> {
+        let remote_url = remote_url.trim();
+        let (host, web_base_url, path) = if remote_url.contains("://") {
+            let url = Url::parse(remote_url).ok()?;
+            let host = url.host_str()?.to_ascii_lowercase();
+            let web_base_url = match url.scheme() {
+                "http" | "https" => match url.port() {
+                    Some(port) => format!("{}://{host}:{port}", url.scheme()),
+                    None => format!("{}://{host}", url.scheme()),
+                },
+                _ => format!("https://{host}"),
+            };
+            (host, web_base_url, url.path().to_string())
+        } else {
+            // scp-like syntax: git@host:group/project.git
+            let (authority, path) = remote_url.split_once(':')?;
+            let host = authority
+                .rsplit_once('@')
+                .map_or(authority, |(_, host)| host)
+                .to_ascii_lowercase();
+            let web_base_url = format!("https://{host}");
+            (host, web_base_url, path.to_string())
+        };
+
+        let path = path.split("/-/").next().unwrap_or_default();
+        let path = path.trim_matches('/');
+        let path = path.strip_suffix(".git").unwrap_or(path).to_string();
+        if host.is_empty() || path.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            host,
+            web_base_url,
+            path,
+        })
+    }
+}
+
+/// Configured hosts may be written as URLs
+fn configured_host(configured: &str) -> String {
+    let configured = configured.trim();
+    Url::parse(configured)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| configured.trim_end_matches('/').to_string())
+        .to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_forge_from_remote_url() {
+        let gitlab = GitLabConfig {
+            token: None,
+            hosts: vec!["https://git.example.com/".to_string()],
+        };
+
+        let cases = [
+            ("git@github.com:acme/widgets.git", Some(ForgeKind::GitHub)),
+            (
+                "https://github.com/acme/widgets/pull/7",
+                Some(ForgeKind::GitHub),
+            ),
+            (
+                "https://gitlab.com/acme/widgets.git",
+                Some(ForgeKind::GitLab),
+            ),
+            (
+                "git@gitlab.acme.io:team/widgets.git",
+                Some(ForgeKind::GitLab),
+            ),
+            (
+                "ssh://git@git.example.com:2222/team/widgets.git",
+                Some(ForgeKind::GitLab),
+            ),
+            ("https://bitbucket.org/acme/widgets.git", None),
+            ("/srv/git/widgets.git", None),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(ForgeKind::from_remote_url(url, &gitlab), expected, "{url}");
+        }
+    }
+}
@@ -1,6 +1,11 @@
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, RwLock},
+};
 
 use chrono::{DateTime, Utc};
+use db::models::project::MergeStrategy;
 use git2::{
     BranchType, Delta, DiffFindOptions, DiffOptions, Error as GitError, Reference, Remote,
     Repository, Sort,
@@ -11,9 +16,13 @@ use ts_rs::TS;
 use utils::diff::{Diff, DiffChangeKind, FileDiffDetails, compute_line_change_counts};
 
 mod cli;
+mod signing;
+mod sparse;
 
 use cli::{ChangeType, StatusDiffEntry, StatusDiffOptions};
 pub use cli::{GitCli, GitCliError};
+pub use signing::{CommitSigningConfig, CommitSigningMode, SigningFormat};
+pub use sparse::SparseCheckout;
 
 use super::file_ranker::FileStat;
 use crate::services::github::GitHubRepoInfo;
@@ -38,14 +47,34 @@ pub enum GitServiceError {
     WorktreeDirty(String, String),
     #[error("Rebase in progress; resolve or abort it before retrying")]
     RebaseInProgress,
+    #[error("Commit signing failed: {0}")]
+    SigningFailed(String),
+}
+
+impl GitServiceError {
+    /// Error for a failed git command that creates commits, telling signing failures
+    /// apart from other failures
+    fn commit_failed(context: &str, err: GitCliError) -> Self {
+        let message = err.to_string();
+        if signing::is_signing_failure(&message) {
+            GitServiceError::SigningFailed(message)
+        } else {
+            GitServiceError::InvalidRepository(format!("{context}: {message}"))
+        }
+    }
 }
+
 /// Service for managing Git operations in task execution workflows
 #[derive(Clone)]
-pub struct GitService {}
+pub struct GitService {
+    commit_signing: Arc<RwLock<CommitSigningConfig>>,
+}
 
 // Max inline diff size for UI (in bytes). Files larger than this will have
 // their contents omitted from the diff stream to avoid UI crashes.
 const MAX_INLINE_DIFF_BYTES: usize = 2 * 1024 * 1024; // ~2MB
+/// First line of a Git LFS pointer file
+const LFS_POINTER_PREFIX: &[u8] = b"version https://git-lfs.github.com/spec/v1";
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -72,6 +101,17 @@ pub struct HeadInfo {
     pub oid: String,
 }
 
+/// Commit subjects on both sides of a stopped rebase, newest first
+#[derive(Debug, Clone, Default)]
+pub struct RebaseCommitSubjects {
+    /// Task commit the rebase stopped on
+    pub replaying: Option<String>,
+    /// Commits on the new base that the task branch didn't have
+    pub base: Vec<String>,
+    /// Commits of the task branch being replayed
+    pub task: Vec<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Commit(git2::Oid);
 
@@ -149,7 +189,56 @@ impl Default for GitService {
 impl GitService {
     /// Create a new GitService for the given repository path
     pub fn new() -> Self {
-        Self {}
+        Self {
+            commit_signing: Arc::new(RwLock::new(CommitSigningConfig::default())),
+        }
+    }
+
+    /// Set how commits created by this service and its clones are signed
+    pub fn set_commit_signing(&self, config: CommitSigningConfig) {
+        *self
+            .commit_signing
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = config;
+    }
+
+    fn commit_signing(&self) -> CommitSigningConfig {
+        self.commit_signing
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Git CLI for commands that create commits, with the commit signing setting applied
+    fn committing_cli(&self) -> GitCli {
+        GitCli::with_config_overrides(self.commit_signing().cli_overrides())
+    }
+
+    /// Create a commit with libgit2, signed when commit signing is enabled for `repo`
+    fn create_commit(
+        &self,
+        repo: &Repository,
+        update_ref: Option<&str>,
+        signature: &git2::Signature,
+        message: &str,
+        tree: &git2::Tree,
+        parents: &[&git2::Commit],
+    ) -> Result<git2::Oid, GitServiceError> {
+        let Some(signer) = self.commit_signing().signer(repo)? else {
+            return Ok(repo.commit(update_ref, signature, signature, message, tree, parents)?);
+        };
+
+        let buffer = repo.commit_create_buffer(signature, signature, message, tree, parents)?;
+        let content = buffer.as_str().ok_or_else(|| {
+            GitServiceError::SigningFailed("commit content is not valid UTF-8".to_string())
+        })?;
+        let gpgsig = signer.sign(content)?;
+        let oid = repo.commit_signed(content, &gpgsig, None)?;
+        if let Some(refname) = update_ref {
+            let log_message = message.lines().next().unwrap_or_default();
+            repo.reference(refname, oid, true, log_message)?;
+        }
+        Ok(oid)
     }
 
     pub fn is_branch_name_valid(&self, name: &str) -> bool {
@@ -255,10 +344,10 @@ impl GitService {
         let tree = repo.find_tree(tree_id)?;
 
         // Create initial commit on main branch
-        let _commit_id = repo.commit(
+        let _commit_id = self.create_commit(
+            repo,
             Some("refs/heads/main"),
             &signature,
-            &signature,
             "Initial commit",
             &tree,
             &[],
@@ -285,8 +374,9 @@ impl GitService {
             .map_err(|e| GitServiceError::InvalidRepository(format!("git add failed: {e}")))?;
         // Only ensure identity once we know we're about to commit
         self.ensure_cli_commit_identity(path)?;
-        git.commit(path, message)
-            .map_err(|e| GitServiceError::InvalidRepository(format!("git commit failed: {e}")))?;
+        self.committing_cli()
+            .commit(path, message)
+            .map_err(|e| GitServiceError::commit_failed("git commit failed", e))?;
         Ok(true)
     }
 
@@ -454,7 +544,7 @@ impl GitService {
                 }
 
                 // Only build old/new content if not omitted
-                let (old_path, old_content) = if matches!(status, Delta::Added) {
+                let (old_path, mut old_content) = if matches!(status, Delta::Added) {
                     (None, None)
                 } else {
                     let path_opt = delta
@@ -475,7 +565,7 @@ impl GitService {
                     }
                 };
 
-                let (new_path, new_content) = if matches!(status, Delta::Deleted) {
+                let (new_path, mut new_content) = if matches!(status, Delta::Deleted) {
                     (None, None)
                 } else {
                     let path_opt = delta
@@ -506,6 +596,27 @@ impl GitService {
                     _ => DiffChangeKind::Modified,
                 };
 
+                // Submodules have no blobs; show the commit they point at instead
+                let is_submodule = delta.old_file().mode() == git2::FileMode::Commit
+                    || delta.new_file().mode() == git2::FileMode::Commit;
+                if is_submodule {
+                    change = DiffChangeKind::Submodule;
+                    old_content = (delta.old_file().mode() == git2::FileMode::Commit)
+                        .then(|| Self::submodule_pointer(delta.old_file().id()));
+                    new_content = (delta.new_file().mode() == git2::FileMode::Commit)
+                        .then(|| Self::submodule_pointer(delta.new_file().id()));
+                }
+                // Git LFS files are binary; their pointers say nothing useful
+                let is_lfs = !is_submodule
+                    && [delta.old_file().path(), delta.new_file().path()]
+                        .into_iter()
+                        .flatten()
+                        .any(|path| Self::is_lfs_path(repo, path));
+                if is_lfs {
+                    old_content = None;
+                    new_content = None;
+                }
+
                 // Detect pure mode changes (e.g., chmod +/-x) and classify as PermissionChange
                 if matches!(status, Delta::Modified)
                     && delta.old_file().mode() != delta.new_file().mode()
@@ -518,8 +629,9 @@ impl GitService {
                 }
 
                 // Always compute line stats via libgit2 Patch
-                let (additions, deletions) = if let Ok(Some(patch)) =
-                    git2::Patch::from_diff(&diff, delta_index)
+                let (additions, deletions) = if is_lfs {
+                    (None, None)
+                } else if let Ok(Some(patch)) = git2::Patch::from_diff(&diff, delta_index)
                     && let Ok((_ctx, adds, dels)) = patch.line_stats()
                 {
                     (Some(adds), Some(dels))
@@ -557,10 +669,29 @@ impl GitService {
             .unwrap_or_default()
     }
 
+    /// Diff content of a submodule, as `git diff` shows it
+    fn submodule_pointer(commit: git2::Oid) -> String {
+        format!("Subproject commit {commit}\n")
+    }
+
+    /// HEAD of the submodule checked out at `rel_path` in a worktree
+    fn submodule_head(repo: &Repository, rel_path: &Path) -> Option<git2::Oid> {
+        let submodule = Repository::open(repo.workdir()?.join(rel_path)).ok()?;
+        submodule.head().ok()?.target()
+    }
+
+    /// Whether the repo's attributes store a path in Git LFS
+    fn is_lfs_path(repo: &Repository, path: &Path) -> bool {
+        repo.get_attr(path, "filter", git2::AttrCheckFlags::FILE_THEN_INDEX)
+            .ok()
+            .flatten()
+            == Some("lfs")
+    }
+
     /// Helper function to convert blob to string content
     fn blob_to_string(blob: &git2::Blob) -> Option<String> {
-        if blob.is_binary() {
-            None // Skip binary files
+        if blob.is_binary() || blob.content().starts_with(LFS_POINTER_PREFIX) {
+            None // Skip binary files and LFS pointers
         } else {
             std::str::from_utf8(blob.content())
                 .ok()
@@ -592,8 +723,8 @@ impl GitService {
             return None;
         }
 
-        // Binary guard - skip files containing null bytes
-        if bytes.contains(&0) {
+        // Binary guard - skip files containing null bytes, and LFS pointers
+        if bytes.contains(&0) || bytes.starts_with(LFS_POINTER_PREFIX) {
             tracing::debug!("Skipping binary file: {:?}", abs_path);
             return None;
         }
@@ -668,6 +799,36 @@ impl GitService {
             ChangeType::Unknown(_) => (e.old_path.clone(), Some(e.path.clone())),
         };
 
+        // Submodules are compared by the commit they point at
+        let old_submodule = old_path_opt.as_ref().and_then(|oldp| {
+            base_tree
+                .get_path(Path::new(oldp))
+                .ok()
+                .filter(|entry| entry.kind() == Some(git2::ObjectType::Commit))
+                .map(|entry| entry.id())
+        });
+        let new_submodule = new_path_opt
+            .as_ref()
+            .and_then(|newp| Self::submodule_head(repo, Path::new(newp)));
+        if old_submodule.is_some() || new_submodule.is_some() {
+            return Diff {
+                change: DiffChangeKind::Submodule,
+                old_path: old_path_opt,
+                new_path: new_path_opt,
+                old_content: old_submodule.map(Self::submodule_pointer),
+                new_content: new_submodule.map(Self::submodule_pointer),
+                content_omitted: false,
+                additions: Some(usize::from(new_submodule.is_some())),
+                deletions: Some(usize::from(old_submodule.is_some())),
+            };
+        }
+
+        // Git LFS files are binary; their contents are never shown
+        let is_lfs = [&old_path_opt, &new_path_opt]
+            .into_iter()
+            .flatten()
+            .any(|path| Self::is_lfs_path(repo, Path::new(path)));
+
         // Decide if we should omit content by size (either side)
         let mut content_omitted = false;
         // Old side (from base tree)
@@ -695,7 +856,7 @@ impl GitService {
         }
 
         // Load contents only if not omitted
-        let (old_content, new_content) = if content_omitted {
+        let (old_content, new_content) = if content_omitted || is_lfs {
             (None, None)
         } else {
             // Load old content from base tree if possible
@@ -781,7 +942,7 @@ impl GitService {
         Ok(None)
     }
 
-    /// Merge changes from a task branch into the base branch.
+    /// Merge changes from a task branch into the base branch as a single squash commit.
     pub fn merge_changes(
         &self,
         base_worktree_path: &Path,
@@ -789,6 +950,28 @@ impl GitService {
         task_branch_name: &str,
         base_branch_name: &str,
         commit_message: &str,
+    ) -> Result<String, GitServiceError> {
+        self.merge_changes_with_strategy(
+            base_worktree_path,
+            task_worktree_path,
+            task_branch_name,
+            base_branch_name,
+            commit_message,
+            MergeStrategy::Squash,
+        )
+    }
+
+    /// Merge changes from a task branch into the base branch. The task branch must already
+    /// contain the base branch, so [`MergeStrategy::Rebase`] is a fast-forward that keeps the
+    /// task's commits as they are and ignores `commit_message`. Returns the new base head.
+    pub fn merge_changes_with_strategy(
+        &self,
+        base_worktree_path: &Path,
+        task_worktree_path: &Path,
+        task_branch_name: &str,
+        base_branch_name: &str,
+        commit_message: &str,
+        strategy: MergeStrategy,
     ) -> Result<String, GitServiceError> {
         // Open the repositories
         let task_repo = self.open_repo(task_worktree_path)?;
@@ -809,7 +992,7 @@ impl GitService {
         match self.find_checkout_path_for_branch(base_worktree_path, base_branch_name)? {
             Some(base_checkout_path) => {
                 // base branch is checked out somewhere - use CLI merge
-                let git_cli = GitCli::new();
+                let git_cli = self.committing_cli();
 
                 // Safety check: base branch has no staged changes
                 if git_cli
@@ -826,16 +1009,27 @@ impl GitService {
 
                 // Use CLI merge in base context
                 self.ensure_cli_commit_identity(&base_checkout_path)?;
-                let sha = git_cli
-                    .merge_squash_commit(
+                let result = match strategy {
+                    MergeStrategy::Squash => git_cli.merge_squash_commit(
                         &base_checkout_path,
                         base_branch_name,
                         task_branch_name,
                         commit_message,
-                    )
-                    .map_err(|e| {
-                        GitServiceError::InvalidRepository(format!("CLI merge failed: {e}"))
-                    })?;
+                    ),
+                    MergeStrategy::Rebase => git_cli.merge_fast_forward(
+                        &base_checkout_path,
+                        base_branch_name,
+                        task_branch_name,
+                    ),
+                    MergeStrategy::MergeCommit => git_cli.merge_no_ff_commit(
+                        &base_checkout_path,
+                        base_branch_name,
+                        task_branch_name,
+                        commit_message,
+                    ),
+                };
+                let sha =
+                    result.map_err(|e| GitServiceError::commit_failed("CLI merge failed", e))?;
 
                 // Update task branch ref for continuity
                 let task_refname = format!("refs/heads/{task_branch_name}");
@@ -856,28 +1050,48 @@ impl GitService {
                 let base_commit = base_branch.get().peel_to_commit()?;
                 let task_commit = task_branch.get().peel_to_commit()?;
 
-                // Create the squash commit in-memory (no checkout) and update the base branch ref
+                // Create the commit in-memory (no checkout) and update the base branch ref
                 let signature = self.signature_with_fallback(&task_repo)?;
-                let squash_commit_id = self.perform_squash_merge(
-                    &task_repo,
-                    &base_commit,
-                    &task_commit,
-                    &signature,
-                    commit_message,
-                    base_branch_name,
-                )?;
+                let merged_commit_id = match strategy {
+                    MergeStrategy::Squash => self.perform_squash_merge(
+                        &task_repo,
+                        &base_commit,
+                        &task_commit,
+                        &signature,
+                        commit_message,
+                        base_branch_name,
+                    )?,
+                    MergeStrategy::Rebase => {
+                        let refname = format!("refs/heads/{base_branch_name}");
+                        task_repo.reference(
+                            &refname,
+                            task_commit.id(),
+                            true,
+                            "Fast-forward merge",
+                        )?;
+                        task_commit.id()
+                    }
+                    MergeStrategy::MergeCommit => self.perform_merge_commit(
+                        &task_repo,
+                        &base_commit,
+                        &task_commit,
+                        &signature,
+                        commit_message,
+                        base_branch_name,
+                    )?,
+                };
 
-                // Update the task branch to the new squash commit so follow-up
+                // Update the task branch to the merged commit so follow-up
                 // work can continue from the merged state without conflicts.
                 let task_refname = format!("refs/heads/{task_branch_name}");
                 base_repo.reference(
                     &task_refname,
-                    squash_commit_id,
+                    merged_commit_id,
                     true,
-                    "Reset task branch after squash merge",
+                    "Reset task branch after merge",
                 )?;
 
-                Ok(squash_commit_id.to_string())
+                Ok(merged_commit_id.to_string())
             }
         }
     }
@@ -1097,6 +1311,23 @@ impl GitService {
         Ok((st.uncommitted_tracked, st.untracked))
     }
 
+    /// Paths of tracked files with staged or unstaged changes, including unmerged ones
+    pub fn get_uncommitted_paths(
+        &self,
+        worktree_path: &Path,
+    ) -> Result<Vec<String>, GitServiceError> {
+        let cli = GitCli::new();
+        let st = cli
+            .get_worktree_status(worktree_path)
+            .map_err(|e| GitServiceError::InvalidRepository(format!("git status failed: {e}")))?;
+        Ok(st
+            .entries
+            .into_iter()
+            .filter(|entry| !entry.is_untracked)
+            .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+            .collect())
+    }
+
     /// Evaluate whether any action is needed to reset to `target_commit_oid` and
     /// optionally perform the actions.
     pub fn reconcile_worktree_to_commit(
@@ -1168,13 +1399,58 @@ impl GitService {
         worktree_path: &Path,
         branch: &str,
         create_branch: bool,
+    ) -> Result<(), GitServiceError> {
+        self.add_sparse_worktree(repo_path, worktree_path, branch, create_branch, None)
+    }
+
+    /// Add a worktree, checking out only the sparse set when one is given
+    pub fn add_sparse_worktree(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+        branch: &str,
+        create_branch: bool,
+        sparse: Option<&SparseCheckout>,
     ) -> Result<(), GitServiceError> {
         let git = GitCli::new();
-        git.worktree_add(repo_path, worktree_path, branch, create_branch)
+        git.worktree_add(repo_path, worktree_path, branch, create_branch, sparse)
             .map_err(|e| GitServiceError::InvalidRepository(e.to_string()))?;
+        self.checkout_submodules_and_lfs(worktree_path);
         Ok(())
     }
 
+    /// Check out the submodules and Git LFS objects of a worktree, when the repo uses them.
+    /// Failures are only logged: the worktree is still usable without them.
+    pub fn checkout_submodules_and_lfs(&self, worktree_path: &Path) {
+        let git = GitCli::new();
+        if worktree_path.join(".gitmodules").is_file()
+            && let Err(e) = git.submodule_update(worktree_path)
+        {
+            tracing::warn!(
+                "Failed to update submodules in {}: {}",
+                worktree_path.display(),
+                e
+            );
+        }
+        let uses_lfs = std::fs::read_to_string(worktree_path.join(".gitattributes"))
+            .is_ok_and(|attributes| attributes.contains("filter=lfs"));
+        if uses_lfs && let Err(e) = git.lfs_pull(worktree_path) {
+            tracing::warn!(
+                "Failed to check out Git LFS files in {}: {}",
+                worktree_path.display(),
+                e
+            );
+        }
+    }
+
+    /// The sparse checkout of a worktree, or `None` when it has the full tree
+    pub fn sparse_checkout(
+        &self,
+        worktree_path: &Path,
+    ) -> Result<Option<SparseCheckout>, GitServiceError> {
+        Ok(GitCli::new().sparse_checkout(worktree_path)?)
+    }
+
     /// Remove a worktree
     pub fn remove_worktree(
         &self,
@@ -1201,6 +1477,140 @@ impl GitService {
         Ok(())
     }
 
+    /// Fast-forward the detached HEAD of a worktree to `target_branch`, returning the new HEAD
+    pub fn fast_forward_detached_worktree(
+        &self,
+        worktree_path: &Path,
+        target_branch: &str,
+    ) -> Result<String, GitServiceError> {
+        let git = GitCli::new();
+        git.git(
+            worktree_path,
+            ["merge", "--ff-only", "--quiet", target_branch],
+        )
+        .map_err(|e| {
+            GitServiceError::InvalidRepository(format!("git merge --ff-only failed: {e}"))
+        })?;
+        self.checkout_submodules_and_lfs(worktree_path);
+        let head = git
+            .git(worktree_path, ["rev-parse", "HEAD"])
+            .map_err(|e| GitServiceError::InvalidRepository(e.to_string()))?;
+        Ok(head.trim().to_string())
+    }
+
+    /// Create a branch at the detached HEAD of a worktree and check it out
+    pub fn create_branch_in_worktree(
+        &self,
+        worktree_path: &Path,
+        branch_name: &str,
+    ) -> Result<(), GitServiceError> {
+        GitCli::new()
+            .git(
+                worktree_path,
+                ["switch", "--quiet", "--create", branch_name],
+            )
+            .map_err(|e| GitServiceError::InvalidRepository(format!("git switch failed: {e}")))?;
+        Ok(())
+    }
+
+    /// Delete a local branch that isn't checked out anywhere
+    pub fn delete_local_branch(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+    ) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let mut branch = repo
+            .find_branch(branch_name, BranchType::Local)
+            .map_err(|_| GitServiceError::BranchNotFound(branch_name.to_string()))?;
+        branch.delete()?;
+        Ok(())
+    }
+
+    /// Whether `commit_sha` names a commit present in the repository
+    pub fn commit_exists(&self, repo_path: &Path, commit_sha: &str) -> bool {
+        let Ok(repo) = self.open_repo(repo_path) else {
+            return false;
+        };
+        git2::Oid::from_str(commit_sha)
+            .and_then(|oid| repo.find_commit(oid))
+            .is_ok()
+    }
+
+    /// Write the commits of `branch_name` since `base_commit` to `output` as a
+    /// `git format-patch` series in a single mbox
+    pub fn export_patch_series(
+        &self,
+        repo_path: &Path,
+        base_commit: &Commit,
+        branch_name: &str,
+        output: &Path,
+    ) -> Result<(), GitServiceError> {
+        GitCli::new()
+            .format_patch(repo_path, &format!("{base_commit}..{branch_name}"), output)
+            .map_err(|e| {
+                GitServiceError::InvalidRepository(format!("git format-patch failed: {e}"))
+            })
+    }
+
+    /// Write the commits of `branch_name` since `base_commit` to `output` as a git bundle
+    /// holding `refs/heads/<branch_name>`
+    pub fn export_bundle(
+        &self,
+        repo_path: &Path,
+        base_commit: &Commit,
+        branch_name: &str,
+        output: &Path,
+    ) -> Result<(), GitServiceError> {
+        GitCli::new()
+            .bundle_create(repo_path, output, &format!("{base_commit}..{branch_name}"))
+            .map_err(|e| {
+                GitServiceError::InvalidRepository(format!("git bundle create failed: {e}"))
+            })
+    }
+
+    /// Commit a `git format-patch` series on top of the worktree's HEAD. A series that
+    /// doesn't apply is aborted and leaves HEAD where it was.
+    pub fn apply_patch_series(
+        &self,
+        worktree_path: &Path,
+        series: &Path,
+    ) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(worktree_path)?;
+        self.check_worktree_clean(&repo)?;
+        self.ensure_cli_commit_identity(worktree_path)?;
+
+        let git = self.committing_cli();
+        if let Err(e) = git.am(worktree_path, series) {
+            if let Err(abort_err) = git.abort_am(worktree_path) {
+                tracing::warn!("Failed to abort git am: {}", abort_err);
+            }
+            return Err(GitServiceError::MergeConflicts(format!(
+                "The patch series does not apply cleanly: {e}"
+            )));
+        }
+        self.checkout_submodules_and_lfs(worktree_path);
+        Ok(())
+    }
+
+    /// Reset the worktree to `commit_sha`, fetching it from the `refname` of a git bundle
+    pub fn reset_worktree_to_bundle(
+        &self,
+        worktree_path: &Path,
+        bundle: &Path,
+        refname: &str,
+        commit_sha: &str,
+    ) -> Result<(), GitServiceError> {
+        GitCli::new()
+            .fetch_bundle(worktree_path, bundle, refname)
+            .map_err(|e| {
+                GitServiceError::InvalidRepository(format!("git fetch from bundle failed: {e}"))
+            })?;
+        self.reset_worktree_to_commit(worktree_path, commit_sha, false)?;
+        self.checkout_submodules_and_lfs(worktree_path);
+        Ok(())
+    }
+
     pub fn prune_worktrees(&self, repo_path: &Path) -> Result<(), GitServiceError> {
         let git = GitCli::new();
         git.worktree_prune(repo_path)
@@ -1301,10 +1711,10 @@ impl GitService {
         let tree = repo.find_tree(tree_id)?;
 
         // Create a squash commit: use merged tree with base_commit as sole parent
-        let squash_commit_id = repo.commit(
+        let squash_commit_id = self.create_commit(
+            repo,
             None,           // Don't update any reference yet
-            signature,      // Author
-            signature,      // Committer
+            signature,      // Author and committer
             commit_message, // Custom message
             &tree,          // Merged tree content
             &[base_commit], // Single parent: base branch commit
@@ -1317,6 +1727,45 @@ impl GitService {
         Ok(squash_commit_id)
     }
 
+    fn perform_merge_commit(
+        &self,
+        repo: &Repository,
+        base_commit: &git2::Commit,
+        task_commit: &git2::Commit,
+        signature: &git2::Signature,
+        commit_message: &str,
+        base_branch_name: &str,
+    ) -> Result<git2::Oid, GitServiceError> {
+        let mut merge_opts = git2::MergeOptions::new();
+        merge_opts.find_renames(true);
+        merge_opts.fail_on_conflict(true);
+        let mut index = repo.merge_commits(base_commit, task_commit, Some(&merge_opts))?;
+
+        if index.has_conflicts() {
+            return Err(GitServiceError::MergeConflicts(
+                "Merge failed due to conflicts. Please resolve conflicts manually.".to_string(),
+            ));
+        }
+
+        let tree_id = index.write_tree_to(repo)?;
+        let tree = repo.find_tree(tree_id)?;
+
+        // Both branches are parents so the task's commits stay in the history
+        let merge_commit_id = self.create_commit(
+            repo,
+            None,
+            signature,
+            commit_message,
+            &tree,
+            &[base_commit, task_commit],
+        )?;
+
+        let refname = format!("refs/heads/{base_branch_name}");
+        repo.reference(&refname, merge_commit_id, true, "Merge commit")?;
+
+        Ok(merge_commit_id)
+    }
+
     /// Rebase a worktree branch onto a new base
     pub fn rebase_branch(
         &self,
@@ -1336,7 +1785,7 @@ impl GitService {
 
         // If a rebase is already in progress, refuse to proceed instead of
         // aborting (which might destroy user changes mid-rebase).
-        let git = GitCli::new();
+        let git = self.committing_cli();
         if git.is_rebase_in_progress(worktree_path).unwrap_or(false) {
             return Err(GitServiceError::RebaseInProgress);
         }
@@ -1393,6 +1842,9 @@ impl GitService {
                     );
                     return Err(GitServiceError::MergeConflicts(msg));
                 }
+                if signing::is_signing_failure(&stderr) {
+                    return Err(GitServiceError::SigningFailed(stderr));
+                }
                 return Err(GitServiceError::InvalidRepository(format!(
                     "Rebase failed: {}",
                     stderr.lines().next().unwrap_or("")
@@ -1578,6 +2030,61 @@ impl GitService {
         Ok(())
     }
 
+    /// Commit the resolved conflicts of a stopped rebase and carry on replaying the task
+    /// branch. Stopping again on a later commit is reported as `MergeConflicts`.
+    pub fn continue_rebase(&self, worktree_path: &Path) -> Result<(), GitServiceError> {
+        let git = self.committing_cli();
+        if !git.is_rebase_in_progress(worktree_path).unwrap_or(false) {
+            return Ok(());
+        }
+        self.ensure_cli_commit_identity(worktree_path)?;
+        match git.continue_rebase(worktree_path) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                let conflicts = git.get_conflicted_files(worktree_path).unwrap_or_default();
+                if conflicts.is_empty() {
+                    Err(GitServiceError::commit_failed(
+                        "git rebase --continue failed",
+                        e,
+                    ))
+                } else {
+                    Err(GitServiceError::MergeConflicts(format!(
+                        "Rebase stopped on another conflict. Conflicted files: {}.",
+                        conflicts.join(", ")
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Subjects of the commits on either side of a stopped rebase that touch `paths`
+    pub fn rebase_commit_subjects(
+        &self,
+        worktree_path: &Path,
+        paths: &[String],
+    ) -> Result<RebaseCommitSubjects, GitServiceError> {
+        let git = GitCli::new();
+        let Some((onto, orig_head)) = git
+            .rebase_state_commits(worktree_path)
+            .map_err(|e| GitServiceError::InvalidRepository(e.to_string()))?
+        else {
+            return Ok(RebaseCommitSubjects::default());
+        };
+        let log = |range: String| {
+            git.log_subjects(worktree_path, &range, paths)
+                .map_err(|e| GitServiceError::InvalidRepository(format!("git log failed: {e}")))
+        };
+
+        Ok(RebaseCommitSubjects {
+            replaying: git
+                .log_subjects(worktree_path, "REBASE_HEAD^!", &[])
+                .ok()
+                .and_then(|subjects| subjects.into_iter().next()),
+            base: log(format!("{orig_head}..{onto}"))?,
+            task: log(format!("{onto}..{orig_head}"))?,
+        })
+    }
+
     pub fn find_branch<'a>(
         repo: &'a Repository,
         branch_name: &str,
@@ -1595,21 +2102,27 @@ impl GitService {
         }
     }
 
-    /// Extract GitHub owner and repo name from git repo path
-    pub fn get_github_repo_info(
-        &self,
-        repo_path: &Path,
-    ) -> Result<GitHubRepoInfo, GitServiceError> {
+    /// URL of the default remote of the repo at `repo_path`
+    pub fn get_remote_url(&self, repo_path: &Path) -> Result<String, GitServiceError> {
         let repo = self.open_repo(repo_path)?;
         let remote_name = self.default_remote_name(&repo);
         let remote = repo.find_remote(&remote_name).map_err(|_| {
             GitServiceError::InvalidRepository(format!("No '{remote_name}' remote found"))
         })?;
 
-        let url = remote
+        remote
             .url()
-            .ok_or_else(|| GitServiceError::InvalidRepository("Remote has no URL".to_string()))?;
-        GitHubRepoInfo::from_remote_url(url).map_err(|e| {
+            .map(str::to_string)
+            .ok_or_else(|| GitServiceError::InvalidRepository("Remote has no URL".to_string()))
+    }
+
+    /// Extract GitHub owner and repo name from git repo path
+    pub fn get_github_repo_info(
+        &self,
+        repo_path: &Path,
+    ) -> Result<GitHubRepoInfo, GitServiceError> {
+        let url = self.get_remote_url(repo_path)?;
+        GitHubRepoInfo::from_remote_url(&url).map_err(|e| {
             GitServiceError::InvalidRepository(format!("Failed to parse remote URL: {e}"))
         })
     }
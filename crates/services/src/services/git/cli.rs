@@ -25,7 +25,10 @@ use std::{
 use thiserror::Error;
 use utils::shell::resolve_executable_path_blocking; // TODO: make GitCli async
 
-use crate::services::{filesystem_watcher::ALWAYS_SKIP_DIRS, git::Commit};
+use crate::services::{
+    filesystem_watcher::ALWAYS_SKIP_DIRS,
+    git::{Commit, SparseCheckout},
+};
 
 #[derive(Debug, Error)]
 pub enum GitCliError {
@@ -42,7 +45,10 @@ pub enum GitCliError {
 }
 
 #[derive(Clone, Default)]
-pub struct GitCli;
+pub struct GitCli {
+    /// `-c key=value` config passed to every command
+    config_overrides: Vec<(String, String)>,
+}
 
 /// Parsed change type from `git diff --name-status` output
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -79,15 +85,25 @@ pub struct StatusDiffOptions {
 
 impl GitCli {
     pub fn new() -> Self {
-        Self {}
+        Self::default()
+    }
+
+    /// A CLI that passes `overrides` as `-c key=value` to every command
+    pub fn with_config_overrides(overrides: Vec<(String, String)>) -> Self {
+        Self {
+            config_overrides: overrides,
+        }
     }
-    /// Run `git -C <repo> worktree add <path> <branch>` (optionally creating the branch with -b)
+    /// Run `git -C <repo> worktree add <path> <branch>` (optionally creating the branch with -b).
+    /// With `sparse`, the worktree is added without a checkout and only the sparse set is
+    /// checked out afterwards.
     pub fn worktree_add(
         &self,
         repo_path: &Path,
         worktree_path: &Path,
         branch: &str,
         create_branch: bool,
+        sparse: Option<&SparseCheckout>,
     ) -> Result<(), GitCliError> {
         self.ensure_available()?;
 
@@ -96,14 +112,167 @@ impl GitCli {
             args.push("-b".into());
             args.push(OsString::from(branch));
         }
+        if sparse.is_some() {
+            args.push("--no-checkout".into());
+        }
         args.push(worktree_path.as_os_str().into());
         args.push(OsString::from(branch));
         self.git(repo_path, args)?;
 
-        // Good practice: reapply sparse-checkout in the new worktree to ensure materialization matches
-        // Non-fatal if it fails or not configured.
-        let _ = self.git(worktree_path, ["sparse-checkout", "reapply"]);
+        match sparse {
+            Some(sparse) => {
+                self.sparse_checkout_set(worktree_path, sparse)?;
+                self.git(worktree_path, ["checkout"])?;
+            }
+            None => {
+                // Good practice: reapply sparse-checkout in the new worktree to ensure materialization matches
+                // Non-fatal if it fails or not configured.
+                let _ = self.git(worktree_path, ["sparse-checkout", "reapply"]);
+            }
+        }
+
+        Ok(())
+    }
 
+    /// Run `git sparse-checkout set` in a worktree, in cone mode or not as configured.
+    /// Patterns are passed on stdin so they may start with `!` or `-`.
+    pub fn sparse_checkout_set(
+        &self,
+        worktree_path: &Path,
+        sparse: &SparseCheckout,
+    ) -> Result<(), GitCliError> {
+        let mode = if sparse.cone() { "--cone" } else { "--no-cone" };
+        let mut input = sparse.patterns().join("\n");
+        input.push('\n');
+        self.git_with_stdin(
+            worktree_path,
+            ["sparse-checkout", "set", mode, "--stdin"],
+            None,
+            input.as_bytes(),
+        )?;
+        Ok(())
+    }
+
+    /// The sparse checkout of a worktree, or `None` when the full tree is checked out
+    pub fn sparse_checkout(
+        &self,
+        worktree_path: &Path,
+    ) -> Result<Option<SparseCheckout>, GitCliError> {
+        let enabled = self.git(
+            worktree_path,
+            [
+                "config",
+                "--bool",
+                "--default",
+                "false",
+                "core.sparseCheckout",
+            ],
+        )?;
+        if enabled.trim() != "true" {
+            return Ok(None);
+        }
+        let cone = self.git(
+            worktree_path,
+            [
+                "config",
+                "--bool",
+                "--default",
+                "false",
+                "core.sparseCheckoutCone",
+            ],
+        )?;
+        let patterns = self.git(worktree_path, ["sparse-checkout", "list"])?;
+        Ok(SparseCheckout::from_patterns(
+            &patterns,
+            cone.trim() == "true",
+        ))
+    }
+
+    /// Initialize and check out the submodules of a worktree, recursively
+    pub fn submodule_update(&self, worktree_path: &Path) -> Result<(), GitCliError> {
+        self.git(
+            worktree_path,
+            ["submodule", "update", "--init", "--recursive"],
+        )?;
+        Ok(())
+    }
+
+    /// Download the Git LFS objects of a worktree's checkout and replace their pointers
+    pub fn lfs_pull(&self, worktree_path: &Path) -> Result<(), GitCliError> {
+        self.git(worktree_path, ["lfs", "pull"])?;
+        Ok(())
+    }
+
+    /// Write the commits in `range` to `output` as a single mbox of patches
+    pub fn format_patch(
+        &self,
+        repo_path: &Path,
+        range: &str,
+        output: &Path,
+    ) -> Result<(), GitCliError> {
+        let mut output_arg = OsString::from("--output=");
+        output_arg.push(output);
+        let args: Vec<OsString> = vec![
+            "format-patch".into(),
+            "--binary".into(),
+            output_arg,
+            range.into(),
+        ];
+        self.git(repo_path, args)?;
+        Ok(())
+    }
+
+    /// Write the commits in `range`, and the ref it ends at, to a bundle file
+    pub fn bundle_create(
+        &self,
+        repo_path: &Path,
+        output: &Path,
+        range: &str,
+    ) -> Result<(), GitCliError> {
+        let args: Vec<OsString> = vec![
+            "bundle".into(),
+            "create".into(),
+            "--quiet".into(),
+            output.as_os_str().into(),
+            range.into(),
+        ];
+        self.git(repo_path, args)?;
+        Ok(())
+    }
+
+    /// Fetch `refname` from a bundle file into the worktree's FETCH_HEAD
+    pub fn fetch_bundle(
+        &self,
+        worktree_path: &Path,
+        bundle: &Path,
+        refname: &str,
+    ) -> Result<(), GitCliError> {
+        let args: Vec<OsString> = vec![
+            "fetch".into(),
+            "--quiet".into(),
+            "--no-tags".into(),
+            bundle.as_os_str().into(),
+            refname.into(),
+        ];
+        self.git(worktree_path, args)?;
+        Ok(())
+    }
+
+    /// Apply an mbox of patches as commits on top of the worktree's HEAD
+    pub fn am(&self, worktree_path: &Path, series: &Path) -> Result<(), GitCliError> {
+        let args: Vec<OsString> = vec![
+            "am".into(),
+            "--quiet".into(),
+            "--3way".into(),
+            series.as_os_str().into(),
+        ];
+        self.git(worktree_path, args)?;
+        Ok(())
+    }
+
+    /// Abort a `git am` in progress, restoring the worktree's HEAD
+    pub fn abort_am(&self, worktree_path: &Path) -> Result<(), GitCliError> {
+        self.git(worktree_path, ["am", "--abort"])?;
         Ok(())
     }
 
@@ -594,6 +763,42 @@ impl GitCli {
         Ok(sha)
     }
 
+    /// Checkout base branch and fast-forward it to from_branch. Returns new HEAD sha.
+    pub fn merge_fast_forward(
+        &self,
+        repo_path: &Path,
+        base_branch: &str,
+        from_branch: &str,
+    ) -> Result<String, GitCliError> {
+        self.git(repo_path, ["checkout", base_branch]).map(|_| ())?;
+        self.git(repo_path, ["merge", "--ff-only", from_branch])
+            .map(|_| ())?;
+        let sha = self
+            .git(repo_path, ["rev-parse", "HEAD"])?
+            .trim()
+            .to_string();
+        Ok(sha)
+    }
+
+    /// Checkout base branch and merge from_branch with a merge commit, even when a
+    /// fast-forward is possible. Returns new HEAD sha.
+    pub fn merge_no_ff_commit(
+        &self,
+        repo_path: &Path,
+        base_branch: &str,
+        from_branch: &str,
+        message: &str,
+    ) -> Result<String, GitCliError> {
+        self.git(repo_path, ["checkout", base_branch]).map(|_| ())?;
+        self.git(repo_path, ["merge", "--no-ff", "-m", message, from_branch])
+            .map(|_| ())?;
+        let sha = self
+            .git(repo_path, ["rev-parse", "HEAD"])?
+            .trim()
+            .to_string();
+        Ok(sha)
+    }
+
     /// Update a ref to a specific sha in the repo.
     pub fn update_ref(
         &self,
@@ -640,6 +845,62 @@ impl GitCli {
         }
         Ok(files)
     }
+
+    /// The `(onto, orig_head)` commits of the rebase in progress in this worktree, read
+    /// from Git's rebase state directory. Returns None when no rebase is in progress.
+    pub fn rebase_state_commits(
+        &self,
+        worktree_path: &Path,
+    ) -> Result<Option<(String, String)>, GitCliError> {
+        for state_dir in ["rebase-merge", "rebase-apply"] {
+            let out = self.git(worktree_path, ["rev-parse", "--git-path", state_dir])?;
+            // The path is relative to the worktree unless the worktree is linked
+            let state_dir = worktree_path.join(out.trim());
+            if !state_dir.exists() {
+                continue;
+            }
+            let read = |name: &str| {
+                std::fs::read_to_string(state_dir.join(name))
+                    .map(|s| s.trim().to_string())
+                    .map_err(|e| GitCliError::CommandFailed(format!("reading {name}: {e}")))
+            };
+            return Ok(Some((read("onto")?, read("orig-head")?)));
+        }
+        Ok(None)
+    }
+
+    /// Subjects of the commits in `range` that touch any of `paths`, newest first.
+    pub fn log_subjects(
+        &self,
+        worktree_path: &Path,
+        range: &str,
+        paths: &[String],
+    ) -> Result<Vec<String>, GitCliError> {
+        let mut args: Vec<OsString> = vec!["log".into(), "--format=%s".into(), range.into()];
+        args.push("--".into());
+        args.extend(paths.iter().map(OsString::from));
+        let out = self.git(worktree_path, args)?;
+        Ok(out
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Stage the working tree and continue the rebase in progress without opening an
+    /// editor. A commit whose resolution leaves nothing to commit is skipped.
+    pub fn continue_rebase(&self, worktree_path: &Path) -> Result<(), GitCliError> {
+        self.add_all(worktree_path)?;
+        let envs = [(OsString::from("GIT_EDITOR"), OsString::from("true"))];
+        let step = if self.has_staged_changes(worktree_path)? {
+            "--continue"
+        } else {
+            "--skip"
+        };
+        self.git_with_env(worktree_path, ["rebase", step], &envs)?;
+        Ok(())
+    }
 }
 
 // Private methods
@@ -704,6 +965,9 @@ impl GitCli {
         let git = resolve_executable_path_blocking("git").ok_or(GitCliError::NotAvailable)?;
         let mut cmd = Command::new(&git);
         cmd.arg("-C").arg(repo_path);
+        for (key, value) in &self.config_overrides {
+            cmd.arg("-c").arg(format!("{key}={value}"));
+        }
 
         if let Some(envs) = envs {
             for (k, v) in envs {
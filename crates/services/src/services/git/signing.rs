@@ -0,0 +1,354 @@
+//! Signing of the commits vibe-kanban creates itself.
+//!
+//! Commits made through the git CLI (agent turn commits, CLI merges, rebases) are signed
+//! by git according to the repository's config; the vibe-kanban setting is layered on top
+//! as `-c` overrides. Commits built with libgit2 (merges into a base branch that isn't
+//! checked out) are signed here the same way git would: the commit buffer is handed to
+//! `gpg`, `gpgsm` or `ssh-keygen` and the signature stored in the `gpgsig` header.
+use std::{
+    io::Write as _,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use git2::Repository;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use super::GitServiceError;
+
+/// Whether commits created by vibe-kanban are signed
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum CommitSigningMode {
+    /// Follow `commit.gpgsign` of the repository's git config
+    #[default]
+    GitConfig,
+    Always,
+    Never,
+}
+
+/// Signature format, as in git's `gpg.format`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum SigningFormat {
+    Openpgp,
+    Ssh,
+    X509,
+}
+
+impl SigningFormat {
+    fn as_git_config(&self) -> &'static str {
+        match self {
+            SigningFormat::Openpgp => "openpgp",
+            SigningFormat::Ssh => "ssh",
+            SigningFormat::X509 => "x509",
+        }
+    }
+
+    fn from_git_config(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "openpgp" => Some(SigningFormat::Openpgp),
+            "ssh" => Some(SigningFormat::Ssh),
+            "x509" => Some(SigningFormat::X509),
+            _ => None,
+        }
+    }
+
+    fn default_program(&self) -> &'static str {
+        match self {
+            SigningFormat::Openpgp => "gpg",
+            SigningFormat::Ssh => "ssh-keygen",
+            SigningFormat::X509 => "gpgsm",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub struct CommitSigningConfig {
+    #[serde(default)]
+    pub mode: CommitSigningMode,
+    /// Overrides `gpg.format` from the git config
+    #[serde(default)]
+    pub format: Option<SigningFormat>,
+    /// Overrides `user.signingkey` from the git config: a GPG key id, or an SSH key path
+    /// or literal public key
+    #[serde(default)]
+    pub signing_key: Option<String>,
+}
+
+impl CommitSigningConfig {
+    /// `-c` overrides for git commands that create commits
+    pub(super) fn cli_overrides(&self) -> Vec<(String, String)> {
+        let mut overrides = Vec::new();
+        match self.mode {
+            CommitSigningMode::GitConfig => {}
+            CommitSigningMode::Always => {
+                overrides.push(("commit.gpgsign".to_string(), "true".to_string()))
+            }
+            CommitSigningMode::Never => {
+                overrides.push(("commit.gpgsign".to_string(), "false".to_string()));
+                return overrides;
+            }
+        }
+        if let Some(format) = self.format {
+            overrides.push(("gpg.format".to_string(), format.as_git_config().to_string()));
+        }
+        if let Some(key) = self.signing_key.as_deref().filter(|k| !k.trim().is_empty()) {
+            overrides.push(("user.signingkey".to_string(), key.trim().to_string()));
+        }
+        overrides
+    }
+
+    /// The signer for libgit2 commits in `repo`, or `None` when they aren't signed
+    pub(super) fn signer(&self, repo: &Repository) -> Result<Option<Signer>, GitServiceError> {
+        let cfg = repo.config()?;
+        let enabled = match self.mode {
+            CommitSigningMode::GitConfig => cfg.get_bool("commit.gpgsign").unwrap_or(false),
+            CommitSigningMode::Always => true,
+            CommitSigningMode::Never => false,
+        };
+        if !enabled {
+            return Ok(None);
+        }
+
+        let format = match self.format {
+            Some(format) => format,
+            None => match cfg.get_string("gpg.format") {
+                Ok(value) => SigningFormat::from_git_config(&value).ok_or_else(|| {
+                    GitServiceError::SigningFailed(format!("unsupported gpg.format '{value}'"))
+                })?,
+                Err(_) => SigningFormat::Openpgp,
+            },
+        };
+        let key = self
+            .signing_key
+            .clone()
+            .or_else(|| cfg.get_string("user.signingkey").ok())
+            .map(|key| key.trim().to_string())
+            .filter(|key| !key.is_empty());
+        let program = cfg
+            .get_string(&format!("gpg.{}.program", format.as_git_config()))
+            .ok()
+            .or_else(|| {
+                // gpg.program predates gpg.format and only applies to OpenPGP
+                (format == SigningFormat::Openpgp)
+                    .then(|| cfg.get_string("gpg.program").ok())
+                    .flatten()
+            })
+            .unwrap_or_else(|| format.default_program().to_string());
+
+        Ok(Some(Signer {
+            format,
+            key,
+            program,
+        }))
+    }
+}
+
+/// A resolved signing setup for one repository
+#[derive(Debug, Clone)]
+pub(super) struct Signer {
+    format: SigningFormat,
+    key: Option<String>,
+    program: String,
+}
+
+impl Signer {
+    /// Detached ASCII-armored signature of a commit buffer
+    pub(super) fn sign(&self, buffer: &str) -> Result<String, GitServiceError> {
+        match self.format {
+            SigningFormat::Openpgp | SigningFormat::X509 => self.sign_gpg(buffer),
+            SigningFormat::Ssh => self.sign_ssh(buffer),
+        }
+    }
+
+    fn sign_gpg(&self, buffer: &str) -> Result<String, GitServiceError> {
+        let mut cmd = Command::new(&self.program);
+        cmd.arg("--status-fd=2").arg("-bsa");
+        if let Some(key) = &self.key {
+            cmd.arg("-u").arg(key);
+        }
+        let output = run_with_stdin(&mut cmd, buffer.as_bytes(), &self.program)?;
+        String::from_utf8(output)
+            .ok()
+            .filter(|sig| sig.contains("-----BEGIN"))
+            .ok_or_else(|| {
+                GitServiceError::SigningFailed(format!("{} produced no signature", self.program))
+            })
+    }
+
+    fn sign_ssh(&self, buffer: &str) -> Result<String, GitServiceError> {
+        let key = self.key.as_deref().ok_or_else(|| {
+            GitServiceError::SigningFailed(
+                "user.signingkey must be set to sign commits with SSH".to_string(),
+            )
+        })?;
+
+        // Like git, a literal public key is written to a file and the private key is
+        // looked up in the ssh-agent
+        let literal_key = key
+            .strip_prefix("key::")
+            .or_else(|| is_literal_ssh_key(key).then_some(key));
+        let mut key_file = None;
+        let key_path = match literal_key {
+            Some(literal) => {
+                let mut file = tempfile::NamedTempFile::new()?;
+                file.write_all(literal.as_bytes())?;
+                let path = file.path().to_path_buf();
+                key_file = Some(file);
+                path
+            }
+            None => expand_home(key),
+        };
+
+        let mut buffer_file = tempfile::NamedTempFile::new()?;
+        buffer_file.write_all(buffer.as_bytes())?;
+        let buffer_path = buffer_file.path().to_path_buf();
+
+        let mut cmd = Command::new(&self.program);
+        cmd.args(["-Y", "sign", "-n", "git", "-f"]).arg(&key_path);
+        if key_file.is_some() {
+            cmd.arg("-U");
+        }
+        cmd.arg(&buffer_path);
+        run_with_stdin(&mut cmd, &[], &self.program)?;
+
+        let mut sig_path = buffer_path.into_os_string();
+        sig_path.push(".sig");
+        let sig_path = PathBuf::from(sig_path);
+        let signature = std::fs::read_to_string(&sig_path).map_err(|e| {
+            GitServiceError::SigningFailed(format!("{} wrote no signature: {e}", self.program))
+        });
+        let _ = std::fs::remove_file(&sig_path);
+        signature
+    }
+}
+
+fn run_with_stdin(
+    cmd: &mut Command,
+    input: &[u8],
+    program: &str,
+) -> Result<Vec<u8>, GitServiceError> {
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| GitServiceError::SigningFailed(format!("cannot run {program}: {e}")))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input)?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // gpg reports progress on the status fd; keep only the human-readable lines
+        let message = stderr
+            .lines()
+            .filter(|line| !line.starts_with("[GNUPG:]"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        return Err(GitServiceError::SigningFailed(format!(
+            "{program} failed to sign the commit: {}",
+            message.trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+fn is_literal_ssh_key(key: &str) -> bool {
+    key.starts_with("ssh-") || key.starts_with("ecdsa-") || key.starts_with("sk-")
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Whether a failed git command that creates commits failed because signing did
+pub(super) fn is_signing_failure(message: &str) -> bool {
+    let lower = message.to_ascii_lowercase();
+    lower.contains("failed to sign")
+        || lower.contains("unable to sign")
+        || lower.contains("cannot run gpg")
+        || lower.contains("cannot run ssh-keygen")
+        || lower.contains("couldn't load public key")
+        || lower.contains("gpg.ssh.defaultkeycommand")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cli_overrides_follow_mode() {
+        let config = CommitSigningConfig {
+            mode: CommitSigningMode::Always,
+            format: Some(SigningFormat::Ssh),
+            signing_key: Some(" ~/.ssh/id_ed25519.pub ".to_string()),
+        };
+        assert_eq!(
+            config.cli_overrides(),
+            vec![
+                ("commit.gpgsign".to_string(), "true".to_string()),
+                ("gpg.format".to_string(), "ssh".to_string()),
+                (
+                    "user.signingkey".to_string(),
+                    "~/.ssh/id_ed25519.pub".to_string()
+                ),
+            ]
+        );
+
+        let never = CommitSigningConfig {
+            mode: CommitSigningMode::Never,
+            ..config
+        };
+        assert_eq!(
+            never.cli_overrides(),
+            vec![("commit.gpgsign".to_string(), "false".to_string())]
+        );
+
+        assert!(CommitSigningConfig::default().cli_overrides().is_empty());
+    }
+
+    #[test]
+    fn signer_reads_git_config() {
+        let td = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init(td.path()).unwrap();
+        let mut cfg = repo.config().unwrap();
+        cfg.set_bool("commit.gpgsign", true).unwrap();
+        cfg.set_str("gpg.format", "ssh").unwrap();
+        cfg.set_str("user.signingkey", "/keys/id.pub").unwrap();
+
+        let signer = CommitSigningConfig::default()
+            .signer(&repo)
+            .unwrap()
+            .expect("signing is enabled in the git config");
+        assert_eq!(signer.format, SigningFormat::Ssh);
+        assert_eq!(signer.key.as_deref(), Some("/keys/id.pub"));
+        assert_eq!(signer.program, "ssh-keygen");
+
+        let never = CommitSigningConfig {
+            mode: CommitSigningMode::Never,
+            ..Default::default()
+        };
+        assert!(never.signer(&repo).unwrap().is_none());
+    }
+
+    #[test]
+    fn recognises_signing_failures() {
+        assert!(is_signing_failure(
+            "--- stderr\nerror: gpg failed to sign the data\nfatal: failed to write commit object"
+        ));
+        assert!(is_signing_failure(
+            "error: Couldn't load public key /missing.pub: No such file or directory"
+        ));
+        assert!(!is_signing_failure("error: pathspec 'x' did not match"));
+    }
+}
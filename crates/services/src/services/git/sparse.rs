@@ -0,0 +1,163 @@
+//! Sparse checkout of workspace worktrees.
+//!
+//! A project repo can list the parts of the repository its attempts need; worktrees are
+//! then added with `--no-checkout` and only those parts are materialized. In cone mode
+//! each pattern is a directory: everything below it is checked out, along with the files
+//! directly inside each of its parent directories and the repository root. Without cone
+//! mode the patterns are gitignore-style lines, as in `git sparse-checkout set --no-cone`.
+use std::path::Path;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+#[derive(Debug, Clone)]
+pub struct SparseCheckout {
+    patterns: Vec<String>,
+    cone: bool,
+    matcher: Option<Gitignore>,
+}
+
+impl PartialEq for SparseCheckout {
+    fn eq(&self, other: &Self) -> bool {
+        self.patterns == other.patterns && self.cone == other.cone
+    }
+}
+
+impl Eq for SparseCheckout {}
+
+impl SparseCheckout {
+    /// Parse newline-separated patterns; blank lines and `#` comments are skipped.
+    /// Returns `None` when no patterns are left, i.e. the full tree is checked out.
+    pub fn from_patterns(patterns: &str, cone: bool) -> Option<Self> {
+        let patterns: Vec<String> = patterns
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let line = line.replace('\\', "/");
+                if cone {
+                    line.trim_matches('/').to_string()
+                } else {
+                    line
+                }
+            })
+            .filter(|line| !line.is_empty())
+            .collect();
+        if patterns.is_empty() {
+            return None;
+        }
+
+        let matcher = if cone {
+            None
+        } else {
+            let mut builder = GitignoreBuilder::new("");
+            for pattern in &patterns {
+                if let Err(e) = builder.add_line(None, pattern) {
+                    tracing::warn!("Invalid sparse-checkout pattern '{pattern}': {e}");
+                }
+            }
+            builder.build().ok()
+        };
+
+        Some(Self {
+            patterns,
+            cone,
+            matcher,
+        })
+    }
+
+    /// The sparse checkout configured for a project repo, if any
+    pub fn from_config(patterns: Option<&str>, cone: bool) -> Option<Self> {
+        patterns.and_then(|patterns| Self::from_patterns(patterns, cone))
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    pub fn cone(&self) -> bool {
+        self.cone
+    }
+
+    /// Whether a repo-relative path is materialized by this sparse checkout
+    pub fn includes(&self, path: &str, is_dir: bool) -> bool {
+        let path = path.replace('\\', "/");
+        let path = path.trim_matches('/');
+        if path.is_empty() {
+            return true;
+        }
+
+        if !self.cone {
+            return self.matcher.as_ref().is_none_or(|matcher| {
+                matcher
+                    .matched_path_or_any_parents(Path::new(path), is_dir)
+                    .is_ignore()
+            });
+        }
+
+        let dir = if is_dir {
+            path
+        } else {
+            match path.rsplit_once('/') {
+                Some((parent, _)) => parent,
+                // Files in the repository root are always included
+                None => return true,
+            }
+        };
+        self.patterns.iter().any(|pattern| {
+            dir == pattern
+                || is_below(dir, pattern)
+                // Parents of a pattern directory keep their immediate files
+                || is_below(pattern, dir)
+        })
+    }
+}
+
+fn is_below(path: &str, dir: &str) -> bool {
+    path.strip_prefix(dir)
+        .is_some_and(|rest| rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_patterns_skipping_comments_and_blanks() {
+        let sparse = SparseCheckout::from_patterns(
+            "# services we work on\n/services/api/\n\n  web \n",
+            true,
+        )
+        .unwrap();
+        assert_eq!(sparse.patterns(), ["services/api", "web"]);
+        assert!(SparseCheckout::from_patterns("\n# nothing\n", true).is_none());
+        assert!(SparseCheckout::from_config(None, true).is_none());
+    }
+
+    #[test]
+    fn cone_mode_includes_pattern_dirs_and_files_of_their_parents() {
+        let sparse = SparseCheckout::from_patterns("services/api", true).unwrap();
+
+        assert!(sparse.includes("README.md", false));
+        assert!(sparse.includes("services/Cargo.toml", false));
+        assert!(sparse.includes("services/api/src/main.rs", false));
+        assert!(sparse.includes("services", true));
+        assert!(sparse.includes("services/api/src", true));
+
+        assert!(!sparse.includes("services/billing/src/lib.rs", false));
+        assert!(!sparse.includes("services/billing", true));
+        assert!(!sparse.includes("services/api-gateway/main.rs", false));
+        assert!(!sparse.includes("docs/guide.md", false));
+    }
+
+    #[test]
+    fn non_cone_mode_uses_gitignore_style_patterns() {
+        let sparse =
+            SparseCheckout::from_patterns("/docs/\n!/docs/internal/\n*.toml", false).unwrap();
+
+        assert!(sparse.includes("docs/guide.md", false));
+        assert!(sparse.includes("crates/db/Cargo.toml", false));
+        assert!(!sparse.includes("docs/internal/notes.md", false));
+        assert!(!sparse.includes("README.md", false));
+        assert!(!sparse.includes("src/main.rs", false));
+    }
+}
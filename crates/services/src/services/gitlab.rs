@@ -0,0 +1,435 @@
+use std::{future::Future, time::Duration};
+
+use backon::{ExponentialBuilder, Retryable};
+use chrono::{DateTime, Utc};
+use db::models::merge::{MergeStatus, PullRequestInfo};
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use thiserror::Error;
+use tracing::info;
+
+use crate::services::{
+    config::GitLabConfig,
+    forge::RemoteUrl,
+    github::{CreatePrRequest, UnifiedPrComment},
+};
+
+const NOTES_PER_PAGE: usize = 100;
+
+#[derive(Debug, Error)]
+pub enum GitLabServiceError {
+    #[error("Repository error: {0}")]
+    Repository(String),
+    #[error("Merge request error: {0}")]
+    MergeRequest(String),
+    #[error(
+        "GitLab authentication failed: {0}. Add a personal access token with the `api` scope in Settings or set GITLAB_TOKEN"
+    )]
+    AuthFailed(String),
+    #[error("Insufficient permissions: {0}")]
+    InsufficientPermissions(String),
+    #[error("GitLab project not found or no access: {0}")]
+    ProjectNotFoundOrNoAccess(String),
+    #[error("GitLab request failed: {0}")]
+    Request(String),
+    #[error("GitLab API returned {status}: {body}")]
+    Api { status: u16, body: String },
+}
+
+impl GitLabServiceError {
+    pub fn should_retry(&self) -> bool {
+        match self {
+            Self::Request(_) => true,
+            Self::Api { status, .. } => (500..=599).contains(status),
+            _ => false,
+        }
+    }
+
+    fn from_response(status: StatusCode, body: String) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED => Self::AuthFailed(body),
+            StatusCode::FORBIDDEN => Self::InsufficientPermissions(body),
+            StatusCode::NOT_FOUND => Self::ProjectNotFoundOrNoAccess(body),
+            _ => Self::Api {
+                status: status.as_u16(),
+                body,
+            },
+        }
+    }
+}
+
+/// A GitLab project, addressed by its full namespace path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitLabRepoInfo {
+    /// Scheme and host of the instance, e.g. `https://gitlab.com`
+    pub base_url: String,
+    /// Namespace and project, e.g. `group/subgroup/project`
+    pub project_path: String,
+}
+
+impl GitLabRepoInfo {
+    /// Supports HTTPS, SSH and merge request URLs. Subgroups are kept in the project path.
+    pub fn from_remote_url(remote_url: &str) -> Result<Self, GitLabServiceError> {
+        let remote = RemoteUrl::parse(remote_url).ok_or_else(|| {
+            GitLabServiceError::Repository(format!("Invalid GitLab URL format: {remote_url}"))
+        })?;
+        if !remote.path.contains('/') {
+            return Err(GitLabServiceError::Repository(format!(
+                "GitLab URL has no project path: {remote_url}"
+            )));
+        }
+
+        Ok(Self {
+            base_url: remote.web_base_url,
+            project_path: remote.path,
+        })
+    }
+
+    fn api_url(&self, endpoint: &str) -> String {
+        let project_id: String =
+            url::form_urlencoded::byte_serialize(self.project_path.as_bytes()).collect();
+        format!("{}/api/v4/projects/{project_id}{endpoint}", self.base_url)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MergeRequest {
+    iid: i64,
+    web_url: String,
+    state: String,
+    merged_at: Option<DateTime<Utc>>,
+    merge_commit_sha: Option<String>,
+    squash_commit_sha: Option<String>,
+}
+
+impl From<MergeRequest> for PullRequestInfo {
+    fn from(mr: MergeRequest) -> Self {
+        PullRequestInfo {
+            number: mr.iid,
+            url: mr.web_url,
+            status: merge_status(&mr.state),
+            merged_at: mr.merged_at,
+            merge_commit_sha: mr.merge_commit_sha.or(mr.squash_commit_sha),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct CreateMergeRequest<'a> {
+    source_branch: &'a str,
+    target_branch: &'a str,
+    title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Note {
+    id: i64,
+    body: String,
+    author: NoteAuthor,
+    created_at: DateTime<Utc>,
+    system: bool,
+    position: Option<NotePosition>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NoteAuthor {
+    username: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct NotePosition {
+    new_path: Option<String>,
+    old_path: Option<String>,
+    new_line: Option<i64>,
+    old_line: Option<i64>,
+}
+
+fn merge_status(state: &str) -> MergeStatus {
+    match state {
+        "opened" => MergeStatus::Open,
+        "merged" => MergeStatus::Merged,
+        "closed" | "locked" => MergeStatus::Closed,
+        _ => MergeStatus::Unknown,
+    }
+}
+
+/// Merge requests on GitLab.com or a self-hosted instance through the REST API
+#[derive(Debug, Clone)]
+pub struct GitLabService {
+    client: Client,
+    token: String,
+}
+
+impl GitLabService {
+    /// Uses the token from the config, falling back to the `GITLAB_TOKEN` environment variable
+    pub fn new(config: &GitLabConfig) -> Result<Self, GitLabServiceError> {
+        let token = config
+            .token
+            .clone()
+            .filter(|token| !token.trim().is_empty())
+            .or_else(|| std::env::var("GITLAB_TOKEN").ok())
+            .filter(|token| !token.trim().is_empty())
+            .ok_or_else(|| {
+                GitLabServiceError::AuthFailed("no access token configured".to_string())
+            })?;
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .user_agent(concat!("vibe-kanban/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| GitLabServiceError::Request(e.to_string()))?;
+
+        Ok(Self { client, token })
+    }
+
+    pub async fn check_token(&self, repo_info: &GitLabRepoInfo) -> Result<(), GitLabServiceError> {
+        let url = format!("{}/api/v4/user", repo_info.base_url);
+        self.with_retry(|| async {
+            self.send::<serde_json::Value>(self.client.get(&url))
+                .await
+                .map(|_| ())
+        })
+        .await
+    }
+
+    /// Create a merge request. Drafts are marked with the `Draft:` title prefix.
+    pub async fn create_mr(
+        &self,
+        repo_info: &GitLabRepoInfo,
+        request: &CreatePrRequest,
+    ) -> Result<PullRequestInfo, GitLabServiceError> {
+        let title = if request.draft.unwrap_or(false) {
+            format!("Draft: {}", request.title)
+        } else {
+            request.title.clone()
+        };
+        let payload = CreateMergeRequest {
+            source_branch: &request.head_branch,
+            target_branch: &request.base_branch,
+            title,
+            description: request.body.as_deref(),
+        };
+        let url = repo_info.api_url("/merge_requests");
+
+        let mr: MergeRequest = self
+            .with_retry(|| async { self.send(self.client.post(&url).json(&payload)).await })
+            .await
+            .map_err(|err| match err {
+                GitLabServiceError::Api { status: 409, body } => {
+                    GitLabServiceError::MergeRequest(format!(
+                        "A merge request for {} already exists: {body}",
+                        request.head_branch
+                    ))
+                }
+                err => err,
+            })?;
+
+        info!(
+            "Created GitLab MR !{} for branch {} in {}",
+            mr.iid, request.head_branch, repo_info.project_path
+        );
+
+        Ok(mr.into())
+    }
+
+    /// Update and get the status of a merge request
+    pub async fn update_mr_status(
+        &self,
+        repo_info: &GitLabRepoInfo,
+        mr_iid: i64,
+    ) -> Result<PullRequestInfo, GitLabServiceError> {
+        let url = repo_info.api_url(&format!("/merge_requests/{mr_iid}"));
+        let mr: MergeRequest = self
+            .with_retry(|| async { self.send(self.client.get(&url)).await })
+            .await?;
+        Ok(mr.into())
+    }
+
+    /// List all merge requests from a branch (including closed/merged), newest first
+    pub async fn list_all_mrs_for_branch(
+        &self,
+        repo_info: &GitLabRepoInfo,
+        branch_name: &str,
+    ) -> Result<Vec<PullRequestInfo>, GitLabServiceError> {
+        let url = repo_info.api_url("/merge_requests");
+        let mrs: Vec<MergeRequest> = self
+            .with_retry(|| async {
+                self.send(self.client.get(&url).query(&[
+                    ("source_branch", branch_name),
+                    ("state", "all"),
+                    ("order_by", "created_at"),
+                    ("sort", "desc"),
+                ]))
+                .await
+            })
+            .await?;
+
+        let mut prs: Vec<PullRequestInfo> = mrs.into_iter().map(PullRequestInfo::from).collect();
+        // Prefer an open merge request, like the GitHub lookup does
+        prs.sort_by_key(|pr| !matches!(pr.status, MergeStatus::Open));
+        Ok(prs)
+    }
+
+    /// Fetch the comments of a merge request in creation order. Diff notes become review
+    /// comments; system notes such as "added 1 commit" are skipped.
+    pub async fn get_mr_comments(
+        &self,
+        repo_info: &GitLabRepoInfo,
+        mr_iid: i64,
+    ) -> Result<Vec<UnifiedPrComment>, GitLabServiceError> {
+        let url = repo_info.api_url(&format!("/merge_requests/{mr_iid}/notes"));
+        let mr_url = format!(
+            "{}/{}/-/merge_requests/{mr_iid}",
+            repo_info.base_url, repo_info.project_path
+        );
+
+        let mut notes: Vec<Note> = Vec::new();
+        let mut page = 1;
+        loop {
+            let batch: Vec<Note> = self
+                .with_retry(|| async {
+                    self.send(self.client.get(&url).query(&[
+                        ("sort", "asc".to_string()),
+                        ("order_by", "created_at".to_string()),
+                        ("per_page", NOTES_PER_PAGE.to_string()),
+                        ("page", page.to_string()),
+                    ]))
+                    .await
+                })
+                .await?;
+            let done = batch.len() < NOTES_PER_PAGE;
+            notes.extend(batch);
+            if done {
+                break;
+            }
+            page += 1;
+        }
+
+        let mut comments: Vec<UnifiedPrComment> = notes
+            .into_iter()
+            .filter(|note| !note.system)
+            .map(|note| note_to_comment(note, &mr_url))
+            .collect();
+        comments.sort_by_key(|comment| comment_created_at(comment));
+        Ok(comments)
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<T, GitLabServiceError> {
+        let response = request
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()
+            .await
+            .map_err(|e| GitLabServiceError::Request(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(GitLabServiceError::from_response(status, body));
+        }
+
+        response
+            .json::<T>()
+            .await
+            .map_err(|e| GitLabServiceError::Request(format!("Unexpected response: {e}")))
+    }
+
+    async fn with_retry<T, F, Fut>(&self, call: F) -> Result<T, GitLabServiceError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, GitLabServiceError>>,
+    {
+        call.retry(
+            &ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_times(3)
+                .with_jitter(),
+        )
+        .when(|e: &GitLabServiceError| e.should_retry())
+        .notify(|err: &GitLabServiceError, dur: Duration| {
+            tracing::warn!(
+                "GitLab API call failed, retrying after {:.2}s: {}",
+                dur.as_secs_f64(),
+                err
+            );
+        })
+        .await
+    }
+}
+
+fn note_to_comment(note: Note, mr_url: &str) -> UnifiedPrComment {
+    let url = format!("{mr_url}#note_{}", note.id);
+    match note.position {
+        Some(position) => UnifiedPrComment::Review {
+            id: note.id,
+            author: note.author.username,
+            author_association: String::new(),
+            body: note.body,
+            created_at: note.created_at,
+            url,
+            path: position.new_path.or(position.old_path).unwrap_or_default(),
+            line: position.new_line.or(position.old_line),
+            // GitLab notes don't carry the surrounding hunk
+            diff_hunk: String::new(),
+        },
+        None => UnifiedPrComment::General {
+            id: note.id.to_string(),
+            author: note.author.username,
+            author_association: String::new(),
+            body: note.body,
+            created_at: note.created_at,
+            url,
+        },
+    }
+}
+
+fn comment_created_at(comment: &UnifiedPrComment) -> DateTime<Utc> {
+    match comment {
+        UnifiedPrComment::General { created_at, .. }
+        | UnifiedPrComment::Review { created_at, .. } => *created_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_project_from_remote_urls() {
+        let cases = [
+            "https://gitlab.com/acme/widgets.git",
+            "git@gitlab.com:acme/widgets.git",
+            "ssh://git@gitlab.com:2222/acme/widgets",
+            "https://gitlab.com/acme/widgets/-/merge_requests/42",
+        ];
+        for url in cases {
+            let info = GitLabRepoInfo::from_remote_url(url).unwrap();
+            assert_eq!(info.base_url, "https://gitlab.com", "{url}");
+            assert_eq!(info.project_path, "acme/widgets", "{url}");
+        }
+
+        let nested =
+            GitLabRepoInfo::from_remote_url("http://git.example.com:8080/a/b/c.git").unwrap();
+        assert_eq!(nested.base_url, "http://git.example.com:8080");
+        assert_eq!(nested.project_path, "a/b/c");
+        assert_eq!(
+            nested.api_url("/merge_requests"),
+            "http://git.example.com:8080/api/v4/projects/a%2Fb%2Fc/merge_requests"
+        );
+
+        assert!(GitLabRepoInfo::from_remote_url("https://gitlab.com/acme").is_err());
+    }
+
+    #[test]
+    fn maps_merge_request_states() {
+        assert!(matches!(merge_status("opened"), MergeStatus::Open));
+        assert!(matches!(merge_status("merged"), MergeStatus::Merged));
+        assert!(matches!(merge_status("closed"), MergeStatus::Closed));
+        assert!(matches!(merge_status("locked"), MergeStatus::Closed));
+        assert!(matches!(merge_status("draft"), MergeStatus::Unknown));
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::BTreeMap;
+
+use db::models::{
+    coding_agent_turn::CodingAgentTurn, workspace::Workspace, workspace_repo::WorkspaceRepo,
+};
+use executors::{
+    logs::{
+        NormalizedEntry, NormalizedEntryType, utils::patch::extract_normalized_entry_from_patch,
+    },
+    profile::ExecutorProfileId,
+};
+use futures::StreamExt;
+use utils::{
+    diff::{Diff, create_unified_diff},
+    log_msg::LogMsg,
+    text::truncate_to_char_boundary,
+};
+use uuid::Uuid;
+
+use crate::services::{
+    container::{ContainerError, ContainerService},
+    git::DiffTarget,
+};
+
+/// Conversation text kept in a transfer prompt; the oldest entries are dropped first
+const MAX_CONVERSATION_CHARS: usize = 24_000;
+/// Diff text kept in a transfer prompt
+const MAX_DIFF_CHARS: usize = 48_000;
+/// Longest single prompt, summary or conversation entry
+const MAX_ENTRY_CHARS: usize = 2_000;
+
+/// Everything a newly assigned agent is told about the work done so far in a session
+pub struct HandoffContext<'a> {
+    pub from: &'a ExecutorProfileId,
+    pub turns: &'a [CodingAgentTurn],
+    pub conversation: &'a [NormalizedEntry],
+    pub diff: &'a str,
+}
+
+/// Build the prompt that starts the new executor's conversation when a session is handed
+/// over. `instructions` is what the user wants done next.
+pub fn build_transfer_prompt(context: &HandoffContext, instructions: Option<&str>) -> String {
+    let mut prompt = format!(
+        "You are taking over this task from another coding agent ({}). Its changes are already \
+         in the working tree. Review the history below, then continue the work.\n",
+        context.from
+    );
+
+    if !context.turns.is_empty() {
+        prompt.push_str("\n## Previous turns\n");
+        for (index, turn) in context.turns.iter().enumerate() {
+            prompt.push_str(&format!("\n### Turn {}\n", index + 1));
+            if let Some(turn_prompt) = turn.prompt.as_deref() {
+                prompt.push_str(&format!("Prompt:\n{}\n", clip(turn_prompt.trim())));
+            }
+            if let Some(summary) = turn.summary.as_deref() {
+                prompt.push_str(&format!("Outcome:\n{}\n", clip(summary.trim())));
+            }
+        }
+    }
+
+    let conversation = render_conversation(context.conversation);
+    if !conversation.is_empty() {
+        prompt.push_str("\n## Latest conversation\n");
+        prompt.push_str(&conversation);
+    }
+
+    let diff = context.diff.trim_end();
+    if diff.is_empty() {
+        prompt.push_str("\n## Changes so far\nNo changes have been made yet.\n");
+    } else {
+        prompt.push_str("\n## Changes so far\n```diff\n");
+        prompt.push_str(truncate_to_char_boundary(diff, MAX_DIFF_CHARS));
+        if diff.len() > MAX_DIFF_CHARS {
+            prompt.push_str("\n... (diff truncated)");
+        }
+        prompt.push_str("\n```\n");
+    }
+
+    prompt.push_str("\n## Next step\n");
+    match instructions.map(str::trim).filter(|text| !text.is_empty()) {
+        Some(text) => prompt.push_str(text),
+        None => prompt.push_str("Continue where the previous agent left off."),
+    }
+    prompt.push('\n');
+    prompt
+}
+
+/// Normalized conversation of a coding agent execution, from its live log store when it
+/// is still around and from the stored logs otherwise
+pub async fn load_conversation<C>(container: &C, execution_process_id: Uuid) -> Vec<NormalizedEntry>
+where
+    C: ContainerService + Sync + ?Sized,
+{
+    let mut entries = BTreeMap::new();
+    if let Some(store) = container.get_msg_store_by_id(&execution_process_id).await {
+        for msg in store.get_history() {
+            record_entry(&mut entries, &msg);
+        }
+    } else if let Some(mut stream) = container
+        .stream_normalized_logs(&execution_process_id)
+        .await
+    {
+        while let Some(Ok(msg)) = stream.next().await {
+            if matches!(msg, LogMsg::Finished) {
+                break;
+            }
+            record_entry(&mut entries, &msg);
+        }
+    }
+    entries.into_values().collect()
+}
+
+/// Unified diff of every change in the workspace relative to each repo's base commit,
+/// including uncommitted work
+pub async fn load_workspace_diff<C>(
+    container: &C,
+    workspace: &Workspace,
+) -> Result<String, ContainerError>
+where
+    C: ContainerService + Sync + ?Sized,
+{
+    let repos = WorkspaceRepo::find_repos_with_target_branch_for_workspace(
+        &container.db().pool,
+        workspace.id,
+    )
+    .await?;
+    let workspace_root = container.workspace_to_current_dir(workspace);
+    let prefix_repo_name = repos.len() > 1;
+
+    let mut output = String::new();
+    for repo in repos {
+        let worktree_path = workspace_root.join(&repo.repo.name);
+        let diffs = container
+            .git()
+            .get_base_commit(&repo.repo.path, &workspace.branch, &repo.target_branch)
+            .and_then(|base_commit| {
+                container.git().get_diffs(
+                    DiffTarget::Worktree {
+                        worktree_path: &worktree_path,
+                        base_commit: &base_commit,
+                    },
+                    None,
+                )
+            });
+        let diffs = match diffs {
+            Ok(diffs) => diffs,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to diff repo {} for handoff of workspace {}: {}",
+                    repo.repo.name,
+                    workspace.id,
+                    e
+                );
+                continue;
+            }
+        };
+
+        for diff in diffs {
+            let prefix = prefix_repo_name.then_some(repo.repo.name.as_str());
+            output.push_str(&render_diff(prefix, &diff));
+        }
+    }
+    Ok(output)
+}
+
+fn record_entry(entries: &mut BTreeMap<usize, NormalizedEntry>, msg: &LogMsg) {
+    if let LogMsg::JsonPatch(patch) = msg
+        && let Some((index, entry)) = extract_normalized_entry_from_patch(patch)
+    {
+        entries.insert(index, entry);
+    }
+}
+
+fn render_diff(repo_name: Option<&str>, diff: &Diff) -> String {
+    let path = diff
+        .new_path
+        .as_deref()
+        .or(diff.old_path.as_deref())
+        .unwrap_or("unknown");
+    let path = match repo_name {
+        Some(repo_name) => format!("{repo_name}/{path}"),
+        None => path.to_string(),
+    };
+
+    if diff.content_omitted {
+        return format!("--- a/{path}\n+++ b/{path}\n(content omitted)\n");
+    }
+    create_unified_diff(
+        &path,
+        diff.old_content.as_deref().unwrap_or(""),
+        diff.new_content.as_deref().unwrap_or(""),
+    )
+}
+
+/// The most recent conversation entries that fit the budget, one line block per entry
+fn render_conversation(conversation: &[NormalizedEntry]) -> String {
+    let rendered: Vec<String> = conversation.iter().filter_map(render_entry).collect();
+
+    let mut kept = Vec::new();
+    let mut length = 0;
+    for entry in rendered.iter().rev() {
+        if length + entry.len() > MAX_CONVERSATION_CHARS {
+            break;
+        }
+        length += entry.len();
+        kept.push(entry.as_str());
+    }
+    kept.reverse();
+
+    let mut output = String::new();
+    let omitted = rendered.len() - kept.len();
+    if omitted > 0 {
+        output.push_str(&format!("({omitted} earlier entries omitted)\n"));
+    }
+    for entry in kept {
+        output.push_str(entry);
+    }
+    output
+}
+
+fn render_entry(entry: &NormalizedEntry) -> Option<String> {
+    let content = clip(entry.content.trim());
+    let line = match &entry.entry_type {
+        NormalizedEntryType::UserMessage => format!("User: {content}"),
+        NormalizedEntryType::UserFeedback { denied_tool } => {
+            format!("User denied {denied_tool}: {content}")
+        }
+        NormalizedEntryType::AssistantMessage => format!("Assistant: {content}"),
+        NormalizedEntryType::ToolUse { tool_name, .. } => format!("Tool {tool_name}: {content}"),
+        NormalizedEntryType::ErrorMessage { .. } => format!("Error: {content}"),
+        NormalizedEntryType::SystemMessage
+        | NormalizedEntryType::Thinking
+        | NormalizedEntryType::Loading
+        | NormalizedEntryType::NextAction { .. } => return None,
+    };
+    Some(format!("{line}\n"))
+}
+
+fn clip(text: &str) -> String {
+    if text.len() > MAX_ENTRY_CHARS {
+        format!("{}...", truncate_to_char_boundary(text, MAX_ENTRY_CHARS))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use executors::executors::BaseCodingAgent;
+
+    use super::*;
+
+    fn entry(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    fn turn(prompt: &str, summary: Option<&str>) -> CodingAgentTurn {
+        CodingAgentTurn {
+            id: Uuid::new_v4(),
+            execution_process_id: Uuid::new_v4(),
+            agent_session_id: None,
+            prompt: Some(prompt.to_string()),
+            summary: summary.map(str::to_string),
+            handoff_from_turn_id: None,
+            conflict_repo_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn transfer_prompt_includes_turns_conversation_and_diff() {
+        let from = ExecutorProfileId::with_variant(BaseCodingAgent::ClaudeCode, "PLAN".into());
+        let turns = [turn("Plan the login page", Some("Wrote PLAN.md"))];
+        let conversation = [
+            entry(NormalizedEntryType::UserMessage, "Plan the login page"),
+            entry(NormalizedEntryType::Thinking, "hmm"),
+            entry(
+                NormalizedEntryType::AssistantMessage,
+                "The plan is in PLAN.md",
+            ),
+        ];
+        let diff = create_unified_diff("PLAN.md", "", "step 1\n");
+        let prompt = build_transfer_prompt(
+            &HandoffContext {
+                from: &from,
+                turns: &turns,
+                conversation: &conversation,
+                diff: &diff,
+            },
+            Some("Implement the plan"),
+        );
+
+        assert!(prompt.contains("(CLAUDE_CODE:PLAN)"));
+        assert!(prompt.contains("Outcome:\nWrote PLAN.md"));
+        assert!(prompt.contains("Assistant: The plan is in PLAN.md"));
+        assert!(!prompt.contains("hmm"));
+        assert!(prompt.contains("+step 1"));
+        assert!(prompt.ends_with("## Next step\nImplement the plan\n"));
+    }
+
+    #[test]
+    fn conversation_keeps_the_latest_entries() {
+        let long = "x".repeat(MAX_ENTRY_CHARS);
+        let conversation: Vec<_> = (0..20)
+            .map(|_| entry(NormalizedEntryType::AssistantMessage, &long))
+            .chain([entry(NormalizedEntryType::UserMessage, "last")])
+            .collect();
+
+        let rendered = render_conversation(&conversation);
+
+        assert!(rendered.starts_with("(9 earlier entries omitted)\n"));
+        assert!(rendered.ends_with("User: last\n"));
+        assert!(rendered.len() <= MAX_CONVERSATION_CHARS + 100);
+    }
+}
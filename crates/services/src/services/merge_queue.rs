@@ -0,0 +1,534 @@
+use std::{path::Path, time::Duration};
+
+use db::{
+    DBService,
+    models::{
+        execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+        merge::Merge,
+        merge_queue::{MergeQueueEntry, MergeQueueStatus},
+        project::Project,
+        project_repo::ProjectRepo,
+        repo::Repo,
+        session::{CreateSession, Session, SessionError},
+        task::{Task, TaskStatus},
+        workspace::Workspace,
+    },
+};
+use executors::actions::script::ScriptContext;
+use sqlx::error::Error as SqlxError;
+use thiserror::Error;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::services::{
+    commit_message::{self, CommitMessageContext},
+    container::{ContainerError, ContainerService},
+    git::GitServiceError,
+    repo_script::{self, RepoScriptError},
+};
+
+#[derive(Debug, Error)]
+enum MergeQueueError {
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+    #[error(transparent)]
+    Session(#[from] SessionError),
+    #[error(transparent)]
+    Container(#[from] ContainerError),
+    #[error(transparent)]
+    GitService(#[from] GitServiceError),
+    #[error("{0}")]
+    Rejected(String),
+}
+
+/// Service that lands queued attempts one at a time per repo and target branch, and per
+/// workspace: each is rebased onto the latest target branch, verified with the repo's setup and verify scripts
+/// and only then merged. Failed entries send their task back to review with the reason.
+#[derive(Clone)]
+pub struct MergeQueueService<C> {
+    db: DBService,
+    container: C,
+    poll_interval: Duration,
+}
+
+impl<C> MergeQueueService<C>
+where
+    C: ContainerService + Clone + Send + Sync + 'static,
+{
+    pub async fn spawn(db: DBService, container: C) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            container,
+            poll_interval: Duration::from_secs(5),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting merge queue service with interval {:?}",
+            self.poll_interval
+        );
+
+        match MergeQueueEntry::fail_interrupted(
+            &self.db.pool,
+            "Vibe Kanban stopped while this entry was being processed",
+        )
+        .await
+        {
+            Ok(0) => {}
+            Ok(count) => warn!("Failed {} interrupted merge queue entries", count),
+            Err(e) => error!("Failed to clean up interrupted merge queue entries: {}", e),
+        }
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.start_ready_entries().await {
+                error!("Error processing merge queue: {}", e);
+            }
+        }
+    }
+
+    /// Start the head of every queue that has nothing in progress. Different target
+    /// branches are processed concurrently, as long as they belong to different workspaces.
+    async fn start_ready_entries(&self) -> Result<(), MergeQueueError> {
+        let entries = MergeQueueEntry::find_ready(&self.db.pool).await?;
+        if entries.is_empty() {
+            debug!("No merge queue entries ready");
+            return Ok(());
+        }
+
+        for entry in entries {
+            // Claim the entry before spawning so the next poll doesn't start it again
+            MergeQueueEntry::update_status(&self.db.pool, entry.id, MergeQueueStatus::Rebasing)
+                .await?;
+
+            let service = self.clone();
+            tokio::spawn(async move {
+                service.process_entry(&entry).await;
+            });
+        }
+        Ok(())
+    }
+
+    async fn process_entry(&self, entry: &MergeQueueEntry) {
+        info!(
+            "Merge queue: landing workspace {} on {}",
+            entry.workspace_id, entry.target_branch
+        );
+        if let Err(e) = self.land(entry).await {
+            let reason = e.to_string();
+            warn!(
+                "Merge queue entry {} for workspace {} failed: {}",
+                entry.id, entry.workspace_id, reason
+            );
+            self.fail(entry, &reason).await;
+        }
+    }
+
+    async fn land(&self, entry: &MergeQueueEntry) -> Result<(), MergeQueueError> {
+        let pool = &self.db.pool;
+        let git = self.container.git();
+
+        let workspace = Workspace::find_by_id(pool, entry.workspace_id)
+            .await?
+            .ok_or_else(|| MergeQueueError::Rejected("The workspace no longer exists".into()))?;
+        let task = workspace
+            .parent_task(pool)
+            .await?
+            .ok_or_else(|| MergeQueueError::Rejected("The task no longer exists".into()))?;
+        let repo = Repo::find_by_id(pool, entry.repo_id)
+            .await?
+            .ok_or_else(|| MergeQueueError::Rejected("The repository no longer exists".into()))?;
+
+        if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace.id)
+            .await?
+        {
+            return Err(MergeQueueError::Rejected(
+                "An agent or script was still running in the workspace".into(),
+            ));
+        }
+
+        Task::update_status(pool, task.id, TaskStatus::InProgress).await?;
+
+        let container_ref = self.container.ensure_container_exists(&workspace).await?;
+        let worktree_path = Path::new(&container_ref).join(&repo.name);
+
+        // Rebase onto the target branch as it is now, after the entries ahead of this one
+        if let Err(e) = git.rebase_branch(
+            &repo.path,
+            &worktree_path,
+            &entry.target_branch,
+            &entry.target_branch,
+            &workspace.branch,
+        ) {
+            if matches!(e, GitServiceError::MergeConflicts(_))
+                && let Err(abort_err) = git.abort_conflicts(&worktree_path)
+            {
+                error!(
+                    "Failed to abort rebase in {}: {}",
+                    worktree_path.display(),
+                    abort_err
+                );
+            }
+            return Err(MergeQueueError::Rejected(format!(
+                "Rebase onto {} failed: {e}",
+                entry.target_branch
+            )));
+        }
+
+        MergeQueueEntry::update_status(pool, entry.id, MergeQueueStatus::Verifying).await?;
+        let session = match Session::find_latest_by_workspace_id(pool, workspace.id).await? {
+            Some(session) => session,
+            None => {
+                Session::create(
+                    pool,
+                    &CreateSession { executor: None },
+                    Uuid::new_v4(),
+                    workspace.id,
+                )
+                .await?
+            }
+        };
+        if let Some(project_repo) =
+            ProjectRepo::find_by_project_and_repo(pool, entry.project_id, entry.repo_id).await?
+        {
+            for (name, script, context, run_reason) in [
+                (
+                    "setup",
+                    project_repo.setup_script.as_deref(),
+                    ScriptContext::SetupScript,
+                    ExecutionProcessRunReason::SetupScript,
+                ),
+                (
+                    "verify",
+                    project_repo.verify_script.as_deref(),
+                    ScriptContext::VerifyScript,
+                    ExecutionProcessRunReason::VerifyScript,
+                ),
+            ] {
+                if let Some(script) = script.filter(|script| !script.trim().is_empty()) {
+                    // Run as execution processes of the attempt so the output is in its logs
+                    repo_script::run_script_process(
+                        &self.container,
+                        &session,
+                        &worktree_path,
+                        script,
+                        context,
+                        run_reason,
+                    )
+                    .await
+                    .map_err(|e| script_failure(name, e))?;
+                }
+            }
+        }
+
+        MergeQueueEntry::update_status(pool, entry.id, MergeQueueStatus::Merging).await?;
+        let project = Project::find_by_id(pool, entry.project_id)
+            .await?
+            .ok_or_else(|| MergeQueueError::Rejected("The project no longer exists".into()))?;
+        let executor = session.executor;
+        let commit_message = commit_message::render_commit_message(
+            project.merge_commit_template.as_deref(),
+            &CommitMessageContext {
+                task: &task,
+                executor: executor.as_deref(),
+            },
+        );
+
+        let merge_commit_id = git.merge_changes_with_strategy(
+            &repo.path,
+            &worktree_path,
+            &workspace.branch,
+            &entry.target_branch,
+            &commit_message,
+            project.merge_strategy,
+        )?;
+
+        Merge::create_direct(
+            pool,
+            workspace.id,
+            repo.id,
+            &entry.target_branch,
+            &merge_commit_id,
+        )
+        .await?;
+        MergeQueueEntry::mark_merged(pool, entry.id, &merge_commit_id).await?;
+        // The task is done once the workspace's other repos have landed too
+        if !MergeQueueEntry::has_active_for_workspace(pool, workspace.id).await? {
+            Task::update_status(pool, task.id, TaskStatus::Done).await?;
+        }
+        info!(
+            "Merge queue: merged workspace {} into {} as {}",
+            workspace.id, entry.target_branch, merge_commit_id
+        );
+
+        for dev_server in
+            ExecutionProcess::find_running_dev_servers_by_workspace(pool, workspace.id).await?
+        {
+            if let Err(e) = self
+                .container
+                .stop_execution(&dev_server, ExecutionProcessStatus::Killed)
+                .await
+            {
+                error!(
+                    "Failed to stop dev server {} for merged workspace {}: {}",
+                    dev_server.id, workspace.id, e
+                );
+            }
+        }
+        self.publish_task_update(&task).await;
+
+        Ok(())
+    }
+
+    /// Record the failure and hand the task back for review
+    async fn fail(&self, entry: &MergeQueueEntry, reason: &str) {
+        let pool = &self.db.pool;
+        if let Err(e) = MergeQueueEntry::mark_failed(pool, entry.id, reason).await {
+            error!(
+                "Failed to mark merge queue entry {} failed: {}",
+                entry.id, e
+            );
+        }
+
+        let task = match Workspace::find_by_id(pool, entry.workspace_id).await {
+            Ok(Some(workspace)) => workspace.parent_task(pool).await.ok().flatten(),
+            _ => None,
+        };
+        let Some(task) = task else {
+            return;
+        };
+
+        if let Err(e) = Task::update_status(pool, task.id, TaskStatus::InReview).await {
+            error!("Failed to move task {} back to review: {}", task.id, e);
+        }
+        self.publish_task_update(&task).await;
+
+        self.container
+            .notification_service()
+            .notify(
+                &format!("Merge Failed: {}", task.title),
+                &format!(
+                    "❌ '{}' could not be merged into {}\n{}",
+                    task.title, entry.target_branch, reason
+                ),
+            )
+            .await;
+    }
+
+    async fn publish_task_update(&self, task: &Task) {
+        if let Some(publisher) = self.container.share_publisher()
+            && let Err(err) = publisher.update_shared_task_by_id(task.id).await
+        {
+            warn!(
+                ?err,
+                "Failed to propagate shared task update for {}", task.id
+            );
+        }
+    }
+}
+
+/// Turn a failed setup or verify script into the reason the entry failed
+fn script_failure(name: &str, error: RepoScriptError) -> MergeQueueError {
+    match error {
+        RepoScriptError::Sqlx(e) => MergeQueueError::Sqlx(e),
+        RepoScriptError::Failed { ref output, .. } => {
+            MergeQueueError::Rejected(format!("The {name} script {error}\n\n{output}"))
+        }
+        error => MergeQueueError::Rejected(format!("The {name} script {error}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use db::models::{
+        execution_process_logs::ExecutionProcessLogs, merge_queue::CreateMergeQueueEntry,
+        project_repo::UpdateProjectRepo,
+    };
+    use utils::log_msg::LogMsg;
+
+    use super::*;
+    use crate::services::{
+        git::GitService,
+        test_support::{TestContainer, create_project, create_session, create_workspace, test_db},
+    };
+
+    #[tokio::test]
+    async fn failing_verify_script_fails_the_entry_with_its_output_in_the_logs() {
+        let test_db = test_db().await;
+        let db = &test_db.db;
+        let pool = &db.pool;
+        let dir = test_db.dir.path();
+        let fixture = create_project(db, dir).await;
+        let git = GitService::new();
+        git.initialize_repo_with_main_branch(&fixture.repo.path)
+            .unwrap();
+        let workspace = create_workspace(db, &fixture.task, &fixture.repo, dir).await;
+        let session = create_session(db, &workspace).await;
+        git.add_worktree(
+            &fixture.repo.path,
+            &dir.join(workspace.id.to_string()).join(&fixture.repo.name),
+            &workspace.branch,
+            true,
+        )
+        .unwrap();
+        ProjectRepo::update(
+            pool,
+            fixture.project.id,
+            fixture.repo.id,
+            &UpdateProjectRepo {
+                setup_script: Some("echo installing".to_string()),
+                cleanup_script: None,
+                verify_script: Some("echo 2 tests failed; exit 1".to_string()),
+                copy_files: None,
+                parallel_setup_script: None,
+                sparse_checkout: None,
+                sparse_checkout_cone: None,
+            },
+        )
+        .await
+        .unwrap();
+        let entry = MergeQueueEntry::create(
+            pool,
+            &CreateMergeQueueEntry {
+                project_id: fixture.project.id,
+                workspace_id: workspace.id,
+                repo_id: fixture.repo.id,
+                target_branch: "main".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let service = MergeQueueService {
+            db: db.clone(),
+            container: TestContainer::new(db, dir),
+            poll_interval: Duration::from_secs(5),
+        };
+        MergeQueueEntry::update_status(pool, entry.id, MergeQueueStatus::Rebasing)
+            .await
+            .unwrap();
+        service.process_entry(&entry).await;
+
+        let entry = MergeQueueEntry::find_by_id(pool, entry.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.status, MergeQueueStatus::Failed);
+        let reason = entry.failure_reason.unwrap();
+        assert!(
+            reason.starts_with("The verify script failed with exit code 1"),
+            "{reason}"
+        );
+        assert!(reason.contains("2 tests failed"), "{reason}");
+        assert_eq!(entry.merge_commit, None);
+        let task = Task::find_by_id(pool, fixture.task.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.status, TaskStatus::InReview);
+
+        let processes = ExecutionProcess::find_by_session_id(pool, session.id, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            processes
+                .iter()
+                .map(|process| (process.run_reason.clone(), process.status.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    ExecutionProcessRunReason::SetupScript,
+                    ExecutionProcessStatus::Completed
+                ),
+                (
+                    ExecutionProcessRunReason::VerifyScript,
+                    ExecutionProcessStatus::Failed
+                ),
+            ]
+        );
+        assert_eq!(processes[1].exit_code, Some(1));
+        let logs = ExecutionProcessLogs::find_by_execution_id(pool, processes[1].id)
+            .await
+            .unwrap();
+        assert!(
+            ExecutionProcessLogs::parse_logs(&logs)
+                .unwrap()
+                .iter()
+                .any(|msg| matches!(msg, LogMsg::Stdout(out) if out.contains("2 tests failed")))
+        );
+    }
+
+    #[tokio::test]
+    async fn entries_of_one_workspace_land_one_at_a_time() {
+        let test_db = test_db().await;
+        let db = &test_db.db;
+        let pool = &db.pool;
+        let dir = test_db.dir.path();
+        let fixture = create_project(db, dir).await;
+        let other_repo = Repo::find_or_create(pool, &dir.join("other"), "other")
+            .await
+            .unwrap();
+        ProjectRepo::create(pool, fixture.project.id, other_repo.id)
+            .await
+            .unwrap();
+        let workspace = create_workspace(db, &fixture.task, &fixture.repo, dir).await;
+        let other_workspace = create_workspace(db, &fixture.task, &other_repo, dir).await;
+        let enqueue = |workspace_id: Uuid, repo_id: Uuid| {
+            MergeQueueEntry::create(
+                pool,
+                &CreateMergeQueueEntry {
+                    project_id: fixture.project.id,
+                    workspace_id,
+                    repo_id,
+                    target_branch: "main".to_string(),
+                },
+            )
+        };
+        let first = enqueue(workspace.id, fixture.repo.id).await.unwrap();
+        let second = enqueue(workspace.id, other_repo.id).await.unwrap();
+        let elsewhere = enqueue(other_workspace.id, other_repo.id).await.unwrap();
+        let ready = || async {
+            MergeQueueEntry::find_ready(pool)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.id)
+                .collect::<Vec<_>>()
+        };
+
+        // The second repo's queue is headed by the workspace's own later entry
+        assert_eq!(ready().await, vec![first.id]);
+
+        MergeQueueEntry::update_status(pool, first.id, MergeQueueStatus::Rebasing)
+            .await
+            .unwrap();
+        assert!(ready().await.is_empty());
+        assert!(
+            MergeQueueEntry::has_active_for_workspace(pool, workspace.id)
+                .await
+                .unwrap()
+        );
+
+        MergeQueueEntry::mark_merged(pool, first.id, "abc123")
+            .await
+            .unwrap();
+        assert_eq!(ready().await, vec![second.id]);
+
+        MergeQueueEntry::mark_merged(pool, second.id, "def456")
+            .await
+            .unwrap();
+        assert!(
+            !MergeQueueEntry::has_active_for_workspace(pool, workspace.id)
+                .await
+                .unwrap()
+        );
+        assert_eq!(ready().await, vec![elsewhere.id]);
+    }
+}
@@ -1,7 +1,11 @@
 pub mod analytics;
 pub mod approvals;
+pub mod attempt_archive;
 pub mod auth;
+pub mod budget;
+pub mod commit_message;
 pub mod config;
+pub mod conflict_resolution;
 pub mod container;
 pub mod diff_stream;
 pub mod events;
@@ -9,9 +13,13 @@ pub mod file_ranker;
 pub mod file_search_cache;
 pub mod filesystem;
 pub mod filesystem_watcher;
+pub mod forge;
 pub mod git;
 pub mod github;
+pub mod gitlab;
+pub mod handoff;
 pub mod image;
+pub mod merge_queue;
 pub mod notification;
 pub mod oauth_credentials;
 pub mod pr_monitor;
@@ -19,6 +27,12 @@ pub mod project;
 pub mod queued_message;
 pub mod remote_client;
 pub mod repo;
+pub mod repo_script;
 pub mod share;
+pub mod stack;
+pub mod task_scheduler;
+#[cfg(test)]
+pub(crate) mod test_support;
 pub mod workspace_manager;
 pub mod worktree_manager;
+pub mod worktree_pool;
@@ -1,14 +1,30 @@
-use std::sync::{Arc, OnceLock};
+use std::{
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
 
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
 use tokio::sync::RwLock;
-use utils;
+use utils::{self, approvals::ApprovalRequest};
 
-use crate::services::config::{Config, NotificationConfig, SoundFile};
+use crate::services::{
+    approvals::links::{self, ApprovalLinkDecision, ApprovalLinks},
+    config::{Config, NotificationConfig, SoundFile},
+};
+
+/// How long an approval webhook may take to answer
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Environment variable holding the key approval webhook payloads are signed with
+const WEBHOOK_SECRET_ENV: &str = "VK_APPROVAL_WEBHOOK_SECRET";
+/// Header carrying the payload signature, as `sha256=<hex HMAC-SHA256 of the body>`
+const WEBHOOK_SIGNATURE_HEADER: &str = "X-Vibe-Kanban-Signature-256";
 
 /// Service for handling cross-platform notifications including sound alerts and push notifications
 #[derive(Debug, Clone)]
 pub struct NotificationService {
     config: Arc<RwLock<Config>>,
+    webhook_client: reqwest::Client,
 }
 
 /// Cache for WSL root path from PowerShell
@@ -16,7 +32,14 @@ static WSL_ROOT_PATH_CACHE: OnceLock<Option<String>> = OnceLock::new();
 
 impl NotificationService {
     pub fn new(config: Arc<RwLock<Config>>) -> Self {
-        Self { config }
+        let webhook_client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .unwrap();
+        Self {
+            config,
+            webhook_client,
+        }
     }
 
     /// Send both sound and push notifications if enabled
@@ -25,6 +48,81 @@ impl NotificationService {
         Self::send_notification(&config, title, message).await;
     }
 
+    /// Announce an approval request through the sound and push notifications and the
+    /// approval webhook, with signed links that approve or deny it
+    pub async fn notify_approval(&self, request: &ApprovalRequest, approval_links: &ApprovalLinks) {
+        let (config, approval_config) = {
+            let config = self.config.read().await;
+            (
+                config.notifications.clone(),
+                config.approval_notifications.clone(),
+            )
+        };
+
+        let base_url = approval_config
+            .public_base_url
+            .as_deref()
+            .or(approval_links.local_base_url());
+        let link = |decision| match approval_links.token(&request.id, decision, request.timeout_at)
+        {
+            Ok(token) => base_url.map(|base_url| links::respond_url(base_url, &request.id, &token)),
+            Err(e) => {
+                tracing::error!("Failed to sign approval link for {}: {}", request.id, e);
+                None
+            }
+        };
+        let approve_url = link(ApprovalLinkDecision::Approve);
+        let deny_url = link(ApprovalLinkDecision::Deny);
+
+        let mut message = format!("Tool '{}' requires approval", request.tool_name);
+        if let (Some(approve_url), Some(deny_url)) = (&approve_url, &deny_url) {
+            message.push_str(&format!("\nApprove: {approve_url}\nDeny: {deny_url}"));
+        }
+        Self::send_notification(&config, "Approval Needed", &message).await;
+
+        if let Some(webhook_url) = approval_config.webhook_url {
+            let payload = serde_json::json!({
+                "event": "approval_requested",
+                "approval_id": request.id,
+                "tool_name": request.tool_name,
+                "tool_input": request.tool_input,
+                "execution_process_id": request.execution_process_id,
+                "requested_at": request.created_at,
+                "timeout_at": request.timeout_at,
+                "approve_url": approve_url,
+                "deny_url": deny_url,
+            });
+            tokio::spawn(Self::send_webhook(
+                self.webhook_client.clone(),
+                webhook_url,
+                payload,
+            ));
+        }
+    }
+
+    /// POST `payload` to `url`, signed with the key in `VK_APPROVAL_WEBHOOK_SECRET` when it
+    /// is set; failures are logged and not retried
+    async fn send_webhook(client: reqwest::Client, url: String, payload: serde_json::Value) {
+        let body = payload.to_string();
+        let mut request = client
+            .post(&url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(secret) = std::env::var(WEBHOOK_SECRET_ENV)
+            .ok()
+            .filter(|secret| !secret.is_empty())
+        {
+            request = request.header(WEBHOOK_SIGNATURE_HEADER, webhook_signature(&secret, &body));
+        }
+        let result = request
+            .body(body)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = result {
+            tracing::warn!("Approval webhook {} failed: {}", url, e);
+        }
+    }
+
     /// Internal method to send notifications with a given config
     async fn send_notification(config: &NotificationConfig, title: &str, message: &str) {
         if config.sound_enabled {
@@ -236,3 +334,26 @@ impl NotificationService {
         }
     }
 }
+
+/// `sha256=` followed by the hex HMAC-SHA256 of `body` keyed with `secret`, the format
+/// GitHub uses for its webhook signatures
+fn webhook_signature(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn webhook_signature_is_the_hmac_of_the_body() {
+        // Test vector 2 of RFC 4231
+        assert_eq!(
+            webhook_signature("Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
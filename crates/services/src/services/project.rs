@@ -1,5 +1,5 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
 };
 
@@ -18,6 +18,7 @@ use uuid::Uuid;
 use super::{
     file_ranker::FileRanker,
     file_search_cache::{CacheError, FileSearchCache, SearchMode, SearchQuery},
+    git::SparseCheckout,
     repo::{RepoError, RepoService},
     share::ShareError,
 };
@@ -132,6 +133,9 @@ impl ProjectService {
                     dev_script: None,
                     dev_script_working_dir: None,
                     default_agent_working_dir: Some(repo.name),
+                    merge_strategy: None,
+                    merge_commit_template: None,
+                    worktree_pool_size: None,
                 },
             )
             .await?;
@@ -290,16 +294,35 @@ impl ProjectService {
         Ok(repos)
     }
 
+    /// Search the files of a project's repos. Repos with a sparse checkout only report
+    /// files their worktrees check out.
     pub async fn search_files(
         &self,
+        pool: &SqlitePool,
         cache: &FileSearchCache,
-        repositories: &[Repo],
+        project_id: Uuid,
         query: &SearchQuery,
     ) -> Result<Vec<SearchResult>> {
         let query_str = query.q.trim();
-        if query_str.is_empty() || repositories.is_empty() {
+        if query_str.is_empty() {
+            return Ok(vec![]);
+        }
+        let repositories = ProjectRepo::find_repos_for_project(pool, project_id).await?;
+        if repositories.is_empty() {
             return Ok(vec![]);
         }
+        let sparse_checkouts: HashMap<Uuid, SparseCheckout> =
+            ProjectRepo::find_by_project_id(pool, project_id)
+                .await?
+                .into_iter()
+                .filter_map(|project_repo| {
+                    SparseCheckout::from_config(
+                        project_repo.sparse_checkout.as_deref(),
+                        project_repo.sparse_checkout_cone,
+                    )
+                    .map(|sparse| (project_repo.repo_id, sparse))
+                })
+                .collect();
 
         // Search in parallel and prefix paths with repo name
         let search_futures: Vec<_> = repositories
@@ -308,9 +331,10 @@ impl ProjectService {
                 let repo_name = repo.name.clone();
                 let repo_path = repo.path.clone();
                 let query = query.clone();
+                let sparse = sparse_checkouts.get(&repo.id);
                 async move {
                     let results = self
-                        .search_single_repo(cache, &repo_path, &query)
+                        .search_single_repo(cache, &repo_path, &query, sparse)
                         .await
                         .unwrap_or_else(|e| {
                             tracing::warn!("Search failed for repo {}: {}", repo_name, e);
@@ -354,6 +378,7 @@ impl ProjectService {
         cache: &FileSearchCache,
         repo_path: &Path,
         query: &SearchQuery,
+        sparse: Option<&SparseCheckout>,
     ) -> Result<Vec<SearchResult>> {
         let query_str = query.q.trim();
         if query_str.is_empty() {
@@ -361,11 +386,14 @@ impl ProjectService {
         }
 
         // Try cache first
-        match cache.search(repo_path, query_str, query.mode.clone()).await {
+        match cache
+            .search(repo_path, query_str, query.mode.clone(), sparse)
+            .await
+        {
             Ok(results) => Ok(results),
             Err(CacheError::Miss) | Err(CacheError::BuildError(_)) => {
                 // Fall back to filesystem search
-                self.search_files_in_repo(repo_path, query_str, query.mode.clone())
+                self.search_files_in_repo(repo_path, query_str, query.mode.clone(), sparse)
                     .await
             }
         }
@@ -376,6 +404,7 @@ impl ProjectService {
         repo_path: &Path,
         query: &str,
         mode: SearchMode,
+        sparse: Option<&SparseCheckout>,
     ) -> Result<Vec<SearchResult>> {
         if !repo_path.exists() {
             return Err(ProjectServiceError::PathNotFound(repo_path.to_path_buf()));
@@ -426,6 +455,11 @@ impl ProjectService {
             let relative_path = path
                 .strip_prefix(repo_path)
                 .map_err(std::io::Error::other)?;
+            if let Some(sparse) = sparse
+                && !sparse.includes(&relative_path.to_string_lossy(), path.is_dir())
+            {
+                continue;
+            }
             let relative_path_str = relative_path.to_string_lossy().to_lowercase();
 
             let file_name = path
@@ -0,0 +1,246 @@
+//! Running a repo's scripts outside the agent flow, e.g. while the merge queue verifies an
+//! attempt or a pooled workspace is being prepared.
+use std::{path::Path, sync::Arc, time::Duration};
+
+use db::models::{
+    execution_process::{
+        CreateExecutionProcess, ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
+    },
+    session::Session,
+};
+use executors::actions::{
+    ExecutorAction, ExecutorActionType,
+    script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+};
+use futures::StreamExt;
+use sqlx::Error as SqlxError;
+use thiserror::Error;
+use tokio::{io::AsyncRead, process::Command};
+use tokio_util::io::ReaderStream;
+use utils::{log_msg::LogMsg, msg_store::MsgStore, shell::get_shell_command};
+use uuid::Uuid;
+
+use crate::services::container::ContainerService;
+
+/// Longest a repo script may run before it counts as failed
+pub const SCRIPT_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+/// Script output kept in a failure
+const MAX_OUTPUT_LENGTH: usize = 4096;
+
+#[derive(Debug, Error)]
+pub enum RepoScriptError {
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+    #[error("could not be started: {0}")]
+    Spawn(std::io::Error),
+    #[error("did not finish within {} minutes", SCRIPT_TIMEOUT.as_secs() / 60)]
+    TimedOut,
+    #[error("failed with exit code {}", .exit_code.map_or_else(|| "unknown".to_string(), |code| code.to_string()))]
+    Failed {
+        exit_code: Option<i32>,
+        /// The end of the script's combined output
+        output: String,
+    },
+}
+
+/// Run `script` in `dir` with the user's shell. Output is pushed to `msg_store` as it is
+/// produced, when given.
+pub async fn run_script(
+    dir: &Path,
+    script: &str,
+    msg_store: Option<&MsgStore>,
+) -> Result<(), RepoScriptError> {
+    let (shell_cmd, shell_arg) = get_shell_command();
+    let mut command = Command::new(shell_cmd);
+    command
+        .kill_on_drop(true)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .arg(shell_arg)
+        .arg(script)
+        .current_dir(dir);
+
+    let mut child = command.spawn().map_err(RepoScriptError::Spawn)?;
+    let stdout = child.stdout.take().expect("no stdout");
+    let stderr = child.stderr.take().expect("no stderr");
+    let run = async {
+        tokio::join!(
+            child.wait(),
+            forward_output(stdout, msg_store, LogMsg::Stdout),
+            forward_output(stderr, msg_store, LogMsg::Stderr),
+        )
+    };
+
+    let (status, mut output, stderr_output) = match tokio::time::timeout(SCRIPT_TIMEOUT, run).await
+    {
+        Ok((Ok(status), stdout_output, stderr_output)) => (status, stdout_output, stderr_output),
+        Ok((Err(e), _, _)) => return Err(RepoScriptError::Spawn(e)),
+        Err(_) => return Err(RepoScriptError::TimedOut),
+    };
+
+    if status.success() {
+        return Ok(());
+    }
+    output.push_str(&stderr_output);
+    Err(RepoScriptError::Failed {
+        exit_code: status.code(),
+        output: output_tail(output.trim_end()).to_string(),
+    })
+}
+
+/// Run a repo script in `dir` as an execution process of `session`, so its output shows up
+/// in the attempt's logs. Unlike [`ContainerService::start_execution`], finishing it
+/// doesn't commit changes, start a next action or finalize the task.
+pub async fn run_script_process<C>(
+    container: &C,
+    session: &Session,
+    dir: &Path,
+    script: &str,
+    context: ScriptContext,
+    run_reason: ExecutionProcessRunReason,
+) -> Result<ExecutionProcess, RepoScriptError>
+where
+    C: ContainerService + Sync,
+{
+    let pool = &container.db().pool;
+    let executor_action = ExecutorAction::new(
+        ExecutorActionType::ScriptRequest(ScriptRequest {
+            script: script.to_string(),
+            language: ScriptRequestLanguage::Bash,
+            context,
+            working_dir: None,
+        }),
+        None,
+    );
+    let execution_process = ExecutionProcess::create(
+        pool,
+        &CreateExecutionProcess {
+            session_id: session.id,
+            executor_action,
+            run_reason,
+        },
+        Uuid::new_v4(),
+        &[],
+        ExecutionProcessStatus::Running,
+    )
+    .await?;
+
+    let msg_store = Arc::new(MsgStore::new());
+    container
+        .msg_stores()
+        .write()
+        .await
+        .insert(execution_process.id, msg_store.clone());
+    let logs_saved = container.spawn_stream_raw_logs_to_db(&execution_process.id);
+
+    let result = run_script(dir, script, Some(&msg_store)).await;
+    let (status, exit_code) = match &result {
+        Ok(()) => (ExecutionProcessStatus::Completed, Some(0)),
+        Err(RepoScriptError::Failed { exit_code, .. }) => {
+            (ExecutionProcessStatus::Failed, exit_code.map(i64::from))
+        }
+        Err(e) => {
+            msg_store.push_stderr(format!("The script {e}\n"));
+            (ExecutionProcessStatus::Failed, None)
+        }
+    };
+    msg_store.push_finished();
+    if let Err(e) = logs_saved.await {
+        tracing::error!(
+            "Failed to save the logs of execution process {}: {}",
+            execution_process.id,
+            e
+        );
+    }
+    container
+        .msg_stores()
+        .write()
+        .await
+        .remove(&execution_process.id);
+    ExecutionProcess::update_completion(pool, execution_process.id, status, exit_code).await?;
+
+    result?;
+    Ok(execution_process)
+}
+
+/// Push everything read from `reader` to `msg_store` and return it
+async fn forward_output(
+    reader: impl AsyncRead + Unpin,
+    msg_store: Option<&MsgStore>,
+    to_msg: fn(String) -> LogMsg,
+) -> String {
+    let mut output = String::new();
+    let mut chunks = ReaderStream::new(reader);
+    while let Some(Ok(chunk)) = chunks.next().await {
+        let chunk = String::from_utf8_lossy(&chunk);
+        if let Some(msg_store) = msg_store {
+            msg_store.push(to_msg(chunk.to_string()));
+        }
+        output.push_str(&chunk);
+        if output.len() > 2 * MAX_OUTPUT_LENGTH {
+            output = output_tail(&output).to_string();
+        }
+    }
+    output
+}
+
+fn output_tail(output: &str) -> &str {
+    if output.len() <= MAX_OUTPUT_LENGTH {
+        return output;
+    }
+    let mut start = output.len() - MAX_OUTPUT_LENGTH;
+    while !output.is_char_boundary(start) {
+        start += 1;
+    }
+    &output[start..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_tail_keeps_the_end_on_a_char_boundary() {
+        let output = format!("{}é{}", "a".repeat(10), "b".repeat(MAX_OUTPUT_LENGTH - 1));
+
+        let tail = output_tail(&output);
+
+        assert!(tail.len() <= MAX_OUTPUT_LENGTH);
+        assert!(tail.ends_with('b'));
+        assert!(!tail.contains('a'));
+        assert_eq!(output_tail("short"), "short");
+    }
+
+    #[tokio::test]
+    async fn failed_script_keeps_its_output_and_exit_code() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let msg_store = MsgStore::new();
+
+        let result = run_script(
+            dir.path(),
+            "echo out; echo err >&2; exit 3",
+            Some(&msg_store),
+        )
+        .await;
+
+        match result {
+            Err(RepoScriptError::Failed { exit_code, output }) => {
+                assert_eq!(exit_code, Some(3));
+                assert_eq!(output, "out\nerr");
+            }
+            other => panic!("expected the script to fail, got {other:?}"),
+        }
+        let history = msg_store.get_history();
+        assert!(
+            history
+                .iter()
+                .any(|msg| matches!(msg, LogMsg::Stdout(out) if out == "out\n"))
+        );
+        assert!(
+            history
+                .iter()
+                .any(|msg| matches!(msg, LogMsg::Stderr(err) if err == "err\n"))
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use std::{path::Path, time::Duration};
+
+use db::{
+    DBService,
+    models::{
+        execution_process::ExecutionProcess,
+        merge::{Merge, MergeStatus},
+        repo::Repo,
+        task::Task,
+        workspace::Workspace,
+        workspace_repo::{CreateWorkspaceRepo, StackedWorkspaceRepo, WorkspaceRepo},
+    },
+};
+use sqlx::{SqlitePool, error::Error as SqlxError};
+use thiserror::Error;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+
+use crate::services::{
+    container::{ContainerError, ContainerService},
+    git::{GitService, GitServiceError},
+};
+
+#[derive(Debug, Error)]
+pub enum StackError {
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+    #[error(transparent)]
+    Container(#[from] ContainerError),
+    #[error(transparent)]
+    GitService(#[from] GitServiceError),
+}
+
+/// Point the repos of a new attempt at the branch of its task's parent attempt, for the
+/// repos the parent attempt also works on
+pub async fn stack_on_parent(
+    pool: &SqlitePool,
+    task: &Task,
+    repos: &mut [CreateWorkspaceRepo],
+) -> Result<(), SqlxError> {
+    let Some(parent_workspace_id) = task.parent_workspace_id else {
+        return Ok(());
+    };
+    let Some(parent) = Workspace::find_by_id(pool, parent_workspace_id).await? else {
+        return Ok(());
+    };
+    let parent_repos = WorkspaceRepo::find_by_workspace_id(pool, parent.id).await?;
+    for repo in repos.iter_mut() {
+        if parent_repos.iter().any(|p| p.repo_id == repo.repo_id) {
+            repo.target_branch = parent.branch.clone();
+        }
+    }
+    Ok(())
+}
+
+/// Record the parent commit each repo of a new stacked attempt starts from, so the
+/// attempt is restacked when the parent attempt's branch moves
+pub async fn record_stack_bases(
+    pool: &SqlitePool,
+    git: &GitService,
+    workspace: &Workspace,
+    task: &Task,
+) -> Result<(), StackError> {
+    let Some(parent_workspace_id) = task.parent_workspace_id else {
+        return Ok(());
+    };
+    let Some(parent) = Workspace::find_by_id(pool, parent_workspace_id).await? else {
+        return Ok(());
+    };
+
+    for workspace_repo in WorkspaceRepo::find_by_workspace_id(pool, workspace.id).await? {
+        if workspace_repo.target_branch != parent.branch {
+            continue;
+        }
+        let Some(repo) = Repo::find_by_id(pool, workspace_repo.repo_id).await? else {
+            continue;
+        };
+        let base_commit = git.get_branch_oid(&repo.path, &parent.branch)?;
+        WorkspaceRepo::update_stack_base_commit(pool, workspace.id, repo.id, Some(&base_commit))
+            .await?;
+    }
+    Ok(())
+}
+
+/// Service that keeps stacked attempts on top of their parent attempt. When the parent's
+/// branch is rebased or gets new commits, each child branch is rebased onto it; once the
+/// parent has been merged the child moves to the parent's target branch. A restack that
+/// conflicts is left stopped in the child's worktree for the user or agent to resolve.
+#[derive(Clone)]
+pub struct StackService<C> {
+    db: DBService,
+    container: C,
+    poll_interval: Duration,
+}
+
+impl<C> StackService<C>
+where
+    C: ContainerService + Clone + Send + Sync + 'static,
+{
+    pub async fn spawn(db: DBService, container: C) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            container,
+            poll_interval: Duration::from_secs(30),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting stack service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.restack_all().await {
+                error!("Error restacking attempts: {}", e);
+            }
+        }
+    }
+
+    async fn restack_all(&self) -> Result<(), StackError> {
+        let stacked = WorkspaceRepo::find_stacked(&self.db.pool).await?;
+        if stacked.is_empty() {
+            debug!("No stacked attempts");
+            return Ok(());
+        }
+
+        for entry in &stacked {
+            if let Err(e) = self.restack(entry).await {
+                warn!(
+                    "Failed to restack workspace {} on {}: {}",
+                    entry.workspace_id, entry.parent_branch, e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    async fn restack(&self, entry: &StackedWorkspaceRepo) -> Result<(), StackError> {
+        let pool = &self.db.pool;
+        let git = self.container.git();
+
+        let Some(repo) = Repo::find_by_id(pool, entry.repo_id).await? else {
+            return Ok(());
+        };
+        let Some(parent_repo) =
+            WorkspaceRepo::find_by_workspace_and_repo_id(pool, entry.parent_workspace_id, repo.id)
+                .await?
+        else {
+            return Ok(());
+        };
+
+        // A merged parent hands its children over to its own target branch
+        let parent_merged =
+            Merge::find_by_workspace_and_repo_id(pool, entry.parent_workspace_id, repo.id)
+                .await?
+                .iter()
+                .any(|merge| match merge {
+                    Merge::Direct(_) => true,
+                    Merge::Pr(pr) => matches!(pr.pr_info.status, MergeStatus::Merged),
+                });
+        let new_base_branch = if parent_merged {
+            parent_repo.target_branch.as_str()
+        } else {
+            entry.parent_branch.as_str()
+        };
+        let new_base_commit = git.get_branch_oid(&repo.path, new_base_branch)?;
+        if !parent_merged && new_base_commit == entry.stack_base_commit {
+            return Ok(());
+        }
+
+        if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(
+            pool,
+            entry.workspace_id,
+        )
+        .await?
+        {
+            debug!(
+                "Workspace {} is busy, restacking it later",
+                entry.workspace_id
+            );
+            return Ok(());
+        }
+        let Some(workspace) = Workspace::find_by_id(pool, entry.workspace_id).await? else {
+            return Ok(());
+        };
+        let container_ref = self.container.ensure_container_exists(&workspace).await?;
+        let worktree_path = Path::new(&container_ref).join(&repo.name);
+        if git.is_rebase_in_progress(&worktree_path)? {
+            // Waiting for an earlier restack's conflicts to be resolved
+            return Ok(());
+        }
+
+        let already_on_base = git
+            .get_base_commit(&repo.path, &entry.branch, new_base_branch)?
+            .to_string()
+            == new_base_commit;
+        if !already_on_base {
+            match git.rebase_branch(
+                &repo.path,
+                &worktree_path,
+                new_base_branch,
+                &entry.stack_base_commit,
+                &entry.branch,
+            ) {
+                Ok(_) => {}
+                Err(GitServiceError::MergeConflicts(msg)) => {
+                    self.notify_conflict(&workspace, new_base_branch, &msg)
+                        .await;
+                    return Ok(());
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        if parent_merged {
+            WorkspaceRepo::update_target_branch(pool, workspace.id, repo.id, new_base_branch)
+                .await?;
+            WorkspaceRepo::update_stack_base_commit(pool, workspace.id, repo.id, None).await?;
+            info!(
+                "Moved workspace {} onto {} after its parent was merged",
+                workspace.id, new_base_branch
+            );
+        } else {
+            WorkspaceRepo::update_stack_base_commit(
+                pool,
+                workspace.id,
+                repo.id,
+                Some(&new_base_commit),
+            )
+            .await?;
+            info!(
+                "Restacked workspace {} onto {} at {}",
+                workspace.id, new_base_branch, new_base_commit
+            );
+        }
+        Ok(())
+    }
+
+    async fn notify_conflict(&self, workspace: &Workspace, base_branch: &str, message: &str) {
+        let title = match workspace.parent_task(&self.db.pool).await {
+            Ok(Some(task)) => task.title,
+            _ => workspace.branch.clone(),
+        };
+        self.container
+            .notification_service()
+            .notify(
+                &format!("Restack Conflict: {title}"),
+                &format!("⚠️ '{title}' could not be restacked onto {base_branch}\n{message}"),
+            )
+            .await;
+    }
+}
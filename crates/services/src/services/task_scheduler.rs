@@ -0,0 +1,566 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use db::{
+    DBService,
+    models::{
+        project::Project,
+        project_repo::ProjectRepo,
+        task::Task,
+        task_dependency::TaskDependency,
+        workspace::{CreateWorkspace, Workspace, WorkspaceError},
+        workspace_repo::{CreateWorkspaceRepo, WorkspaceRepo},
+    },
+};
+use sqlx::error::Error as SqlxError;
+use thiserror::Error;
+use tokio::{sync::RwLock, time::interval};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::services::{
+    config::Config,
+    container::{ContainerError, ContainerService},
+    git::GitServiceError,
+};
+
+#[derive(Debug, Error)]
+enum TaskSchedulerError {
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+    #[error(transparent)]
+    Workspace(#[from] WorkspaceError),
+    #[error(transparent)]
+    Container(#[from] ContainerError),
+    #[error(transparent)]
+    GitService(#[from] GitServiceError),
+}
+
+/// Service that starts tasks automatically once every task they depend on is done
+pub struct TaskSchedulerService<C> {
+    db: DBService,
+    container: C,
+    config: Arc<RwLock<Config>>,
+    poll_interval: Duration,
+}
+
+impl<C> TaskSchedulerService<C>
+where
+    C: ContainerService + Clone + Send + Sync + 'static,
+{
+    pub async fn spawn(
+        db: DBService,
+        container: C,
+        config: Arc<RwLock<Config>>,
+    ) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            container,
+            config,
+            poll_interval: Duration::from_secs(10),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting task scheduler service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.schedule_unblocked_tasks().await {
+                error!("Error scheduling unblocked tasks: {}", e);
+            }
+        }
+    }
+
+    /// Start every task whose dependencies have all reached done
+    async fn schedule_unblocked_tasks(&self) -> Result<(), TaskSchedulerError> {
+        let tasks = TaskDependency::find_unblocked_tasks(&self.db.pool).await?;
+
+        if tasks.is_empty() {
+            debug!("No unblocked tasks to schedule");
+            return Ok(());
+        }
+
+        info!("Scheduling {} unblocked tasks", tasks.len());
+
+        for task in tasks {
+            if let Err(e) = self.start_task(&task).await {
+                error!("Error starting unblocked task {}: {}", task.id, e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn start_task(&self, task: &Task) -> Result<(), TaskSchedulerError> {
+        let pool = &self.db.pool;
+
+        let Some(project) = Project::find_by_id(pool, task.project_id).await? else {
+            return Ok(());
+        };
+        let repos = ProjectRepo::find_repos_for_project(pool, project.id).await?;
+        if repos.is_empty() {
+            warn!(
+                "Project {} has no repositories, not starting task {}",
+                project.id, task.id
+            );
+            return Ok(());
+        }
+
+        // Branch off whatever the prerequisites targeted, so their merged work is included
+        let target_branches = self.prerequisite_target_branches(task.id).await?;
+        let mut workspace_repos = Vec::with_capacity(repos.len());
+        for repo in &repos {
+            let target_branch = match target_branches.get(&repo.id) {
+                Some(branch) => branch.clone(),
+                None => self
+                    .container
+                    .git()
+                    .get_current_branch(&repo.path)
+                    .map_err(GitServiceError::from)?,
+            };
+            workspace_repos.push(CreateWorkspaceRepo {
+                repo_id: repo.id,
+                target_branch,
+            });
+        }
+
+        let workspace_id = Uuid::new_v4();
+        let branch = self
+            .container
+            .git_branch_from_workspace(&workspace_id, &task.title)
+            .await;
+        let agent_working_dir = project
+            .default_agent_working_dir
+            .filter(|dir| !dir.is_empty());
+
+        let workspace = Workspace::create(
+            pool,
+            &CreateWorkspace {
+                branch,
+                agent_working_dir,
+            },
+            workspace_id,
+            task.id,
+        )
+        .await?;
+        if let Err(e) = self.start_workspace(&workspace, &workspace_repos).await {
+            // Leave the task as if it had never been started, so the next poll retries it
+            self.roll_back_start(task, workspace.id).await;
+            return Err(e);
+        }
+
+        info!(
+            "Started task {} now that its dependencies are done",
+            task.id
+        );
+        Ok(())
+    }
+
+    async fn start_workspace(
+        &self,
+        workspace: &Workspace,
+        workspace_repos: &[CreateWorkspaceRepo],
+    ) -> Result<(), TaskSchedulerError> {
+        WorkspaceRepo::create_many(&self.db.pool, workspace.id, workspace_repos).await?;
+        let executor_profile_id = self.config.read().await.executor_profile.clone();
+        self.container
+            .start_workspace(workspace, executor_profile_id)
+            .await?;
+        Ok(())
+    }
+
+    /// Remove a workspace whose start failed, along with its worktrees, and put the task
+    /// back to its status before the start
+    async fn roll_back_start(&self, task: &Task, workspace_id: Uuid) {
+        self.container.discard_workspace(workspace_id).await;
+        if let Err(e) = Task::update_status(&self.db.pool, task.id, task.status.clone()).await {
+            error!("Failed to restore status of task {}: {}", task.id, e);
+        }
+    }
+
+    /// Target branch per repo taken from the latest workspace of each prerequisite task
+    async fn prerequisite_target_branches(
+        &self,
+        task_id: Uuid,
+    ) -> Result<HashMap<Uuid, String>, TaskSchedulerError> {
+        let pool = &self.db.pool;
+        let mut target_branches = HashMap::new();
+
+        for prerequisite in TaskDependency::find_prerequisites(pool, task_id).await? {
+            let Some(workspace) = Workspace::fetch_all(pool, Some(prerequisite.id))
+                .await?
+                .into_iter()
+                .next()
+            else {
+                continue;
+            };
+            for workspace_repo in WorkspaceRepo::find_by_workspace_id(pool, workspace.id).await? {
+                target_branches
+                    .entry(workspace_repo.repo_id)
+                    .or_insert(workspace_repo.target_branch);
+            }
+        }
+
+        Ok(target_branches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::{Path, PathBuf},
+        str::FromStr,
+        sync::Mutex,
+    };
+
+    use anyhow::anyhow;
+    use async_trait::async_trait;
+    use db::models::{
+        execution_process::{ExecutionContext, ExecutionProcess, ExecutionProcessStatus},
+        project::CreateProject,
+        repo::Repo,
+        task::{CreateTask, TaskStatus},
+    };
+    use executors::{actions::ExecutorAction, profile::ExecutorProfileId};
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use tempfile::TempDir;
+    use utils::{log_msg::LogMsg, msg_store::MsgStore};
+
+    use super::*;
+    use crate::services::{
+        config::ConcurrencyConfig,
+        container::ContainerRef,
+        git::{GitService, SparseCheckout},
+        notification::NotificationService,
+        share::SharePublisher,
+    };
+
+    /// Container service that records the executions it is asked to start instead of
+    /// running them
+    #[derive(Clone)]
+    struct TestContainer {
+        db: DBService,
+        git: GitService,
+        notification_service: NotificationService,
+        msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>,
+        root: PathBuf,
+        fail_create: bool,
+        launched: Arc<Mutex<Vec<Uuid>>>,
+    }
+
+    impl TestContainer {
+        fn new(db: &DBService, root: &Path) -> Self {
+            let mut config = Config::default();
+            config.notifications.sound_enabled = false;
+            config.notifications.push_enabled = false;
+            Self {
+                db: db.clone(),
+                git: GitService::new(),
+                notification_service: NotificationService::new(Arc::new(RwLock::new(config))),
+                msg_stores: Arc::new(RwLock::new(HashMap::new())),
+                root: root.to_path_buf(),
+                fail_create: false,
+                launched: Arc::new(Mutex::new(Vec::new())),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ContainerService for TestContainer {
+        fn msg_stores(&self) -> &Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>> {
+            &self.msg_stores
+        }
+
+        fn db(&self) -> &DBService {
+            &self.db
+        }
+
+        fn git(&self) -> &GitService {
+            &self.git
+        }
+
+        fn share_publisher(&self) -> Option<&SharePublisher> {
+            None
+        }
+
+        fn notification_service(&self) -> &NotificationService {
+            &self.notification_service
+        }
+
+        fn workspace_to_current_dir(&self, workspace: &Workspace) -> PathBuf {
+            self.root.join(workspace.id.to_string())
+        }
+
+        async fn create(&self, workspace: &Workspace) -> Result<ContainerRef, ContainerError> {
+            if self.fail_create {
+                return Err(ContainerError::Other(anyhow!("container creation failed")));
+            }
+            let container_ref = self
+                .workspace_to_current_dir(workspace)
+                .to_string_lossy()
+                .to_string();
+            Workspace::update_container_ref(&self.db.pool, workspace.id, &container_ref).await?;
+            Ok(container_ref)
+        }
+
+        async fn kill_all_running_processes(&self) -> Result<(), ContainerError> {
+            Ok(())
+        }
+
+        async fn delete(&self, _workspace: &Workspace) -> Result<(), ContainerError> {
+            Ok(())
+        }
+
+        async fn ensure_container_exists(
+            &self,
+            workspace: &Workspace,
+        ) -> Result<ContainerRef, ContainerError> {
+            match &workspace.container_ref {
+                Some(container_ref) => Ok(container_ref.clone()),
+                None => self.create(workspace).await,
+            }
+        }
+
+        async fn is_container_clean(&self, _workspace: &Workspace) -> Result<bool, ContainerError> {
+            Ok(true)
+        }
+
+        async fn start_execution_inner(
+            &self,
+            _workspace: &Workspace,
+            execution_process: &ExecutionProcess,
+            _executor_action: &ExecutorAction,
+        ) -> Result<(), ContainerError> {
+            self.launched.lock().unwrap().push(execution_process.id);
+            Ok(())
+        }
+
+        async fn stop_execution(
+            &self,
+            execution_process: &ExecutionProcess,
+            status: ExecutionProcessStatus,
+        ) -> Result<(), ContainerError> {
+            ExecutionProcess::update_completion(&self.db.pool, execution_process.id, status, None)
+                .await?;
+            Ok(())
+        }
+
+        async fn try_commit_changes(
+            &self,
+            _ctx: &ExecutionContext,
+        ) -> Result<bool, ContainerError> {
+            Ok(false)
+        }
+
+        async fn copy_project_files(
+            &self,
+            _source_dir: &Path,
+            _target_dir: &Path,
+            _copy_files: &str,
+            _sparse: Option<&SparseCheckout>,
+        ) -> Result<(), ContainerError> {
+            Ok(())
+        }
+
+        async fn stream_diff(
+            &self,
+            _workspace: &Workspace,
+            _stats_only: bool,
+        ) -> Result<
+            futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>,
+            ContainerError,
+        > {
+            Ok(Box::pin(futures::stream::empty()))
+        }
+
+        async fn git_branch_prefix(&self) -> String {
+            "vk".to_string()
+        }
+
+        async fn concurrency_config(&self) -> ConcurrencyConfig {
+            ConcurrencyConfig::default()
+        }
+
+        async fn executor_fallbacks(&self) -> Vec<ExecutorProfileId> {
+            Vec::new()
+        }
+    }
+
+    async fn test_db(dir: &Path) -> DBService {
+        let options = SqliteConnectOptions::from_str(&format!(
+            "sqlite://{}",
+            dir.join("db.sqlite").to_string_lossy()
+        ))
+        .unwrap()
+        .create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .connect_with(options)
+            .await
+            .unwrap();
+        sqlx::migrate!("../db/migrations").run(&pool).await.unwrap();
+        DBService { pool }
+    }
+
+    async fn create_task(db: &DBService, project_id: Uuid, title: &str) -> Task {
+        Task::create(
+            &db.pool,
+            &CreateTask::from_title_description(project_id, title.to_string(), None),
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn failed_starts_are_rolled_back_and_retried() {
+        let dir = TempDir::new().unwrap();
+        let db = &test_db(dir.path()).await;
+        let pool = &db.pool;
+        let project = Project::create(
+            pool,
+            &CreateProject {
+                name: "project".to_string(),
+                repositories: Vec::new(),
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        let repo = Repo::find_or_create(pool, &dir.path().join("repo"), "repo")
+            .await
+            .unwrap();
+        ProjectRepo::create(pool, project.id, repo.id)
+            .await
+            .unwrap();
+
+        // The prerequisite ran on `main`, which the dependent task branches off
+        let prerequisite = create_task(db, project.id, "prerequisite").await;
+        let prerequisite_workspace = Workspace::create(
+            pool,
+            &CreateWorkspace {
+                branch: "vk/prerequisite".to_string(),
+                agent_working_dir: None,
+            },
+            Uuid::new_v4(),
+            prerequisite.id,
+        )
+        .await
+        .unwrap();
+        WorkspaceRepo::create_many(
+            pool,
+            prerequisite_workspace.id,
+            &[CreateWorkspaceRepo {
+                repo_id: repo.id,
+                target_branch: "main".to_string(),
+            }],
+        )
+        .await
+        .unwrap();
+        let task = create_task(db, project.id, "dependent").await;
+        TaskDependency::create(pool, task.id, prerequisite.id)
+            .await
+            .unwrap();
+        Task::update_status(pool, prerequisite.id, TaskStatus::Done)
+            .await
+            .unwrap();
+
+        let mut container = TestContainer::new(db, dir.path());
+        container.fail_create = true;
+        let mut scheduler = TaskSchedulerService {
+            db: db.clone(),
+            container,
+            config: Arc::new(RwLock::new(Config::default())),
+            poll_interval: Duration::from_secs(10),
+        };
+
+        assert!(scheduler.start_task(&task).await.is_err());
+        assert!(
+            Workspace::fetch_all(pool, Some(task.id))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let unblocked = TaskDependency::find_unblocked_tasks(pool).await.unwrap();
+        assert_eq!(
+            unblocked.iter().map(|t| t.id).collect::<Vec<_>>(),
+            vec![task.id]
+        );
+        assert_eq!(unblocked[0].status, TaskStatus::Todo);
+
+        scheduler.container.fail_create = false;
+        scheduler.start_task(&task).await.unwrap();
+        assert_eq!(
+            Workspace::fetch_all(pool, Some(task.id))
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(scheduler.container.launched.lock().unwrap().len(), 1);
+        assert!(
+            TaskDependency::find_unblocked_tasks(pool)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn only_prerequisites_finished_after_the_dependency_was_added_unblock() {
+        let dir = TempDir::new().unwrap();
+        let db = &test_db(dir.path()).await;
+        let pool = &db.pool;
+        let project = Project::create(
+            pool,
+            &CreateProject {
+                name: "project".to_string(),
+                repositories: Vec::new(),
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        let prerequisite = create_task(db, project.id, "prerequisite").await;
+        Task::update_status(pool, prerequisite.id, TaskStatus::Done)
+            .await
+            .unwrap();
+        let task = create_task(db, project.id, "dependent").await;
+
+        // Depending on a task that is already done does not start anything
+        TaskDependency::create(pool, task.id, prerequisite.id)
+            .await
+            .unwrap();
+        assert!(
+            TaskDependency::find_unblocked_tasks(pool)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // Reopening and finishing the prerequisite again does
+        Task::update_status(pool, prerequisite.id, TaskStatus::InProgress)
+            .await
+            .unwrap();
+        assert!(
+            TaskDependency::find_unblocked_tasks(pool)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        Task::update_status(pool, prerequisite.id, TaskStatus::Done)
+            .await
+            .unwrap();
+        let unblocked = TaskDependency::find_unblocked_tasks(pool).await.unwrap();
+        assert_eq!(
+            unblocked.iter().map(|t| t.id).collect::<Vec<_>>(),
+            vec![task.id]
+        );
+    }
+}
@@ -0,0 +1,357 @@
+//! A migrated database and a container service that runs nothing, shared by the unit tests
+//! of services built on [`ContainerService`].
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use db::{
+    DBService,
+    models::{
+        execution_process::{
+            CreateExecutionProcess, ExecutionContext, ExecutionProcess, ExecutionProcessRunReason,
+            ExecutionProcessStatus,
+        },
+        project::{CreateProject, Project},
+        project_repo::ProjectRepo,
+        repo::Repo,
+        session::{CreateSession, Session},
+        task::{CreateTask, Task},
+        workspace::{CreateWorkspace, Workspace},
+        workspace_repo::{CreateWorkspaceRepo, WorkspaceRepo},
+    },
+};
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType,
+        script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+    },
+    executors::{BaseCodingAgent, ExecutorError},
+    profile::ExecutorProfileId,
+};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use tempfile::TempDir;
+use tokio::sync::RwLock;
+use utils::{log_msg::LogMsg, msg_store::MsgStore};
+use uuid::Uuid;
+
+use crate::services::{
+    config::{ConcurrencyConfig, Config},
+    container::{ContainerError, ContainerRef, ContainerService},
+    git::{GitService, SparseCheckout},
+    notification::NotificationService,
+    share::SharePublisher,
+};
+
+/// A database with every migration applied, removed when dropped
+pub struct TestDb {
+    pub db: DBService,
+    pub dir: TempDir,
+}
+
+pub async fn test_db() -> TestDb {
+    let dir = TempDir::new().unwrap();
+    let options = SqliteConnectOptions::from_str(&format!(
+        "sqlite://{}",
+        dir.path().join("db.sqlite").to_string_lossy()
+    ))
+    .unwrap()
+    .create_if_missing(true);
+    let pool = SqlitePoolOptions::new()
+        .connect_with(options)
+        .await
+        .unwrap();
+    sqlx::migrate!("../db/migrations").run(&pool).await.unwrap();
+    TestDb {
+        db: DBService { pool },
+        dir,
+    }
+}
+
+/// A project with one repository and a task in it
+pub struct TestProject {
+    pub project: Project,
+    pub repo: Repo,
+    pub task: Task,
+}
+
+pub async fn create_project(db: &DBService, dir: &Path) -> TestProject {
+    let pool = &db.pool;
+    let project = Project::create(
+        pool,
+        &CreateProject {
+            name: "project".to_string(),
+            repositories: Vec::new(),
+        },
+        Uuid::new_v4(),
+    )
+    .await
+    .unwrap();
+    let repo = Repo::find_or_create(pool, &dir.join("repo"), "repo")
+        .await
+        .unwrap();
+    ProjectRepo::create(pool, project.id, repo.id)
+        .await
+        .unwrap();
+    let task = Task::create(
+        pool,
+        &CreateTask::from_title_description(project.id, "task".to_string(), None),
+        Uuid::new_v4(),
+    )
+    .await
+    .unwrap();
+    TestProject {
+        project,
+        repo,
+        task,
+    }
+}
+
+/// A workspace of `task` on `repo` whose container already exists
+pub async fn create_workspace(db: &DBService, task: &Task, repo: &Repo, dir: &Path) -> Workspace {
+    let pool = &db.pool;
+    let id = Uuid::new_v4();
+    Workspace::create(
+        pool,
+        &CreateWorkspace {
+            branch: format!("vk/{id}"),
+            agent_working_dir: None,
+        },
+        id,
+        task.id,
+    )
+    .await
+    .unwrap();
+    WorkspaceRepo::create_many(
+        pool,
+        id,
+        &[CreateWorkspaceRepo {
+            repo_id: repo.id,
+            target_branch: "main".to_string(),
+        }],
+    )
+    .await
+    .unwrap();
+    Workspace::update_container_ref(pool, id, &dir.join(id.to_string()).to_string_lossy())
+        .await
+        .unwrap();
+    Workspace::find_by_id(pool, id).await.unwrap().unwrap()
+}
+
+pub async fn create_session(db: &DBService, workspace: &Workspace) -> Session {
+    Session::create(
+        &db.pool,
+        &CreateSession {
+            executor: Some("CLAUDE_CODE".to_string()),
+        },
+        Uuid::new_v4(),
+        workspace.id,
+    )
+    .await
+    .unwrap()
+}
+
+pub fn script_action(context: ScriptContext) -> ExecutorAction {
+    ExecutorAction::new(
+        ExecutorActionType::ScriptRequest(ScriptRequest {
+            script: "true".to_string(),
+            language: ScriptRequestLanguage::Bash,
+            context,
+            working_dir: None,
+        }),
+        None,
+    )
+}
+
+/// An execution process record in `status`, without anything running
+pub async fn create_process(
+    db: &DBService,
+    session: &Session,
+    executor_action: ExecutorAction,
+    run_reason: ExecutionProcessRunReason,
+    status: ExecutionProcessStatus,
+) -> ExecutionProcess {
+    ExecutionProcess::create(
+        &db.pool,
+        &CreateExecutionProcess {
+            session_id: session.id,
+            executor_action,
+            run_reason,
+        },
+        Uuid::new_v4(),
+        &[],
+        status,
+    )
+    .await
+    .unwrap()
+}
+
+/// Container service that records what it is asked to run instead of spawning anything
+#[derive(Clone)]
+pub struct TestContainer {
+    db: DBService,
+    git: GitService,
+    notification_service: NotificationService,
+    msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>,
+    root: PathBuf,
+    /// Coding agents that fail to start as if they weren't installed
+    pub unavailable: Vec<BaseCodingAgent>,
+    pub fallbacks: Vec<ExecutorProfileId>,
+    pub concurrency: ConcurrencyConfig,
+    /// Execution processes started, in order
+    pub launched: Arc<Mutex<Vec<Uuid>>>,
+    /// Workspaces whose container was deleted
+    pub deleted: Arc<Mutex<Vec<Uuid>>>,
+}
+
+impl TestContainer {
+    pub fn new(db: &DBService, root: &Path) -> Self {
+        let mut config = Config::default();
+        config.notifications.sound_enabled = false;
+        config.notifications.push_enabled = false;
+        Self {
+            db: db.clone(),
+            git: GitService::new(),
+            notification_service: NotificationService::new(Arc::new(RwLock::new(config))),
+            msg_stores: Arc::new(RwLock::new(HashMap::new())),
+            root: root.to_path_buf(),
+            unavailable: Vec::new(),
+            fallbacks: Vec::new(),
+            concurrency: ConcurrencyConfig::default(),
+            launched: Arc::new(Mutex::new(Vec::new())),
+            deleted: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn launched(&self) -> Vec<Uuid> {
+        self.launched.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl ContainerService for TestContainer {
+    fn msg_stores(&self) -> &Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>> {
+        &self.msg_stores
+    }
+
+    fn db(&self) -> &DBService {
+        &self.db
+    }
+
+    fn git(&self) -> &GitService {
+        &self.git
+    }
+
+    fn share_publisher(&self) -> Option<&SharePublisher> {
+        None
+    }
+
+    fn notification_service(&self) -> &NotificationService {
+        &self.notification_service
+    }
+
+    fn workspace_to_current_dir(&self, workspace: &Workspace) -> PathBuf {
+        self.root.join(workspace.id.to_string())
+    }
+
+    async fn create(&self, workspace: &Workspace) -> Result<ContainerRef, ContainerError> {
+        let container_ref = self
+            .workspace_to_current_dir(workspace)
+            .to_string_lossy()
+            .to_string();
+        Workspace::update_container_ref(&self.db.pool, workspace.id, &container_ref).await?;
+        Ok(container_ref)
+    }
+
+    async fn kill_all_running_processes(&self) -> Result<(), ContainerError> {
+        Ok(())
+    }
+
+    async fn delete(&self, workspace: &Workspace) -> Result<(), ContainerError> {
+        self.deleted.lock().unwrap().push(workspace.id);
+        Ok(())
+    }
+
+    async fn ensure_container_exists(
+        &self,
+        workspace: &Workspace,
+    ) -> Result<ContainerRef, ContainerError> {
+        match &workspace.container_ref {
+            Some(container_ref) => Ok(container_ref.clone()),
+            None => self.create(workspace).await,
+        }
+    }
+
+    async fn is_container_clean(&self, _workspace: &Workspace) -> Result<bool, ContainerError> {
+        Ok(true)
+    }
+
+    async fn start_execution_inner(
+        &self,
+        _workspace: &Workspace,
+        execution_process: &ExecutionProcess,
+        executor_action: &ExecutorAction,
+    ) -> Result<(), ContainerError> {
+        if let ExecutorActionType::CodingAgentInitialRequest(request) = executor_action.typ()
+            && self
+                .unavailable
+                .contains(&request.executor_profile_id.executor)
+        {
+            return Err(ExecutorError::ExecutableNotFound {
+                program: request.executor_profile_id.executor.to_string(),
+            }
+            .into());
+        }
+        self.launched.lock().unwrap().push(execution_process.id);
+        Ok(())
+    }
+
+    async fn stop_execution(
+        &self,
+        execution_process: &ExecutionProcess,
+        status: ExecutionProcessStatus,
+    ) -> Result<(), ContainerError> {
+        ExecutionProcess::update_completion(&self.db.pool, execution_process.id, status, None)
+            .await?;
+        Ok(())
+    }
+
+    async fn try_commit_changes(&self, _ctx: &ExecutionContext) -> Result<bool, ContainerError> {
+        Ok(false)
+    }
+
+    async fn copy_project_files(
+        &self,
+        _source_dir: &Path,
+        _target_dir: &Path,
+        _copy_files: &str,
+        _sparse: Option<&SparseCheckout>,
+    ) -> Result<(), ContainerError> {
+        Ok(())
+    }
+
+    async fn stream_diff(
+        &self,
+        _workspace: &Workspace,
+        _stats_only: bool,
+    ) -> Result<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>, ContainerError>
+    {
+        Ok(Box::pin(futures::stream::empty()))
+    }
+
+    async fn git_branch_prefix(&self) -> String {
+        "vk".to_string()
+    }
+
+    async fn concurrency_config(&self) -> ConcurrencyConfig {
+        self.concurrency.clone()
+    }
+
+    async fn executor_fallbacks(&self) -> Vec<ExecutorProfileId> {
+        self.fallbacks.clone()
+    }
+}
@@ -1,17 +1,27 @@
-use std::path::{Path, PathBuf};
-
-use db::models::{repo::Repo, workspace::Workspace as DbWorkspace};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use db::models::{
+    pooled_workspace::PooledWorkspace, repo::Repo, workspace::Workspace as DbWorkspace,
+};
 use sqlx::{Pool, Sqlite};
 use thiserror::Error;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-use super::worktree_manager::{WorktreeCleanup, WorktreeError, WorktreeManager};
+use super::{
+    git::SparseCheckout,
+    worktree_manager::{WorktreeCleanup, WorktreeError, WorktreeManager},
+};
 
 #[derive(Debug, Clone)]
 pub struct RepoWorkspaceInput {
     pub repo: Repo,
     pub target_branch: String,
+    /// Only this part of the repo is checked out, when set
+    pub sparse_checkout: Option<SparseCheckout>,
 }
 
 impl RepoWorkspaceInput {
@@ -19,8 +29,14 @@ impl RepoWorkspaceInput {
         Self {
             repo,
             target_branch,
+            sparse_checkout: None,
         }
     }
+
+    pub fn with_sparse_checkout(mut self, sparse_checkout: Option<SparseCheckout>) -> Self {
+        self.sparse_checkout = sparse_checkout;
+        self
+    }
 }
 
 #[derive(Debug, Error)]
@@ -90,6 +106,7 @@ impl WorkspaceManager {
                 &worktree_path,
                 &input.target_branch,
                 true,
+                input.sparse_checkout.as_ref(),
             )
             .await
             {
@@ -137,11 +154,13 @@ impl WorkspaceManager {
         })
     }
 
-    /// Ensure all worktrees in a workspace exist (for cold restart scenarios)
+    /// Ensure all worktrees in a workspace exist (for cold restart scenarios).
+    /// Recreated worktrees check out only the sparse set of their repo, if it has one.
     pub async fn ensure_workspace_exists(
         workspace_dir: &Path,
         repos: &[Repo],
         branch_name: &str,
+        sparse_checkouts: &HashMap<Uuid, SparseCheckout>,
     ) -> Result<(), WorkspaceError> {
         if repos.is_empty() {
             return Err(WorkspaceError::NoRepositories);
@@ -166,8 +185,13 @@ impl WorkspaceManager {
                 worktree_path.display()
             );
 
-            WorktreeManager::ensure_worktree_exists(&repo.path, branch_name, &worktree_path)
-                .await?;
+            WorktreeManager::ensure_worktree_exists(
+                &repo.path,
+                branch_name,
+                &worktree_path,
+                sparse_checkouts.get(&repo.id),
+            )
+            .await?;
         }
 
         Ok(())
@@ -328,7 +352,10 @@ impl WorkspaceManager {
             }
 
             let workspace_path_str = path.to_string_lossy().to_string();
-            if let Ok(false) = DbWorkspace::container_ref_exists(db, &workspace_path_str).await {
+            if let Ok(false) = DbWorkspace::container_ref_exists(db, &workspace_path_str).await
+                && let Ok(false) =
+                    PooledWorkspace::container_ref_exists(db, &workspace_path_str).await
+            {
                 info!("Found orphaned workspace: {}", workspace_path_str);
                 if let Err(e) = Self::cleanup_workspace_without_repos(&path).await {
                     error!(
@@ -345,7 +372,9 @@ impl WorkspaceManager {
         }
     }
 
-    async fn cleanup_workspace_without_repos(workspace_dir: &Path) -> Result<(), WorkspaceError> {
+    pub async fn cleanup_workspace_without_repos(
+        workspace_dir: &Path,
+    ) -> Result<(), WorkspaceError> {
         info!(
             "Cleaning up orphaned workspace at {}",
             workspace_dir.display()
@@ -0,0 +1,637 @@
+//! Pool of pre-warmed workspaces.
+//!
+//! Creating a workspace adds a worktree per repo, copies the project files and runs the
+//! setup scripts before the coding agent can start. A project with a worktree pool size
+//! keeps that many workspaces ready: each repo is checked out on a detached HEAD at the head
+//! of the repo's current branch, with files copied and setup done. A new attempt on the same
+//! repos and target branches claims one, fast-forwards it to the target branch and starts
+//! its own branch there. If the fast-forward moved a worktree, the attempt runs the setup
+//! scripts again.
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use db::{
+    DBService,
+    models::{
+        pooled_workspace::{PooledWorkspace, PooledWorkspaceRepo},
+        project::Project,
+        project_repo::ProjectRepo,
+        repo::Repo,
+    },
+};
+use sqlx::{SqlitePool, error::Error as SqlxError};
+use thiserror::Error;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::services::{
+    container::{ContainerError, ContainerService},
+    git::{GitService, GitServiceError, SparseCheckout},
+    repo_script,
+    workspace_manager::{
+        RepoWorkspaceInput, RepoWorktree, WorkspaceError, WorkspaceManager, WorktreeContainer,
+    },
+    worktree_manager::{WorktreeError, WorktreeManager},
+};
+
+/// Prefix of pooled workspace directories in the workspace base dir
+const POOL_DIR_PREFIX: &str = "pool-";
+
+#[derive(Debug, Error)]
+pub enum WorktreePoolError {
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+    #[error(transparent)]
+    Container(#[from] ContainerError),
+    #[error(transparent)]
+    GitService(#[from] GitServiceError),
+    #[error(transparent)]
+    Worktree(#[from] WorktreeError),
+    #[error(transparent)]
+    Workspace(#[from] WorkspaceError),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Setup(String),
+    #[error("{0}")]
+    Outdated(String),
+}
+
+/// A project repo as pooled workspaces check it out
+struct PoolRepo {
+    repo: Repo,
+    target_branch: String,
+    head_commit: String,
+    setup_script: Option<String>,
+    copy_files: Option<String>,
+    sparse_checkout: Option<SparseCheckout>,
+}
+
+/// Service that keeps the worktree pool of each project at its configured size. New pooled
+/// workspaces are created and set up one at a time; when a target branch moves, the
+/// pooled worktrees are fast-forwarded and set up again so a claim only has to catch up
+/// on what changed since the last poll.
+#[derive(Clone)]
+pub struct WorktreePoolService<C> {
+    db: DBService,
+    container: C,
+    poll_interval: Duration,
+}
+
+impl<C> WorktreePoolService<C>
+where
+    C: ContainerService + Clone + Send + Sync + 'static,
+{
+    pub async fn spawn(db: DBService, container: C) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            container,
+            poll_interval: Duration::from_secs(60),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting worktree pool service with interval {:?}",
+            self.poll_interval
+        );
+
+        // Workspaces that were being created or refreshed when vibe-kanban stopped
+        match PooledWorkspace::find_not_ready(&self.db.pool).await {
+            Ok(entries) => {
+                for entry in &entries {
+                    self.discard(entry).await;
+                }
+            }
+            Err(e) => error!("Failed to clean up unfinished pooled workspaces: {}", e),
+        }
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.replenish_all().await {
+                error!("Error replenishing worktree pools: {}", e);
+            }
+        }
+    }
+
+    async fn replenish_all(&self) -> Result<(), WorktreePoolError> {
+        for project in Project::find_all(&self.db.pool).await? {
+            if let Err(e) = self.replenish(&project).await {
+                warn!(
+                    "Failed to replenish the worktree pool of project '{}': {}",
+                    project.name, e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    async fn replenish(&self, project: &Project) -> Result<(), WorktreePoolError> {
+        let entries = PooledWorkspace::find_by_project_id(&self.db.pool, project.id).await?;
+        let pool_size = usize::try_from(project.worktree_pool_size).unwrap_or(0);
+        if pool_size == 0 && entries.is_empty() {
+            return Ok(());
+        }
+
+        let repos = if pool_size == 0 {
+            Vec::new()
+        } else {
+            self.pool_repos(project.id).await?
+        };
+
+        let mut kept = 0;
+        for entry in &entries {
+            if kept >= pool_size || repos.is_empty() {
+                self.discard(entry).await;
+                continue;
+            }
+            match self.refresh(entry, &repos).await {
+                Ok(true) => kept += 1,
+                // Claimed by an attempt in the meantime
+                Ok(false) => {}
+                Err(e) => {
+                    info!("Discarding pooled workspace {}: {}", entry.container_ref, e);
+                    self.discard(entry).await;
+                }
+            }
+        }
+
+        while kept < pool_size && !repos.is_empty() {
+            self.create_entry(project, &repos).await?;
+            kept += 1;
+        }
+        Ok(())
+    }
+
+    /// The project's repos with the target branch pooled workspaces are set up for: the
+    /// branch checked out in the repo itself
+    async fn pool_repos(&self, project_id: Uuid) -> Result<Vec<PoolRepo>, WorktreePoolError> {
+        let pool = &self.db.pool;
+        let git = self.container.git();
+        let mut configs: HashMap<Uuid, ProjectRepo> =
+            ProjectRepo::find_by_project_id(pool, project_id)
+                .await?
+                .into_iter()
+                .map(|config| (config.repo_id, config))
+                .collect();
+
+        let mut repos = Vec::new();
+        for repo in ProjectRepo::find_repos_for_project(pool, project_id).await? {
+            let Some(config) = configs.remove(&repo.id) else {
+                continue;
+            };
+            let target_branch = git
+                .get_current_branch(&repo.path)
+                .map_err(GitServiceError::from)?;
+            let head_commit = git.get_branch_oid(&repo.path, &target_branch)?;
+            repos.push(PoolRepo {
+                repo,
+                target_branch,
+                head_commit,
+                setup_script: config.setup_script,
+                copy_files: config.copy_files,
+                sparse_checkout: SparseCheckout::from_config(
+                    config.sparse_checkout.as_deref(),
+                    config.sparse_checkout_cone,
+                ),
+            });
+        }
+        Ok(repos)
+    }
+
+    async fn create_entry(
+        &self,
+        project: &Project,
+        repos: &[PoolRepo],
+    ) -> Result<(), WorktreePoolError> {
+        let pool = &self.db.pool;
+        let pooled_dir = WorkspaceManager::get_workspace_base_dir()
+            .join(format!("{POOL_DIR_PREFIX}{}", Uuid::new_v4().simple()));
+        let entry =
+            PooledWorkspace::create(pool, project.id, &pooled_dir.to_string_lossy()).await?;
+
+        if let Err(e) = self.populate(&entry, &pooled_dir, repos).await {
+            self.discard(&entry).await;
+            return Err(e);
+        }
+        PooledWorkspace::mark_ready(pool, entry.id).await?;
+        info!(
+            "Added pooled workspace {} to project '{}'",
+            pooled_dir.display(),
+            project.name
+        );
+        Ok(())
+    }
+
+    async fn populate(
+        &self,
+        entry: &PooledWorkspace,
+        pooled_dir: &Path,
+        repos: &[PoolRepo],
+    ) -> Result<(), WorktreePoolError> {
+        tokio::fs::create_dir_all(pooled_dir).await?;
+        let git = self.container.git();
+
+        for pool_repo in repos {
+            let worktree_path = pooled_dir.join(&pool_repo.repo.name);
+            // A commit id instead of a branch name leaves the worktree on a detached HEAD
+            git.add_sparse_worktree(
+                &pool_repo.repo.path,
+                &worktree_path,
+                &pool_repo.head_commit,
+                false,
+                pool_repo.sparse_checkout.as_ref(),
+            )?;
+            PooledWorkspaceRepo::create(
+                &self.db.pool,
+                entry.id,
+                pool_repo.repo.id,
+                &pool_repo.target_branch,
+                &pool_repo.head_commit,
+            )
+            .await?;
+
+            if let Some(copy_files) = pool_repo
+                .copy_files
+                .as_deref()
+                .filter(|copy_files| !copy_files.trim().is_empty())
+            {
+                self.container
+                    .copy_project_files(
+                        &pool_repo.repo.path,
+                        &worktree_path,
+                        copy_files,
+                        pool_repo.sparse_checkout.as_ref(),
+                    )
+                    .await?;
+            }
+        }
+
+        for pool_repo in repos {
+            run_setup_script(pooled_dir, pool_repo).await?;
+        }
+        Ok(())
+    }
+
+    /// Bring a pooled workspace up to date with the target branches. Returns false when it
+    /// was claimed before it could be refreshed.
+    async fn refresh(
+        &self,
+        entry: &PooledWorkspace,
+        repos: &[PoolRepo],
+    ) -> Result<bool, WorktreePoolError> {
+        if entry.ready_at.is_none() {
+            return Ok(true);
+        }
+        let pool = &self.db.pool;
+        let git = self.container.git();
+        let pooled_dir = PathBuf::from(&entry.container_ref);
+        let entry_repos = PooledWorkspaceRepo::find_by_pooled_workspace_id(pool, entry.id).await?;
+
+        let targets: Vec<(Uuid, &str)> = repos
+            .iter()
+            .map(|pool_repo| (pool_repo.repo.id, pool_repo.target_branch.as_str()))
+            .collect();
+        if !checks_out_targets(&entry_repos, &targets) {
+            return Err(WorktreePoolError::Outdated(
+                "the project's repos or their current branches changed".to_string(),
+            ));
+        }
+        for pool_repo in repos {
+            let worktree_path = pooled_dir.join(&pool_repo.repo.name);
+            if git.sparse_checkout(&worktree_path)? != pool_repo.sparse_checkout {
+                return Err(WorktreePoolError::Outdated(format!(
+                    "the sparse checkout of '{}' changed",
+                    pool_repo.repo.name
+                )));
+            }
+        }
+
+        let stale: Vec<(&PooledWorkspaceRepo, &PoolRepo)> = entry_repos
+            .iter()
+            .filter_map(|entry_repo| {
+                repos
+                    .iter()
+                    .find(|pool_repo| pool_repo.repo.id == entry_repo.repo_id)
+                    .filter(|pool_repo| pool_repo.head_commit != entry_repo.base_commit)
+                    .map(|pool_repo| (entry_repo, pool_repo))
+            })
+            .collect();
+        if stale.is_empty() {
+            return Ok(true);
+        }
+        if !PooledWorkspace::take_for_refresh(pool, entry.id).await? {
+            return Ok(false);
+        }
+
+        for (entry_repo, pool_repo) in &stale {
+            let worktree_path = pooled_dir.join(&pool_repo.repo.name);
+            let head =
+                git.fast_forward_detached_worktree(&worktree_path, &pool_repo.target_branch)?;
+            PooledWorkspaceRepo::update_base_commit(pool, entry_repo.id, &head).await?;
+            run_setup_script(&pooled_dir, pool_repo).await?;
+        }
+        PooledWorkspace::mark_ready(pool, entry.id).await?;
+        debug!(
+            "Refreshed pooled workspace {} for {} moved branch(es)",
+            entry.container_ref,
+            stale.len()
+        );
+        Ok(true)
+    }
+
+    /// Remove a pooled workspace and its worktrees, unless an attempt claimed it first
+    async fn discard(&self, entry: &PooledWorkspace) {
+        match PooledWorkspace::delete(&self.db.pool, entry.id).await {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                error!(
+                    "Failed to remove pooled workspace {}: {}",
+                    entry.container_ref, e
+                );
+                return;
+            }
+        }
+        if let Err(e) =
+            WorkspaceManager::cleanup_workspace_without_repos(Path::new(&entry.container_ref)).await
+        {
+            warn!(
+                "Failed to clean up pooled workspace {}: {}",
+                entry.container_ref, e
+            );
+        }
+    }
+}
+
+/// A pooled workspace claimed as the workspace of an attempt
+pub struct ClaimedWorkspace {
+    pub container: WorktreeContainer,
+    /// Whether the setup scripts ran at the commits the worktrees are now on. A target
+    /// branch that moved since the last refresh leaves the setup to be run again.
+    pub setup_current: bool,
+}
+
+/// Claim a ready pooled workspace of the project set up for exactly these repos and target
+/// branches, and turn it into the workspace at `workspace_dir` on `branch_name`. Returns
+/// `None` when the pool has no such workspace.
+pub async fn claim_pooled_workspace(
+    pool: &SqlitePool,
+    git: &GitService,
+    project_id: Uuid,
+    repos: &[RepoWorkspaceInput],
+    workspace_dir: &Path,
+    branch_name: &str,
+) -> Result<Option<ClaimedWorkspace>, WorktreePoolError> {
+    let targets: Vec<(Uuid, &str)> = repos
+        .iter()
+        .map(|input| (input.repo.id, input.target_branch.as_str()))
+        .collect();
+
+    for entry in PooledWorkspace::find_by_project_id(pool, project_id).await? {
+        if entry.ready_at.is_none() {
+            continue;
+        }
+        let entry_repos = PooledWorkspaceRepo::find_by_pooled_workspace_id(pool, entry.id).await?;
+        if !checks_out_targets(&entry_repos, &targets) {
+            continue;
+        }
+        let pooled_dir = PathBuf::from(&entry.container_ref);
+        let same_sparse_checkout = repos.iter().all(|input| {
+            git.sparse_checkout(&pooled_dir.join(&input.repo.name))
+                .is_ok_and(|sparse| sparse == input.sparse_checkout)
+        });
+        if !same_sparse_checkout || !PooledWorkspace::claim(pool, entry.id).await? {
+            continue;
+        }
+
+        let mut branched_repos = Vec::new();
+        return match adopt(
+            git,
+            &pooled_dir,
+            &entry_repos,
+            repos,
+            workspace_dir,
+            branch_name,
+            &mut branched_repos,
+        )
+        .await
+        {
+            Ok(claimed) => {
+                info!(
+                    "Claimed pooled workspace {} as {}",
+                    pooled_dir.display(),
+                    workspace_dir.display()
+                );
+                Ok(Some(claimed))
+            }
+            Err(e) => {
+                // Leave nothing behind so the workspace can be created from scratch
+                for dir in [pooled_dir.as_path(), workspace_dir] {
+                    if let Err(cleanup_err) =
+                        WorkspaceManager::cleanup_workspace_without_repos(dir).await
+                    {
+                        warn!(
+                            "Failed to clean up {} after a failed claim: {}",
+                            dir.display(),
+                            cleanup_err
+                        );
+                    }
+                }
+                for repo_path in branched_repos {
+                    if let Err(cleanup_err) = git.delete_local_branch(&repo_path, branch_name) {
+                        warn!(
+                            "Failed to delete branch {} after a failed claim: {}",
+                            branch_name, cleanup_err
+                        );
+                    }
+                }
+                Err(e)
+            }
+        };
+    }
+    Ok(None)
+}
+
+/// Fast-forward the pooled worktrees to their target branches, move them into the
+/// workspace directory and start the workspace branch in each
+async fn adopt(
+    git: &GitService,
+    pooled_dir: &Path,
+    entry_repos: &[PooledWorkspaceRepo],
+    repos: &[RepoWorkspaceInput],
+    workspace_dir: &Path,
+    branch_name: &str,
+    branched_repos: &mut Vec<PathBuf>,
+) -> Result<ClaimedWorkspace, WorktreePoolError> {
+    let mut setup_current = true;
+    for input in repos {
+        let head = git.fast_forward_detached_worktree(
+            &pooled_dir.join(&input.repo.name),
+            &input.target_branch,
+        )?;
+        setup_current &= entry_repos.iter().any(|entry_repo| {
+            entry_repo.repo_id == input.repo.id && entry_repo.base_commit == head
+        });
+    }
+
+    tokio::fs::create_dir_all(workspace_dir).await?;
+    let mut worktrees = Vec::with_capacity(repos.len());
+    for input in repos {
+        let worktree_path = workspace_dir.join(&input.repo.name);
+        WorktreeManager::move_worktree(
+            &input.repo.path,
+            &pooled_dir.join(&input.repo.name),
+            &worktree_path,
+        )
+        .await?;
+        git.create_branch_in_worktree(&worktree_path, branch_name)?;
+        branched_repos.push(input.repo.path.clone());
+        worktrees.push(RepoWorktree {
+            repo_id: input.repo.id,
+            repo_name: input.repo.name.clone(),
+            source_repo_path: input.repo.path.clone(),
+            worktree_path,
+        });
+    }
+
+    if let Err(e) = tokio::fs::remove_dir_all(pooled_dir).await {
+        debug!(
+            "Could not remove pooled workspace directory {}: {}",
+            pooled_dir.display(),
+            e
+        );
+    }
+    Ok(ClaimedWorkspace {
+        container: WorktreeContainer {
+            workspace_dir: workspace_dir.to_path_buf(),
+            worktrees,
+        },
+        setup_current,
+    })
+}
+
+/// Whether a pooled workspace has exactly these repos, each set up for the given target
+/// branch
+fn checks_out_targets(entry_repos: &[PooledWorkspaceRepo], targets: &[(Uuid, &str)]) -> bool {
+    entry_repos.len() == targets.len()
+        && targets.iter().all(|(repo_id, target_branch)| {
+            entry_repos.iter().any(|entry_repo| {
+                entry_repo.repo_id == *repo_id && entry_repo.target_branch == *target_branch
+            })
+        })
+}
+
+/// Run a repo's setup script in its pooled worktree
+async fn run_setup_script(
+    pooled_dir: &Path,
+    pool_repo: &PoolRepo,
+) -> Result<(), WorktreePoolError> {
+    let Some(script) = pool_repo
+        .setup_script
+        .as_deref()
+        .filter(|script| !script.trim().is_empty())
+    else {
+        return Ok(());
+    };
+
+    // There is no attempt yet whose logs could show the output
+    repo_script::run_script(&pooled_dir.join(&pool_repo.repo.name), script, None)
+        .await
+        .map_err(|e| {
+            WorktreePoolError::Setup(format!("the setup script of '{}' {e}", pool_repo.repo.name))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn entry_repo(repo_id: Uuid, target_branch: &str) -> PooledWorkspaceRepo {
+        PooledWorkspaceRepo {
+            id: Uuid::new_v4(),
+            pooled_workspace_id: Uuid::nil(),
+            repo_id,
+            target_branch: target_branch.to_string(),
+            base_commit: "abc123".to_string(),
+        }
+    }
+
+    #[test]
+    fn pooled_workspace_must_match_every_repo_and_target_branch() {
+        let (api, web) = (Uuid::new_v4(), Uuid::new_v4());
+        let entry_repos = vec![entry_repo(api, "main"), entry_repo(web, "develop")];
+
+        assert!(checks_out_targets(
+            &entry_repos,
+            &[(web, "develop"), (api, "main")]
+        ));
+        assert!(!checks_out_targets(&entry_repos, &[(api, "main")]));
+        assert!(!checks_out_targets(
+            &entry_repos,
+            &[(api, "main"), (web, "main")]
+        ));
+        assert!(!checks_out_targets(
+            &entry_repos,
+            &[(api, "main"), (web, "develop"), (Uuid::new_v4(), "main")]
+        ));
+    }
+
+    #[tokio::test]
+    async fn claim_needs_setup_again_when_the_target_branch_moved() {
+        let root = TempDir::new().unwrap();
+        let git = GitService::new();
+        let repo_path = root.path().join("repo");
+        git.initialize_repo_with_main_branch(&repo_path).unwrap();
+        let repo = Repo {
+            id: Uuid::new_v4(),
+            path: repo_path.clone(),
+            name: "repo".to_string(),
+            display_name: "repo".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let inputs = vec![RepoWorkspaceInput::new(repo.clone(), "main".to_string())];
+
+        for moved in [false, true] {
+            let head = git.get_branch_oid(&repo_path, "main").unwrap();
+            let pooled_dir = root.path().join(format!("pool-{moved}"));
+            git.add_sparse_worktree(&repo_path, &pooled_dir.join("repo"), &head, false, None)
+                .unwrap();
+            if moved {
+                std::fs::write(repo_path.join("moved.txt"), "moved\n").unwrap();
+                git.commit(&repo_path, "Move main").unwrap();
+            }
+            let entry_repos = vec![PooledWorkspaceRepo {
+                base_commit: head,
+                ..entry_repo(repo.id, "main")
+            }];
+
+            let claimed = adopt(
+                &git,
+                &pooled_dir,
+                &entry_repos,
+                &inputs,
+                &root.path().join(format!("workspace-{moved}")),
+                &format!("vk/claim-{moved}"),
+                &mut Vec::new(),
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(claimed.setup_current, !moved);
+        }
+    }
+}
@@ -4,6 +4,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use db::models::project::MergeStrategy;
 use git2::{PushOptions, Repository, build::CheckoutBuilder};
 use services::services::git::{GitCli, GitCliError, GitService};
 use tempfile::TempDir;
@@ -598,6 +599,74 @@ fn merge_preserves_unstaged_changes_on_base() {
     assert_eq!(m, "merged content\n");
 }
 
+#[test]
+fn rebase_strategy_fast_forwards_and_keeps_commits() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let s = GitService::new();
+    let repo = Repository::open(&repo_path).unwrap();
+    // main checked out: CLI path
+    checkout_branch(&repo, "main");
+    let wt_repo = Repository::open(&worktree_path).unwrap();
+    write_file(&worktree_path, "one.txt", "one\n");
+    commit_all(&wt_repo, "feat: one");
+    write_file(&worktree_path, "two.txt", "two\n");
+    commit_all(&wt_repo, "feat: two");
+    let feature_head = s.get_branch_oid(&repo_path, "feature").unwrap();
+
+    let sha = s
+        .merge_changes_with_strategy(
+            &repo_path,
+            &worktree_path,
+            "feature",
+            "main",
+            "unused",
+            MergeStrategy::Rebase,
+        )
+        .unwrap();
+
+    assert_eq!(sha, feature_head);
+    assert_eq!(s.get_branch_oid(&repo_path, "main").unwrap(), feature_head);
+    let head = repo
+        .find_commit(git2::Oid::from_str(&sha).unwrap())
+        .unwrap();
+    assert_eq!(head.summary(), Some("feat: two"));
+    assert_eq!(head.parent(0).unwrap().summary(), Some("feat: one"));
+}
+
+#[test]
+fn merge_commit_strategy_joins_both_branches() {
+    // main not checked out: libgit2 path
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let s = GitService::new();
+    let wt_repo = Repository::open(&worktree_path).unwrap();
+    write_file(&worktree_path, "feature.txt", "feature\n");
+    commit_all(&wt_repo, "feat: change");
+    let main_before = s.get_branch_oid(&repo_path, "main").unwrap();
+    let feature_before = s.get_branch_oid(&repo_path, "feature").unwrap();
+
+    let sha = s
+        .merge_changes_with_strategy(
+            &repo_path,
+            &worktree_path,
+            "feature",
+            "main",
+            "Merge feature",
+            MergeStrategy::MergeCommit,
+        )
+        .unwrap();
+
+    assert_eq!(s.get_branch_oid(&repo_path, "main").unwrap(), sha);
+    let repo = Repository::open(&repo_path).unwrap();
+    let merge_commit = repo
+        .find_commit(git2::Oid::from_str(&sha).unwrap())
+        .unwrap();
+    assert_eq!(merge_commit.message(), Some("Merge feature"));
+    let parents: Vec<String> = merge_commit.parent_ids().map(|id| id.to_string()).collect();
+    assert_eq!(parents, vec![main_before, feature_before]);
+}
+
 #[test]
 fn update_ref_does_not_destroy_feature_worktree_dirty_state() {
     let td = TempDir::new().unwrap();
@@ -713,6 +782,111 @@ fn rebase_refuses_to_abort_existing_rebase() {
     // Note: We do not auto-abort; user should resolve or abort explicitly
 }
 
+#[test]
+fn rebase_commit_subjects_lists_both_sides_of_conflict() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_conflict_repo_with_worktree(&td);
+    let svc = GitService::new();
+    let _ = svc
+        .rebase_branch(
+            &repo_path,
+            &worktree_path,
+            "new-base",
+            "old-base",
+            "feature",
+        )
+        .expect_err("rebase should stop on the conflict");
+
+    let files = svc.get_conflicted_files(&worktree_path).unwrap();
+    assert_eq!(files, vec!["conflict.txt".to_string()]);
+    let subjects = svc.rebase_commit_subjects(&worktree_path, &files).unwrap();
+
+    assert_eq!(
+        subjects.replaying.as_deref(),
+        Some("feature conflicting change")
+    );
+    assert_eq!(subjects.base, vec!["new-base change".to_string()]);
+    assert!(
+        subjects
+            .task
+            .contains(&"feature conflicting change".to_string())
+    );
+}
+
+#[test]
+fn continue_rebase_finishes_after_conflicts_are_resolved() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_conflict_repo_with_worktree(&td);
+    let svc = GitService::new();
+    let _ = svc
+        .rebase_branch(
+            &repo_path,
+            &worktree_path,
+            "new-base",
+            "old-base",
+            "feature",
+        )
+        .expect_err("rebase should stop on the conflict");
+
+    write_file(&worktree_path, "conflict.txt", "resolved version\n");
+    svc.continue_rebase(&worktree_path)
+        .expect("continue should finish the rebase");
+
+    assert!(!svc.is_rebase_in_progress(&worktree_path).unwrap());
+    assert_eq!(svc.get_head_info(&worktree_path).unwrap().branch, "feature");
+    let content = fs::read_to_string(worktree_path.join("conflict.txt")).unwrap();
+    assert_eq!(content, "resolved version\n");
+    let (_, behind) = svc
+        .get_branch_status(&repo_path, "feature", "new-base")
+        .unwrap();
+    assert_eq!(behind, 0, "feature should now sit on top of new-base");
+}
+
+#[test]
+fn rebase_from_recorded_base_commit_follows_amended_parent() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let svc = GitService::new();
+    // The child (feature) was stacked on old-base at this commit
+    let stack_base = svc.get_branch_oid(&repo_path, "old-base").unwrap();
+
+    // Amend the parent's last commit, rewriting its history
+    let repo = Repository::open(&repo_path).unwrap();
+    checkout_branch(&repo, "old-base");
+    write_file(&repo_path, "base.txt", "amended old-base\n");
+    let mut index = repo.index().unwrap();
+    index
+        .add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    head.amend(Some("HEAD"), None, None, None, None, Some(&tree))
+        .unwrap();
+
+    svc.rebase_branch(
+        &repo_path,
+        &worktree_path,
+        "old-base",
+        &stack_base,
+        "feature",
+    )
+    .expect("restack should succeed");
+
+    let base = fs::read_to_string(worktree_path.join("base.txt")).unwrap();
+    assert_eq!(base, "amended old-base\n");
+    let feat = fs::read_to_string(worktree_path.join("feat.txt")).unwrap();
+    assert_eq!(feat, "feat change\n");
+    let (ahead, behind) = svc
+        .get_branch_status(&repo_path, "feature", "old-base")
+        .unwrap();
+    assert_eq!(
+        (ahead, behind),
+        (1, 0),
+        "only the child's commit is replayed"
+    );
+}
+
 #[test]
 fn rebase_fast_forwards_when_no_unique_commits() {
     let td = TempDir::new().unwrap();
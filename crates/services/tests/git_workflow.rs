@@ -6,7 +6,7 @@ use std::{
 
 use git2::{Repository, build::CheckoutBuilder};
 use services::services::{
-    git::{DiffTarget, GitCli, GitService},
+    git::{DiffTarget, GitCli, GitService, GitServiceError, SparseCheckout},
     github::{GitHubRepoInfo, GitHubServiceError},
 };
 use tempfile::TempDir;
@@ -544,3 +544,387 @@ fn squash_merge_libgit2_sets_author_without_user() {
         assert_eq!(email.as_deref(), Some("noreply@vibekanban.com"));
     }
 }
+
+/// Write an executable stand-in for `gpg` that prints a fixed signature, or fails
+#[cfg(unix)]
+fn write_fake_gpg(dir: &Path, succeed: bool) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join(if succeed { "fake-gpg" } else { "failing-gpg" });
+    let script = if succeed {
+        // git only accepts the signature if gpg reports SIG_CREATED on the status fd
+        "#!/bin/sh\ncat > /dev/null\nprintf '[GNUPG:] BEGIN_SIGNING\\n[GNUPG:] SIG_CREATED D 1 8 00 0 X\\n' >&2\necho '-----BEGIN PGP SIGNATURE-----'\necho 'fake'\necho '-----END PGP SIGNATURE-----'\n"
+    } else {
+        "#!/bin/sh\ncat > /dev/null\necho 'no secret key' >&2\nexit 2\n"
+    };
+    fs::write(&path, script).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[cfg(unix)]
+#[test]
+fn squash_merge_libgit2_is_signed_when_signing_is_enabled() {
+    use services::services::git::{CommitSigningConfig, CommitSigningMode};
+
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let worktree_path = td.path().join("wt_feature");
+    let fake_gpg = write_fake_gpg(td.path(), true);
+    {
+        let repo = Repository::open(&repo_path).unwrap();
+        let mut cfg = repo.config().unwrap();
+        cfg.set_str("gpg.program", fake_gpg.to_str().unwrap())
+            .unwrap();
+    }
+    let s = GitService::new();
+    s.set_commit_signing(CommitSigningConfig {
+        mode: CommitSigningMode::Always,
+        ..Default::default()
+    });
+
+    create_branch(&repo_path, "feature");
+    s.add_worktree(&repo_path, &worktree_path, "feature", false)
+        .unwrap();
+    write_file(&worktree_path, "f.txt", "feat\n");
+    assert!(s.commit(&worktree_path, "feat").unwrap());
+
+    // Main repo is not on the base branch, so merge_changes takes the libgit2 path
+    create_branch(&repo_path, "dev");
+    checkout_branch(&repo_path, "dev");
+    let merge_sha = s
+        .merge_changes(&repo_path, &worktree_path, "feature", "main", "squash")
+        .unwrap();
+
+    let repo = Repository::open(&repo_path).unwrap();
+    let oid = git2::Oid::from_str(&merge_sha).unwrap();
+    let (signature, _) = repo.extract_signature(&oid, None).unwrap();
+    assert!(signature.as_str().unwrap().contains("fake"));
+    assert_eq!(
+        repo.revparse_single("main").unwrap().id().to_string(),
+        merge_sha
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn commit_reports_signing_failure() {
+    use services::services::git::GitServiceError;
+
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let failing_gpg = write_fake_gpg(td.path(), false);
+    {
+        let repo = Repository::open(&repo_path).unwrap();
+        let mut cfg = repo.config().unwrap();
+        cfg.set_bool("commit.gpgsign", true).unwrap();
+        cfg.set_str("gpg.program", failing_gpg.to_str().unwrap())
+            .unwrap();
+    }
+    write_file(&repo_path, "x.txt", "x\n");
+
+    let err = GitService::new()
+        .commit(&repo_path, "signed")
+        .expect_err("signing should fail");
+    assert!(
+        matches!(err, GitServiceError::SigningFailed(_)),
+        "unexpected error: {err:?}"
+    );
+}
+
+#[test]
+fn sparse_worktree_checks_out_only_the_sparse_set() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "README.md", "readme\n");
+    write_file(&repo_path, "services/Cargo.toml", "[workspace]\n");
+    write_file(&repo_path, "services/api/src/main.rs", "fn main() {}\n");
+    write_file(&repo_path, "services/billing/src/lib.rs", "\n");
+    write_file(&repo_path, "docs/guide.md", "guide\n");
+    let _ = s.commit(&repo_path, "monorepo").unwrap();
+    create_branch(&repo_path, "feature");
+
+    let worktree_path = td.path().join("wt_sparse");
+    let sparse = SparseCheckout::from_patterns("services/api\n", true).unwrap();
+    s.add_sparse_worktree(&repo_path, &worktree_path, "feature", false, Some(&sparse))
+        .unwrap();
+
+    // Cone mode keeps the files of the root and of the pattern's parents
+    assert!(worktree_path.join("README.md").exists());
+    assert!(worktree_path.join("services/Cargo.toml").exists());
+    assert!(worktree_path.join("services/api/src/main.rs").exists());
+    assert!(!worktree_path.join("services/billing").exists());
+    assert!(!worktree_path.join("docs").exists());
+    assert_eq!(s.sparse_checkout(&worktree_path).unwrap(), Some(sparse));
+
+    // The main checkout keeps the full tree
+    assert_eq!(s.sparse_checkout(&repo_path).unwrap(), None);
+    assert!(repo_path.join("docs/guide.md").exists());
+
+    // Files left out of the worktree are not reported as deleted
+    write_file(&worktree_path, "services/api/src/routes.rs", "\n");
+    let base_commit = s.get_base_commit(&repo_path, "feature", "main").unwrap();
+    let diffs = s
+        .get_diffs(
+            DiffTarget::Worktree {
+                worktree_path: &worktree_path,
+                base_commit: &base_commit,
+            },
+            None,
+        )
+        .unwrap();
+    let paths: Vec<String> = diffs.iter().map(GitService::diff_path).collect();
+    assert_eq!(paths, vec!["services/api/src/routes.rs".to_string()]);
+}
+
+#[test]
+fn detached_worktree_fast_forwards_and_starts_a_branch() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "a\n");
+    let _ = s.commit(&repo_path, "first").unwrap();
+    let first = s.get_branch_oid(&repo_path, "main").unwrap();
+
+    // A commit id leaves the worktree on a detached HEAD, as pooled workspaces are
+    let worktree_path = td.path().join("wt_pooled");
+    s.add_worktree(&repo_path, &worktree_path, &first, false)
+        .unwrap();
+
+    write_file(&repo_path, "b.txt", "b\n");
+    let _ = s.commit(&repo_path, "second").unwrap();
+    let second = s.get_branch_oid(&repo_path, "main").unwrap();
+
+    let head = s
+        .fast_forward_detached_worktree(&worktree_path, "main")
+        .unwrap();
+    assert_eq!(head, second);
+    assert!(worktree_path.join("b.txt").exists());
+
+    s.create_branch_in_worktree(&worktree_path, "vk/claimed")
+        .unwrap();
+    assert_eq!(s.get_current_branch(&worktree_path).unwrap(), "vk/claimed");
+    assert_eq!(s.get_branch_oid(&repo_path, "vk/claimed").unwrap(), second);
+
+    create_branch(&repo_path, "vk/abandoned");
+    s.delete_local_branch(&repo_path, "vk/abandoned").unwrap();
+    assert!(!s.check_branch_exists(&repo_path, "vk/abandoned").unwrap());
+}
+
+/// Commit a submodule pointer at `path` without cloning the submodule
+fn commit_gitlink(repo_path: &Path, path: &str, target: &str, message: &str) {
+    let repo = Repository::open(repo_path).unwrap();
+    let mut index = repo.index().unwrap();
+    index
+        .add(&git2::IndexEntry {
+            ctime: git2::IndexTime::new(0, 0),
+            mtime: git2::IndexTime::new(0, 0),
+            dev: 0,
+            ino: 0,
+            mode: 0o160000,
+            uid: 0,
+            gid: 0,
+            file_size: 0,
+            id: git2::Oid::from_str(target).unwrap(),
+            flags: 0,
+            flags_extended: 0,
+            path: path.as_bytes().to_vec(),
+        })
+        .unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let parent = repo.head().unwrap().peel_to_commit().unwrap();
+    let sig = repo.signature().unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &[&parent])
+        .unwrap();
+    // An empty directory is how git leaves a submodule that isn't checked out
+    fs::create_dir_all(repo_path.join(path)).unwrap();
+}
+
+#[test]
+fn submodule_pointer_changes_have_their_own_diff_kind() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "README.md", "readme\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+    let old_target = "1111111111111111111111111111111111111111";
+    let new_target = "2222222222222222222222222222222222222222";
+    commit_gitlink(&repo_path, "vendor/lib", old_target, "add submodule");
+
+    create_branch(&repo_path, "feature");
+    checkout_branch(&repo_path, "feature");
+    commit_gitlink(&repo_path, "vendor/lib", new_target, "bump submodule");
+
+    let diffs = s
+        .get_diffs(
+            DiffTarget::Branch {
+                repo_path: Path::new(&repo_path),
+                branch_name: "feature",
+                base_branch: "main",
+            },
+            None,
+        )
+        .unwrap();
+    assert_eq!(diffs.len(), 1);
+    let submodule = &diffs[0];
+    assert!(matches!(submodule.change, DiffChangeKind::Submodule));
+    assert_eq!(submodule.new_path.as_deref(), Some("vendor/lib"));
+    assert_eq!(
+        submodule.old_content.as_deref(),
+        Some(format!("Subproject commit {old_target}\n").as_str())
+    );
+    assert_eq!(
+        submodule.new_content.as_deref(),
+        Some(format!("Subproject commit {new_target}\n").as_str())
+    );
+}
+
+#[test]
+fn lfs_files_are_diffed_as_binary() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(
+        &repo_path,
+        ".gitattributes",
+        "*.bin filter=lfs diff=lfs merge=lfs -text\n",
+    );
+    let _ = s.commit(&repo_path, "track binaries with lfs").unwrap();
+    create_branch(&repo_path, "feature");
+    checkout_branch(&repo_path, "feature");
+    write_file(
+        &repo_path,
+        "assets/model.bin",
+        "version https://git-lfs.github.com/spec/v1\noid sha256:4d7a\nsize 12\n",
+    );
+    let _ = s.commit(&repo_path, "add model").unwrap();
+
+    let diffs = s
+        .get_diffs(
+            DiffTarget::Branch {
+                repo_path: Path::new(&repo_path),
+                branch_name: "feature",
+                base_branch: "main",
+            },
+            None,
+        )
+        .unwrap();
+    let model = diffs
+        .iter()
+        .find(|d| d.new_path.as_deref() == Some("assets/model.bin"))
+        .expect("lfs diff present");
+    assert!(model.new_content.is_none());
+    assert!(model.additions.is_none());
+
+    // Checked-out LFS content in a worktree isn't shown either
+    checkout_branch(&repo_path, "main");
+    let worktree_path = td.path().join("wt_lfs");
+    s.add_worktree(&repo_path, &worktree_path, "feature", false)
+        .unwrap();
+    write_file(&worktree_path, "assets/weights.bin", "plain text weights\n");
+    let base_commit = s.get_base_commit(&repo_path, "feature", "main").unwrap();
+    let diffs = s
+        .get_diffs(
+            DiffTarget::Worktree {
+                worktree_path: &worktree_path,
+                base_commit: &base_commit,
+            },
+            None,
+        )
+        .unwrap();
+    let weights = diffs
+        .iter()
+        .find(|d| d.new_path.as_deref() == Some("assets/weights.bin"))
+        .expect("lfs worktree diff present");
+    assert!(weights.new_content.is_none());
+}
+
+#[test]
+fn exported_commits_import_into_another_clone() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "a\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+
+    // The other machine's clone predates the attempt's commits
+    let clone_path = td.path().join("clone");
+    Repository::clone(repo_path.to_str().unwrap(), &clone_path).unwrap();
+    configure_user(&clone_path, "Other User", "other@example.com");
+
+    create_branch(&repo_path, "vk/feature");
+    checkout_branch(&repo_path, "vk/feature");
+    write_file(&repo_path, "a.txt", "a\nb\n");
+    let _ = s.commit(&repo_path, "extend a").unwrap();
+    write_file(&repo_path, "c.txt", "c\n");
+    let _ = s.commit(&repo_path, "add c").unwrap();
+    let head = s.get_branch_oid(&repo_path, "vk/feature").unwrap();
+    let base_commit = s.get_base_commit(&repo_path, "vk/feature", "main").unwrap();
+    assert!(!s.commit_exists(&clone_path, &head));
+
+    let series = td.path().join("feature.patch");
+    let bundle = td.path().join("feature.bundle");
+    s.export_patch_series(&repo_path, &base_commit, "vk/feature", &series)
+        .unwrap();
+    s.export_bundle(&repo_path, &base_commit, "vk/feature", &bundle)
+        .unwrap();
+
+    // Patches are recreated as new commits on top of the worktree's HEAD
+    create_branch(&clone_path, "vk/patched");
+    let patched = td.path().join("wt_patched");
+    s.add_worktree(&clone_path, &patched, "vk/patched", false)
+        .unwrap();
+    s.apply_patch_series(&patched, &series).unwrap();
+    assert_eq!(fs::read_to_string(patched.join("a.txt")).unwrap(), "a\nb\n");
+    assert!(patched.join("c.txt").exists());
+    let patched_head = s.get_head_info(&patched).unwrap().oid;
+    let (ahead, behind) = s
+        .ahead_behind_commits_by_oid(&clone_path, &patched_head, &base_commit.to_string())
+        .unwrap();
+    assert_eq!((ahead, behind), (2, 0));
+
+    // A bundle brings over the original commits
+    create_branch(&clone_path, "vk/bundled");
+    let bundled = td.path().join("wt_bundled");
+    s.add_worktree(&clone_path, &bundled, "vk/bundled", false)
+        .unwrap();
+    s.reset_worktree_to_bundle(&bundled, &bundle, "refs/heads/vk/feature", &head)
+        .unwrap();
+    assert_eq!(s.get_head_info(&bundled).unwrap().oid, head);
+    assert_eq!(s.get_branch_oid(&clone_path, "vk/bundled").unwrap(), head);
+    assert!(bundled.join("c.txt").exists());
+}
+
+#[test]
+fn patch_series_that_does_not_apply_leaves_head_unchanged() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "a\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+
+    create_branch(&repo_path, "vk/feature");
+    checkout_branch(&repo_path, "vk/feature");
+    write_file(&repo_path, "a.txt", "feature\n");
+    let _ = s.commit(&repo_path, "rewrite a").unwrap();
+    let base_commit = s.get_base_commit(&repo_path, "vk/feature", "main").unwrap();
+    let series = td.path().join("feature.patch");
+    s.export_patch_series(&repo_path, &base_commit, "vk/feature", &series)
+        .unwrap();
+
+    checkout_branch(&repo_path, "main");
+    write_file(&repo_path, "a.txt", "main\n");
+    let _ = s.commit(&repo_path, "rewrite a differently").unwrap();
+    let main_head = s.get_branch_oid(&repo_path, "main").unwrap();
+
+    create_branch(&repo_path, "vk/import");
+    let worktree_path = td.path().join("wt_conflict");
+    s.add_worktree(&repo_path, &worktree_path, "vk/import", false)
+        .unwrap();
+    let err = s.apply_patch_series(&worktree_path, &series).unwrap_err();
+    assert!(matches!(err, GitServiceError::MergeConflicts(_)));
+    assert_eq!(s.get_head_info(&worktree_path).unwrap().oid, main_head);
+    assert!(s.is_worktree_clean(&worktree_path).unwrap());
+}
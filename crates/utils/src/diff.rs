@@ -40,6 +40,8 @@ pub enum DiffChangeKind {
     Renamed,
     Copied,
     PermissionChange,
+    /// The commit a submodule points at changed; contents are `Subproject commit <sha>` lines
+    Submodule,
 }
 
 // ==============================
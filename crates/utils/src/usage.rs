@@ -0,0 +1,47 @@
+use std::ops::Add;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Token counts and spend reported by a coding agent, normalized across executors.
+/// Each report covers the whole execution so far, so a later report supersedes earlier ones.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+pub struct UsageReport {
+    /// Input tokens that were not served from the prompt cache
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    /// Input tokens written to the prompt cache
+    pub cache_creation_input_tokens: i64,
+    /// Input tokens served from the prompt cache
+    pub cache_read_input_tokens: i64,
+    /// Spend in USD, when the agent reports it
+    pub cost_usd: Option<f64>,
+}
+
+impl UsageReport {
+    pub fn total_tokens(&self) -> i64 {
+        self.input_tokens
+            + self.output_tokens
+            + self.cache_creation_input_tokens
+            + self.cache_read_input_tokens
+    }
+}
+
+impl Add for UsageReport {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let cost_usd = match (self.cost_usd, other.cost_usd) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(0.0) + b.unwrap_or(0.0)),
+        };
+        Self {
+            input_tokens: self.input_tokens + other.input_tokens,
+            output_tokens: self.output_tokens + other.output_tokens,
+            cache_creation_input_tokens: self.cache_creation_input_tokens
+                + other.cache_creation_input_tokens,
+            cache_read_input_tokens: self.cache_read_input_tokens + other.cache_read_input_tokens,
+            cost_usd,
+        }
+    }
+}
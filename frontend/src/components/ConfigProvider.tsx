@@ -82,6 +82,23 @@ const DEFAULT_WEB_CONFIG = {
     showcases: { seen_features: [] },
     pr_auto_description_enabled: false,
     pr_auto_description_prompt: null,
+    concurrency: {
+      max_running_agents: null,
+      max_running_agents_per_project: null,
+    },
+    budget: {
+      max_tokens: null,
+      max_runtime_seconds: null,
+      max_tool_calls: null,
+    },
+    verify: {
+      max_auto_retries: 0,
+    },
+    executor_fallbacks: [],
+    gitlab: {
+      token: null,
+      hosts: [],
+    },
   },
   environment: {
     os_type: "web",
@@ -7,6 +7,7 @@ import {
   Copy,
   ExternalLink,
   FilePlus2,
+  FolderGit2,
   Key,
   MessageSquare,
   PencilLine,
@@ -52,6 +53,7 @@ function labelAndIcon(diff: Diff) {
   if (c === "copied") return { label: "Copied", Icon: Copy };
   if (c === "permissionChange")
     return { label: "Permission Changed", Icon: Key };
+  if (c === "submodule") return { label: "Submodule", Icon: FolderGit2 };
   return { label: undefined as string | undefined, Icon: PencilLine };
 }
 
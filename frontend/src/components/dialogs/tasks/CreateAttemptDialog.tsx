@@ -14,6 +14,8 @@ import {
   DialogHeader,
   DialogTitle,
 } from "@/components/ui/dialog";
+import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
 import { useProject } from "@/contexts/ProjectContext";
 import {
   useAttempt,
@@ -50,6 +52,7 @@ const CreateAttemptDialogImpl = NiceModal.create<CreateAttemptDialogProps>(
 
     const [userSelectedProfile, setUserSelectedProfile] =
       useState<ExecutorProfileId | null>(null);
+    const [stackOnParent, setStackOnParent] = useState(true);
 
     const { data: attempts = [], isLoading: isLoadingAttempts } =
       useTaskAttemptsWithSessions(taskId, {
@@ -94,6 +97,7 @@ const CreateAttemptDialogImpl = NiceModal.create<CreateAttemptDialogProps>(
     useEffect(() => {
       if (!modal.visible) {
         setUserSelectedProfile(null);
+        setStackOnParent(true);
         resetBranchSelection();
       }
     }, [modal.visible, resetBranchSelection]);
@@ -151,6 +155,7 @@ const CreateAttemptDialogImpl = NiceModal.create<CreateAttemptDialogProps>(
         await createAttempt({
           profile: effectiveProfile,
           repos,
+          stackOnParent: !!parentAttempt && stackOnParent,
         });
 
         modal.hide();
@@ -198,6 +203,29 @@ const CreateAttemptDialogImpl = NiceModal.create<CreateAttemptDialogProps>(
               onBranchChange={setRepoBranch}
             />
 
+            {parentAttempt && (
+              <div className="space-y-1">
+                <div className="flex items-center gap-2">
+                  <Switch
+                    checked={stackOnParent}
+                    id="stack-on-parent-switch"
+                    onCheckedChange={setStackOnParent}
+                  />
+                  <Label
+                    className="cursor-pointer text-sm"
+                    htmlFor="stack-on-parent-switch"
+                  >
+                    {t("createAttemptDialog.stackOnParent")}
+                  </Label>
+                </div>
+                <p className="text-muted-foreground text-xs">
+                  {t("createAttemptDialog.stackOnParentHelp", {
+                    branch: parentAttempt.branch,
+                  })}
+                </p>
+              </div>
+            )}
+
             {error && (
               <div className="text-destructive text-sm">
                 {t("createAttemptDialog.error")}
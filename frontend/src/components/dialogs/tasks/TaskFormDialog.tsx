@@ -81,6 +81,7 @@ type TaskFormValues = {
   executorProfileId: ExecutorProfileId | null;
   repoBranches: RepoBranch[];
   autoStart: boolean;
+  stackOnParent: boolean;
 };
 
 const TaskFormDialogImpl = NiceModal.create<TaskFormDialogProps>((props) => {
@@ -136,6 +137,7 @@ const TaskFormDialogImpl = NiceModal.create<TaskFormDialogProps>((props) => {
           executorProfileId: baseProfile,
           repoBranches: defaultRepoBranches,
           autoStart: false,
+          stackOnParent: false,
         };
 
       case "duplicate":
@@ -146,6 +148,7 @@ const TaskFormDialogImpl = NiceModal.create<TaskFormDialogProps>((props) => {
           executorProfileId: baseProfile,
           repoBranches: defaultRepoBranches,
           autoStart: true,
+          stackOnParent: false,
         };
 
       case "subtask":
@@ -158,6 +161,7 @@ const TaskFormDialogImpl = NiceModal.create<TaskFormDialogProps>((props) => {
           executorProfileId: baseProfile,
           repoBranches: defaultRepoBranches,
           autoStart: true,
+          stackOnParent: mode === "subtask",
         };
     }
   }, [mode, props, system.config?.executor_profile, defaultRepoBranches]);
@@ -202,6 +206,7 @@ const TaskFormDialogImpl = NiceModal.create<TaskFormDialogProps>((props) => {
             task,
             executor_profile_id: value.executorProfileId!,
             repos,
+            stack_on_parent: mode === "subtask" && value.stackOnParent,
           },
           { onSuccess: () => modal.remove() }
         );
@@ -606,6 +611,30 @@ const TaskFormDialogImpl = NiceModal.create<TaskFormDialogProps>((props) => {
                         }}
                       </form.Field>
                     )}
+                    {mode === "subtask" && (
+                      <form.Field name="stackOnParent">
+                        {(field) => (
+                          <div className="flex items-center gap-2">
+                            <Switch
+                              checked={field.state.value}
+                              disabled={
+                                isSubmitting || !autoStartField.state.value
+                              }
+                              id="stack-on-parent-switch"
+                              onCheckedChange={(checked) =>
+                                field.handleChange(checked)
+                              }
+                            />
+                            <Label
+                              className="cursor-pointer text-sm"
+                              htmlFor="stack-on-parent-switch"
+                            >
+                              {t("createAttemptDialog.stackOnParent")}
+                            </Label>
+                          </div>
+                        )}
+                      </form.Field>
+                    )}
                   </div>
                 );
               }}
@@ -33,6 +33,7 @@ const DEFAULT_DIFF_COLLAPSE_DEFAULTS: DiffCollapseDefaults = {
   renamed: true,
   copied: true,
   permissionChange: true,
+  submodule: false,
 };
 
 const DEFAULT_COLLAPSE_MAX_LINES = 200;
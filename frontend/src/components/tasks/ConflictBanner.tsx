@@ -11,6 +11,8 @@ export type Props = Readonly<{
   onAbort: () => void;
   op?: ConflictOp | null;
   onResolve?: () => void;
+  /** Ask the agent to resolve the conflicts and continue the rebase */
+  onResolveWithAgent?: () => void;
   enableResolve: boolean;
   enableAbort: boolean;
 }>;
@@ -45,6 +47,7 @@ export function ConflictBanner({
   onAbort,
   op,
   onResolve,
+  onResolveWithAgent,
   enableResolve,
   enableAbort,
 }: Props) {
@@ -105,6 +108,17 @@ export function ConflictBanner({
             Resolve conflicts
           </Button>
         )}
+        {onResolveWithAgent && (
+          <Button
+            className="border-warning/40 text-warning-foreground hover:bg-warning/10 dark:text-warning/90"
+            disabled={!enableResolve}
+            onClick={onResolveWithAgent}
+            size="sm"
+            variant="outline"
+          >
+            Resolve with agent
+          </Button>
+        )}
         <Button
           className="border-warning/40 text-warning-foreground hover:bg-warning/10 dark:text-warning/90"
           onClick={onOpenEditor}
@@ -1,4 +1,4 @@
-import { Link, Loader2, XCircle } from "lucide-react";
+import { Clock, Link, Loader2, Lock, ShieldX, XCircle } from "lucide-react";
 import { useCallback, useEffect, useRef, useState } from "react";
 import { useTranslation } from "react-i18next";
 import type { TaskWithAttemptStatus } from "shared/types";
@@ -111,8 +111,18 @@ export function TaskCard({
               {task.has_in_progress_attempt && (
                 <Loader2 className="h-4 w-4 animate-spin text-blue-500" />
               )}
-              {task.last_attempt_failed && (
-                <XCircle className="h-4 w-4 text-destructive" />
+              {task.is_queued && (
+                <Clock className="h-4 w-4 text-muted-foreground" />
+              )}
+              {task.verification_failed ? (
+                <ShieldX className="h-4 w-4 text-destructive" />
+              ) : (
+                task.last_attempt_failed && (
+                  <XCircle className="h-4 w-4 text-destructive" />
+                )
+              )}
+              {task.is_blocked && (
+                <Lock className="h-4 w-4 text-muted-foreground" />
               )}
               {task.parent_workspace_id && (
                 <Button
@@ -5,6 +5,7 @@ import {
   ExternalLink,
   GitBranch as GitBranchIcon,
   GitPullRequest,
+  ListOrdered,
   RefreshCw,
   Settings,
 } from "lucide-react";
@@ -12,6 +13,7 @@ import { useCallback, useMemo, useState } from "react";
 import { useTranslation } from "react-i18next";
 import type {
   Merge,
+  MergeQueueEntry,
   RepoBranchStatus,
   TaskWithAttemptStatus,
   Workspace,
@@ -27,7 +29,8 @@ import {
   TooltipProvider,
   TooltipTrigger,
 } from "@/components/ui/tooltip.tsx";
-import { useRepoBranches } from "@/hooks";
+import { useGitOperationsError } from "@/contexts/GitOperationsContext";
+import { useMergeQueue, useRepoBranches } from "@/hooks";
 import { useAttemptRepo } from "@/hooks/useAttemptRepo";
 import { useGitOperations } from "@/hooks/useGitOperations";
 
@@ -58,6 +61,8 @@ function GitOperations({
   const git = useGitOperations(selectedAttempt.id, selectedRepoId ?? undefined);
   const { data: branches = [] } = useRepoBranches(selectedRepoId);
   const isChangingTargetBranch = git.states.changeTargetBranchPending;
+  const { setError } = useGitOperationsError();
+  const mergeQueue = useMergeQueue(task.project_id);
 
   // Local state for git operations
   const [merging, setMerging] = useState(false);
@@ -65,6 +70,7 @@ function GitOperations({
   const [rebasing, setRebasing] = useState(false);
   const [mergeSuccess, setMergeSuccess] = useState(false);
   const [pushSuccess, setPushSuccess] = useState(false);
+  const [queueing, setQueueing] = useState(false);
 
   // Target branch change handlers
   const handleChangeTargetBranchClick = async (newBranch: string) => {
@@ -106,6 +112,33 @@ function GitOperations({
     [getSelectedRepoStatus]
   );
 
+  // Latest merge queue entry of the selected repo, with its place in line
+  const queueInfo = useMemo(() => {
+    const repoId = getSelectedRepoId();
+    const entries = mergeQueue.entries.filter(
+      (e: MergeQueueEntry) =>
+        e.workspace_id === selectedAttempt.id &&
+        e.repo_id === repoId &&
+        e.status !== "cancelled"
+    );
+    const entry = entries[entries.length - 1];
+    if (!entry) return { entry: null, position: 0, isActive: false };
+
+    const position =
+      mergeQueue.entries
+        .filter(
+          (e: MergeQueueEntry) =>
+            e.repo_id === entry.repo_id &&
+            e.target_branch === entry.target_branch &&
+            e.status === "queued"
+        )
+        .findIndex((e: MergeQueueEntry) => e.id === entry.id) + 1;
+    const isActive = ["queued", "rebasing", "verifying", "merging"].includes(
+      entry.status
+    );
+    return { entry, position, isActive };
+  }, [mergeQueue.entries, selectedAttempt.id, getSelectedRepoId]);
+
   const hasConflictsCalculated =
     (selectedRepoStatus?.conflicted_files?.length ?? 0) > 0;
 
@@ -173,6 +206,33 @@ function GitOperations({
     await performMerge();
   };
 
+  const handleQueueClick = async () => {
+    const repoId = getSelectedRepoId();
+    if (!repoId) return;
+    const isQueued = queueInfo.entry?.status === "queued";
+    try {
+      setQueueing(true);
+      if (isQueued) {
+        await mergeQueue.cancel(selectedAttempt.id, repoId);
+      } else {
+        await mergeQueue.enqueue(selectedAttempt.id, repoId);
+      }
+      setError(null);
+    } catch (err) {
+      const message =
+        err && typeof err === "object" && "message" in err
+          ? String(err.message)
+          : t(
+              isQueued
+                ? "git.mergeQueue.errors.cancel"
+                : "git.mergeQueue.errors.enqueue"
+            );
+      setError(message);
+    } finally {
+      setQueueing(false);
+    }
+  };
+
   const handlePushClick = async () => {
     try {
       setPushing(true);
@@ -278,6 +338,39 @@ function GitOperations({
         const commitsAhead = selectedRepoStatus?.commits_ahead ?? 0;
         const commitsBehind = selectedRepoStatus?.commits_behind ?? 0;
 
+        const queueEntry = queueInfo.entry;
+        if (queueEntry && queueInfo.isActive) {
+          return (
+            <span className="inline-flex items-center gap-1 rounded-full bg-sky-100/60 px-2 py-0.5 text-sky-700 dark:bg-sky-900/30 dark:text-sky-300">
+              <ListOrdered className="h-3.5 w-3.5" />
+              {t(`git.mergeQueue.status.${queueEntry.status}`, {
+                position: queueInfo.position,
+              })}
+            </span>
+          );
+        }
+
+        if (queueEntry?.status === "failed" && !mergeInfo.hasMerged) {
+          return (
+            <TooltipProvider>
+              <Tooltip>
+                <TooltipTrigger asChild>
+                  <span className="inline-flex items-center gap-1 rounded-full bg-amber-100/60 px-2 py-0.5 text-amber-700 dark:bg-amber-900/30 dark:text-amber-300">
+                    <AlertTriangle className="h-3.5 w-3.5" />
+                    {t("git.mergeQueue.status.failed")}
+                  </span>
+                </TooltipTrigger>
+                <TooltipContent
+                  className="max-w-md whitespace-pre-wrap font-mono text-xs"
+                  side="bottom"
+                >
+                  {queueEntry.failure_reason}
+                </TooltipContent>
+              </Tooltip>
+            </TooltipProvider>
+          );
+        }
+
         if (hasConflictsCalculated) {
           return (
             <span className="inline-flex items-center gap-1 rounded-full bg-amber-100/60 px-2 py-0.5 text-amber-700 dark:bg-amber-900/30 dark:text-amber-300">
@@ -486,6 +579,46 @@ function GitOperations({
               <span className="max-w-[10ch] truncate">{mergeButtonLabel}</span>
             </Button>
 
+            <TooltipProvider>
+              <Tooltip>
+                <TooltipTrigger asChild>
+                  <Button
+                    aria-label={
+                      queueInfo.entry?.status === "queued"
+                        ? t("git.mergeQueue.dequeue")
+                        : t("git.mergeQueue.queue")
+                    }
+                    className="shrink-0 gap-1 border-success text-success hover:bg-success"
+                    disabled={
+                      queueing ||
+                      (queueInfo.isActive &&
+                        queueInfo.entry?.status !== "queued") ||
+                      (!queueInfo.isActive &&
+                        (mergeInfo.hasMergedPR ||
+                          mergeInfo.hasOpenPR ||
+                          merging ||
+                          hasConflictsCalculated ||
+                          isAttemptRunning ||
+                          (selectedRepoStatus?.commits_ahead ?? 0) === 0))
+                    }
+                    onClick={handleQueueClick}
+                    size="xs"
+                    variant="outline"
+                  >
+                    <ListOrdered className="h-3.5 w-3.5" />
+                    <span className="max-w-[10ch] truncate">
+                      {queueInfo.entry?.status === "queued"
+                        ? t("git.mergeQueue.dequeue")
+                        : t("git.mergeQueue.queue")}
+                    </span>
+                  </Button>
+                </TooltipTrigger>
+                <TooltipContent className="max-w-xs" side="bottom">
+                  {t("git.mergeQueue.tooltip")}
+                </TooltipContent>
+              </Tooltip>
+            </TooltipProvider>
+
             <Button
               aria-label={prButtonLabel}
               className="shrink-0 gap-1 border-info text-info hover:bg-info"
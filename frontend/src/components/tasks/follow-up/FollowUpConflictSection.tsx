@@ -30,7 +30,10 @@ export function FollowUpConflictSection({
   const op = repoWithConflicts?.conflict_op ?? null;
   const openInEditor = useOpenInEditor(workspaceId);
   const repoId = repoWithConflicts?.repo_id;
-  const { abortConflicts } = useAttemptConflicts(workspaceId, repoId);
+  const { abortConflicts, resolveConflicts } = useAttemptConflicts(
+    workspaceId,
+    repoId
+  );
 
   // write using setAborting and read through abortingRef in async handlers
   const [aborting, setAborting] = useState(false);
@@ -38,6 +41,7 @@ export function FollowUpConflictSection({
   useEffect(() => {
     abortingRef.current = aborting;
   }, [aborting]);
+  const [resolving, setResolving] = useState(false);
 
   if (!repoWithConflicts) return null;
 
@@ -48,7 +52,7 @@ export function FollowUpConflictSection({
         baseBranch={repoWithConflicts.target_branch_name ?? ""}
         conflictedFiles={repoWithConflicts.conflicted_files || []}
         enableAbort={enableAbort && !aborting}
-        enableResolve={enableResolve && !aborting}
+        enableResolve={enableResolve && !aborting && !resolving}
         onAbort={async () => {
           if (!workspaceId) return;
           if (!enableAbort || abortingRef.current) return;
@@ -67,6 +71,21 @@ export function FollowUpConflictSection({
           openInEditor(first ? { filePath: first } : undefined);
         }}
         onResolve={onResolve}
+        onResolveWithAgent={
+          op === "rebase"
+            ? async () => {
+                if (!enableResolve || resolving) return;
+                try {
+                  setResolving(true);
+                  await resolveConflicts();
+                } catch (e) {
+                  console.error("Failed to start conflict resolution", e);
+                } finally {
+                  setResolving(false);
+                }
+              }
+            : undefined
+        }
         op={op}
       />
       {/* Conflict instructions preview (non-editable) */}
@@ -15,6 +15,7 @@ export { useDevServer } from "./useDevServer";
 export { useGitOperations } from "./useGitOperations";
 export { useImageUpload } from "./useImageUpload";
 export { useMerge } from "./useMerge";
+export { useMergeQueue } from "./useMergeQueue";
 export { useNavigateWithSearch } from "./useNavigateWithSearch";
 export { useOpenInEditor } from "./useOpenInEditor";
 export { useOrganizationInvitations } from "./useOrganizationInvitations";
@@ -9,6 +9,7 @@ import { attemptsApi } from "@/lib/api";
 type CreateAttemptArgs = {
   profile: ExecutorProfileId;
   repos: WorkspaceRepoInput[];
+  stackOnParent?: boolean;
 };
 
 type UseAttemptCreationArgs = {
@@ -23,11 +24,12 @@ export function useAttemptCreation({
   const queryClient = useQueryClient();
 
   const mutation = useMutation({
-    mutationFn: ({ profile, repos }: CreateAttemptArgs) =>
+    mutationFn: ({ profile, repos, stackOnParent }: CreateAttemptArgs) =>
       attemptsApi.create({
         task_id: taskId,
         executor_profile_id: profile,
         repos,
+        stack_on_parent: stackOnParent ?? false,
       }),
     onSuccess: (newAttempt: Workspace) => {
       queryClient.setQueryData(
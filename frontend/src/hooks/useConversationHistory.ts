@@ -119,6 +119,7 @@ export const useConversationHistory = ({
       (ep) =>
         ep.run_reason === "setupscript" ||
         ep.run_reason === "cleanupscript" ||
+        ep.run_reason === "verifyscript" ||
         ep.run_reason === "codingagent"
     );
   }, [executionProcessesRaw]);
@@ -280,7 +281,8 @@ export const useConversationHistory = ({
               liveProcessStatus === ExecutionProcessStatus.running;
             const processFailedOrKilled =
               liveProcessStatus === ExecutionProcessStatus.failed ||
-              liveProcessStatus === ExecutionProcessStatus.killed;
+              liveProcessStatus === ExecutionProcessStatus.killed ||
+              liveProcessStatus === ExecutionProcessStatus.budget_exceeded;
 
             if (isProcessRunning) {
               hasRunningProcess = true;
@@ -325,6 +327,9 @@ export const useConversationHistory = ({
               case "CleanupScript":
                 toolName = "Cleanup Script";
                 break;
+              case "VerifyScript":
+                toolName = "Verify Script";
+                break;
               case "ToolInstallScript":
                 toolName = "Tool Install Script";
                 break;
@@ -342,7 +347,9 @@ export const useConversationHistory = ({
 
             if (
               (executionProcess?.status === ExecutionProcessStatus.failed ||
-                executionProcess?.status === ExecutionProcessStatus.killed) &&
+                executionProcess?.status === ExecutionProcessStatus.killed ||
+                executionProcess?.status ===
+                  ExecutionProcessStatus.budget_exceeded) &&
               index === Object.keys(executionProcessState).length - 1
             ) {
               lastProcessFailedOrKilled = true;
@@ -1,9 +1,12 @@
 import { useMutation, useQueryClient } from "@tanstack/react-query";
+import type { MergeStrategy } from "shared/types";
 import { attemptsApi } from "@/lib/api";
 import { repoBranchKeys } from "./useRepoBranches";
 
 type MergeParams = {
   repoId: string;
+  /** Defaults to the project's merge strategy */
+  strategy?: MergeStrategy;
 };
 
 export function useMerge(
@@ -18,6 +21,7 @@ export function useMerge(
       if (!attemptId) return Promise.resolve();
       return attemptsApi.merge(attemptId, {
         repo_id: params.repoId,
+        strategy: params.strategy,
       });
     },
     onSuccess: () => {
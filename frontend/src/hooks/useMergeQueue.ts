@@ -0,0 +1,62 @@
+import { useCallback, useMemo } from "react";
+import type { MergeQueueEntry } from "shared/types";
+import { attemptsApi } from "@/lib/api";
+import { useJsonPatchWsStream } from "./useJsonPatchWsStream";
+
+type MergeQueueState = {
+  merge_queue: Record<string, MergeQueueEntry>;
+};
+
+export interface UseMergeQueueResult {
+  /** Entries in queue order */
+  entries: MergeQueueEntry[];
+  isConnected: boolean;
+  error: string | null;
+  enqueue: (attemptId: string, repoId: string) => Promise<MergeQueueEntry>;
+  cancel: (attemptId: string, repoId: string) => Promise<void>;
+}
+
+/**
+ * Stream a project's merge queue via WebSocket (JSON Patch).
+ * Server sends initial snapshot: replace /merge_queue with an object keyed by id.
+ */
+export const useMergeQueue = (projectId?: string): UseMergeQueueResult => {
+  const endpoint = projectId
+    ? `/api/projects/${encodeURIComponent(projectId)}/merge-queue/stream/ws`
+    : undefined;
+
+  const initialData = useCallback(
+    (): MergeQueueState => ({ merge_queue: {} }),
+    []
+  );
+
+  const { data, isConnected, error } = useJsonPatchWsStream<MergeQueueState>(
+    endpoint,
+    !!projectId,
+    initialData
+  );
+
+  const entries = useMemo(
+    () =>
+      Object.values(data?.merge_queue ?? {}).sort(
+        (a, b) =>
+          new Date(a.created_at as unknown as string).getTime() -
+          new Date(b.created_at as unknown as string).getTime()
+      ),
+    [data]
+  );
+
+  const enqueue = useCallback(
+    (attemptId: string, repoId: string) =>
+      attemptsApi.enqueueMerge(attemptId, { repo_id: repoId }),
+    []
+  );
+
+  const cancel = useCallback(
+    (attemptId: string, repoId: string) =>
+      attemptsApi.cancelQueuedMerge(attemptId, { repo_id: repoId }),
+    []
+  );
+
+  return { entries, isConnected, error, enqueue, cancel };
+};
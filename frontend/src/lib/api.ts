@@ -5,6 +5,7 @@ import type {
   ApiResponse,
   ApprovalResponse,
   ApprovalStatus,
+  AttemptComparison,
   AvailabilityInfo,
   BaseCodingAgent,
   ChangeTargetBranchRequest,
@@ -12,7 +13,9 @@ import type {
   CheckEditorAvailabilityResponse,
   Config,
   CreateAndStartTaskRequest,
+  CreateBestOfNAttemptsBody,
   CreateFollowUpAttempt,
+  CreateHandoff,
   CreateGitHubPrRequest,
   CreateInvitationRequest,
   CreateInvitationResponse,
@@ -27,11 +30,14 @@ import type {
   CreateTask,
   CreateTaskAttemptBody,
   CurrentUserResponse,
+  DailyUsage,
   DirectoryEntry,
   DirectoryListResponse,
   EditorType,
+  ExecutionBudget,
   ExecutionProcess,
   ExecutionProcessRepoState,
+  ExecutionProcessUsage,
   GetMcpServerResponse,
   GhCliSetupError,
   GitBranch,
@@ -43,6 +49,8 @@ import type {
   ListMembersResponse,
   ListOrganizationsResponse,
   McpServerQuery,
+  MergeQueueEntry,
+  MergeQueueRequest,
   MergeTaskAttemptRequest,
   OpenEditorRequest,
   OpenEditorResponse,
@@ -57,6 +65,7 @@ import type {
   RemoteProject,
   RemoteProjectMembersResponse,
   RenameBranchRequest,
+  ResolveConflictsRequest,
   RenameBranchResponse,
   Repo,
   RepoBranchStatus,
@@ -76,9 +85,12 @@ import type {
   Tag,
   TagSearchParams,
   Task,
+  TaskDependencies,
+  TaskDependency,
   TaskRelationships,
   TaskWithAttemptStatus,
   TokenResponse,
+  UpdateExecutionBudget,
   UpdateMcpServersBody,
   UpdateMemberRoleRequest,
   UpdateMemberRoleResponse,
@@ -87,6 +99,7 @@ import type {
   UpdateScratch,
   UpdateTag,
   UpdateTask,
+  UsageSummary,
   UserSystemInfo,
   Workspace,
 } from "shared/types";
@@ -811,6 +824,41 @@ export const projectsApi = {
     return handleApiResponse<Repo[]>(response);
   },
 
+  getUsage: async (projectId: string): Promise<UsageSummary> => {
+    const response = await makeRequest(`/api/projects/${projectId}/usage`);
+    return handleApiResponse<UsageSummary>(response);
+  },
+
+  getMergeQueue: async (projectId: string): Promise<MergeQueueEntry[]> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/merge-queue`
+    );
+    return handleApiResponse<MergeQueueEntry[]>(response);
+  },
+
+  getBudget: async (projectId: string): Promise<ExecutionBudget | null> => {
+    const response = await makeRequest(`/api/projects/${projectId}/budget`);
+    return handleApiResponse<ExecutionBudget | null>(response);
+  },
+
+  updateBudget: async (
+    projectId: string,
+    data: UpdateExecutionBudget
+  ): Promise<ExecutionBudget> => {
+    const response = await makeRequest(`/api/projects/${projectId}/budget`, {
+      method: "PUT",
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<ExecutionBudget>(response);
+  },
+
+  deleteBudget: async (projectId: string): Promise<void> => {
+    const response = await makeRequest(`/api/projects/${projectId}/budget`, {
+      method: "DELETE",
+    });
+    return handleApiResponse<void>(response);
+  },
+
   addRepository: async (
     projectId: string,
     data: CreateProjectRepo
@@ -911,6 +959,63 @@ export const tasksApi = {
     return handleApiResponse<ShareTaskResponse>(response);
   },
 
+  getUsage: async (taskId: string): Promise<UsageSummary> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/usage`);
+    return handleApiResponse<UsageSummary>(response);
+  },
+
+  getBudget: async (taskId: string): Promise<ExecutionBudget | null> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/budget`);
+    return handleApiResponse<ExecutionBudget | null>(response);
+  },
+
+  updateBudget: async (
+    taskId: string,
+    data: UpdateExecutionBudget
+  ): Promise<ExecutionBudget> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/budget`, {
+      method: "PUT",
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<ExecutionBudget>(response);
+  },
+
+  deleteBudget: async (taskId: string): Promise<void> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/budget`, {
+      method: "DELETE",
+    });
+    return handleApiResponse<void>(response);
+  },
+
+  getDependencies: async (taskId: string): Promise<TaskDependencies> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/dependencies`);
+    return handleApiResponse<TaskDependencies>(response);
+  },
+
+  addDependency: async (
+    taskId: string,
+    dependsOnTaskId: string
+  ): Promise<TaskDependency> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/dependencies`, {
+      method: "POST",
+      body: JSON.stringify({ depends_on_task_id: dependsOnTaskId }),
+    });
+    return handleApiResponse<TaskDependency>(response);
+  },
+
+  removeDependency: async (
+    taskId: string,
+    dependsOnTaskId: string
+  ): Promise<void> => {
+    const response = await makeRequest(
+      `/api/tasks/${taskId}/dependencies/${dependsOnTaskId}`,
+      {
+        method: "DELETE",
+      }
+    );
+    return handleApiResponse<void>(response);
+  },
+
   reassign: async (
     sharedTaskId: string,
     data: { new_assignee_user_id: string | null }
@@ -981,6 +1086,17 @@ export const sessionsApi = {
     });
     return handleApiResponse<ExecutionProcess>(response);
   },
+
+  handoff: async (
+    sessionId: string,
+    data: CreateHandoff
+  ): Promise<ExecutionProcess> => {
+    const response = await makeRequest(`/api/sessions/${sessionId}/handoff`, {
+      method: "POST",
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<ExecutionProcess>(response);
+  },
 };
 
 // Task Attempts APIs
@@ -1019,6 +1135,36 @@ export const attemptsApi = {
     return handleApiResponse<Workspace>(response);
   },
 
+  createBestOfN: async (
+    data: CreateBestOfNAttemptsBody
+  ): Promise<Workspace[]> => {
+    const response = await makeRequest("/api/task-attempts/best-of-n", {
+      method: "POST",
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<Workspace[]>(response);
+  },
+
+  compare: async (
+    taskId: string,
+    includeArchived = false
+  ): Promise<AttemptComparison[]> => {
+    const response = await makeRequest(
+      `/api/task-attempts/compare?task_id=${taskId}&include_archived=${includeArchived}`
+    );
+    return handleApiResponse<AttemptComparison[]>(response);
+  },
+
+  promote: async (attemptId: string): Promise<Workspace> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/promote`,
+      {
+        method: "POST",
+      }
+    );
+    return handleApiResponse<Workspace>(response);
+  },
+
   stop: async (attemptId: string): Promise<void> => {
     const response = await makeRequest(`/api/task-attempts/${attemptId}/stop`, {
       method: "POST",
@@ -1080,6 +1226,34 @@ export const attemptsApi = {
     return handleApiResponse<void>(response);
   },
 
+  enqueueMerge: async (
+    attemptId: string,
+    data: MergeQueueRequest
+  ): Promise<MergeQueueEntry> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/merge-queue`,
+      {
+        method: "POST",
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<MergeQueueEntry>(response);
+  },
+
+  cancelQueuedMerge: async (
+    attemptId: string,
+    data: MergeQueueRequest
+  ): Promise<void> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/merge-queue/cancel`,
+      {
+        method: "POST",
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<void>(response);
+  },
+
   push: async (
     attemptId: string,
     data: PushTaskAttemptRequest
@@ -1164,6 +1338,20 @@ export const attemptsApi = {
     return handleApiResponse<void>(response);
   },
 
+  resolveConflicts: async (
+    attemptId: string,
+    data: ResolveConflictsRequest
+  ): Promise<ExecutionProcess> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/conflicts/resolve`,
+      {
+        method: "POST",
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<ExecutionProcess>(response);
+  },
+
   createPR: async (
     attemptId: string,
     data: CreateGitHubPrRequest
@@ -1250,6 +1438,15 @@ export const executionProcessesApi = {
     return handleApiResponse<ExecutionProcessRepoState[]>(response);
   },
 
+  getUsage: async (
+    processId: string
+  ): Promise<ExecutionProcessUsage | null> => {
+    const response = await makeRequest(
+      `/api/execution-processes/${processId}/usage`
+    );
+    return handleApiResponse<ExecutionProcessUsage | null>(response);
+  },
+
   stopExecutionProcess: async (processId: string): Promise<void> => {
     const response = await makeRequest(
       `/api/execution-processes/${processId}/stop`,
@@ -1261,6 +1458,23 @@ export const executionProcessesApi = {
   },
 };
 
+// Usage APIs
+export const usageApi = {
+  getDaily: async (params?: {
+    projectId?: string;
+    days?: number;
+  }): Promise<DailyUsage[]> => {
+    const query = new URLSearchParams();
+    if (params?.projectId) query.set("project_id", params.projectId);
+    if (params?.days) query.set("days", String(params.days));
+    const queryString = query.toString();
+    const response = await makeRequest(
+      `/api/usage/daily${queryString ? `?${queryString}` : ""}`
+    );
+    return handleApiResponse<DailyUsage[]>(response);
+  },
+};
+
 // File System APIs
 export const fileSystemApi = {
   list: async (path?: string): Promise<DirectoryListResponse> => {
@@ -3,8 +3,10 @@ import { Loader2, Volume2 } from "lucide-react";
 import { useCallback, useEffect, useMemo, useState } from "react";
 import { useTranslation } from "react-i18next";
 import {
+  type CommitSigningMode,
   DEFAULT_PR_DESCRIPTION_PROMPT,
   EditorType,
+  type SigningFormat,
   SoundFile,
   ThemeMode,
   type UiLanguage,
@@ -37,6 +39,14 @@ import { useEditorAvailability } from "@/hooks/useEditorAvailability";
 import { getLanguageOptions } from "@/i18n/languages";
 import { toPrettyCase } from "@/utils/string";
 
+const COMMIT_SIGNING_MODES: CommitSigningMode[] = [
+  "git_config",
+  "always",
+  "never",
+];
+
+const SIGNING_FORMATS: SigningFormat[] = ["openpgp", "ssh", "x509"];
+
 export function GeneralSettings() {
   const { t } = useTranslation(["settings", "common"]);
   const { isSignedIn } = useClerkUser();
@@ -489,6 +499,92 @@ export function GeneralSettings() {
               )}
             </p>
           </div>
+
+          <div className="space-y-2">
+            <Label htmlFor="commit-signing-mode">
+              {t("settings.general.git.commitSigning.label")}
+            </Label>
+            <Select
+              onValueChange={(value: CommitSigningMode) =>
+                updateDraft({
+                  commit_signing: { ...draft!.commit_signing, mode: value },
+                })
+              }
+              value={draft?.commit_signing.mode}
+            >
+              <SelectTrigger id="commit-signing-mode">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                {COMMIT_SIGNING_MODES.map((mode) => (
+                  <SelectItem key={mode} value={mode}>
+                    {t(`settings.general.git.commitSigning.modes.${mode}`)}
+                  </SelectItem>
+                ))}
+              </SelectContent>
+            </Select>
+            <p className="text-muted-foreground text-sm">
+              {t("settings.general.git.commitSigning.helper")}
+            </p>
+          </div>
+
+          {draft?.commit_signing.mode !== "never" && (
+            <div className="grid gap-4 sm:grid-cols-2">
+              <div className="space-y-2">
+                <Label htmlFor="commit-signing-format">
+                  {t("settings.general.git.commitSigning.format.label")}
+                </Label>
+                <Select
+                  onValueChange={(value: SigningFormat | "git_config") =>
+                    updateDraft({
+                      commit_signing: {
+                        ...draft!.commit_signing,
+                        format: value === "git_config" ? null : value,
+                      },
+                    })
+                  }
+                  value={draft?.commit_signing.format ?? "git_config"}
+                >
+                  <SelectTrigger id="commit-signing-format">
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    <SelectItem value="git_config">
+                      {t("settings.general.git.commitSigning.format.gitConfig")}
+                    </SelectItem>
+                    {SIGNING_FORMATS.map((format) => (
+                      <SelectItem key={format} value={format}>
+                        {t(
+                          `settings.general.git.commitSigning.format.${format}`
+                        )}
+                      </SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+              </div>
+              <div className="space-y-2">
+                <Label htmlFor="commit-signing-key">
+                  {t("settings.general.git.commitSigning.key.label")}
+                </Label>
+                <Input
+                  id="commit-signing-key"
+                  onChange={(e) =>
+                    updateDraft({
+                      commit_signing: {
+                        ...draft!.commit_signing,
+                        signing_key: e.target.value || null,
+                      },
+                    })
+                  }
+                  placeholder={t(
+                    "settings.general.git.commitSigning.key.placeholder"
+                  )}
+                  type="text"
+                  value={draft?.commit_signing.signing_key ?? ""}
+                />
+              </div>
+            </div>
+          )}
         </CardContent>
       </Card>
 
@@ -558,6 +654,56 @@ export function GeneralSettings() {
               {t("settings.general.pullRequests.customPrompt.helper")}
             </p>
           </div>
+          <div className="space-y-2">
+            <Label htmlFor="gitlab-token">
+              {t("settings.general.pullRequests.gitlabToken.label")}
+            </Label>
+            <Input
+              autoComplete="off"
+              id="gitlab-token"
+              onChange={(e) =>
+                updateDraft({
+                  gitlab: {
+                    ...draft!.gitlab,
+                    token: e.target.value || null,
+                  },
+                })
+              }
+              placeholder={t(
+                "settings.general.pullRequests.gitlabToken.placeholder"
+              )}
+              type="password"
+              value={draft?.gitlab.token ?? ""}
+            />
+            <p className="text-muted-foreground text-sm">
+              {t("settings.general.pullRequests.gitlabToken.helper")}
+            </p>
+          </div>
+          <div className="space-y-2">
+            <Label htmlFor="gitlab-hosts">
+              {t("settings.general.pullRequests.gitlabHosts.label")}
+            </Label>
+            <Input
+              id="gitlab-hosts"
+              onChange={(e) =>
+                updateDraft({
+                  gitlab: {
+                    ...draft!.gitlab,
+                    hosts: e.target.value
+                      ? e.target.value.split(",").map((host) => host.trim())
+                      : [],
+                  },
+                })
+              }
+              placeholder={t(
+                "settings.general.pullRequests.gitlabHosts.placeholder"
+              )}
+              value={draft?.gitlab.hosts.join(", ") ?? ""}
+            />
+            <p className="text-muted-foreground text-sm">
+              {t("settings.general.pullRequests.gitlabHosts.helper")}
+            </p>
+          </div>
         </CardContent>
       </Card>
 
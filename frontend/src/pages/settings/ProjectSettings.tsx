@@ -4,7 +4,13 @@ import { Loader2, Plus, Trash2 } from "lucide-react";
 import { useCallback, useEffect, useMemo, useState } from "react";
 import { useTranslation } from "react-i18next";
 import { useSearchParams } from "react-router-dom";
-import type { Project, ProjectRepo, Repo, UpdateProject } from "shared/types";
+import type {
+  MergeStrategy,
+  Project,
+  ProjectRepo,
+  Repo,
+  UpdateProject,
+} from "shared/types";
 import { RepoPickerDialog } from "@/components/dialogs/shared/RepoPickerDialog";
 import { CopyFilesField } from "@/components/projects/CopyFilesField";
 import { Alert, AlertDescription } from "@/components/ui/alert";
@@ -38,13 +44,23 @@ interface ProjectFormState {
   dev_script: string;
   dev_script_working_dir: string;
   default_agent_working_dir: string;
+  merge_strategy: MergeStrategy;
+  merge_commit_template: string;
+  worktree_pool_size: number;
 }
 
+const MERGE_STRATEGIES: MergeStrategy[] = ["squash", "rebase", "merge_commit"];
+
+const MAX_WORKTREE_POOL_SIZE = 10;
+
 interface RepoScriptsFormState {
   setup_script: string;
   parallel_setup_script: boolean;
   cleanup_script: string;
+  verify_script: string;
   copy_files: string;
+  sparse_checkout: string;
+  sparse_checkout_cone: boolean;
 }
 
 function projectToFormState(project: Project): ProjectFormState {
@@ -53,6 +69,9 @@ function projectToFormState(project: Project): ProjectFormState {
     dev_script: project.dev_script ?? "",
     dev_script_working_dir: project.dev_script_working_dir ?? "",
     default_agent_working_dir: project.default_agent_working_dir ?? "",
+    merge_strategy: project.merge_strategy,
+    merge_commit_template: project.merge_commit_template ?? "",
+    worktree_pool_size: project.worktree_pool_size,
   };
 }
 
@@ -63,7 +82,10 @@ function projectRepoToScriptsFormState(
     setup_script: projectRepo?.setup_script ?? "",
     parallel_setup_script: projectRepo?.parallel_setup_script ?? false,
     cleanup_script: projectRepo?.cleanup_script ?? "",
+    verify_script: projectRepo?.verify_script ?? "",
     copy_files: projectRepo?.copy_files ?? "",
+    sparse_checkout: projectRepo?.sparse_checkout ?? "",
+    sparse_checkout_cone: projectRepo?.sparse_checkout_cone ?? true,
   };
 }
 
@@ -397,6 +419,9 @@ export function ProjectSettings() {
         dev_script_working_dir: draft.dev_script_working_dir.trim() || null,
         default_agent_working_dir:
           draft.default_agent_working_dir.trim() || null,
+        merge_strategy: draft.merge_strategy,
+        merge_commit_template: draft.merge_commit_template,
+        worktree_pool_size: draft.worktree_pool_size,
       };
 
       updateProject.mutate({
@@ -424,8 +449,11 @@ export function ProjectSettings() {
         {
           setup_script: scriptsDraft.setup_script.trim() || null,
           cleanup_script: scriptsDraft.cleanup_script.trim() || null,
+          verify_script: scriptsDraft.verify_script.trim() || null,
           copy_files: scriptsDraft.copy_files.trim() || null,
           parallel_setup_script: scriptsDraft.parallel_setup_script,
+          sparse_checkout: scriptsDraft.sparse_checkout.trim() || null,
+          sparse_checkout_cone: scriptsDraft.sparse_checkout_cone,
         }
       );
       setSelectedProjectRepo(updatedRepo);
@@ -630,6 +658,81 @@ export function ProjectSettings() {
                 </p>
               </div>
 
+              <div className="space-y-2">
+                <Label htmlFor="merge-strategy">
+                  {t("settings.projects.merge.strategy.label")}
+                </Label>
+                <Select
+                  onValueChange={(value) =>
+                    updateDraft({ merge_strategy: value as MergeStrategy })
+                  }
+                  value={draft.merge_strategy}
+                >
+                  <SelectTrigger id="merge-strategy">
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    {MERGE_STRATEGIES.map((strategy) => (
+                      <SelectItem key={strategy} value={strategy}>
+                        {t(
+                          `settings.projects.merge.strategy.options.${strategy}`
+                        )}
+                      </SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+                <p className="text-muted-foreground text-sm">
+                  {t("settings.projects.merge.strategy.helper")}
+                </p>
+              </div>
+
+              <div className="space-y-2">
+                <Label htmlFor="merge-commit-template">
+                  {t("settings.projects.merge.template.label")}
+                </Label>
+                <AutoExpandingTextarea
+                  className="w-full rounded-md border border-input bg-background px-3 py-2 font-mono text-foreground focus:outline-none focus:ring-2 focus:ring-ring"
+                  disabled={draft.merge_strategy === "rebase"}
+                  id="merge-commit-template"
+                  maxRows={8}
+                  onChange={(e) =>
+                    updateDraft({ merge_commit_template: e.target.value })
+                  }
+                  placeholder={t(
+                    "settings.projects.merge.template.placeholder"
+                  )}
+                  value={draft.merge_commit_template}
+                />
+                <p className="text-muted-foreground text-sm">
+                  {t("settings.projects.merge.template.helper")}
+                </p>
+              </div>
+
+              <div className="space-y-2">
+                <Label htmlFor="worktree-pool-size">
+                  {t("settings.projects.worktreePool.label")}
+                </Label>
+                <Input
+                  className="w-32"
+                  id="worktree-pool-size"
+                  max={MAX_WORKTREE_POOL_SIZE}
+                  min={0}
+                  onChange={(e) =>
+                    updateDraft({
+                      worktree_pool_size: Math.min(
+                        MAX_WORKTREE_POOL_SIZE,
+                        Math.max(0, Math.floor(Number(e.target.value) || 0))
+                      ),
+                    })
+                  }
+                  type="number"
+                  value={draft.worktree_pool_size}
+                />
+                <p className="text-muted-foreground text-sm">
+                  {t("settings.projects.worktreePool.helper")}
+                </p>
+              </div>
+
               {/* Save Button */}
               <div className="flex items-center justify-between border-t pt-4">
                 {hasUnsavedProjectChanges ? (
@@ -875,6 +978,27 @@ export function ProjectSettings() {
                         </p>
                       </div>
 
+                      <div className="space-y-2">
+                        <Label htmlFor="verify-script">
+                          {t("settings.projects.scripts.verify.label")}
+                        </Label>
+                        <AutoExpandingTextarea
+                          className="w-full rounded-md border border-input bg-background px-3 py-2 font-mono text-foreground focus:outline-none focus:ring-2 focus:ring-ring"
+                          id="verify-script"
+                          maxRows={12}
+                          onChange={(e) =>
+                            updateScriptsDraft({
+                              verify_script: e.target.value,
+                            })
+                          }
+                          placeholder={placeholders.verify}
+                          value={scriptsDraft.verify_script}
+                        />
+                        <p className="text-muted-foreground text-sm">
+                          {t("settings.projects.scripts.verify.helper")}
+                        </p>
+                      </div>
+
                       <div className="space-y-2">
                         <Label>
                           {t("settings.projects.scripts.copyFiles.label")}
@@ -891,6 +1015,57 @@ export function ProjectSettings() {
                         </p>
                       </div>
 
+                      <div className="space-y-2">
+                        <Label htmlFor="sparse-checkout">
+                          {t("settings.projects.scripts.sparseCheckout.label")}
+                        </Label>
+                        <AutoExpandingTextarea
+                          className="w-full rounded-md border border-input bg-background px-3 py-2 font-mono text-foreground focus:outline-none focus:ring-2 focus:ring-ring"
+                          id="sparse-checkout"
+                          maxRows={12}
+                          onChange={(e) =>
+                            updateScriptsDraft({
+                              sparse_checkout: e.target.value,
+                            })
+                          }
+                          placeholder={
+                            scriptsDraft.sparse_checkout_cone
+                              ? "services/api\nlibs/shared"
+                              : "/services/api/\n!/services/api/fixtures/\n*.toml"
+                          }
+                          value={scriptsDraft.sparse_checkout}
+                        />
+                        <p className="text-muted-foreground text-sm">
+                          {t("settings.projects.scripts.sparseCheckout.helper")}
+                        </p>
+
+                        <div className="flex items-center space-x-2 pt-2">
+                          <Checkbox
+                            checked={scriptsDraft.sparse_checkout_cone}
+                            disabled={!scriptsDraft.sparse_checkout.trim()}
+                            id="sparse-checkout-cone"
+                            onCheckedChange={(checked) =>
+                              updateScriptsDraft({
+                                sparse_checkout_cone: checked === true,
+                              })
+                            }
+                          />
+                          <Label
+                            className="cursor-pointer font-normal text-sm"
+                            htmlFor="sparse-checkout-cone"
+                          >
+                            {t(
+                              "settings.projects.scripts.sparseCheckout.coneLabel"
+                            )}
+                          </Label>
+                        </div>
+                        <p className="pl-6 text-muted-foreground text-sm">
+                          {t(
+                            "settings.projects.scripts.sparseCheckout.coneHelper"
+                          )}
+                        </p>
+                      </div>
+
                       {/* Scripts Save Buttons */}
                       <div className="flex items-center justify-between border-t pt-4">
                         {hasUnsavedScriptsChanges ? (
@@ -31,6 +31,8 @@ export type UserData = {
   username: string | null;
 };
 
+export type MergeStrategy = "squash" | "rebase" | "merge_commit";
+
 export type Project = {
   id: string;
   name: string;
@@ -38,6 +40,16 @@ export type Project = {
   dev_script_working_dir: string | null;
   default_agent_working_dir: string | null;
   remote_project_id: string | null;
+  merge_strategy: MergeStrategy;
+  /**
+   * Commit message template for direct merges. Supports `{task_title}`,
+   * `{task_description}`, `{task_id}`, `{task_short_id}` and `{executor}`.
+   */
+  merge_commit_template: string | null;
+  /**
+   * Number of set-up worktrees kept ready for new attempts; 0 disables the pool
+   */
+  worktree_pool_size: number;
   created_at: Date;
   updated_at: Date;
 };
@@ -52,6 +64,18 @@ export type UpdateProject = {
   dev_script: string | null;
   dev_script_working_dir: string | null;
   default_agent_working_dir: string | null;
+  /**
+   * Left unchanged when omitted
+   */
+  merge_strategy: MergeStrategy | null;
+  /**
+   * Left unchanged when omitted; an empty template restores the default message
+   */
+  merge_commit_template: string | null;
+  /**
+   * Left unchanged when omitted
+   */
+  worktree_pool_size: number | null;
 };
 
 export type SearchResult = {
@@ -77,8 +101,20 @@ export type ProjectRepo = {
   repo_id: string;
   setup_script: string | null;
   cleanup_script: string | null;
+  /**
+   * Runs after the coding agent and cleanup script; the task only moves to In Review when it passes
+   */
+  verify_script: string | null;
   copy_files: string | null;
   parallel_setup_script: boolean;
+  /**
+   * Newline-separated sparse-checkout patterns; worktrees check out the full tree when unset
+   */
+  sparse_checkout: string | null;
+  /**
+   * Treat the sparse-checkout patterns as directories (git's cone mode)
+   */
+  sparse_checkout_cone: boolean;
 };
 
 export type CreateProjectRepo = { display_name: string; git_repo_path: string };
@@ -86,8 +122,11 @@ export type CreateProjectRepo = { display_name: string; git_repo_path: string };
 export type UpdateProjectRepo = {
   setup_script: string | null;
   cleanup_script: string | null;
+  verify_script: string | null;
   copy_files: string | null;
   parallel_setup_script: boolean | null;
+  sparse_checkout: string | null;
+  sparse_checkout_cone: boolean | null;
 };
 
 export type WorkspaceRepo = {
@@ -95,6 +134,10 @@ export type WorkspaceRepo = {
   workspace_id: string;
   repo_id: string;
   target_branch: string;
+  /**
+   * Commit of the parent attempt's branch this repo is stacked on, if it is stacked
+   */
+  stack_base_commit: string | null;
   created_at: Date;
   updated_at: Date;
 };
@@ -144,8 +187,20 @@ export type Task = {
 
 export type TaskWithAttemptStatus = {
   has_in_progress_attempt: boolean;
+  /**
+   * True while an execution for this task is waiting for a concurrency slot
+   */
+  is_queued: boolean;
   last_attempt_failed: boolean;
+  /**
+   * True when the latest attempt went to review with its verify script still failing
+   */
+  verification_failed: boolean;
   executor: string;
+  /**
+   * True while any task this one depends on is not yet done
+   */
+  is_blocked: boolean;
   id: string;
   project_id: string;
   title: string;
@@ -181,6 +236,20 @@ export type UpdateTask = {
   image_ids: Array<string> | null;
 };
 
+export type TaskDependency = {
+  id: string;
+  task_id: string;
+  depends_on_task_id: string;
+  created_at: Date;
+};
+
+export type CreateTaskDependency = { depends_on_task_id: string };
+
+export type TaskDependencies = {
+  depends_on: Array<Task>;
+  dependents: Array<Task>;
+};
+
 export type DraftFollowUpData = { message: string; variant: string | null };
 
 export type ScratchPayload =
@@ -229,6 +298,10 @@ export type Workspace = {
   branch: string;
   agent_working_dir: string | null;
   setup_completed_at: string | null;
+  /**
+   * Set when another attempt of the task was promoted over this one
+   */
+  archived_at: string | null;
   created_at: string;
   updated_at: string;
 };
@@ -261,15 +334,18 @@ export type ExecutionProcess = {
 };
 
 export enum ExecutionProcessStatus {
+  queued = "queued",
   running = "running",
   completed = "completed",
   failed = "failed",
   killed = "killed",
+  budget_exceeded = "budget_exceeded",
 }
 
 export type ExecutionProcessRunReason =
   | "setupscript"
   | "cleanupscript"
+  | "verifyscript"
   | "codingagent"
   | "devserver";
 
@@ -284,6 +360,158 @@ export type ExecutionProcessRepoState = {
   updated_at: Date;
 };
 
+export type ExecutionProcessUsage = {
+  execution_process_id: string;
+  coding_agent_turn_id: string | null;
+  input_tokens: bigint;
+  output_tokens: bigint;
+  cache_creation_input_tokens: bigint;
+  cache_read_input_tokens: bigint;
+  cost_usd: number | null;
+  created_at: Date;
+  updated_at: Date;
+};
+
+export type UsageSummary = {
+  execution_count: bigint;
+  /**
+   * Coding agent executions without a usage report, because their agent does not report
+   * usage or never got far enough to. The token totals leave them out rather than count 0.
+   */
+  unreported_execution_count: bigint;
+  input_tokens: bigint;
+  output_tokens: bigint;
+  cache_creation_input_tokens: bigint;
+  cache_read_input_tokens: bigint;
+  cost_usd: number;
+};
+
+export type DailyUsage = {
+  day: string;
+  execution_count: bigint;
+  input_tokens: bigint;
+  output_tokens: bigint;
+  cache_creation_input_tokens: bigint;
+  cache_read_input_tokens: bigint;
+  cost_usd: number;
+};
+
+export type ExecutionBudget = {
+  id: string;
+  project_id: string | null;
+  task_id: string | null;
+  max_tokens: number | null;
+  max_runtime_seconds: number | null;
+  max_tool_calls: number | null;
+  created_at: Date;
+  updated_at: Date;
+};
+
+export type UpdateExecutionBudget = {
+  max_tokens: number | null;
+  max_runtime_seconds: number | null;
+  max_tool_calls: number | null;
+};
+
+export type ApprovalLogStatus =
+  | "pending"
+  | "approved"
+  | "denied"
+  | "timed_out"
+  | "cancelled";
+
+export type ApprovalLogEntry = {
+  /**
+   * Id of the approval request
+   */
+  id: string;
+  project_id: string;
+  task_id: string;
+  execution_process_id: string;
+  tool_name: string;
+  tool_call_id: string;
+  tool_input: JsonValue;
+  status: ApprovalLogStatus;
+  deny_reason: string | null;
+  /**
+   * Who decided: the responding user or the approval rule that fired. Unset while
+   * pending and when the approval timed out or was cancelled.
+   */
+  responder: string | null;
+  requested_at: string;
+  responded_at: string | null;
+  /**
+   * Time from the request to its decision, timeout or cancellation
+   */
+  latency_ms: number | null;
+};
+
+export type ApprovalLogQuery = {
+  project_id: string | null;
+  task_id: string | null;
+  tool_name: string | null;
+  /**
+   * Most recent entries to return, at most 500
+   */
+  limit: number | null;
+};
+
+export type ApprovalRuleAction = "allow" | "deny" | "ask";
+
+export type ApprovalRule = {
+  id: string;
+  project_id: string;
+  name: string;
+  /**
+   * Rules are checked in ascending position and the first match decides
+   */
+  position: number;
+  /**
+   * Executor the rule applies to, e.g. `CLAUDE_CODE`; every executor when unset
+   */
+  executor: string | null;
+  /**
+   * Executor profile variant the rule applies to; every variant when unset
+   */
+  variant: string | null;
+  /**
+   * Regex the whole tool name must match; every tool when unset
+   */
+  tool_pattern: string | null;
+  /**
+   * Regex searched for in each string of the tool input; every input when unset. Allow
+   * rules must match the whole command, or path for file tools, so `cargo test.*` is needed
+   * to allow arguments, and never match commands that chain or redirect others.
+   */
+  input_pattern: string | null;
+  /**
+   * Only match tool calls with a path argument outside the workspace
+   */
+  outside_workspace: boolean;
+  action: ApprovalRuleAction;
+  /**
+   * Given to the agent when the rule denies a tool call
+   */
+  reason: string | null;
+  created_at: Date;
+  updated_at: Date;
+};
+
+export type CreateApprovalRule = {
+  name: string;
+  /**
+   * Appended after the project's other rules when omitted
+   */
+  position: number | null;
+  executor: string | null;
+  variant: string | null;
+  tool_pattern: string | null;
+  input_pattern: string | null;
+  outside_workspace: boolean;
+  action: ApprovalRuleAction;
+  reason: string | null;
+};
+
 export type Merge =
   | ({ type: "direct" } & DirectMerge)
   | ({ type: "pr" } & PrMerge);
@@ -316,6 +544,31 @@ export type PullRequestInfo = {
   merge_commit_sha: string | null;
 };
 
+export type MergeQueueStatus =
+  | "queued"
+  | "rebasing"
+  | "verifying"
+  | "merging"
+  | "merged"
+  | "failed"
+  | "cancelled";
+
+export type MergeQueueEntry = {
+  id: string;
+  project_id: string;
+  workspace_id: string;
+  repo_id: string;
+  target_branch: string;
+  status: MergeQueueStatus;
+  /**
+   * Why the entry failed, shown on the task
+   */
+  failure_reason: string | null;
+  merge_commit: string | null;
+  created_at: Date;
+  updated_at: Date;
+};
+
 export type ApprovalStatus =
   | { status: "pending" }
   | { status: "approved" }
@@ -333,6 +586,26 @@ export type ApprovalResponse = {
   status: ApprovalStatus;
 };
 
+export type UsageReport = {
+  /**
+   * Input tokens that were not served from the prompt cache
+   */
+  input_tokens: bigint;
+  output_tokens: bigint;
+  /**
+   * Input tokens written to the prompt cache
+   */
+  cache_creation_input_tokens: bigint;
+  /**
+   * Input tokens served from the prompt cache
+   */
+  cache_read_input_tokens: bigint;
+  /**
+   * Spend in USD, when the agent reports it
+   */
+  cost_usd: number | null;
+};
+
 export type Diff = {
   change: DiffChangeKind;
   oldPath: string | null;
@@ -356,7 +629,8 @@ export type DiffChangeKind =
   | "modified"
   | "renamed"
   | "copied"
-  | "permissionChange";
+  | "permissionChange"
+  | "submodule";
 
 export type ApiResponse<T, E = T> = {
   success: boolean;
@@ -522,6 +796,14 @@ export type InitRepoRequest = { parent_path: string; folder_name: string };
 
 export type TagSearchParams = { search: string | null };
 
+export type DailyUsageQuery = {
+  project_id: string | null;
+  /**
+   * How many days back to report, defaults to 30
+   */
+  days: bigint | null;
+};
+
 export type TokenResponse = { access_token: string; expires_at: string | null };
 
 export type UserSystemInfo = {
@@ -568,6 +850,17 @@ export type CreateFollowUpAttempt = {
   perform_git_reset: boolean | null;
 };
 
+export type CreateHandoff = {
+  /**
+   * Executor that continues the session
+   */
+  executor_profile_id: ExecutorProfileId;
+  /**
+   * What the new executor should do next. Defaults to carrying on with the task.
+   */
+  prompt: string | null;
+};
+
 export type ChangeTargetBranchRequest = {
   repo_id: string;
   new_target_branch: string;
@@ -579,7 +872,15 @@ export type ChangeTargetBranchResponse = {
   status: [number, number];
 };
 
-export type MergeTaskAttemptRequest = { repo_id: string };
+export type MergeTaskAttemptRequest = {
+  repo_id: string;
+  /**
+   * Overrides the project's merge strategy for this merge
+   */
+  strategy?: MergeStrategy | null;
+};
+
+export type MergeQueueRequest = { repo_id: string };
 
 export type PushTaskAttemptRequest = { repo_id: string };
 
@@ -602,6 +903,11 @@ export type CreateAndStartTaskRequest = {
   task: CreateTask;
   executor_profile_id: ExecutorProfileId;
   repos: Array<WorkspaceRepoInput>;
+  /**
+   * Base the attempt on the parent attempt's branch for the repos both share, and
+   * restack it whenever that branch changes
+   */
+  stack_on_parent: boolean;
 };
 
 export type CreateGitHubPrRequest = {
@@ -637,10 +943,53 @@ export type CreateTaskAttemptBody = {
   task_id: string;
   executor_profile_id: ExecutorProfileId;
   repos: Array<WorkspaceRepoInput>;
+  /**
+   * Base the attempt on the parent attempt's branch for the repos both share, and
+   * restack it whenever that branch changes
+   */
+  stack_on_parent: boolean;
 };
 
 export type WorkspaceRepoInput = { repo_id: string; target_branch: string };
 
+export type CreateBestOfNAttemptsBody = {
+  task_id: string;
+  executor_profile_ids: Array<ExecutorProfileId>;
+  repos: Array<WorkspaceRepoInput>;
+};
+
+export type AttemptDiffStats = {
+  files_changed: number;
+  additions: number;
+  deletions: number;
+};
+
+export type AttemptComparison = {
+  workspace: Workspace;
+  executor_profile_id: ExecutorProfileId | null;
+  /**
+   * Status of the latest coding agent run
+   */
+  status: ExecutionProcessStatus | null;
+  /**
+   * Changes against the target branch, summed over all repos
+   */
+  diff_stats: AttemptDiffStats;
+  /**
+   * Exit code of the latest cleanup script run
+   */
+  cleanup_script_exit_code: bigint | null;
+  /**
+   * Exit code of the latest verify script run
+   */
+  verify_script_exit_code: bigint | null;
+  usage: UsageSummary;
+  /**
+   * Wall-clock time of the latest coding agent run, up to now if it is still running
+   */
+  duration_seconds: number | null;
+};
+
 export type RunAgentSetupRequest = { executor_profile_id: ExecutorProfileId };
 
 export type RunAgentSetupResponse = Record<string, never>;
@@ -658,6 +1007,8 @@ export type RebaseTaskAttemptRequest = {
 
 export type AbortConflictsRequest = { repo_id: string };
 
+export type ResolveConflictsRequest = { repo_id: string };
+
 export type GitOperationError =
   | { type: "merge_conflicts"; message: string; op: ConflictOp }
   | { type: "rebase_in_progress" };
@@ -768,6 +1119,14 @@ export type RepoBranchStatus = {
   conflicted_files: Array<string>;
 };
 
+export type ExecutionConversation = {
+  entries: Array<NormalizedEntry>;
+  /**
+   * The agent's final message once the turn is over, otherwise its latest message
+   */
+  summary: string | null;
+};
+
 export type DirectoryEntry = {
   name: string;
   path: string;
@@ -799,6 +1158,19 @@ export type Config = {
   showcases: ShowcaseState;
   pr_auto_description_enabled: boolean;
   pr_auto_description_prompt: string | null;
+  concurrency: ConcurrencyConfig;
+  budget: BudgetConfig;
+  verify: VerifyConfig;
+  /**
+   * Executors tried in order when the selected one is not installed or not signed in
+   */
+  executor_fallbacks: Array<ExecutorProfileId>;
+  gitlab: GitLabConfig;
+  /**
+   * Signing of the commits vibe-kanban creates for agent turns and merges
+   */
+  commit_signing: CommitSigningConfig;
+  approval_notifications: ApprovalNotificationConfig;
 };
 
 export type NotificationConfig = {
@@ -866,6 +1238,64 @@ export type UiLanguage = "BROWSER" | "EN" | "JA" | "ES" | "KO" | "ZH_HANS";
 
 export type ShowcaseState = { seen_features: Array<string> };
 
+export type ConcurrencyConfig = {
+  max_running_agents: number | null;
+  max_running_agents_per_project: number | null;
+};
+
+export type BudgetConfig = {
+  max_tokens: number | null;
+  max_runtime_seconds: number | null;
+  max_tool_calls: number | null;
+};
+
+export type VerifyConfig = {
+  /**
+   * Follow-ups sent to the agent with the failing output before giving up. 0 disables retries.
+   */
+  max_auto_retries: number;
+};
+
+export type GitLabConfig = {
+  token: string | null;
+  /**
+   * Hosts of self-hosted GitLab instances, e.g. `git.example.com`. gitlab.com and hosts
+   * starting with `gitlab.` are recognised without being listed.
+   */
+  hosts: Array<string>;
+};
+
+export type ApprovalNotificationConfig = {
+  /**
+   * Receives a JSON POST for every approval request, with links to approve or deny it.
+   * The body is signed in the `X-Vibe-Kanban-Signature-256` header when the
+   * `VK_APPROVAL_WEBHOOK_SECRET` environment variable is set.
+   */
+  webhook_url: string | null;
+  /**
+   * Base URL of this server used in approval links, e.g. when it is reached through a
+   * tunnel. Defaults to the local address the server listens on.
+   */
+  public_base_url: string | null;
+};
+
+export type CommitSigningConfig = {
+  mode: CommitSigningMode;
+  /**
+   * Overrides `gpg.format` from the git config
+   */
+  format: SigningFormat | null;
+  /**
+   * Overrides `user.signingkey` from the git config: a GPG key id, or an SSH key path
+   * or literal public key
+   */
+  signing_key: string | null;
+};
+
+export type CommitSigningMode = "git_config" | "always" | "never";
+
+export type SigningFormat = "openpgp" | "ssh" | "x509";
+
 export type GitBranch = {
   name: string;
   is_current: boolean;
@@ -902,6 +1332,8 @@ export type QueueStatus =
 
 export type ConflictOp = "rebase" | "merge" | "cherry_pick" | "revert";
 
+export type AttemptArchiveFormat = "patch" | "bundle";
+
 export type ExecutorAction = {
   typ: ExecutorActionType;
   next_action: ExecutorAction | null;
@@ -923,6 +1355,7 @@ export type ExecutorActionType =
 export type ScriptContext =
   | "SetupScript"
   | "CleanupScript"
+  | "VerifyScript"
   | "DevServer"
   | "ToolInstallScript";
 
@@ -949,6 +1382,8 @@ export enum BaseCodingAgent {
   QWEN_CODE = "QWEN_CODE",
   COPILOT = "COPILOT",
   DROID = "DROID",
+  AIDER = "AIDER",
+  CUSTOM_AGENT = "CUSTOM_AGENT",
 }
 
 export type CodingAgent =
@@ -960,7 +1395,9 @@ export type CodingAgent =
   | { CURSOR_AGENT: CursorAgent }
   | { QWEN_CODE: QwenCode }
   | { COPILOT: Copilot }
-  | { DROID: Droid };
+  | { DROID: Droid }
+  | { AIDER: Aider }
+  | { CUSTOM_AGENT: CustomAgent };
 
 export type AvailabilityInfo =
   | { type: "LOGIN_DETECTED"; last_auth_timestamp: bigint }
@@ -1009,6 +1446,7 @@ export type ExecutorConfigs = {
 export enum BaseAgentCapability {
   SESSION_FORK = "SESSION_FORK",
   SETUP_HELPER = "SETUP_HELPER",
+  USAGE_REPORTING = "USAGE_REPORTING",
 }
 
 export type ClaudeCode = {
@@ -1147,6 +1585,46 @@ export type DroidReasoningEffort =
   | "medium"
   | "high";
 
+export type Aider = {
+  append_prompt: AppendPrompt;
+  model?: string | null;
+  edit_format?: string | null;
+  auto_commits?: boolean | null;
+  base_command_override?: string | null;
+  additional_params?: Array<string> | null;
+  env?: { [key in string]?: string } | null;
+};
+
+export type CustomAgent = {
+  append_prompt: AppendPrompt;
+  command: string;
+  prompt_input: PromptInput;
+  prompt_flag?: string | null;
+  resume_args?: Array<string> | null;
+  output_format: OutputFormat;
+  session_id_pattern?: string | null;
+  jsonl?: JsonlMapping | null;
+  mcp_config_path?: string | null;
+  base_command_override?: string | null;
+  additional_params?: Array<string> | null;
+  env?: { [key in string]?: string } | null;
+};
+
+export type PromptInput = "argument" | "stdin" | "file";
+
+export type OutputFormat = "plain_text" | "acp" | "jsonl";
+
+export type JsonlMapping = {
+  type_field: string;
+  content_field: string;
+  session_id_field?: string | null;
+  tool_name_field?: string | null;
+  assistant_types: Array<string>;
+  thinking_types: Array<string>;
+  tool_types: Array<string>;
+  error_types: Array<string>;
+};
+
 export type AppendPrompt = string | null;
 
 export type CodingAgentInitialRequest = {